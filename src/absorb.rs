@@ -0,0 +1,355 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Moving submodules' embedded `.git` directories into `.git/modules`, for `submod absorb`.
+
+A submodule cloned by hand, or by an older tool, keeps its git directory inside its
+worktree, so deleting the directory deletes its history too. Absorbing moves that git
+directory to `.git/modules/<name>`, or to `modules/<name>` in its parent's git directory
+for a nested submodule, and links the two the way `git submodule add` does: a `gitdir:`
+file in the worktree, and `core.worktree` in the git directory. Nested submodules whose git
+directories were inside a parent's embedded one move along with it, and are relinked.
+"]
+
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::doctor;
+use crate::utilities::relative_path;
+
+/// A submodule git directory that absorbing moves, or that moves along with its parent's
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relocation {
+    /// The submodule's directory, relative to the superproject root
+    pub path: String,
+    /// The submodule's worktree
+    pub worktree: PathBuf,
+    /// Where its git directory is now
+    pub from: PathBuf,
+    /// Where its git directory belongs
+    pub to: PathBuf,
+    /// Whether the git directory is embedded in the worktree. Otherwise it's inside a
+    /// parent's embedded git directory and moves with it; only its links change.
+    pub embedded: bool,
+}
+
+/// Find the git directories that absorbing the submodules at `paths`, and the submodules
+/// nested in them, would relocate.
+///
+/// `root` is the superproject and `git_dir` its git directory, both canonical; `paths` of
+/// `None` means all of its submodules. Parents come before the submodules nested in them.
+///
+/// # Errors
+///
+/// Returns an error if a `.gitmodules` file can't be read.
+pub fn plan(root: &Path, git_dir: &Path, paths: Option<&[String]>) -> Result<Vec<Relocation>> {
+    let mut plan = Vec::new();
+    walk(root, "", git_dir, git_dir, paths, &mut plan)?;
+    Ok(plan)
+}
+
+/// Plan the submodules of the worktree at `worktree`, whose git directory moves from
+/// `git_dir` to `git_dir_after` (or stays put, if they're the same)
+fn walk(
+    worktree: &Path,
+    prefix: &str,
+    git_dir: &Path,
+    git_dir_after: &Path,
+    only: Option<&[String]>,
+    plan: &mut Vec<Relocation>,
+) -> Result<()> {
+    let sections = doctor::read_sections(&worktree.join(".gitmodules"))?;
+    for (name, values) in &sections {
+        let Some(path) = values.get("path").map(|path| path.trim_end_matches('/')) else {
+            continue;
+        };
+        if only.is_some_and(|only| !only.iter().any(|wanted| wanted == path)) {
+            continue;
+        }
+        let display = if prefix.is_empty() {
+            path.to_string()
+        } else {
+            format!("{prefix}/{path}")
+        };
+        let sub_worktree = worktree.join(path);
+        let dot_git = sub_worktree.join(".git");
+        let (from, to, embedded) = if dot_git.is_dir() {
+            let to = git_dir_after.join("modules").join(name);
+            (dot_git, to, true)
+        } else if let Some(from) = read_gitfile(&dot_git) {
+            let to = from
+                .strip_prefix(git_dir)
+                .map_or_else(|_| from.clone(), |rest| git_dir_after.join(rest));
+            (from, to, false)
+        } else {
+            continue;
+        };
+        if embedded || from != to {
+            plan.push(Relocation {
+                path: display.clone(),
+                worktree: sub_worktree.clone(),
+                from: from.clone(),
+                to: to.clone(),
+                embedded,
+            });
+        }
+        walk(&sub_worktree, &display, &from, &to, None, plan)?;
+    }
+    Ok(())
+}
+
+/// The git directory a `.git` file points to, if it's a readable `gitdir:` file
+fn read_gitfile(dot_git: &Path) -> Option<PathBuf> {
+    let text = fs::read_to_string(dot_git).ok()?;
+    let target = Path::new(text.strip_prefix("gitdir:")?.trim());
+    dot_git.parent()?.join(target).canonicalize().ok()
+}
+
+/// What a relocation changed, to put back if a later one fails
+struct Done<'a> {
+    relocation: &'a Relocation,
+    /// Whether its embedded git directory was moved
+    moved: bool,
+    /// The worktree's `.git` file before it was relinked, if it had one
+    gitfile: Option<Vec<u8>>,
+    /// The git directory's `core.worktree` before it was relinked
+    worktree_setting: Option<String>,
+}
+
+/// Carry out a [`plan`]: move each embedded git directory into place, then point every
+/// relocated git directory and its worktree at each other.
+///
+/// Nothing moves unless every embedded git directory can, and if a move or link fails,
+/// the ones already made are put back.
+///
+/// # Errors
+///
+/// Returns an error if something already exists where a git directory would go, if an
+/// embedded repository has linked worktrees (they would lose track of it), or if a move
+/// or link fails.
+pub fn absorb(plan: &[Relocation]) -> Result<()> {
+    for relocation in plan.iter().filter(|relocation| relocation.embedded) {
+        let path = &relocation.path;
+        if relocation.to.exists() {
+            bail!(
+                "Can't absorb {path}: {} already exists",
+                relocation.to.display()
+            );
+        }
+        let has_worktrees = fs::read_dir(relocation.from.join("worktrees"))
+            .is_ok_and(|mut entries| entries.next().is_some());
+        if has_worktrees {
+            bail!(
+                "Can't absorb {path}: its repository has linked worktrees, which would lose track of it"
+            );
+        }
+    }
+    let mut done = Vec::new();
+    for relocation in plan {
+        // A nested git directory has already moved with its parent's, to `to`
+        let git_dir = if relocation.embedded {
+            &relocation.from
+        } else {
+            &relocation.to
+        };
+        done.push(Done {
+            relocation,
+            moved: false,
+            gitfile: (!relocation.embedded)
+                .then(|| fs::read(relocation.worktree.join(".git")).ok())
+                .flatten(),
+            worktree_setting: core_worktree(git_dir),
+        });
+        let step = done.last_mut().map_or(Ok(()), relocate);
+        if let Err(e) = step {
+            for step in done.iter().rev() {
+                undo(step);
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Move one git directory into place, if it's embedded, and link it to its worktree
+fn relocate(step: &mut Done<'_>) -> Result<()> {
+    let relocation = step.relocation;
+    if relocation.embedded {
+        if let Some(parent) = relocation.to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&relocation.from, &relocation.to).with_context(|| {
+            format!(
+                "Failed to move {} to {}",
+                relocation.from.display(),
+                relocation.to.display()
+            )
+        })?;
+        step.moved = true;
+    }
+    link(&relocation.worktree, &relocation.to)
+        .with_context(|| format!("Failed to link {} to its git directory", relocation.path))
+}
+
+/// Put back what a relocation changed, as far as it can
+fn undo(step: &Done<'_>) {
+    let relocation = step.relocation;
+    let dot_git = relocation.worktree.join(".git");
+    let git_dir = if step.moved || !relocation.embedded {
+        &relocation.to
+    } else {
+        &relocation.from
+    };
+    if let Ok(mut config) = git2::Config::open(&git_dir.join("config")) {
+        let _ = match step.worktree_setting.as_deref() {
+            Some(setting) => config.set_str("core.worktree", setting),
+            None => config.remove("core.worktree"),
+        };
+    }
+    match &step.gitfile {
+        Some(contents) => {
+            let _ = fs::write(&dot_git, contents);
+        }
+        None if dot_git.is_file() => {
+            let _ = fs::remove_file(&dot_git);
+        }
+        None => {}
+    }
+    if step.moved {
+        let _ = fs::rename(&relocation.to, &relocation.from);
+    }
+}
+
+/// The `core.worktree` set in the git directory at `git_dir`, if any
+fn core_worktree(git_dir: &Path) -> Option<String> {
+    let config = git2::Config::open(&git_dir.join("config")).ok()?;
+    config.get_string("core.worktree").ok()
+}
+
+/// Point the worktree at its git directory with a `.git` file, and the git directory back
+/// at the worktree with `core.worktree`, both relative like git writes them
+fn link(worktree: &Path, git_dir: &Path) -> Result<()> {
+    fs::write(
+        worktree.join(".git"),
+        format!("gitdir: {}\n", relative_path(worktree, git_dir).display()),
+    )?;
+    let mut config = git2::Config::open(&git_dir.join("config"))?;
+    config.set_str(
+        "core.worktree",
+        &relative_path(git_dir, worktree).to_string_lossy(),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    /// A fake repository layout: a superproject with `libs/a` embedded, `b` already
+    /// absorbed, and submodules nested in each
+    fn layout() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let git_dir = root.join(".git");
+        let gitmodules = |worktree: &Path, entries: &[(&str, &str)]| {
+            let text = entries
+                .iter()
+                .map(|(name, path)| format!("[submodule \"{name}\"]\n\tpath = {path}\n"))
+                .collect::<Vec<_>>()
+                .concat();
+            fs::write(worktree.join(".gitmodules"), text).unwrap();
+        };
+        gitmodules(&root, &[("a", "libs/a"), ("b", "b")]);
+
+        // `a` is embedded; its `inner` is absorbed into a's embedded git directory, and
+        // its `loose` is embedded too
+        let a = root.join("libs/a");
+        fs::create_dir_all(a.join(".git/modules/inner")).unwrap();
+        gitmodules(&a, &[("inner", "inner"), ("loose", "loose")]);
+        fs::create_dir_all(a.join("inner")).unwrap();
+        fs::write(a.join("inner/.git"), "gitdir: ../.git/modules/inner\n").unwrap();
+        fs::create_dir_all(a.join("loose/.git")).unwrap();
+
+        // `b` is absorbed, but its `c` is embedded
+        let b = root.join("b");
+        fs::create_dir_all(git_dir.join("modules/b")).unwrap();
+        fs::create_dir_all(b.join("c/.git")).unwrap();
+        fs::write(b.join(".git"), "gitdir: ../.git/modules/b\n").unwrap();
+        gitmodules(&b, &[("c", "c")]);
+        (dir, root, git_dir)
+    }
+
+    #[test]
+    fn test_plan_finds_embedded_git_dirs_and_what_moves_with_them() {
+        let (_dir, root, git_dir) = layout();
+        let plan = plan(&root, &git_dir, None).unwrap();
+        let summary: Vec<_> = plan
+            .iter()
+            .map(|r| {
+                (
+                    r.path.as_str(),
+                    relative_path(&root, &r.to).display().to_string(),
+                    r.embedded,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("libs/a", ".git/modules/a".to_string(), true),
+                (
+                    "libs/a/inner",
+                    ".git/modules/a/modules/inner".to_string(),
+                    false
+                ),
+                (
+                    "libs/a/loose",
+                    ".git/modules/a/modules/loose".to_string(),
+                    true
+                ),
+                ("b/c", ".git/modules/b/modules/c".to_string(), true),
+            ]
+        );
+
+        let only_b = super::plan(&root, &git_dir, Some(&["b".to_string()])).unwrap();
+        assert_eq!(only_b.len(), 1);
+        assert_eq!(only_b[0].path, "b/c");
+    }
+
+    #[test]
+    fn test_absorb_refuses_to_overwrite_an_existing_git_dir() {
+        let (_dir, root, git_dir) = layout();
+        fs::create_dir_all(git_dir.join("modules/a")).unwrap();
+        let plan = plan(&root, &git_dir, None).unwrap();
+        let err = absorb(&plan).unwrap_err().to_string();
+        assert!(err.contains("Can't absorb libs/a"), "{err}");
+        // Nothing moved
+        assert!(root.join("b/c/.git").is_dir());
+    }
+
+    #[test]
+    fn test_absorb_puts_back_what_moved_when_a_later_move_fails() {
+        let (_dir, root, git_dir) = layout();
+        let mut plan = plan(&root, &git_dir, None).unwrap();
+        // Passes the up-front check, but the last move can't land where a file is
+        fs::create_dir_all(git_dir.join("modules/b/modules")).unwrap();
+        plan.last_mut().unwrap().to = git_dir.join("modules/b/modules/c/deeper");
+        fs::write(git_dir.join("modules/b/modules/c"), "in the way").unwrap();
+
+        assert!(absorb(&plan).is_err());
+        // Everything is where it was
+        assert!(root.join("libs/a/.git").is_dir());
+        assert!(root.join("libs/a/loose/.git").is_dir());
+        assert!(root.join("b/c/.git").is_dir());
+        assert!(!git_dir.join("modules/a").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("libs/a/inner/.git")).unwrap(),
+            "gitdir: ../.git/modules/inner\n"
+        );
+        assert_eq!(core_worktree(&root.join("libs/a/.git/modules/inner")), None);
+    }
+}
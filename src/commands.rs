@@ -24,8 +24,20 @@ Defines the CLI structure and commands using [`clap`] for managing git submodule
 - [`Commands::List`](src/commands.rs): Lists all submodules, optionally recursively.
 - [`Commands::Init`](src/commands.rs): Initializes missing submodules.
 - [`Commands::Update`](src/commands.rs): Updates all submodules.
+- [`Commands::Deepen`](src/commands.rs): Fetches more history into a shallow submodule.
+- [`Commands::Unshallow`](src/commands.rs): Fetches the full history of a shallow submodule.
 - [`Commands::Reset`](src/commands.rs): Hard resets submodules (stash, reset --hard, clean).
 - [`Commands::Sync`](src/commands.rs): Runs a full sync (check, init, update).
+- [`Commands::Diff`](src/commands.rs): Summarizes submodule changes between two superproject revisions.
+- [`Commands::Doctor`](src/commands.rs): Reports where submod.toml, .gitmodules, and .git/config disagree, and optionally fixes it.
+- [`Commands::Import`](src/commands.rs): Imports submodules from another tool's manifest: an Android `repo` manifest, a vcstool `.repos` file, or a west manifest.
+- [`Commands::Export`](src/commands.rs): Writes the submodules as a vcstool or west manifest, locked to their commits.
+- [`Commands::Vendor`](src/commands.rs): Replaces a submodule with its checked-out files, recording where they came from in `VENDORED.toml`.
+- [`Commands::Adopt`](src/commands.rs): Makes an existing nested clone or vendored copy a submodule, without cloning it again.
+- [`Commands::Absorb`](src/commands.rs): Moves submodules' own `.git` directories into `.git/modules`, recursing into nested submodules.
+- [`Commands::Foreach`](src/commands.rs): Runs a command in each submodule, optionally filtered by group or to dirty ones.
+- [`Commands::Hooks`](src/commands.rs): Installs or removes git hooks that sync submodules after checkouts, merges, and rebases.
+- [`Commands::Worktree`](src/commands.rs): Cleans up after deleted worktrees of the superproject, and the submodule checkouts they held.
 - [`Commands::GenerateConfig`](src/commands.rs): Generates a new configuration file.
 - [`Commands::NukeItFromOrbit`](src/commands.rs): Deletes all submodules or specific ones, optionally leaving them dead. (reinits by default)
 - [`Commands::Completions`](src/commands.rs): Generates shell completions for the specified shell.
@@ -40,6 +52,15 @@ submod init
 submod update
 submod reset --all
 submod sync
+submod foreach --jobs 4 -- git status --short
+submod diff main HEAD --format markdown
+submod doctor --fix --source gitmodules
+submod import repo-manifest .repo/manifests/default.xml --init
+submod export west --output west.yml
+submod vendor my-lib
+submod adopt third_party/zlib --url https://github.com/madler/zlib.git
+submod absorb
+submod worktree prune
 submod completeme bash
 ```
 
@@ -50,13 +71,18 @@ Use the `--config` option to specify a custom config file location.
 See the [README.md](../README.md) for full usage and configuration details.
 "#]
 
+use crate::completions;
 use crate::shells::Shell;
 use clap::{Parser, Subcommand};
+use clap_complete::{ArgValueCandidates, ArgValueCompleter};
 
-use crate::long_abouts::COMPLETE_ME;
+use crate::long_abouts::{
+    ADOPT, COMPLETE_ME, DOCTOR, EXPORT, FOREACH, IMPORT_REPO_MANIFEST, IMPORT_VCSTOOL, IMPORT_WEST,
+    TUI, VENDOR,
+};
 use crate::options::{
-    SerializableFetchRecurse as FetchRecurse, SerializableIgnore as Ignore,
-    SerializableUpdate as Update,
+    ConfigSource, ManifestFormat, OutputFormat, SerializableFetchRecurse as FetchRecurse,
+    SerializableIgnore as Ignore, SerializableUpdate as Update,
 };
 use std::{ffi::OsString, path::PathBuf};
 
@@ -74,9 +100,28 @@ pub struct Cli {
     #[arg(long = "config", global = true, default_value = "submod.toml", value_parser = clap::value_parser!(PathBuf), value_hint = clap::ValueHint::FilePath, help = "Optionally provide a different configuration file path. Defaults to submod.toml in the current directory.")]
     pub config: PathBuf,
 
-    /// Enable verbose output with detailed status information.
-    #[arg(long, short, global = true)]
-    pub verbose: bool,
+    /// Enable verbose output with detailed status information. Repeat for more detailed logs:
+    /// `-v` logs each command with its timing, `-vv` each git operation and the backend that
+    /// served it, and `-vvv` everything. `SUBMOD_LOG` overrides this, e.g.
+    /// `SUBMOD_LOG=submod=debug,gix=trace`.
+    #[arg(long, short, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Write logs to this file (appending) instead of stderr.
+    #[arg(long = "log-file", global = true, value_name = "PATH", value_parser = clap::value_parser!(PathBuf), value_hint = clap::ValueHint::FilePath)]
+    pub log_file: Option<PathBuf>,
+
+    /// Print only warnings and errors. Also turns off the live progress rows.
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Skip the lifecycle hooks declared in the configuration.
+    #[arg(long = "no-hooks", global = true)]
+    pub no_hooks: bool,
+
+    /// How to write output: text, Markdown (for `diff`), or JSON for scripts.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
 }
 
 /// Supported commands for the `submod` tool.
@@ -101,6 +146,7 @@ pub enum Commands {
         #[arg(
             short = 'b',
             long = "branch",
+            add = ArgValueCandidates::new(completions::branch_names),
             help = "Branch to use for the submodule. If not provided, defaults to the submodule's default branch."
         )]
         branch: Option<String>,
@@ -116,6 +162,7 @@ pub enum Commands {
             short = 'x',
             long = "sparse-paths",
             value_delimiter = ',',
+            add = ArgValueCompleter::new(completions::sparse_paths),
             help = "Sparse checkout paths (comma-separated). Can be globs or paths"
         )]
         sparse_paths: Option<Vec<String>>,
@@ -146,6 +193,42 @@ pub enum Commands {
         #[arg(short = 's', long = "shallow", default_value = "false", action = clap::ArgAction::SetTrue, default_missing_value = "true", help = "If given, sets the submodule as a shallow clone. It will only fetch the last commit of the branch, not the full history.")]
         shallow: bool,
 
+        #[arg(long = "depth", value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "shallow", help = "Clone the submodule with only the last N commits of history. `--shallow` is the same as `--depth 1`.")]
+        depth: Option<u32>,
+
+        #[arg(
+            long = "shallow-since",
+            value_name = "DATE",
+            help = "Clone only the history after DATE (e.g. 2024-01-01). Ignored if a depth is also given."
+        )]
+        shallow_since: Option<String>,
+
+        #[arg(long = "ssh-key", value_name = "PATH", value_hint = clap::ValueHint::FilePath, help = "Private key to use for this submodule's SSH remote, e.g. a deploy key.")]
+        ssh_key: Option<String>,
+
+        #[arg(
+            long = "credential-helper",
+            value_name = "HELPER",
+            help = "Git credential helper to use for this submodule's HTTP(S) remote, by name (e.g. `store`); helpers that run a command (`!...` or an absolute path) are refused."
+        )]
+        credential_helper: Option<String>,
+
+        #[arg(
+            long = "http-extra-header-env",
+            value_name = "VAR",
+            help = "Name of an environment variable holding an extra HTTP header (e.g. `Authorization: Bearer ...`) to send to this submodule's remote. Only the variable name is stored."
+        )]
+        http_extra_header_env: Option<String>,
+
+        #[arg(
+            short = 'g',
+            long = "group",
+            value_delimiter = ',',
+            add = ArgValueCandidates::new(completions::group_names),
+            help = "Groups to put the submodule in (comma-separated), for selecting it with `--group` in commands like `foreach`."
+        )]
+        groups: Option<Vec<String>>,
+
         #[arg(long = "no-init", default_value = "false", action = clap::ArgAction::SetTrue, default_missing_value = "true", help = "If given, we'll add the submodule to your submod.toml but not initialize it.")]
         no_init: bool,
     },
@@ -153,23 +236,27 @@ pub enum Commands {
     #[command(
         name = "change",
         next_help_heading = "Change a Submodule's Settings",
-        about = "Change the configuration of an existing submodule. Any field you provide will overwrite an existing value (unless both are defaults). If you change the path, it will nuke-it-from-orbit (delete it and re-clone it)."
+        about = "Change the configuration of an existing submodule. Any field you provide will overwrite an existing value (unless both are defaults). If you change the path or URL, the existing clone is moved or repointed, keeping its local branches and changes."
     )]
     Change {
-        #[arg(required = true, value_parser = clap::value_parser!(String), value_hint = clap::ValueHint::CommandName, help = "The name of the submodule to change. Must match an existing submodule.", long_help = "The name of the submodule to change. Must match an existing submodule in your submod.toml. Because we use this value to lookup your config, you cannot change the name from the CLI. You must manually change it in your submod.toml. All other options can be changed here.")]
+        #[arg(required = true, value_parser = clap::value_parser!(String), add = ArgValueCandidates::new(completions::submodule_names), help = "The name of the submodule to change. Must match an existing submodule.", long_help = "The name of the submodule to change. Must match an existing submodule in your submod.toml. To give it a new name, use `--rename`.")]
         name: String,
 
-        #[arg(short = 'p', long = "path", value_parser = clap::value_parser!(OsString), value_hint = clap::ValueHint::DirPath, help = "New local path for the submodule. Implies `nuke-it-from-orbit` (no-kill) if the path changes.")]
+        #[arg(short = 'p', long = "path", value_parser = clap::value_parser!(OsString), value_hint = clap::ValueHint::DirPath, help = "New local path for the submodule. Moves the existing clone with `git mv`, without re-cloning.")]
         path: Option<OsString>,
 
+        #[arg(long = "rename", value_parser = clap::value_parser!(String), help = "Give the submodule a new name, in submod.toml and in git: its `.gitmodules` and `.git/config` sections and its git directory under `.git/modules` move to the new name. Its path stays the same.")]
+        rename: Option<String>,
+
         #[arg(
             short = 'b',
             long = "branch",
+            add = ArgValueCandidates::new(completions::branch_names),
             help = "Branch to use for the submodule. If not provided, defaults to the submodule's default branch."
         )]
         branch: Option<String>,
 
-        #[arg(short = 'x', long = "sparse-paths", value_delimiter = ',', value_parser = clap::value_parser!(OsString), help = "Replace the sparse checkout paths (comma-separated), or add if not set. Use `--append` to append to existing sparse paths.", default_missing_value = "none")]
+        #[arg(short = 'x', long = "sparse-paths", value_delimiter = ',', value_parser = clap::value_parser!(OsString), add = ArgValueCompleter::new(completions::sparse_paths), help = "Replace the sparse checkout paths (comma-separated), or add if not set. Use `--append` to append to existing sparse paths.", default_missing_value = "none")]
         sparse_paths: Option<Vec<OsString>>,
 
         #[arg(requires("sparse_paths"), short = 'a', long = "append", value_parser = clap::value_parser!(bool), default_value = "false", default_missing_value = "true", help = "If given, appends the new sparse paths to the existing ones.")]
@@ -214,9 +301,27 @@ pub enum Commands {
         )]
         shallow: bool,
 
-        #[arg(short = 'U', long = "url", value_parser = clap::value_parser!(String), help = "Change the URL of the submodule. The submodule name from the url must match an existing submodule.")]
+        #[arg(long = "depth", value_parser = clap::value_parser!(u32), help = "Set the clone depth for the submodule. Use 0 to go back to a full clone.")]
+        depth: Option<u32>,
+
+        #[arg(
+            long = "shallow-since",
+            value_name = "DATE",
+            help = "Only clone history after DATE (e.g. 2024-01-01). Pass an empty string to clear it."
+        )]
+        shallow_since: Option<String>,
+
+        #[arg(short = 'U', long = "url", value_parser = clap::value_parser!(String), help = "Change the URL of the submodule in place, keeping its clone. The new URL must have the commit the superproject records at a branch or tag tip.")]
         url: Option<String>,
 
+        #[arg(
+            long = "fetch-url",
+            requires = "url",
+            default_value_t = false,
+            help = "Fetch from the new URL and search its history for the recorded commit, instead of only checking its branch and tag tips."
+        )]
+        fetch_url: bool,
+
         #[arg(long = "active", num_args = 0..=1, value_parser = clap::value_parser!(bool), default_missing_value = "true", help = "Set to true/false to enable or disable the submodule. Omit to leave unchanged. For a quick disable, use `submod disable <name>` instead.")]
         active: Option<bool>,
     },
@@ -285,7 +390,7 @@ pub enum Commands {
     )]
     Delete {
         /// Name of the submodule to delete.
-        #[arg(help = "Name of the submodule to delete.")]
+        #[arg(add = ArgValueCandidates::new(completions::submodule_names), help = "Name of the submodule to delete.")]
         name: String,
     },
 
@@ -297,7 +402,7 @@ pub enum Commands {
     )]
     Disable {
         /// Name of the submodule to disable.
-        #[arg(help = "Name of the submodule to disable.")]
+        #[arg(add = ArgValueCandidates::new(completions::submodule_names), help = "Name of the submodule to disable.")]
         name: String,
     },
 
@@ -309,6 +414,31 @@ pub enum Commands {
     )]
     Update,
 
+    #[command(
+        name = "deepen",
+        next_help_heading = "Deepen a Shallow Submodule",
+        about = "Fetches more history into a shallow submodule, extending it from its current shallow boundary."
+    )]
+    Deepen {
+        /// Name of the submodule to deepen.
+        #[arg(add = ArgValueCandidates::new(completions::submodule_names), help = "Name of the submodule to deepen.")]
+        name: String,
+
+        #[arg(long = "by", value_parser = clap::value_parser!(u32).range(1..), help = "Number of additional commits of history to fetch.")]
+        by: u32,
+    },
+
+    #[command(
+        name = "unshallow",
+        next_help_heading = "Unshallow a Submodule",
+        about = "Fetches the complete history of a shallow submodule and removes its depth settings from the configuration."
+    )]
+    Unshallow {
+        /// Name of the submodule to unshallow.
+        #[arg(add = ArgValueCandidates::new(completions::submodule_names), help = "Name of the submodule to unshallow.")]
+        name: String,
+    },
+
     #[command(
         name = "reset",
         visible_alias = "r",
@@ -322,6 +452,7 @@ pub enum Commands {
         #[arg(
             required_unless_present = "all",
             value_delimiter = ',',
+            add = ArgValueCandidates::new(completions::submodule_names),
             help = "Names of specific submodules to reset. If `--all` is not given, you must specify at least one submodule name."
         )]
         names: Vec<String>,
@@ -333,7 +464,225 @@ pub enum Commands {
         next_help_heading = "Sync Submodules",
         about = "Runs a full sync: check, init, update. Ensures all submodules are in sync with the configuration."
     )]
-    Sync,
+    Sync {
+        #[arg(long = "only-changed", default_value = "false", action = clap::ArgAction::SetTrue, default_missing_value = "true", help = "Only sync submodules that aren't checked out or whose checkout differs from the commit the superproject records. Skips the status check. Used by the git hooks from `submod hooks install`.")]
+        only_changed: bool,
+    },
+
+    #[command(
+        name = "diff",
+        visible_alias = "log",
+        next_help_heading = "Summarize Submodule Changes",
+        about = "Summarizes how submodules changed between two superproject revisions: commit ranges, commits by author, and diffstats, including added, removed, and moved submodules."
+    )]
+    Diff {
+        #[arg(
+            value_name = "REV_A",
+            help = "The revision to compare from. Defaults to `HEAD`."
+        )]
+        from: Option<String>,
+
+        #[arg(
+            value_name = "REV_B",
+            help = "The revision to compare to. Defaults to the working tree, using each submodule's checked-out commit."
+        )]
+        to: Option<String>,
+    },
+
+    #[command(
+        name = "doctor",
+        next_help_heading = "Find Config Drift",
+        about = "Reports every way submod.toml, .gitmodules, and .git/config disagree about submodules, plus leftover module directories and missing gitlinks. Exits non-zero if anything is wrong.",
+        long_about = DOCTOR
+    )]
+    Doctor {
+        #[arg(
+            long = "fix",
+            default_value_t = false,
+            help = "Rewrite the sources that disagree with the source of truth."
+        )]
+        fix: bool,
+
+        #[arg(
+            long = "source",
+            value_enum,
+            default_value_t = ConfigSource::SubmodToml,
+            help = "Which file is right when fixing."
+        )]
+        source: ConfigSource,
+    },
+
+    #[command(
+        name = "import",
+        next_help_heading = "Import From Another Tool",
+        about = "Imports submodules into submod.toml from another multi-repo tool's manifest. Manifest features submodules can't express are reported."
+    )]
+    Import {
+        #[command(subcommand)]
+        format: ImportFormat,
+    },
+
+    #[command(
+        name = "export",
+        next_help_heading = "Export to Another Tool",
+        about = "Writes the submodules as a vcstool or west manifest, each locked to its commit. Settings the format can't express are reported.",
+        long_about = EXPORT
+    )]
+    Export {
+        #[arg(
+            value_enum,
+            value_name = "FORMAT",
+            help = "The manifest format to write."
+        )]
+        manifest: ManifestFormat,
+
+        #[arg(short = 'o', long = "output", value_parser = clap::value_parser!(PathBuf), value_hint = clap::ValueHint::FilePath, help = "Write the manifest to this file instead of stdout.")]
+        output: Option<PathBuf>,
+
+        #[arg(
+            long = "rev",
+            value_name = "REV",
+            help = "Lock submodules to the commits recorded at this superproject revision instead of their checked-out commits."
+        )]
+        rev: Option<String>,
+    },
+
+    #[command(
+        name = "vendor",
+        next_help_heading = "Vendor a Submodule",
+        about = "Replaces a submodule with its checked-out files, staged in the superproject, and records its URL and commit in VENDORED.toml.",
+        long_about = VENDOR
+    )]
+    Vendor {
+        #[arg(add = ArgValueCandidates::new(completions::submodule_names), help = "The name of the submodule to vendor.")]
+        name: String,
+
+        #[arg(long = "force", default_value = "false", action = clap::ArgAction::SetTrue, help = "Vendor even if no remote-tracking branch has the checked-out commit. Its history is deleted with the clone.")]
+        force: bool,
+    },
+
+    #[command(
+        name = "adopt",
+        next_help_heading = "Adopt a Directory",
+        about = "Makes an existing nested clone or vendored copy a submodule without cloning it again. The gitlink and .gitmodules entry are staged.",
+        long_about = ADOPT
+    )]
+    Adopt {
+        #[arg(value_name = "PATH", value_hint = clap::ValueHint::DirPath, help = "The directory to adopt, relative to the superproject root.")]
+        path: String,
+
+        #[arg(
+            short = 'u',
+            long = "url",
+            help = "The upstream URL. Defaults to a nested clone's `origin`, or a vendored copy's URL in VENDORED.toml."
+        )]
+        url: Option<String>,
+
+        #[arg(
+            short = 'n',
+            long = "name",
+            help = "The submodule's name. Defaults to its name in VENDORED.toml, or the directory's name."
+        )]
+        name: Option<String>,
+
+        #[arg(
+            short = 'b',
+            long = "branch",
+            add = ArgValueCandidates::new(completions::branch_names),
+            help = "The branch to track. Defaults to a nested clone's current branch, or a vendored copy's branch in VENDORED.toml."
+        )]
+        branch: Option<String>,
+
+        #[arg(long = "ssh-key", value_name = "PATH", value_hint = clap::ValueHint::FilePath, help = "Private key to use for this submodule's SSH remote, e.g. a deploy key.")]
+        ssh_key: Option<String>,
+
+        #[arg(
+            long = "credential-helper",
+            value_name = "HELPER",
+            help = "Git credential helper to use for this submodule's HTTP(S) remote, by name (e.g. `store`); helpers that run a command (`!...` or an absolute path) are refused."
+        )]
+        credential_helper: Option<String>,
+
+        #[arg(
+            long = "http-extra-header-env",
+            value_name = "VAR",
+            help = "Name of an environment variable holding an extra HTTP header (e.g. `Authorization: Bearer ...`) to send to this submodule's remote. Only the variable name is stored."
+        )]
+        http_extra_header_env: Option<String>,
+    },
+
+    #[command(
+        name = "absorb",
+        next_help_heading = "Absorb Git Directories",
+        about = "Moves submodules' own .git directories into .git/modules, including those of nested submodules, like `git submodule absorbgitdirs`. The files aren't touched."
+    )]
+    Absorb {
+        #[arg(
+            value_delimiter = ',',
+            add = ArgValueCandidates::new(completions::submodule_names),
+            help = "Names of the submodules to absorb (comma-separated). Defaults to all of them."
+        )]
+        names: Vec<String>,
+    },
+
+    #[command(
+        name = "foreach",
+        next_help_heading = "Run a Command in Each Submodule",
+        about = "Runs a command in each checked-out submodule and prints each submodule's output, then a summary of failures. Exits non-zero if the command failed anywhere.",
+        long_about = FOREACH
+    )]
+    Foreach {
+        #[arg(
+            short = 'g',
+            long = "group",
+            value_delimiter = ',',
+            add = ArgValueCandidates::new(completions::group_names),
+            help = "Only run in submodules in one of these groups (comma-separated)."
+        )]
+        groups: Vec<String>,
+
+        #[arg(short = 'j', long = "jobs", default_value = "1", value_parser = clap::value_parser!(u32).range(1..), help = "How many submodules to run the command in at once.")]
+        jobs: u32,
+
+        #[arg(long = "dirty-only", default_value = "false", action = clap::ArgAction::SetTrue, default_missing_value = "true", help = "Only run in submodules with uncommitted changes to tracked files. Untracked files don't count.")]
+        dirty_only: bool,
+
+        #[arg(
+            required = true,
+            last = true,
+            value_name = "COMMAND",
+            help = "The command to run, after `--`. A single argument runs through the shell; several run as a program and its arguments."
+        )]
+        command: Vec<String>,
+    },
+
+    #[command(
+        name = "hooks",
+        next_help_heading = "Manage Git Hooks",
+        about = "Installs or removes git hooks that run `submod sync --only-changed` after checkouts, merges, and rebases."
+    )]
+    Hooks {
+        #[command(subcommand)]
+        action: HooksAction,
+    },
+
+    #[command(
+        name = "tui",
+        next_help_heading = "Submodule Dashboard",
+        about = "Opens a terminal dashboard of the submodules' status, with keys to update, reset, or open a shell in them.",
+        long_about = TUI
+    )]
+    Tui,
+
+    #[command(
+        name = "worktree",
+        next_help_heading = "Manage Worktrees",
+        about = "Manages the submodule checkouts of the superproject's linked worktrees (`git worktree add`), which share the main worktree's clones."
+    )]
+    Worktree {
+        #[command(subcommand)]
+        action: WorktreeAction,
+    },
 
     #[command(name = "generate-config", visible_aliases = ["gc", "genconf"], next_help_heading = "Generate a Config File", about = "Generates a new configuration file.")]
     GenerateConfig {
@@ -364,6 +713,7 @@ pub enum Commands {
         #[arg(
             required_unless_present = "all",
             value_delimiter = ',',
+            add = ArgValueCandidates::new(completions::submodule_names),
             help = "... or only specific ones? 😔 (comma-separated list of names"
         )]
         names: Option<Vec<String>>,
@@ -379,3 +729,89 @@ pub enum Commands {
         shell: Shell,
     },
 }
+
+/// Actions for `submod hooks`.
+#[derive(Subcommand, Debug)]
+pub enum HooksAction {
+    #[command(
+        about = "Writes managed post-checkout, post-merge, and post-rewrite hooks. Existing hooks are kept as `<hook>.pre-submod` and still run first."
+    )]
+    Install,
+
+    #[command(about = "Removes the managed hooks and restores any hooks they replaced.")]
+    Uninstall,
+}
+
+/// Actions for `submod worktree`.
+#[derive(Subcommand, Debug)]
+pub enum WorktreeAction {
+    #[command(
+        about = "Forgets deleted worktrees of the superproject, and the submodule checkouts they held, in the superproject and every clone in .git/modules."
+    )]
+    Prune {
+        #[arg(short = 'n', long = "dry-run", default_value = "false", action = clap::ArgAction::SetTrue, default_missing_value = "true", help = "Only report what would be pruned.")]
+        dry_run: bool,
+    },
+}
+
+/// Manifest formats for `submod import`.
+#[derive(Subcommand, Debug)]
+pub enum ImportFormat {
+    #[command(
+        name = "repo-manifest",
+        about = "Imports the projects of an Android `repo` manifest (e.g. default.xml), following its includes.",
+        long_about = IMPORT_REPO_MANIFEST
+    )]
+    RepoManifest {
+        #[arg(value_name = "MANIFEST", value_parser = clap::value_parser!(PathBuf), value_hint = clap::ValueHint::FilePath, help = "The manifest file to import.")]
+        manifest: PathBuf,
+
+        #[arg(
+            long = "manifest-url",
+            value_name = "URL",
+            help = "The URL the manifest repository is cloned from, for remotes with a relative `fetch` like `..`."
+        )]
+        manifest_url: Option<String>,
+
+        #[arg(
+            long = "init",
+            default_value_t = false,
+            help = "Add and clone the imported submodules, skipping inactive ones."
+        )]
+        init: bool,
+    },
+
+    #[command(
+        name = "vcstool",
+        about = "Imports the git repositories of a vcstool `.repos` file.",
+        long_about = IMPORT_VCSTOOL
+    )]
+    Vcstool {
+        #[arg(value_name = "FILE", value_parser = clap::value_parser!(PathBuf), value_hint = clap::ValueHint::FilePath, help = "The `.repos` file to import.")]
+        file: PathBuf,
+
+        #[arg(
+            long = "init",
+            default_value_t = false,
+            help = "Add and clone the imported submodules."
+        )]
+        init: bool,
+    },
+
+    #[command(
+        name = "west",
+        about = "Imports the projects of a Zephyr west manifest (west.yml).",
+        long_about = IMPORT_WEST
+    )]
+    West {
+        #[arg(value_name = "FILE", value_parser = clap::value_parser!(PathBuf), value_hint = clap::ValueHint::FilePath, help = "The west manifest to import.")]
+        file: PathBuf,
+
+        #[arg(
+            long = "init",
+            default_value_t = false,
+            help = "Add and clone the imported submodules, skipping inactive ones."
+        )]
+        init: bool,
+    },
+}
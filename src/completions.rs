@@ -0,0 +1,245 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Completions worked out while you type, for the shell scripts `COMPLETE=<shell> submod` prints.
+
+clap's dynamic engine calls these for the arguments they're attached to in
+[`commands`](crate::commands): submodule names and groups come from submod.toml (or the file
+`--config` names), branches from the repository the command line names, and sparse paths from
+the tree that repository has checked out. They read everything from disk and never fetch.
+
+A completer only sees the word being completed, so the rest of the command line is read from
+the process's arguments: the shell passes it after `--`. Anything that can't be read gives no
+candidates rather than an error.
+"]
+
+use std::collections::BTreeSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use clap_complete::CompletionCandidate;
+
+use crate::config::Config;
+
+/// The config file `submod` reads when `--config` isn't given
+const DEFAULT_CONFIG: &str = "submod.toml";
+
+/// The names of the configured submodules
+#[must_use]
+pub fn submodule_names() -> Vec<CompletionCandidate> {
+    let line = command_line();
+    let Some(config) = load_config(&line) else {
+        return Vec::new();
+    };
+    let names: BTreeSet<(String, String)> = config
+        .get_submodules()
+        .map(|(name, entry)| (name.clone(), entry.path.clone().unwrap_or_default()))
+        .collect();
+    names
+        .into_iter()
+        .map(|(name, path)| CompletionCandidate::new(name).help(Some(path.into())))
+        .collect()
+}
+
+/// The groups the configured submodules are in
+#[must_use]
+pub fn group_names() -> Vec<CompletionCandidate> {
+    let line = command_line();
+    let Some(config) = load_config(&line) else {
+        return Vec::new();
+    };
+    let groups: BTreeSet<String> = config
+        .get_submodules()
+        .flat_map(|(_, entry)| entry.groups.iter().flatten().cloned())
+        .collect();
+    groups.into_iter().map(CompletionCandidate::new).collect()
+}
+
+/// The branches of the repository on the command line: its own, and its remotes' with the
+/// remote's name dropped
+#[must_use]
+pub fn branch_names() -> Vec<CompletionCandidate> {
+    let line = command_line();
+    let Some(repo) = named_repository(&line) else {
+        return Vec::new();
+    };
+    branches(&repo)
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// The files and directories under the path being typed, in the tree that the repository on
+/// the command line has checked out
+#[must_use]
+pub fn sparse_paths(current: &OsStr) -> Vec<CompletionCandidate> {
+    let line = command_line();
+    let Some(repo) = named_repository(&line) else {
+        return Vec::new();
+    };
+    tree_entries(&repo, &current.to_string_lossy())
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// The command line being completed: what the shell passed after `--`, program name first
+fn command_line() -> Vec<String> {
+    let args: Vec<String> = std::env::args_os()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    args.iter()
+        .position(|arg| arg == "--")
+        .map_or_else(Vec::new, |escape| args[escape + 1..].to_vec())
+}
+
+/// The config file `line` names with `--config`, or the default one
+fn config_path(line: &[String]) -> PathBuf {
+    let mut args = line.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            if let Some(path) = args.next() {
+                return PathBuf::from(path);
+            }
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            return PathBuf::from(path);
+        }
+    }
+    PathBuf::from(DEFAULT_CONFIG)
+}
+
+/// The config `line` would run with, if it can be read
+fn load_config(line: &[String]) -> Option<Config> {
+    Config::default()
+        .load(config_path(line), Config::default())
+        .ok()
+}
+
+/// The repository `line` names: the clone of a configured submodule named on it, or else a
+/// repository whose path is on it, like `submod add file:///srv/lib.git` or
+/// `submod adopt vendor/lib`
+fn named_repository(line: &[String]) -> Option<gix::Repository> {
+    let root = gix::discover(".")
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let superproject = root.canonicalize().ok();
+    let config = load_config(line);
+    // The first word is the program, and the last the one being completed
+    let words = line
+        .get(1..line.len().saturating_sub(1))
+        .unwrap_or_default();
+    words.iter().find_map(|word| {
+        let dir = config
+            .as_ref()
+            .and_then(|config| config.get_submodule(word))
+            .map_or_else(
+                || PathBuf::from(word.strip_prefix("file://").unwrap_or(word)),
+                |entry| root.join(entry.path.as_deref().unwrap_or(word)),
+            );
+        // `.` is no submodule's repository
+        (dir.canonicalize().ok() != superproject)
+            .then(|| gix::open(&dir).ok())
+            .flatten()
+    })
+}
+
+/// The names of `repo`'s branches, and of its remotes' branches without the remote's name
+fn branches(repo: &gix::Repository) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let Ok(references) = repo.references() else {
+        return names;
+    };
+    if let Ok(local) = references.local_branches() {
+        names.extend(
+            local
+                .flatten()
+                .map(|branch| branch.name().shorten().to_string()),
+        );
+    }
+    if let Ok(remote) = references.remote_branches() {
+        names.extend(remote.flatten().filter_map(|branch| {
+            let name = branch.name().shorten().to_string();
+            name.split_once('/')
+                .map(|(_, name)| name.to_string())
+                .filter(|name| name != "HEAD")
+        }));
+    }
+    names
+}
+
+/// The entries of the directory `current` is in, in the tree `repo` has checked out, whose
+/// names start with what follows that directory. Directories end in `/`, so completing one
+/// goes on into it.
+fn tree_entries(repo: &gix::Repository, current: &str) -> Vec<String> {
+    let (dir, prefix) = current
+        .rsplit_once('/')
+        .map_or(("", current), |(dir, prefix)| (dir, prefix));
+    let Ok(mut tree) = repo.head_tree() else {
+        return Vec::new();
+    };
+    if !dir.is_empty() {
+        let Some(subtree) = tree
+            .lookup_entry_by_path(dir)
+            .ok()
+            .flatten()
+            .filter(|entry| entry.mode().is_tree())
+            .and_then(|entry| entry.object().ok())
+            .map(gix::Object::into_tree)
+        else {
+            return Vec::new();
+        };
+        tree = subtree;
+    }
+    let base = if dir.is_empty() {
+        String::new()
+    } else {
+        format!("{dir}/")
+    };
+    let mut entries: Vec<String> = tree
+        .iter()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.filename().to_string();
+            let slash = if entry.mode().is_tree() { "/" } else { "" };
+            name.starts_with(prefix)
+                .then(|| format!("{base}{name}{slash}"))
+        })
+        .collect();
+    entries.sort();
+    entries
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn line(words: &[&str]) -> Vec<String> {
+        words.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_config_path_follows_config_option() {
+        assert_eq!(
+            config_path(&line(&["submod", "reset", ""])),
+            PathBuf::from("submod.toml")
+        );
+        assert_eq!(
+            config_path(&line(&["submod", "--config", "other.toml", "reset", ""])),
+            PathBuf::from("other.toml")
+        );
+        assert_eq!(
+            config_path(&line(&[
+                "submod",
+                "change",
+                "--config=alt/sub.toml",
+                "lib",
+                ""
+            ])),
+            PathBuf::from("alt/sub.toml")
+        );
+    }
+}
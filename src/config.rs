@@ -21,10 +21,11 @@ Features:
 "]
 
 use crate::git_ops::GitOperations;
+use crate::hooks::HookEvent;
 use crate::options::SerializableBranch;
 use crate::options::{
-    ConfigLevel, GitmodulesConvert, SerializableFetchRecurse, SerializableIgnore,
-    SerializableUpdate,
+    ConfigLevel, GitmodulesConvert, HookFailurePolicy, SerializableFetchRecurse,
+    SerializableIgnore, SerializableUpdate,
 };
 use anyhow::Result;
 use serde::de::Deserializer;
@@ -57,6 +58,16 @@ fn shallow_filter(shallow: &bool) -> bool {
     !shallow
 }
 
+/// Resolve a configured clone depth. An explicit `depth` wins; a depth of 0 means a full
+/// clone. Otherwise `shallow = true` is treated as `depth = 1`.
+fn effective_depth(depth: Option<u32>, shallow: Option<bool>) -> Option<u32> {
+    match depth {
+        Some(0) => None,
+        Some(d) => Some(d),
+        None => (shallow == Some(true)).then_some(1),
+    }
+}
+
 // Just a type wrapper around str to make it clear what we're working with
 /// A type alias for submodule names used throughout the configuration.
 pub type SubmoduleName = String;
@@ -179,6 +190,13 @@ pub struct SubmoduleDefaults {
     /// Individual submodules can override this per-entry.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub use_git_default_sparse_checkout: Option<bool>,
+    /// Lifecycle hooks for every submodule (`[defaults.hooks]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<SubmoduleHooks>,
+    /// Branch for submodules following the superproject's branch (`branch = "."`) to
+    /// use when their remote doesn't have it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_branch: Option<String>,
 }
 
 impl Iterator for SubmoduleDefaults {
@@ -213,6 +231,12 @@ impl SubmoduleDefaults {
         if other.use_git_default_sparse_checkout.is_some() {
             mut_self.use_git_default_sparse_checkout = other.use_git_default_sparse_checkout;
         }
+        if other.hooks.is_some() {
+            mut_self.hooks = other.hooks;
+        }
+        if other.fallback_branch.is_some() {
+            mut_self.fallback_branch = other.fallback_branch;
+        }
         {
             let ignore = mut_self.ignore;
             let update = mut_self.update;
@@ -223,11 +247,97 @@ impl SubmoduleDefaults {
                     .or_else(|| Some(SerializableFetchRecurse::default())),
                 update: update.or_else(|| Some(SerializableUpdate::default())),
                 use_git_default_sparse_checkout: mut_self.use_git_default_sparse_checkout,
+                hooks: mut_self.hooks,
+                fallback_branch: mut_self.fallback_branch,
             }
         }
     }
 }
 
+/// Commands to run around submod operations, declared as `[defaults.hooks]` or
+/// `[<name>.hooks]`.
+///
+/// A submodule's list for an event replaces the defaults' list for that event (an empty
+/// list turns it off); `timeout` and `on_failure` fall back to the defaults the same way.
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct SubmoduleHooks {
+    /// Run before a submodule is added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_add: Option<Vec<String>>,
+    /// Run after a submodule is added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_add: Option<Vec<String>>,
+    /// Run before a submodule is initialized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_init: Option<Vec<String>>,
+    /// Run after a submodule is initialized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_init: Option<Vec<String>>,
+    /// Run before a submodule is updated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_update: Option<Vec<String>>,
+    /// Run after a submodule is updated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_update: Option<Vec<String>>,
+    /// Run before `submod sync` processes a submodule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_sync: Option<Vec<String>>,
+    /// Run after `submod sync` has processed a submodule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_sync: Option<Vec<String>>,
+    /// Seconds each hook command may run before it is stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// What to do when a hook fails or times out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<HookFailurePolicy>,
+}
+
+#[allow(dead_code)]
+impl SubmoduleHooks {
+    /// The commands set for `event`, or `None` if this table leaves it unset
+    #[must_use]
+    pub fn get(&self, event: HookEvent) -> Option<&[String]> {
+        match event {
+            HookEvent::PreAdd => &self.pre_add,
+            HookEvent::PostAdd => &self.post_add,
+            HookEvent::PreInit => &self.pre_init,
+            HookEvent::PostInit => &self.post_init,
+            HookEvent::PreUpdate => &self.pre_update,
+            HookEvent::PostUpdate => &self.post_update,
+            HookEvent::PreSync => &self.pre_sync,
+            HookEvent::PostSync => &self.post_sync,
+        }
+        .as_deref()
+    }
+
+    /// The commands configured for `event`
+    #[must_use]
+    pub fn commands(&self, event: HookEvent) -> &[String] {
+        self.get(event).unwrap_or_default()
+    }
+
+    /// Layer these hooks over `defaults`, keeping any setting this one leaves unset
+    #[must_use]
+    pub fn merged_over(&self, defaults: &Self) -> Self {
+        let pick = |own: &Option<Vec<String>>, default: &Option<Vec<String>>| {
+            own.clone().or_else(|| default.clone())
+        };
+        Self {
+            pre_add: pick(&self.pre_add, &defaults.pre_add),
+            post_add: pick(&self.post_add, &defaults.post_add),
+            pre_init: pick(&self.pre_init, &defaults.pre_init),
+            post_init: pick(&self.post_init, &defaults.post_init),
+            pre_update: pick(&self.pre_update, &defaults.pre_update),
+            post_update: pick(&self.post_update, &defaults.post_update),
+            pre_sync: pick(&self.pre_sync, &defaults.pre_sync),
+            post_sync: pick(&self.post_sync, &defaults.post_sync),
+            timeout: self.timeout.or(defaults.timeout),
+            on_failure: self.on_failure.or(defaults.on_failure),
+        }
+    }
+}
+
 /// Options for adding a submodule
 #[derive(Debug, Clone)]
 pub struct SubmoduleAddOptions {
@@ -245,8 +355,14 @@ pub struct SubmoduleAddOptions {
     pub update: Option<SerializableUpdate>,
     /// Fetch recurse setting (optional)
     pub fetch_recurse: Option<SerializableFetchRecurse>,
-    /// Whether to create a shallow clone
+    /// Whether to create a shallow clone (an alias for `depth = Some(1)`)
     pub shallow: bool,
+    /// Clone depth (optional, takes precedence over `shallow`)
+    pub depth: Option<u32>,
+    /// Only clone history after this date (optional)
+    pub shallow_since: Option<String>,
+    /// Credentials the clone should use
+    pub auth: SubmoduleAuth,
     /// Whether to skip initialization after adding
     #[allow(dead_code)]
     pub no_init: bool,
@@ -261,20 +377,29 @@ impl SubmoduleAddOptions {
             url: Some(self.url),
             path: Some(self.path.to_string_lossy().to_string()),
             branch: self.branch,
+            fallback_branch: None,
             ignore: self.ignore,
             update: self.update,
             fetch_recurse: self.fetch_recurse,
             shallow: Some(self.shallow),
+            depth: self.depth,
+            shallow_since: self.shallow_since,
+            ssh_key: self.auth.ssh_key,
+            credential_helper: self.auth.credential_helper,
+            http_extra_header_env: self.auth.http_extra_header_env,
             active: Some(!self.no_init), // we're adding so unless we have a 'no_init" flag, we can assume active
             no_init: Some(self.no_init),
             sparse_paths: None,
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
         }
     }
 
     /// Create an add options from a entries tuple (name and `SubmoduleEntry`)
     pub fn from_submodule_entries_tuple(entry: (SubmoduleName, SubmoduleEntry)) -> Self {
         let (name, submodule_entry) = entry;
+        let auth = SubmoduleAuth::from_entry(&submodule_entry);
         Self {
             name: name.clone(),
             url: submodule_entry
@@ -288,10 +413,19 @@ impl SubmoduleAddOptions {
             update: submodule_entry.update,
             fetch_recurse: submodule_entry.fetch_recurse,
             shallow: submodule_entry.shallow.is_some_and(|s| s),
+            depth: submodule_entry.depth,
+            shallow_since: submodule_entry.shallow_since,
+            auth,
             no_init: submodule_entry.no_init.is_some_and(|f| f),
         }
     }
 
+    /// The clone depth to use, resolving `shallow` as an alias for a depth of 1.
+    #[must_use]
+    pub fn effective_depth(&self) -> Option<u32> {
+        effective_depth(self.depth, Some(self.shallow))
+    }
+
     /// Convert an `AddOptions` to a `SubmoduleEntries` tuple
     #[must_use]
     pub fn into_entries_tuple(self) -> (SubmoduleName, SubmoduleEntry) {
@@ -308,6 +442,12 @@ pub struct SubmoduleUpdateOptions {
     pub recursive: bool,
     /// Whether to force the update
     pub force: bool,
+    /// Clone/fetch depth for a shallow submodule
+    pub depth: Option<u32>,
+    /// Only fetch history after this date for a shallow submodule
+    pub shallow_since: Option<String>,
+    /// Credentials the clone or fetch should use
+    pub auth: SubmoduleAuth,
 }
 
 #[allow(dead_code)]
@@ -319,6 +459,13 @@ impl SubmoduleUpdateOptions {
             strategy,
             recursive,
             force,
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth {
+                ssh_key: None,
+                credential_helper: None,
+                http_extra_header_env: None,
+            },
         }
     }
 
@@ -326,12 +473,39 @@ impl SubmoduleUpdateOptions {
     #[must_use]
     pub fn forced(&self) -> Self {
         Self {
-            strategy: self.strategy.clone(),
-            recursive: self.recursive,
             force: true, // Set force to true
+            ..self.clone()
         }
     }
 
+    /// Get a new instance carrying the shallow settings of a submodule entry
+    #[must_use]
+    pub fn with_shallow_from(mut self, entry: &SubmoduleEntry) -> Self {
+        self.depth = entry.effective_depth();
+        self.shallow_since.clone_from(&entry.shallow_since);
+        self
+    }
+
+    /// Get a new instance using the given update strategy
+    #[must_use]
+    pub fn with_strategy(mut self, strategy: SerializableUpdate) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Get a new instance carrying the credential settings of a submodule entry
+    #[must_use]
+    pub fn with_auth_from(mut self, entry: &SubmoduleEntry) -> Self {
+        self.auth = SubmoduleAuth::from_entry(entry);
+        self
+    }
+
+    /// Whether these options ask for a shallow clone or fetch
+    #[must_use]
+    pub const fn is_shallow(&self) -> bool {
+        self.depth.is_some() || self.shallow_since.is_some()
+    }
+
     /// Convert from `SubmoduleGitOptions` to `SubmoduleUpdateOptions`
     #[must_use]
     pub fn from_options(options: SubmoduleGitOptions) -> Self {
@@ -342,10 +516,129 @@ impl SubmoduleUpdateOptions {
                 Some(SerializableFetchRecurse::Always)
             ),
             force: false, // Default to not force
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth::default(),
         }
     }
 }
 
+/// Per-submodule transport credentials.
+///
+/// None of these hold a secret: `ssh_key` is a path to a private key,
+/// `credential_helper` is a git credential helper spec, and
+/// `http_extra_header_env` names the environment variable the header is read
+/// from when a transport is set up.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct SubmoduleAuth {
+    /// Private key to offer over SSH
+    pub ssh_key: Option<String>,
+    /// Credential helper for HTTP(S) authentication (`credential.helper` syntax)
+    pub credential_helper: Option<String>,
+    /// Environment variable holding an `http.extraHeader` value, e.g. `Authorization: Bearer ...`
+    pub http_extra_header_env: Option<String>,
+}
+
+#[allow(dead_code)]
+impl SubmoduleAuth {
+    /// Take the credential settings of a submodule entry
+    #[must_use]
+    pub fn from_entry(entry: &SubmoduleEntry) -> Self {
+        Self {
+            ssh_key: entry.ssh_key.clone(),
+            credential_helper: entry.credential_helper.clone(),
+            http_extra_header_env: entry.http_extra_header_env.clone(),
+        }
+    }
+
+    /// Whether no credential setting is configured
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.ssh_key.is_none()
+            && self.credential_helper.is_none()
+            && self.http_extra_header_env.is_none()
+    }
+
+    /// Resolve `ssh_key`, expanding a leading `~/` and anchoring relative paths at `root`
+    #[must_use]
+    pub fn ssh_key_path(&self, root: &Path) -> Option<PathBuf> {
+        let key = self.ssh_key.as_deref()?;
+        let home = std::env::var_os("HOME");
+        let path = match (key.strip_prefix("~/"), home) {
+            (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+            _ => PathBuf::from(key),
+        };
+        Some(if path.is_absolute() {
+            path
+        } else {
+            root.join(path)
+        })
+    }
+
+    /// The ssh command that offers only `ssh_key`.
+    ///
+    /// Builds on `GIT_SSH_COMMAND` when it is set, so a wrapper the user already
+    /// relies on keeps working.
+    #[must_use]
+    pub fn ssh_command(&self, root: &Path) -> Option<String> {
+        let key = self.ssh_key_path(root)?;
+        let base = std::env::var("GIT_SSH_COMMAND")
+            .ok()
+            .filter(|cmd| !cmd.trim().is_empty())
+            .unwrap_or_else(|| "ssh".to_string());
+        let quoted = key.to_string_lossy().replace('\'', "'\\''");
+        Some(format!("{base} -i '{quoted}' -o IdentitiesOnly=yes"))
+    }
+
+    /// Read the `http.extraHeader` value from the variable named by `http_extra_header_env`
+    pub fn http_extra_header(&self) -> Result<Option<String>> {
+        let Some(var) = self.http_extra_header_env.as_deref() else {
+            return Ok(None);
+        };
+        match std::env::var(var) {
+            Ok(header) if !header.trim().is_empty() => Ok(Some(header)),
+            _ => Err(anyhow::anyhow!(
+                "Environment variable '{var}' (http_extra_header_env) is not set"
+            )),
+        }
+    }
+
+    /// The credential helper, if one is set and it only names an installed helper.
+    ///
+    /// submod.toml is committed, so like git with `.gitmodules`, it only gets to name a
+    /// helper run as `git credential-<name>`; one that runs a command of its own belongs
+    /// in the user's git config.
+    pub fn helper(&self) -> Result<Option<&str>> {
+        let Some(helper) = self.credential_helper.as_deref() else {
+            return Ok(None);
+        };
+        let program = helper.split_whitespace().next().unwrap_or_default();
+        if helper.starts_with('!') || Path::new(program).is_absolute() {
+            return Err(anyhow::anyhow!(
+                "credential_helper {helper:?} runs a command, which submod won't take from submod.toml; name an installed helper like \"store\", or set this one with `git config credential.<url>.helper`"
+            ));
+        }
+        Ok(Some(helper))
+    }
+
+    /// These settings as git config overrides, in `(key, value)` form
+    pub fn config_overrides(&self, root: &Path) -> Result<Vec<(String, String)>> {
+        let mut overrides = Vec::new();
+        if let Some(command) = self.ssh_command(root) {
+            overrides.push(("core.sshCommand".to_string(), command));
+        }
+        if let Some(helper) = self.helper()? {
+            // An empty value first resets any helpers inherited from other config files
+            overrides.push(("credential.helper".to_string(), String::new()));
+            overrides.push(("credential.helper".to_string(), helper.to_string()));
+        }
+        if let Some(header) = self.http_extra_header()? {
+            overrides.push(("http.extraHeader".to_string(), header));
+        }
+        Ok(overrides)
+    }
+}
+
 /// Settings for a submodule that are not git-specific
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct OtherSubmoduleSettings {
@@ -454,6 +747,10 @@ pub struct SubmoduleEntry {
     pub url: Option<String>,
     /// Branch to track (optional)
     pub branch: Option<SerializableBranch>,
+    /// Branch to use when following the superproject's branch (`branch = "."`) and the
+    /// remote doesn't have it. Overrides `[defaults]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_branch: Option<String>,
     /// Ignore rule (optional)
     pub ignore: Option<SerializableIgnore>,
     /// Update strategy (optional)
@@ -463,8 +760,24 @@ pub struct SubmoduleEntry {
     pub fetch_recurse: Option<SerializableFetchRecurse>,
     /// Whether the submodule is active
     pub active: Option<bool>,
-    /// Whether the submodule is shallow (depth == 1)
+    /// Whether the submodule is shallow. Kept as an alias for `depth = 1`.
     pub shallow: Option<bool>,
+    /// Number of commits to fetch when cloning (`git clone --depth`). Takes precedence over `shallow`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depth: Option<u32>,
+    /// Only fetch history after this date when cloning (`git clone --shallow-since`), e.g. `"2024-01-01"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shallow_since: Option<String>,
+    /// Private key to use for SSH remotes, e.g. a deploy key (`~/` is expanded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<String>,
+    /// Git credential helper for HTTP(S) remotes, in `credential.helper` syntax
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_helper: Option<String>,
+    /// Name of an environment variable holding an `http.extraHeader` value.
+    /// The header itself is never stored in the config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_extra_header_env: Option<String>,
     /// Whether to skip initialization after adding
     #[serde(skip)] // never write, we use this for stateful decisions
     pub no_init: Option<bool>,
@@ -475,6 +788,12 @@ pub struct SubmoduleEntry {
     /// deny-all-by-default model.  Overrides the global `[defaults]` setting.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub use_git_default_sparse_checkout: Option<bool>,
+    /// Groups this submodule belongs to, for selecting submodules with `--group`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<String>>,
+    /// Lifecycle hooks for this submodule (`[<name>.hooks]`), layered over `[defaults.hooks]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<SubmoduleHooks>,
 }
 
 #[allow(dead_code)]
@@ -497,14 +816,22 @@ impl SubmoduleEntry {
             url, // keep url explicitly None if we can't get it right now
             path,
             branch,
+            fallback_branch: None,
             ignore,
             update,
             fetch_recurse,
             active,
             shallow,
+            depth: None,
+            shallow_since: None,
+            ssh_key: None,
+            credential_helper: None,
+            http_extra_header_env: None,
             no_init,
             sparse_paths: None,
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
         }
     }
 
@@ -677,6 +1004,19 @@ impl SubmoduleEntry {
     pub fn is_active(&self) -> bool {
         self.active.unwrap_or(true)
     }
+
+    /// The clone depth to use, resolving `shallow = true` as an alias for `depth = 1`.
+    /// Returns `None` for a full clone.
+    #[must_use]
+    pub fn effective_depth(&self) -> Option<u32> {
+        effective_depth(self.depth, self.shallow)
+    }
+
+    /// Whether this submodule is configured as a shallow clone (by depth or by date)
+    #[must_use]
+    pub fn is_shallow(&self) -> bool {
+        self.effective_depth().is_some() || self.shallow_since.is_some()
+    }
 }
 
 impl From<OtherSubmoduleSettings> for SubmoduleEntry {
@@ -687,13 +1027,21 @@ impl From<OtherSubmoduleSettings> for SubmoduleEntry {
             path: other.path,
             active: Some(other.active),
             shallow: Some(other.shallow),
+            depth: None,
+            shallow_since: None,
+            ssh_key: None,
+            credential_helper: None,
+            http_extra_header_env: None,
             ignore: default_git_options.ignore,
             fetch_recurse: default_git_options.fetch_recurse,
             branch: default_git_options.branch,
+            fallback_branch: None,
             update: default_git_options.update,
             no_init: Some(other.no_init),
             sparse_paths: None,
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
         }
     }
 }
@@ -784,6 +1132,38 @@ impl SubmoduleEntries {
         self.clone()
     }
 
+    /// Give the submodule `name` a new name, keeping its entry and sparse checkout.
+    ///
+    /// Returns `false`, changing nothing, if there's no submodule `name` or `new_name` is
+    /// already taken.
+    pub fn rename_submodule(&mut self, name: &str, new_name: SubmoduleName) -> bool {
+        let Some(submodules) = &mut self.submodules else {
+            return false;
+        };
+        if submodules.contains_key(&new_name) {
+            return false;
+        }
+        let Some(entry) = submodules.remove(name) else {
+            return false;
+        };
+        submodules.insert(new_name.clone(), entry);
+        if let Some(sparse_checkouts) = &mut self.sparse_checkouts
+            && let Some(checkout) = sparse_checkouts.remove(name)
+        {
+            sparse_checkouts.insert(new_name, checkout);
+        }
+        true
+    }
+
+    /// The name of the submodule at `path`. Git keys submodules by name, which needn't
+    /// match the path: `git submodule add --name` and renamed submodules both differ.
+    #[must_use]
+    pub fn name_for_path(&self, path: &str) -> Option<&SubmoduleName> {
+        self.submodule_iter()
+            .find(|(_, entry)| entry.path.as_deref() == Some(path))
+            .map(|(name, _)| name)
+    }
+
     /// Returns a list of all submodule names, or `None` if no submodules are configured.
     #[must_use]
     pub fn submodule_names(&self) -> Option<Vec<String>> {
@@ -1197,6 +1577,8 @@ impl Provider for Config {
 
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
+// figment::Jail's test closures return figment::Error, which is large and isn't ours to box
+#[allow(clippy::result_large_err)]
 mod tests {
     use super::*;
 
@@ -1211,12 +1593,16 @@ mod tests {
             fetch_recurse: Some(SerializableFetchRecurse::Always),
             update: Some(SerializableUpdate::Rebase),
             use_git_default_sparse_checkout: None,
+            hooks: None,
+            fallback_branch: None,
         };
         let other = SubmoduleDefaults {
             ignore: Some(SerializableIgnore::Dirty),
             fetch_recurse: None,
             update: Some(SerializableUpdate::Merge),
             use_git_default_sparse_checkout: None,
+            hooks: None,
+            fallback_branch: None,
         };
         let merged = base.merge_from(other);
         // other.ignore overrides
@@ -1234,6 +1620,8 @@ mod tests {
             fetch_recurse: Some(SerializableFetchRecurse::Never),
             update: Some(SerializableUpdate::Checkout),
             use_git_default_sparse_checkout: None,
+            hooks: None,
+            fallback_branch: None,
         };
         let other = SubmoduleDefaults::default();
         let merged = base.merge_from(other);
@@ -1251,6 +1639,8 @@ mod tests {
             fetch_recurse: Some(SerializableFetchRecurse::Always),
             update: Some(SerializableUpdate::Merge),
             use_git_default_sparse_checkout: None,
+            hooks: None,
+            fallback_branch: None,
         };
         let merged = base.merge_from(other);
         assert_eq!(merged.ignore, Some(SerializableIgnore::Dirty));
@@ -1280,12 +1670,16 @@ mod tests {
             fetch_recurse: None,
             update: None,
             use_git_default_sparse_checkout: None,
+            hooks: None,
+            fallback_branch: None,
         };
         let other = SubmoduleDefaults {
             ignore: None,
             fetch_recurse: None,
             update: None,
             use_git_default_sparse_checkout: Some(true),
+            hooks: None,
+            fallback_branch: None,
         };
         let merged = base.merge_from(other);
         assert_eq!(
@@ -1303,12 +1697,16 @@ mod tests {
             fetch_recurse: None,
             update: None,
             use_git_default_sparse_checkout: Some(true),
+            hooks: None,
+            fallback_branch: None,
         };
         let other = SubmoduleDefaults {
             ignore: None,
             fetch_recurse: None,
             update: None,
             use_git_default_sparse_checkout: Some(false),
+            hooks: None,
+            fallback_branch: None,
         };
         let merged = base.merge_from(other);
         assert_eq!(
@@ -1326,6 +1724,8 @@ mod tests {
             fetch_recurse: None,
             update: None,
             use_git_default_sparse_checkout: Some(true),
+            hooks: None,
+            fallback_branch: None,
         };
         let other = SubmoduleDefaults::default();
         let merged = base.merge_from(other);
@@ -1696,9 +2096,17 @@ mod tests {
             fetch_recurse: None,
             active: Some(true),
             shallow: None,
+            depth: None,
+            shallow_since: None,
+            ssh_key: None,
+            credential_helper: None,
+            http_extra_header_env: None,
             no_init: None,
             sparse_paths: Some(vec!["src/".to_string()]),
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
+            fallback_branch: None,
         };
         entries.update_entry("repo".to_string(), entry);
 
@@ -1722,9 +2130,17 @@ mod tests {
             fetch_recurse: None,
             active: Some(true),
             shallow: None,
+            depth: None,
+            shallow_since: None,
+            ssh_key: None,
+            credential_helper: None,
+            http_extra_header_env: None,
             no_init: None,
             sparse_paths: Some(vec!["src/".to_string()]),
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
+            fallback_branch: None,
         };
         entries.update_entry("repo".to_string(), entry_with_sparse);
         assert!(entries.sparse_checkouts().unwrap().contains_key("repo"));
@@ -1739,9 +2155,17 @@ mod tests {
             fetch_recurse: None,
             active: Some(true),
             shallow: None,
+            depth: None,
+            shallow_since: None,
+            ssh_key: None,
+            credential_helper: None,
+            http_extra_header_env: None,
             no_init: None,
             sparse_paths: None,
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
+            fallback_branch: None,
         };
         entries.update_entry("repo".to_string(), entry_no_sparse);
         assert!(!entries.sparse_checkouts().unwrap().contains_key("repo"));
@@ -1816,6 +2240,61 @@ mod tests {
         assert!(entries.contains_key("mod2"));
     }
 
+    #[test]
+    fn test_entries_rename_submodule() {
+        let entry = SubmoduleEntry::new(
+            Some("url".to_string()),
+            Some("libs/one".to_string()),
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            None,
+            None,
+        );
+        let mut entries = SubmoduleEntries::default()
+            .add_submodule("mod1".to_string(), entry.clone())
+            .add_submodule("mod2".to_string(), entry);
+        entries.add_checkout("mod1".to_string(), &["src".to_string()], true);
+
+        assert!(!entries.rename_submodule("mod1", "mod2".to_string()));
+        assert!(!entries.rename_submodule("missing", "mod3".to_string()));
+        assert!(entries.rename_submodule("mod1", "mod3".to_string()));
+        assert!(!entries.contains_key("mod1"));
+        assert_eq!(
+            entries.get("mod3").and_then(|e| e.path.as_deref()),
+            Some("libs/one")
+        );
+        assert_eq!(
+            entries.sparse_checkouts().and_then(|s| s.get("mod3")),
+            Some(&vec!["src".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_entries_name_for_path() {
+        let entry = SubmoduleEntry::new(
+            Some("url".to_string()),
+            Some("third_party/zlib".to_string()),
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            None,
+            None,
+        );
+        let entries = SubmoduleEntries::default().add_submodule("compression".to_string(), entry);
+        assert_eq!(
+            entries
+                .name_for_path("third_party/zlib")
+                .map(String::as_str),
+            Some("compression")
+        );
+        assert_eq!(entries.name_for_path("compression"), None);
+    }
+
     #[test]
     fn test_entries_iter_joins_sparse() {
         let mut entries = SubmoduleEntries::default();
@@ -1909,6 +2388,8 @@ mod tests {
             fetch_recurse: Some(SerializableFetchRecurse::Always),
             update: Some(SerializableUpdate::Rebase),
             use_git_default_sparse_checkout: None,
+            hooks: None,
+            fallback_branch: None,
         };
         let entry = SubmoduleEntry::new(
             Some("url".to_string()),
@@ -1939,6 +2420,8 @@ mod tests {
             fetch_recurse: Some(SerializableFetchRecurse::Always),
             update: Some(SerializableUpdate::Rebase),
             use_git_default_sparse_checkout: None,
+            hooks: None,
+            fallback_branch: None,
         };
         let entry = SubmoduleEntry::new(
             Some("url".to_string()),
@@ -1986,6 +2469,9 @@ mod tests {
             update: None,
             fetch_recurse: None,
             shallow: true,
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth::default(),
             no_init: false,
         };
         let entry = opts.into_submodule_entry();
@@ -2010,6 +2496,9 @@ mod tests {
             update: None,
             fetch_recurse: None,
             shallow: false,
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth::default(),
             no_init: true,
         };
         let entry = opts.into_submodule_entry();
@@ -2029,9 +2518,17 @@ mod tests {
             fetch_recurse: None,
             active: None,
             shallow: None,
+            depth: None,
+            shallow_since: None,
+            ssh_key: None,
+            credential_helper: None,
+            http_extra_header_env: None,
             no_init: None,
             sparse_paths: None,
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
+            fallback_branch: None,
         };
         let opts = SubmoduleAddOptions::from_submodule_entries_tuple(("mymod".to_string(), entry));
         // url fallback: path
@@ -2050,9 +2547,17 @@ mod tests {
             fetch_recurse: None,
             active: None,
             shallow: None,
+            depth: None,
+            shallow_since: None,
+            ssh_key: None,
+            credential_helper: None,
+            http_extra_header_env: None,
             no_init: None,
             sparse_paths: None,
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
+            fallback_branch: None,
         };
         let opts = SubmoduleAddOptions::from_submodule_entries_tuple(("mymod".to_string(), entry));
         // Falls back to name for both url and path
@@ -2103,9 +2608,17 @@ mod tests {
             fetch_recurse: Some(SerializableFetchRecurse::Always),
             active: Some(true),
             shallow: Some(false),
+            depth: None,
+            shallow_since: None,
+            ssh_key: None,
+            credential_helper: None,
+            http_extra_header_env: None,
             no_init: None,
             sparse_paths: Some(vec!["src/".to_string()]),
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
+            fallback_branch: None,
         };
         entries = entries.add_submodule("mymod".to_string(), entry);
 
@@ -2210,6 +2723,252 @@ active = true
         );
     }
 
+    #[test]
+    fn test_config_toml_depth_and_shallow_since() {
+        let toml_str = r#"
+[deep]
+path = "libs/deep"
+url = "https://example.com/deep.git"
+depth = 10
+shallow_since = "2024-01-01"
+
+[alias]
+path = "libs/alias"
+url = "https://example.com/alias.git"
+shallow = true
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let deep = config.submodules.get("deep").unwrap();
+        assert_eq!(deep.depth, Some(10));
+        assert_eq!(deep.shallow_since.as_deref(), Some("2024-01-01"));
+        assert_eq!(deep.effective_depth(), Some(10));
+        assert!(deep.is_shallow());
+
+        // `shallow = true` is an alias for `depth = 1`
+        let alias = config.submodules.get("alias").unwrap();
+        assert_eq!(alias.depth, None);
+        assert_eq!(alias.effective_depth(), Some(1));
+    }
+
+    #[test]
+    fn test_config_toml_hooks_tables() {
+        let toml_str = r#"
+[defaults.hooks]
+post_update = ["make gen"]
+timeout = 30
+
+[foo]
+path = "vendor/foo"
+url = "https://example.com/foo.git"
+
+[foo.hooks]
+post_update = ["make -C vendor/foo gen"]
+pre_sync = []
+on_failure = "abort"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let defaults = config.defaults.hooks.as_ref().unwrap();
+        assert_eq!(defaults.commands(HookEvent::PostUpdate), ["make gen"]);
+        assert_eq!(defaults.timeout, Some(30));
+        assert_eq!(defaults.on_failure, None);
+
+        let foo = config.submodules.get("foo").unwrap();
+        let own = foo.hooks.as_ref().unwrap();
+        assert_eq!(own.get(HookEvent::PreSync), Some(&[][..]));
+        assert_eq!(own.get(HookEvent::PreAdd), None);
+        assert_eq!(own.on_failure, Some(HookFailurePolicy::Abort));
+    }
+
+    // ================================================================
+    // SubmoduleHooks::merged_over
+    // ================================================================
+
+    #[test]
+    fn test_hooks_merged_over_defaults() {
+        let defaults = SubmoduleHooks {
+            pre_update: Some(vec!["echo pre".to_string()]),
+            post_update: Some(vec!["make gen".to_string()]),
+            post_sync: Some(vec!["make check".to_string()]),
+            timeout: Some(30),
+            on_failure: Some(HookFailurePolicy::Abort),
+            ..SubmoduleHooks::default()
+        };
+        let own = SubmoduleHooks {
+            post_update: Some(vec!["make -C vendor/foo gen".to_string()]),
+            post_sync: Some(Vec::new()),
+            timeout: Some(5),
+            ..SubmoduleHooks::default()
+        };
+        let merged = own.merged_over(&defaults);
+
+        // Unset events come from the defaults
+        assert_eq!(merged.commands(HookEvent::PreUpdate), ["echo pre"]);
+        // A submodule's list replaces the defaults' list rather than adding to it
+        assert_eq!(
+            merged.commands(HookEvent::PostUpdate),
+            ["make -C vendor/foo gen"]
+        );
+        // An empty list switches the event off
+        assert!(merged.commands(HookEvent::PostSync).is_empty());
+        assert!(merged.commands(HookEvent::PreAdd).is_empty());
+        assert_eq!(merged.timeout, Some(5));
+        assert_eq!(merged.on_failure, Some(HookFailurePolicy::Abort));
+    }
+
+    // ================================================================
+    // Clone depth resolution
+    // ================================================================
+
+    #[test]
+    fn test_effective_depth_resolution() {
+        assert_eq!(effective_depth(None, None), None);
+        assert_eq!(effective_depth(None, Some(false)), None);
+        assert_eq!(effective_depth(None, Some(true)), Some(1));
+        // An explicit depth wins over the alias, and 0 means a full clone
+        assert_eq!(effective_depth(Some(5), Some(true)), Some(5));
+        assert_eq!(effective_depth(Some(0), Some(true)), None);
+    }
+
+    #[test]
+    fn test_update_options_with_shallow_from_entry() {
+        let mut entry = SubmoduleEntry::new(
+            Some("https://example.com/repo.git".to_string()),
+            Some("libs/repo".to_string()),
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            Some(true),
+            None,
+        );
+        let opts = SubmoduleUpdateOptions::default().with_shallow_from(&entry);
+        assert_eq!(opts.depth, Some(1));
+        assert!(opts.is_shallow());
+
+        entry.shallow = None;
+        entry.shallow_since = Some("2024-01-01".to_string());
+        let opts = SubmoduleUpdateOptions::default().with_shallow_from(&entry);
+        assert_eq!(opts.depth, None);
+        assert_eq!(opts.shallow_since.as_deref(), Some("2024-01-01"));
+        assert!(opts.is_shallow());
+
+        assert!(!SubmoduleUpdateOptions::default().is_shallow());
+    }
+
+    // ================================================================
+    // SubmoduleAuth
+    // ================================================================
+
+    #[test]
+    fn test_auth_ssh_key_path_resolution() {
+        let root = Path::new("/work/super");
+        let auth = |key: &str| SubmoduleAuth {
+            ssh_key: Some(key.to_string()),
+            ..SubmoduleAuth::default()
+        };
+        assert_eq!(
+            auth("/keys/deploy").ssh_key_path(root),
+            Some(PathBuf::from("/keys/deploy"))
+        );
+        assert_eq!(
+            auth("keys/deploy").ssh_key_path(root),
+            Some(PathBuf::from("/work/super/keys/deploy"))
+        );
+        if let Some(home) = std::env::var_os("HOME") {
+            assert_eq!(
+                auth("~/.ssh/deploy").ssh_key_path(root),
+                Some(PathBuf::from(home).join(".ssh/deploy"))
+            );
+        }
+        assert_eq!(SubmoduleAuth::default().ssh_key_path(root), None);
+    }
+
+    #[test]
+    fn test_auth_config_overrides() {
+        let auth = SubmoduleAuth {
+            ssh_key: Some("/keys/it's".to_string()),
+            credential_helper: Some("store".to_string()),
+            http_extra_header_env: None,
+        };
+        let overrides = auth.config_overrides(Path::new("/")).unwrap();
+        let (key, command) = &overrides[0];
+        assert_eq!(key, "core.sshCommand");
+        assert!(
+            command.ends_with(" -i '/keys/it'\\''s' -o IdentitiesOnly=yes"),
+            "{command}"
+        );
+        // The empty helper clears inherited helpers before ours is added
+        assert_eq!(
+            overrides[1..],
+            [
+                ("credential.helper".to_string(), String::new()),
+                ("credential.helper".to_string(), "store".to_string()),
+            ]
+        );
+        assert!(
+            SubmoduleAuth::default()
+                .config_overrides(Path::new("/"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_auth_refuses_helpers_that_run_commands() {
+        for helper in ["!f() { echo password=x; }; f", "/tmp/helper --flag"] {
+            let auth = SubmoduleAuth {
+                credential_helper: Some(helper.to_string()),
+                ..SubmoduleAuth::default()
+            };
+            let err = auth.config_overrides(Path::new("/")).unwrap_err();
+            assert!(err.to_string().contains("runs a command"), "{err}");
+        }
+        let auth = SubmoduleAuth {
+            credential_helper: Some("store --file ~/.submod-credentials".to_string()),
+            ..SubmoduleAuth::default()
+        };
+        assert!(auth.config_overrides(Path::new("/")).is_ok());
+    }
+
+    #[test]
+    fn test_auth_missing_header_env_is_an_error() {
+        let auth = SubmoduleAuth {
+            http_extra_header_env: Some("SUBMOD_TEST_UNSET_HEADER_VAR".to_string()),
+            ..SubmoduleAuth::default()
+        };
+        let err = auth.config_overrides(Path::new("/")).unwrap_err();
+        assert!(err.to_string().contains("SUBMOD_TEST_UNSET_HEADER_VAR"));
+    }
+
+    #[test]
+    fn test_auth_from_entry_and_into_add_options() {
+        let toml = r#"
+[private]
+path = "libs/private"
+url = "git@example.com:org/private.git"
+ssh_key = "~/.ssh/deploy"
+credential_helper = "store"
+http_extra_header_env = "PRIVATE_HEADER"
+"#;
+        let entries: SubmoduleEntries = toml::from_str(toml).unwrap();
+        let entry = entries.get("private").unwrap().clone();
+        let opts =
+            SubmoduleAddOptions::from_submodule_entries_tuple(("private".to_string(), entry));
+        assert_eq!(opts.auth.ssh_key.as_deref(), Some("~/.ssh/deploy"));
+        assert_eq!(opts.auth.credential_helper.as_deref(), Some("store"));
+        assert_eq!(
+            opts.auth.http_extra_header_env.as_deref(),
+            Some("PRIVATE_HEADER")
+        );
+        let entry = opts.into_submodule_entry();
+        assert_eq!(
+            entry.http_extra_header_env.as_deref(),
+            Some("PRIVATE_HEADER")
+        );
+        assert!(!SubmoduleAuth::from_entry(&entry).is_empty());
+    }
+
     // ================================================================
     // SubmoduleGitOptions
     // ================================================================
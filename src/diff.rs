@@ -0,0 +1,629 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Summaries of how submodules changed between two superproject revisions, for `submod diff`.
+
+Each side is a set of gitlinks: the commits a superproject tree (or, for the working tree,
+the checked-out submodules) records for its submodule paths, named by that side's
+`.gitmodules`. Gitlinks pair up by name, and a removed and an added submodule with the same
+URL pair up as a move, since git names submodules by their path. For each change, the
+commits between the old and new gitlink are read from the submodule's own repository.
+"]
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// A commit a superproject records for a submodule path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gitlink {
+    /// The submodule's name in that side's `.gitmodules`, or its path if it isn't listed
+    pub name: String,
+    /// The submodule's path, relative to the superproject root
+    pub path: String,
+    /// The submodule's URL in that side's `.gitmodules`
+    pub url: Option<String>,
+    /// The recorded commit
+    pub oid: String,
+}
+
+/// How a submodule changed between the two sides
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// Only the new side has the submodule
+    Added,
+    /// Only the old side has the submodule
+    Removed,
+    /// The submodule's path changed, and maybe its commit too
+    Moved,
+    /// The submodule's commit changed
+    Updated,
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added => write!(f, "added"),
+            Self::Removed => write!(f, "removed"),
+            Self::Moved => write!(f, "moved"),
+            Self::Updated => write!(f, "updated"),
+        }
+    }
+}
+
+/// One commit in a submodule's range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommitSummary {
+    /// The full commit id
+    pub id: String,
+    /// The author's name
+    pub author: String,
+    /// The first line of the message
+    pub subject: String,
+}
+
+/// Files and lines changed between the old and new commit of a submodule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DiffStat {
+    /// Number of files changed
+    pub files_changed: usize,
+    /// Number of lines added
+    pub insertions: usize,
+    /// Number of lines removed
+    pub deletions: usize,
+}
+
+/// How one submodule changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SubmoduleChange {
+    /// The name to show for the submodule
+    pub name: String,
+    /// What happened to it
+    pub kind: ChangeKind,
+    /// Its path on the old side
+    pub old_path: Option<String>,
+    /// Its path on the new side
+    pub new_path: Option<String>,
+    /// The commit the old side records
+    pub old_oid: Option<String>,
+    /// The commit the new side records
+    pub new_oid: Option<String>,
+    /// Commits in the new commit but not the old one, newest first
+    pub commits: Vec<CommitSummary>,
+    /// Commits in the old commit but not the new one, when the submodule went back or sideways
+    pub rewound: Vec<CommitSummary>,
+    /// Files and lines changed between the old and new commit
+    pub stat: Option<DiffStat>,
+    /// Why commits or the diffstat are missing, if they are
+    pub note: Option<String>,
+}
+
+impl SubmoduleChange {
+    /// The submodule's path, preferring the new side
+    #[must_use]
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// The commit range as `old..new`, abbreviated, or the single commit of an added or
+    /// removed submodule
+    #[must_use]
+    pub fn range(&self) -> String {
+        match (&self.old_oid, &self.new_oid) {
+            (Some(old), Some(new)) if old == new => short(new).to_string(),
+            (Some(old), Some(new)) => format!("{}..{}", short(old), short(new)),
+            (Some(oid), None) | (None, Some(oid)) => short(oid).to_string(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// What happened, e.g. `moved lib/a → lib/b`
+    #[must_use]
+    pub fn headline(&self) -> String {
+        match self.kind {
+            ChangeKind::Moved => format!(
+                "moved {} → {}",
+                self.old_path.as_deref().unwrap_or_default(),
+                self.new_path.as_deref().unwrap_or_default()
+            ),
+            ChangeKind::Added => format!("added at {}", self.path()),
+            ChangeKind::Removed => format!("removed from {}", self.path()),
+            ChangeKind::Updated => format!("updated at {}", self.path()),
+        }
+    }
+}
+
+/// The gitlinks in the tree of `rev`, named by that tree's `.gitmodules`
+pub fn gitlinks_at_rev(repo: &gix::Repository, rev: &str) -> Result<Vec<Gitlink>> {
+    let tree = repo
+        .rev_parse_single(rev)
+        .with_context(|| format!("Failed to resolve revision '{rev}'"))?
+        .object()?
+        .peel_to_tree()
+        .with_context(|| format!("Revision '{rev}' has no tree"))?;
+
+    let mut recorder = gix::traverse::tree::Recorder::default();
+    tree.traverse()
+        .breadthfirst(&mut recorder)
+        .with_context(|| format!("Failed to read the tree of '{rev}'"))?;
+    let links: Vec<(String, String)> = recorder
+        .records
+        .into_iter()
+        .filter(|entry| entry.mode.is_commit())
+        .map(|entry| (entry.filepath.to_string(), entry.oid.to_string()))
+        .collect();
+
+    let gitmodules = match tree.lookup_entry_by_path(".gitmodules")? {
+        Some(entry) => Some(entry.object()?.data.clone()),
+        None => None,
+    };
+    Ok(name_gitlinks(repo, links, gitmodules.as_deref()))
+}
+
+/// The gitlinks of the working tree: every submodule in the index, at its checked-out
+/// commit, or at the commit the index records if it isn't checked out
+pub fn gitlinks_in_worktree(repo: &gix::Repository) -> Result<Vec<Gitlink>> {
+    let root = repo
+        .workdir()
+        .context("Repository has no working directory")?;
+    let index = repo.index_or_empty()?;
+    let links: Vec<(String, String)> = index
+        .entries()
+        .iter()
+        .filter(|entry| entry.mode == gix::index::entry::Mode::COMMIT)
+        .map(|entry| {
+            let path = entry.path(&index).to_string();
+            let oid = gix::open(root.join(&path))
+                .ok()
+                .and_then(|sub| sub.head_id().ok().map(|id| id.to_string()))
+                .unwrap_or_else(|| entry.id.to_string());
+            (path, oid)
+        })
+        .collect();
+
+    let gitmodules = std::fs::read(root.join(".gitmodules")).ok();
+    Ok(name_gitlinks(repo, links, gitmodules.as_deref()))
+}
+
+/// Attach names and URLs from a `.gitmodules` to `(path, oid)` pairs
+fn name_gitlinks(
+    repo: &gix::Repository,
+    links: Vec<(String, String)>,
+    gitmodules: Option<&[u8]>,
+) -> Vec<Gitlink> {
+    let mut by_path: HashMap<String, (String, Option<String>)> = HashMap::new();
+    let config = repo.config_snapshot();
+    if let Some(file) =
+        gitmodules.and_then(|bytes| gix_submodule::File::from_bytes(bytes, None, &config).ok())
+    {
+        for name in file.names() {
+            if let Ok(path) = file.path(name) {
+                let url = file.url(name).ok().map(|url| url.to_bstring().to_string());
+                by_path.insert(path.to_string(), (name.to_string(), url));
+            }
+        }
+    }
+    links
+        .into_iter()
+        .map(|(path, oid)| {
+            let (name, url) = by_path
+                .remove(&path)
+                .unwrap_or_else(|| (path.clone(), None));
+            Gitlink {
+                name,
+                path,
+                url,
+                oid,
+            }
+        })
+        .collect()
+}
+
+/// Pair the gitlinks of two sides into the changes between them, sorted by path.
+///
+/// Commits, diffstats and notes are left empty; see [`describe`].
+#[must_use]
+pub fn pair_gitlinks(old: &[Gitlink], new: &[Gitlink]) -> Vec<SubmoduleChange> {
+    let mut unmatched_new: BTreeMap<&str, &Gitlink> =
+        new.iter().map(|link| (link.name.as_str(), link)).collect();
+    let mut changes = Vec::new();
+    let mut removed = Vec::new();
+
+    for old_link in old {
+        match unmatched_new.remove(old_link.name.as_str()) {
+            Some(new_link) if new_link.path != old_link.path => {
+                changes.push(change(ChangeKind::Moved, Some(old_link), Some(new_link)));
+            }
+            Some(new_link) if new_link.oid != old_link.oid => {
+                changes.push(change(ChangeKind::Updated, Some(old_link), Some(new_link)));
+            }
+            Some(_) => {}
+            None => removed.push(old_link),
+        }
+    }
+
+    // A new path means a new name, so moves show up as a removal plus an addition
+    let mut added: Vec<&Gitlink> = unmatched_new.into_values().collect();
+    for old_link in removed {
+        let moved_to = old_link.url.as_ref().and_then(|url| {
+            added
+                .iter()
+                .position(|new_link| new_link.url.as_ref() == Some(url))
+        });
+        match moved_to {
+            Some(index) => {
+                let new_link = added.remove(index);
+                changes.push(change(ChangeKind::Moved, Some(old_link), Some(new_link)));
+            }
+            None => changes.push(change(ChangeKind::Removed, Some(old_link), None)),
+        }
+    }
+    for new_link in added {
+        changes.push(change(ChangeKind::Added, None, Some(new_link)));
+    }
+
+    changes.sort_by(|a, b| a.path().cmp(b.path()));
+    changes
+}
+
+fn change(kind: ChangeKind, old: Option<&Gitlink>, new: Option<&Gitlink>) -> SubmoduleChange {
+    SubmoduleChange {
+        name: new
+            .or(old)
+            .map(|link| link.name.clone())
+            .unwrap_or_default(),
+        kind,
+        old_path: old.map(|link| link.path.clone()),
+        new_path: new.map(|link| link.path.clone()),
+        old_oid: old.map(|link| link.oid.clone()),
+        new_oid: new.map(|link| link.oid.clone()),
+        commits: Vec::new(),
+        rewound: Vec::new(),
+        stat: None,
+        note: None,
+    }
+}
+
+/// Fill in the commits and diffstat of `change` from the submodule's repository, which is
+/// looked for at its new path, then its old one, under `root`
+pub fn describe(change: &mut SubmoduleChange, root: &Path) {
+    let candidates = [change.new_path.as_deref(), change.old_path.as_deref()];
+    let Some(sub_path) = candidates
+        .into_iter()
+        .flatten()
+        .map(|path| root.join(path))
+        .find(|path| gix::open(path).is_ok())
+    else {
+        change.note = Some("not checked out, so its commits can't be listed".to_string());
+        return;
+    };
+    if let Err(e) = describe_from(change, &sub_path) {
+        change.note = Some(format!("{e:#}"));
+    }
+}
+
+fn describe_from(change: &mut SubmoduleChange, sub_path: &Path) -> Result<()> {
+    let repo = gix::open(sub_path)?;
+    let missing = "commits aren't available locally; fetch the submodule to see them";
+    let oid = |hex: &str| gix::ObjectId::from_hex(hex.as_bytes()).context("invalid commit id");
+    match (&change.old_oid, &change.new_oid) {
+        (Some(old), Some(new)) if old != new => {
+            let (old, new) = (oid(old)?, oid(new)?);
+            if repo.find_object(old).is_err() || repo.find_object(new).is_err() {
+                anyhow::bail!(missing);
+            }
+            change.commits = commits_between(&repo, old, new)?;
+            change.rewound = commits_between(&repo, new, old)?;
+            change.stat = Some(diffstat(sub_path, old, new)?);
+        }
+        (None, Some(new)) => {
+            let commit = repo
+                .find_commit(oid(new)?)
+                .map_err(|_| anyhow::anyhow!(missing))?;
+            change.commits = vec![summarize(&commit)?];
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Commits reachable from `to` but not from `from`, newest first
+fn commits_between(
+    repo: &gix::Repository,
+    from: gix::ObjectId,
+    to: gix::ObjectId,
+) -> Result<Vec<CommitSummary>> {
+    repo.rev_walk([to])
+        .with_hidden([from])
+        .all()?
+        .map(|info| summarize(&info?.object()?))
+        .collect()
+}
+
+fn summarize(commit: &gix::Commit<'_>) -> Result<CommitSummary> {
+    Ok(CommitSummary {
+        id: commit.id.to_string(),
+        author: commit.author()?.name.to_string(),
+        subject: commit.message()?.summary().to_string(),
+    })
+}
+
+/// Files and lines changed between two commits of the repository at `path`
+fn diffstat(path: &Path, old: gix::ObjectId, new: gix::ObjectId) -> Result<DiffStat> {
+    // git2 has line statistics built in; gix would need a blob diff per changed file
+    let repo = git2::Repository::open(path)?;
+    let tree = |oid: gix::ObjectId| -> Result<git2::Tree<'_>> {
+        let commit = repo.find_commit(git2::Oid::from_bytes(oid.as_bytes())?)?;
+        Ok(commit.tree()?)
+    };
+    let stats = repo
+        .diff_tree_to_tree(Some(&tree(old)?), Some(&tree(new)?), None)?
+        .stats()?;
+    Ok(DiffStat {
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
+}
+
+/// The first seven characters of a commit id
+fn short(oid: &str) -> &str {
+    oid.get(..7).unwrap_or(oid)
+}
+
+/// Commits grouped by author, like `git shortlog`, with authors in order of first appearance
+fn shortlog(commits: &[CommitSummary]) -> Vec<(&str, Vec<&CommitSummary>)> {
+    let mut groups: Vec<(&str, Vec<&CommitSummary>)> = Vec::new();
+    for commit in commits {
+        match groups
+            .iter_mut()
+            .find(|(author, _)| *author == commit.author)
+        {
+            Some((_, group)) => group.push(commit),
+            None => groups.push((&commit.author, vec![commit])),
+        }
+    }
+    groups
+}
+
+fn stat_line(stat: &DiffStat) -> String {
+    let plural = |count: usize, one: &str, many: &str| {
+        format!("{count} {}", if count == 1 { one } else { many })
+    };
+    format!(
+        "{}, {}(+), {}(-)",
+        plural(stat.files_changed, "file changed", "files changed"),
+        plural(stat.insertions, "insertion", "insertions"),
+        plural(stat.deletions, "deletion", "deletions")
+    )
+}
+
+/// The changes as plain text
+#[must_use]
+pub fn render_text(changes: &[SubmoduleChange], from: &str, to: &str) -> String {
+    if changes.is_empty() {
+        return format!("No submodule changes between {from} and {to}\n");
+    }
+    let mut out = format!("Submodule changes between {from} and {to}:\n");
+    for change in changes {
+        let mark = match change.kind {
+            ChangeKind::Added => "➕",
+            ChangeKind::Removed => "➖",
+            ChangeKind::Moved => "🚚",
+            ChangeKind::Updated => "📦",
+        };
+        let _ = writeln!(
+            out,
+            "\n{mark} {} ({}) {}",
+            change.name,
+            change.headline(),
+            change.range()
+        );
+        for (author, commits) in shortlog(&change.commits) {
+            let _ = writeln!(out, "   {author} ({}):", commits.len());
+            for commit in commits {
+                let _ = writeln!(out, "      {} {}", short(&commit.id), commit.subject);
+            }
+        }
+        if !change.rewound.is_empty() {
+            let _ = writeln!(out, "   No longer included:");
+            for commit in &change.rewound {
+                let _ = writeln!(out, "      {} {}", short(&commit.id), commit.subject);
+            }
+        }
+        if let Some(stat) = &change.stat {
+            let _ = writeln!(out, "   {}", stat_line(stat));
+        }
+        if let Some(note) = &change.note {
+            let _ = writeln!(out, "   ⚠️  {note}");
+        }
+    }
+    out
+}
+
+/// The changes as Markdown, e.g. for a pull request description
+#[must_use]
+pub fn render_markdown(changes: &[SubmoduleChange], from: &str, to: &str) -> String {
+    if changes.is_empty() {
+        return format!("No submodule changes between `{from}` and `{to}`.\n");
+    }
+    let mut out = format!("## Submodule changes between `{from}` and `{to}`\n");
+    for change in changes {
+        let _ = writeln!(
+            out,
+            "\n### `{}`: {} (`{}`)\n",
+            change.name,
+            change.headline(),
+            change.range()
+        );
+        for (author, commits) in shortlog(&change.commits) {
+            let _ = writeln!(out, "**{author}** ({}):\n", commits.len());
+            for commit in commits {
+                let _ = writeln!(out, "- `{}` {}", short(&commit.id), commit.subject);
+            }
+            out.push('\n');
+        }
+        if !change.rewound.is_empty() {
+            out.push_str("No longer included:\n\n");
+            for commit in &change.rewound {
+                let _ = writeln!(out, "- `{}` {}", short(&commit.id), commit.subject);
+            }
+            out.push('\n');
+        }
+        if let Some(stat) = &change.stat {
+            let _ = writeln!(out, "{}\n", stat_line(stat));
+        }
+        if let Some(note) = &change.note {
+            let _ = writeln!(out, "> ⚠️ {note}\n");
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+/// The changes as a JSON document with `from`, `to` and `submodules` keys
+pub fn render_json(changes: &[SubmoduleChange], from: &str, to: &str) -> Result<String> {
+    #[derive(Serialize)]
+    struct Document<'a> {
+        from: &'a str,
+        to: &'a str,
+        submodules: &'a [SubmoduleChange],
+    }
+    let mut json = serde_json::to_string_pretty(&Document {
+        from,
+        to,
+        submodules: changes,
+    })?;
+    json.push('\n');
+    Ok(json)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn link(name: &str, path: &str, url: &str, oid: &str) -> Gitlink {
+        Gitlink {
+            name: name.to_string(),
+            path: path.to_string(),
+            url: Some(url.to_string()),
+            oid: oid.repeat(40 / oid.len()),
+        }
+    }
+
+    fn commit(id: &str, author: &str, subject: &str) -> CommitSummary {
+        CommitSummary {
+            id: id.repeat(40 / id.len()),
+            author: author.to_string(),
+            subject: subject.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_pair_gitlinks_finds_every_kind_of_change() {
+        let old = [
+            link("lib/same", "lib/same", "u-same", "a"),
+            link("lib/bumped", "lib/bumped", "u-bumped", "a"),
+            link("lib/gone", "lib/gone", "u-gone", "a"),
+            link("lib/old-home", "lib/old-home", "u-moved", "a"),
+            link("renamed", "lib/renamed-old", "u-renamed", "a"),
+        ];
+        let new = [
+            link("lib/same", "lib/same", "u-same", "a"),
+            link("lib/bumped", "lib/bumped", "u-bumped", "b"),
+            link("lib/new-home", "lib/new-home", "u-moved", "b"),
+            link("renamed", "lib/renamed-new", "u-renamed", "a"),
+            link("lib/fresh", "lib/fresh", "u-fresh", "c"),
+        ];
+
+        let changes = pair_gitlinks(&old, &new);
+        let summary: Vec<(ChangeKind, &str, String)> = changes
+            .iter()
+            .map(|change| (change.kind, change.path(), change.range()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    ChangeKind::Updated,
+                    "lib/bumped",
+                    "aaaaaaa..bbbbbbb".to_string()
+                ),
+                (ChangeKind::Added, "lib/fresh", "ccccccc".to_string()),
+                (ChangeKind::Removed, "lib/gone", "aaaaaaa".to_string()),
+                (
+                    ChangeKind::Moved,
+                    "lib/new-home",
+                    "aaaaaaa..bbbbbbb".to_string()
+                ),
+                (ChangeKind::Moved, "lib/renamed-new", "aaaaaaa".to_string()),
+            ]
+        );
+        assert_eq!(changes[3].headline(), "moved lib/old-home → lib/new-home");
+    }
+
+    #[test]
+    fn test_render_text_groups_commits_by_author() {
+        let mut change = pair_gitlinks(
+            &[link("lib", "lib", "u", "a")],
+            &[link("lib", "lib", "u", "b")],
+        )
+        .remove(0);
+        change.commits = vec![
+            commit("3", "Ada", "Third"),
+            commit("2", "Grace", "Second"),
+            commit("1", "Ada", "First"),
+        ];
+        change.stat = Some(DiffStat {
+            files_changed: 1,
+            insertions: 2,
+            deletions: 0,
+        });
+
+        let text = render_text(&[change], "HEAD~1", "HEAD");
+        assert_eq!(
+            text,
+            "Submodule changes between HEAD~1 and HEAD:\n\
+             \n📦 lib (updated at lib) aaaaaaa..bbbbbbb\n\
+             \x20  Ada (2):\n\
+             \x20     3333333 Third\n\
+             \x20     1111111 First\n\
+             \x20  Grace (1):\n\
+             \x20     2222222 Second\n\
+             \x20  1 file changed, 2 insertions(+), 0 deletions(-)\n"
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_and_json() {
+        let mut change = pair_gitlinks(&[], &[link("lib", "lib", "u", "c")]).remove(0);
+        change.note = Some("not checked out".to_string());
+
+        let markdown = render_markdown(std::slice::from_ref(&change), "main", "HEAD");
+        assert!(markdown.starts_with("## Submodule changes between `main` and `HEAD`\n"));
+        assert!(markdown.contains("### `lib`: added at lib (`ccccccc`)"));
+        assert!(markdown.ends_with("> ⚠️ not checked out\n"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_json(&[change], "main", "HEAD").unwrap()).unwrap();
+        assert_eq!(json["to"], "HEAD");
+        assert_eq!(json["submodules"][0]["kind"], "added");
+        assert_eq!(json["submodules"][0]["new_oid"], "c".repeat(40));
+        assert!(json["submodules"][0]["old_path"].is_null());
+
+        assert_eq!(
+            render_text(&[], "a", "b"),
+            "No submodule changes between a and b\n"
+        );
+    }
+}
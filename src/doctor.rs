@@ -0,0 +1,778 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Drift detection across the places a submodule is recorded, for `submod doctor`.
+
+A submodule's settings live in `submod.toml`, in the committed `.gitmodules`, and in this
+clone's `.git/config`; its repository lives under `.git/modules`, and the superproject's
+index records its commit as a gitlink. [`examine`] lines these up and reports:
+
+- every field (`url`, `branch`, `ignore`, `update`, `fetchRecurse`, `active`) whose values
+  disagree, after normalizing spellings (`yes` and `true` are the same `fetchRecurse`)
+  and filling in git's defaults for unset keys
+- submodules missing from one of the config files
+- `.git/config` sections keyed differently from `.gitmodules`, which git ignores
+- module directories no submodule claims, and submodules without a gitlink
+
+`.git/config` only overrides `.gitmodules`, so its `branch`, `ignore`, `update` and
+`fetchRecurse` are compared only when set.
+"]
+
+use crate::options::{
+    ConfigSource, GitmodulesConvert, SerializableBranch, SerializableFetchRecurse,
+    SerializableIgnore, SerializableUpdate,
+};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// The `submodule.<key>.*` values of a git config file, by section key and then by
+/// lowercased value name
+pub type Sections = BTreeMap<String, BTreeMap<String, String>>;
+
+/// A submodule setting `doctor` compares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Field {
+    /// `url`
+    Url,
+    /// `branch`
+    Branch,
+    /// `ignore`
+    Ignore,
+    /// `update`
+    Update,
+    /// `fetchRecurse` (`fetchRecurseSubmodules` in git's files)
+    FetchRecurse,
+    /// `active`
+    Active,
+}
+
+impl Field {
+    /// Every field, in report order
+    pub const ALL: [Self; 6] = [
+        Self::Url,
+        Self::Branch,
+        Self::Ignore,
+        Self::Update,
+        Self::FetchRecurse,
+        Self::Active,
+    ];
+
+    /// The field's name in `submod.toml`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Url => "url",
+            Self::Branch => "branch",
+            Self::Ignore => "ignore",
+            Self::Update => "update",
+            Self::FetchRecurse => "fetchRecurse",
+            Self::Active => "active",
+        }
+    }
+
+    /// The field's key in `.gitmodules` and `.git/config`
+    #[must_use]
+    pub const fn git_key(self) -> &'static str {
+        match self {
+            Self::FetchRecurse => "fetchRecurseSubmodules",
+            other => other.as_str(),
+        }
+    }
+
+    /// What git assumes when the key is unset, for fields with a fixed default
+    #[must_use]
+    pub const fn git_default(self) -> Option<&'static str> {
+        match self {
+            Self::Ignore => Some("none"),
+            Self::Update => Some("checkout"),
+            Self::FetchRecurse => Some("on-demand"),
+            Self::Url | Self::Branch | Self::Active => None,
+        }
+    }
+
+    /// A value in the spelling `.gitmodules` uses, so equivalent values compare equal
+    #[must_use]
+    pub fn canonical(self, raw: &str) -> String {
+        let raw = raw.trim();
+        let known = match self {
+            Self::Ignore => SerializableIgnore::from_gitmodules(raw).map(|v| v.to_gitmodules()),
+            Self::Update => SerializableUpdate::from_gitmodules(raw).map(|v| v.to_gitmodules()),
+            Self::FetchRecurse => {
+                SerializableFetchRecurse::from_gitmodules(git_bool(raw).unwrap_or(raw))
+                    .map(|v| v.to_gitmodules())
+            }
+            Self::Active => git_bool(raw).map(str::to_string).ok_or(()),
+            Self::Branch => SerializableBranch::from_gitmodules(raw).map(|v| v.to_gitmodules()),
+            Self::Url => Err(()),
+        };
+        known.unwrap_or_else(|()| raw.to_string())
+    }
+
+    /// The value a config file gives the field: its canonical value, or git's default
+    fn in_file(self, section: &BTreeMap<String, String>) -> Option<String> {
+        section
+            .get(&self.git_key().to_ascii_lowercase())
+            .map(|raw| self.canonical(raw))
+            .filter(|value| !value.is_empty())
+            .or_else(|| self.git_default().map(str::to_string))
+    }
+}
+
+/// A git boolean in its canonical spelling
+fn git_bool(raw: &str) -> Option<&'static str> {
+    match raw.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some("true"),
+        "false" | "no" | "off" | "0" => Some("false"),
+        _ => None,
+    }
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A submodule as `submod.toml` describes it, with defaults applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TomlSubmodule {
+    /// The submodule's name in `submod.toml`
+    pub name: String,
+    /// The submodule's path
+    pub path: String,
+    /// Effective values, in `.gitmodules` spelling; unset fields are absent or empty
+    pub values: BTreeMap<Field, String>,
+}
+
+/// Everything `doctor` compares
+#[derive(Debug, Clone, Copy)]
+pub struct Sources<'a> {
+    /// The submodules in `submod.toml`
+    pub toml: &'a [TomlSubmodule],
+    /// The submodule sections of `.gitmodules`
+    pub gitmodules: &'a Sections,
+    /// The submodule sections of `.git/config`
+    pub git_config: &'a Sections,
+    /// Git directories under `.git/modules`, relative to it
+    pub module_dirs: &'a BTreeSet<String>,
+    /// Paths the superproject's index records gitlinks for
+    pub gitlinks: &'a BTreeSet<String>,
+}
+
+/// A field whose values disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drift {
+    /// The field
+    pub field: Field,
+    /// Each compared source's value; `None` is unset
+    pub values: Vec<(ConfigSource, Option<String>)>,
+}
+
+impl Drift {
+    /// The value to make every source agree on, taking `truth` as right.
+    ///
+    /// When `truth` doesn't weigh in on the field (`.gitmodules` has no say in `active`,
+    /// and an unset `.git/config` key defers to `.gitmodules`), the value git actually
+    /// uses stands in for it. `None` means the field should be unset.
+    #[must_use]
+    pub fn resolved(&self, truth: ConfigSource) -> Option<String> {
+        [
+            truth,
+            ConfigSource::GitConfig,
+            ConfigSource::Gitmodules,
+            ConfigSource::SubmodToml,
+        ]
+        .into_iter()
+        .find_map(|source| self.value_in(source))
+        .cloned()
+        .flatten()
+    }
+
+    /// The sources whose value differs from the one resolved for `truth`
+    #[must_use]
+    pub fn sources_to_fix(&self, truth: ConfigSource) -> Vec<ConfigSource> {
+        let resolved = self.resolved(truth);
+        self.values
+            .iter()
+            .filter(|(_, value)| *value != resolved)
+            .map(|(source, _)| *source)
+            .collect()
+    }
+
+    fn value_in(&self, source: ConfigSource) -> Option<&Option<String>> {
+        self.values
+            .iter()
+            .find(|(candidate, _)| *candidate == source)
+            .map(|(_, value)| value)
+    }
+}
+
+/// What `doctor` found for one submodule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmoduleReport {
+    /// The submodule's name in `submod.toml`, or its `.gitmodules` key if it isn't there
+    pub name: String,
+    /// The submodule's path
+    pub path: String,
+    /// The key of its `.gitmodules` section
+    pub gitmodules_key: Option<String>,
+    /// The key of its `.git/config` section
+    pub git_config_key: Option<String>,
+    /// Config files that don't list it
+    pub missing_from: Vec<ConfigSource>,
+    /// Fields whose values disagree
+    pub drifts: Vec<Drift>,
+    /// Other problems, described for the user
+    pub problems: Vec<String>,
+}
+
+impl SubmoduleReport {
+    /// How many problems were found
+    #[must_use]
+    pub const fn problem_count(&self) -> usize {
+        self.missing_from.len() + self.drifts.len() + self.problems.len()
+    }
+}
+
+/// Everything `doctor` found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Per-submodule findings, for submodules with problems, sorted by path
+    pub submodules: Vec<SubmoduleReport>,
+    /// Directories under `.git/modules` no submodule claims
+    pub orphaned_module_dirs: Vec<String>,
+    /// `.git/config` sections that match no submodule
+    pub stale_git_config: Vec<String>,
+}
+
+impl Report {
+    /// How many problems were found
+    #[must_use]
+    pub fn problem_count(&self) -> usize {
+        self.submodules
+            .iter()
+            .map(SubmoduleReport::problem_count)
+            .sum::<usize>()
+            + self.orphaned_module_dirs.len()
+            + self.stale_git_config.len()
+    }
+}
+
+/// Compare every source and report where they disagree
+#[must_use]
+pub fn examine(sources: &Sources<'_>) -> Report {
+    let mut claimed_gitmodules = BTreeSet::new();
+    let mut claimed_git_config = BTreeSet::new();
+    let mut claimed_dirs = BTreeSet::new();
+    let mut submodules = Vec::new();
+
+    for toml in sources.toml {
+        let gitmodules_key = sources
+            .gitmodules
+            .iter()
+            .find(|(key, section)| {
+                !claimed_gitmodules.contains(*key)
+                    && section.get("path").map(String::as_str) == Some(toml.path.as_str())
+            })
+            .map(|(key, _)| key.clone())
+            .or_else(|| {
+                [&toml.path, &toml.name]
+                    .into_iter()
+                    .find(|key| {
+                        sources.gitmodules.contains_key(*key) && !claimed_gitmodules.contains(*key)
+                    })
+                    .cloned()
+            });
+        if let Some(key) = &gitmodules_key {
+            claimed_gitmodules.insert(key.clone());
+        }
+        let report = examine_one(
+            sources,
+            Some(toml),
+            &toml.name,
+            &toml.path,
+            gitmodules_key,
+            &mut claimed_git_config,
+            &mut claimed_dirs,
+        );
+        submodules.push(report);
+    }
+    for (key, section) in sources.gitmodules {
+        if claimed_gitmodules.contains(key) {
+            continue;
+        }
+        let path = section.get("path").cloned().unwrap_or_else(|| key.clone());
+        let report = examine_one(
+            sources,
+            None,
+            key,
+            &path,
+            Some(key.clone()),
+            &mut claimed_git_config,
+            &mut claimed_dirs,
+        );
+        submodules.push(report);
+    }
+
+    submodules.retain(|report| report.problem_count() > 0);
+    submodules.sort_by(|a, b| a.path.cmp(&b.path));
+    Report {
+        submodules,
+        orphaned_module_dirs: sources
+            .module_dirs
+            .iter()
+            .filter(|dir| !claimed_dirs.contains(*dir))
+            .cloned()
+            .collect(),
+        stale_git_config: sources
+            .git_config
+            .keys()
+            .filter(|key| !claimed_git_config.contains(*key))
+            .cloned()
+            .collect(),
+    }
+}
+
+fn examine_one(
+    sources: &Sources<'_>,
+    toml: Option<&TomlSubmodule>,
+    name: &str,
+    path: &str,
+    gitmodules_key: Option<String>,
+    claimed_git_config: &mut BTreeSet<String>,
+    claimed_dirs: &mut BTreeSet<String>,
+) -> SubmoduleReport {
+    let mut problems = Vec::new();
+    let mut missing_from = Vec::new();
+    if toml.is_none() {
+        missing_from.push(ConfigSource::SubmodToml);
+    }
+    let gitmodules = gitmodules_key
+        .as_ref()
+        .and_then(|key| sources.gitmodules.get(key));
+    if gitmodules.is_none() {
+        missing_from.push(ConfigSource::Gitmodules);
+    }
+
+    // git looks up `.git/config` by the `.gitmodules` key; a section under another key
+    // for the same submodule is one git never reads
+    let git_key = gitmodules_key.as_deref().unwrap_or(path);
+    let git_config_key = [git_key, path, name]
+        .into_iter()
+        .find(|key| sources.git_config.contains_key(*key) && !claimed_git_config.contains(*key))
+        .map(str::to_string);
+    if let Some(key) = &git_config_key {
+        claimed_git_config.insert(key.clone());
+        if key != git_key {
+            problems.push(format!(
+                "`.git/config` has its settings under \"{key}\", but git looks for \"{git_key}\", so they are ignored"
+            ));
+        }
+    }
+    let git_config = git_config_key
+        .as_ref()
+        .filter(|key| *key == git_key)
+        .and_then(|key| sources.git_config.get(key));
+    let wants_init = toml
+        .is_none_or(|toml| toml.values.get(&Field::Active).map(String::as_str) != Some("false"));
+    if git_config_key.is_none() && wants_init {
+        missing_from.push(ConfigSource::GitConfig);
+    }
+
+    for dir in [git_key, path, name] {
+        claimed_dirs.insert(dir.to_string());
+    }
+    if !sources.gitlinks.contains(path) {
+        problems.push(format!(
+            "the superproject's index has no gitlink at {path}, so no commit is recorded for it"
+        ));
+    }
+
+    let drifts = Field::ALL
+        .into_iter()
+        .filter_map(|field| compare(field, toml, gitmodules, git_config))
+        .collect();
+    SubmoduleReport {
+        name: name.to_string(),
+        path: path.to_string(),
+        gitmodules_key,
+        git_config_key,
+        missing_from,
+        drifts,
+        problems,
+    }
+}
+
+fn compare(
+    field: Field,
+    toml: Option<&TomlSubmodule>,
+    gitmodules: Option<&BTreeMap<String, String>>,
+    git_config: Option<&BTreeMap<String, String>>,
+) -> Option<Drift> {
+    // An update command is a local setting: it's only kept in `.git/config`
+    if field == Field::Update
+        && git_config
+            .and_then(|section| section.get("update"))
+            .is_some_and(|value| value.starts_with('!'))
+    {
+        return None;
+    }
+    let mut values = Vec::new();
+    if let Some(toml) = toml {
+        let value = toml
+            .values
+            .get(&field)
+            .filter(|value| !value.is_empty())
+            .cloned()
+            .or_else(|| field.git_default().map(str::to_string));
+        values.push((ConfigSource::SubmodToml, value));
+    }
+    // git ignores `!command` updates in `.gitmodules`, so it has no say in them
+    let command = field == Field::Update
+        && values
+            .iter()
+            .filter_map(|(_, value)| value.as_ref())
+            .any(|value| value.starts_with('!'));
+    if let Some(section) = gitmodules
+        && field != Field::Active
+        && !command
+    {
+        values.push((ConfigSource::Gitmodules, field.in_file(section)));
+    }
+    if let Some(section) = git_config {
+        let set = section
+            .get(&field.git_key().to_ascii_lowercase())
+            .map(|raw| field.canonical(raw));
+        match field {
+            Field::Url => values.push((ConfigSource::GitConfig, set)),
+            // A registered submodule without an `active` key is active
+            Field::Active => values.push((
+                ConfigSource::GitConfig,
+                set.or_else(|| Some("true".to_string())),
+            )),
+            _ => {
+                if set.is_some() {
+                    values.push((ConfigSource::GitConfig, set));
+                }
+            }
+        }
+    }
+    let distinct: BTreeSet<&Option<String>> = values.iter().map(|(_, value)| value).collect();
+    (distinct.len() > 1).then_some(Drift { field, values })
+}
+
+/// Read the `submodule.*` sections of the git config file at `path`; a missing file has none
+pub fn read_sections(path: &Path) -> Result<Sections> {
+    if !path.exists() {
+        return Ok(Sections::new());
+    }
+    let file =
+        gix::config::File::from_path_no_includes(path.to_path_buf(), gix::config::Source::Local)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut sections = Sections::new();
+    for section in file.sections_by_name("submodule").into_iter().flatten() {
+        let Some(key) = section.header().subsection_name() else {
+            continue;
+        };
+        let values = sections.entry(key.to_string()).or_default();
+        for value_name in section.value_names() {
+            if let Some(value) = section.value(&value_name) {
+                values.insert(value_name.to_ascii_lowercase(), value.to_string());
+            }
+        }
+    }
+    Ok(sections)
+}
+
+/// The git directories under `modules_root` (`.git/modules`), relative to it.
+///
+/// Nested submodules keep their git directories inside their parent's, so the search
+/// stops at each git directory it finds.
+#[must_use]
+pub fn module_dirs(modules_root: &Path) -> BTreeSet<String> {
+    fn walk(root: &Path, dir: &Path, found: &mut BTreeSet<String>) {
+        if dir.join("HEAD").is_file() && dir.join("objects").is_dir() {
+            if let Ok(relative) = dir.strip_prefix(root) {
+                found.insert(relative.to_string_lossy().replace('\\', "/"));
+            }
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                walk(root, &entry.path(), found);
+            }
+        }
+    }
+    let mut found = BTreeSet::new();
+    walk(modules_root, modules_root, &mut found);
+    found.remove("");
+    found
+}
+
+/// A value for display, with unset values spelled out
+#[must_use]
+pub fn show(value: Option<&str>) -> &str {
+    value.unwrap_or("(unset)")
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn section(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_ascii_lowercase(), (*value).to_string()))
+            .collect()
+    }
+
+    fn toml(name: &str, path: &str, pairs: &[(Field, &str)]) -> TomlSubmodule {
+        TomlSubmodule {
+            name: name.to_string(),
+            path: path.to_string(),
+            values: pairs
+                .iter()
+                .map(|(field, value)| (*field, (*value).to_string()))
+                .collect(),
+        }
+    }
+
+    fn set(items: &[&str]) -> BTreeSet<String> {
+        items.iter().map(|item| (*item).to_string()).collect()
+    }
+
+    #[test]
+    fn test_agreeing_sources_report_nothing() {
+        let toml = [toml(
+            "mylib",
+            "lib/x",
+            &[
+                (Field::Url, "https://example.com/x.git"),
+                (Field::Branch, "main"),
+                (Field::Ignore, "none"),
+                (Field::Active, "true"),
+            ],
+        )];
+        let gitmodules = Sections::from([(
+            "lib/x".to_string(),
+            section(&[
+                ("path", "lib/x"),
+                ("url", "https://example.com/x.git"),
+                ("branch", "main"),
+            ]),
+        )]);
+        // `.git/config` only overrides what it sets; spellings are normalized
+        let git_config = Sections::from([(
+            "lib/x".to_string(),
+            section(&[
+                ("url", "https://example.com/x.git"),
+                ("fetchRecurseSubmodules", "on-demand"),
+                ("active", "yes"),
+            ]),
+        )]);
+        let report = examine(&Sources {
+            toml: &toml,
+            gitmodules: &gitmodules,
+            git_config: &git_config,
+            module_dirs: &set(&["lib/x"]),
+            gitlinks: &set(&["lib/x"]),
+        });
+        assert_eq!(report, Report::default());
+    }
+
+    #[test]
+    fn test_update_commands_are_only_expected_in_git_config() {
+        let toml = [toml(
+            "mylib",
+            "lib/x",
+            &[
+                (Field::Url, "https://example.com/x.git"),
+                (Field::Active, "true"),
+            ],
+        )];
+        let gitmodules = Sections::from([(
+            "lib/x".to_string(),
+            section(&[("path", "lib/x"), ("url", "https://example.com/x.git")]),
+        )]);
+        let git_config = Sections::from([(
+            "lib/x".to_string(),
+            section(&[
+                ("url", "https://example.com/x.git"),
+                ("update", "!make sync"),
+            ]),
+        )]);
+        let report = examine(&Sources {
+            toml: &toml,
+            gitmodules: &gitmodules,
+            git_config: &git_config,
+            module_dirs: &set(&["lib/x"]),
+            gitlinks: &set(&["lib/x"]),
+        });
+        assert_eq!(report, Report::default());
+    }
+
+    #[test]
+    fn test_field_drift_is_reported_per_source() {
+        let toml = [toml(
+            "mylib",
+            "lib/x",
+            &[
+                (Field::Url, "https://example.com/x.git"),
+                (Field::Branch, "main"),
+                (Field::Ignore, "all"),
+                (Field::FetchRecurse, "true"),
+            ],
+        )];
+        let gitmodules = Sections::from([(
+            "lib/x".to_string(),
+            section(&[
+                ("path", "lib/x"),
+                ("url", "https://example.com/old.git"),
+                ("fetchRecurseSubmodules", "yes"),
+            ]),
+        )]);
+        let git_config = Sections::from([(
+            "lib/x".to_string(),
+            section(&[("url", "https://example.com/x.git"), ("active", "false")]),
+        )]);
+        let report = examine(&Sources {
+            toml: &toml,
+            gitmodules: &gitmodules,
+            git_config: &git_config,
+            module_dirs: &set(&["lib/x"]),
+            gitlinks: &set(&["lib/x"]),
+        });
+
+        let drifts = &report.submodules[0].drifts;
+        let fields: Vec<Field> = drifts.iter().map(|drift| drift.field).collect();
+        assert_eq!(
+            fields,
+            [Field::Url, Field::Branch, Field::Ignore, Field::Active]
+        );
+        assert_eq!(
+            drifts[1].values,
+            [
+                (ConfigSource::SubmodToml, Some("main".to_string())),
+                (ConfigSource::Gitmodules, None),
+            ]
+        );
+        assert_eq!(
+            drifts[2].values,
+            [
+                (ConfigSource::SubmodToml, Some("all".to_string())),
+                (ConfigSource::Gitmodules, Some("none".to_string())),
+            ]
+        );
+
+        // Fixing from .gitmodules takes its URL; for `active`, it has no say, so the
+        // value git uses wins
+        assert_eq!(
+            drifts[0].sources_to_fix(ConfigSource::Gitmodules),
+            [ConfigSource::SubmodToml, ConfigSource::GitConfig]
+        );
+        assert_eq!(
+            drifts[3].resolved(ConfigSource::Gitmodules),
+            Some("false".to_string())
+        );
+        assert_eq!(
+            drifts[3].sources_to_fix(ConfigSource::SubmodToml),
+            [ConfigSource::GitConfig]
+        );
+    }
+
+    #[test]
+    fn test_missing_entries_keying_and_orphans() {
+        let toml = [
+            toml("named", "lib/named", &[(Field::Url, "u1")]),
+            toml(
+                "dormant",
+                "lib/dormant",
+                &[(Field::Url, "u3"), (Field::Active, "false")],
+            ),
+        ];
+        let gitmodules = Sections::from([
+            (
+                "lib/named".to_string(),
+                section(&[("path", "lib/named"), ("url", "u1")]),
+            ),
+            (
+                "lib/extra".to_string(),
+                section(&[("path", "lib/extra"), ("url", "u2")]),
+            ),
+        ]);
+        // Keyed by the submod.toml name rather than the .gitmodules key
+        let git_config = Sections::from([
+            ("named".to_string(), section(&[("url", "u1")])),
+            ("lib/gone".to_string(), section(&[("url", "u4")])),
+        ]);
+        let report = examine(&Sources {
+            toml: &toml,
+            gitmodules: &gitmodules,
+            git_config: &git_config,
+            module_dirs: &set(&["lib/named", "lib/old"]),
+            gitlinks: &set(&["lib/named", "lib/extra"]),
+        });
+
+        let paths: Vec<&str> = report
+            .submodules
+            .iter()
+            .map(|sub| sub.path.as_str())
+            .collect();
+        assert_eq!(paths, ["lib/dormant", "lib/extra", "lib/named"]);
+
+        let dormant = &report.submodules[0];
+        // Inactive submodules needn't be registered in .git/config
+        assert_eq!(dormant.missing_from, [ConfigSource::Gitmodules]);
+        assert!(dormant.problems[0].contains("no gitlink at lib/dormant"));
+
+        let extra = &report.submodules[1];
+        assert_eq!(
+            extra.missing_from,
+            [ConfigSource::SubmodToml, ConfigSource::GitConfig]
+        );
+
+        let named = &report.submodules[2];
+        assert_eq!(named.git_config_key.as_deref(), Some("named"));
+        assert!(named.problems[0].contains("git looks for \"lib/named\""));
+
+        assert_eq!(report.orphaned_module_dirs, ["lib/old"]);
+        assert_eq!(report.stale_git_config, ["lib/gone"]);
+        assert_eq!(report.problem_count(), 7);
+    }
+
+    #[test]
+    fn test_read_sections_and_module_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let gitmodules = dir.path().join(".gitmodules");
+        std::fs::write(
+            &gitmodules,
+            "[submodule \"lib/x\"]\n\tpath = lib/x\n\tfetchRecurseSubmodules = false\n[core]\n\tbare = false\n",
+        )
+        .unwrap();
+        let sections = read_sections(&gitmodules).unwrap();
+        assert_eq!(
+            sections["lib/x"],
+            section(&[("path", "lib/x"), ("fetchrecursesubmodules", "false")])
+        );
+        assert!(
+            read_sections(&dir.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+
+        let modules = dir.path().join("modules");
+        for git_dir in ["lib/x", "lib/x/modules/nested", "other"] {
+            std::fs::create_dir_all(modules.join(git_dir).join("objects")).unwrap();
+            std::fs::write(modules.join(git_dir).join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        }
+        std::fs::create_dir_all(modules.join("lib/empty")).unwrap();
+        assert_eq!(module_dirs(&modules), set(&["lib/x", "other"]));
+    }
+}
@@ -0,0 +1,280 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Running a command in each submodule, for `submod foreach`.
+
+A single command argument runs through `sh -c` (`cmd /C` on Windows), so pipes and `&&`
+work; several arguments run as a program and its arguments, with no shell. The command
+runs from the submodule's directory with the submodule described in `SUBMOD_*`
+environment variables:
+
+- `SUBMOD_NAME`, `SUBMOD_PATH`, `SUBMOD_URL`: the submodule's name, path and URL
+- `SUBMOD_ROOT`: the superproject's working directory
+
+Up to `jobs` commands run at once. Each command's output is captured, so results can be
+printed one submodule at a time, in order, however the runs interleave.
+"]
+
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+/// A submodule to run a command in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeachTarget {
+    /// The submodule's name in the config
+    pub name: String,
+    /// The submodule's path, relative to the superproject root
+    pub path: String,
+    /// The submodule's URL, if configured
+    pub url: Option<String>,
+}
+
+/// What running the command in one submodule produced
+#[derive(Debug)]
+pub struct ForeachOutcome {
+    /// The submodule the command ran in
+    pub target: ForeachTarget,
+    /// The command's exit status, or why it couldn't be started
+    pub status: Result<ExitStatus, String>,
+    /// Everything the command wrote to stdout
+    pub stdout: Vec<u8>,
+    /// Everything the command wrote to stderr
+    pub stderr: Vec<u8>,
+}
+
+impl ForeachOutcome {
+    /// Whether the command ran and exited successfully
+    #[must_use]
+    pub fn succeeded(&self) -> bool {
+        self.status.as_ref().is_ok_and(ExitStatus::success)
+    }
+
+    /// Why the command failed, if it did
+    #[must_use]
+    pub fn failure(&self) -> Option<String> {
+        match &self.status {
+            Ok(status) if status.success() => None,
+            Ok(status) => Some(format!("exited with {status}")),
+            Err(reason) => Some(reason.clone()),
+        }
+    }
+}
+
+/// Run `command` in the submodule `target` of the superproject at `root`, capturing its output
+#[must_use]
+pub fn run_in(command: &[String], target: &ForeachTarget, root: &Path) -> ForeachOutcome {
+    let mut cmd = build_command(command);
+    in_submodule(&mut cmd, target, root).stdin(Stdio::null());
+    match cmd.output() {
+        Ok(output) => ForeachOutcome {
+            target: target.clone(),
+            status: Ok(output.status),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        },
+        Err(e) => ForeachOutcome {
+            target: target.clone(),
+            status: Err(format!("could not start `{}`: {e}", command.join(" "))),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        },
+    }
+}
+
+/// Open the user's shell (`$SHELL`, or else `sh`; `%COMSPEC%` on Windows) in the submodule
+/// `target`, with the same `SUBMOD_*` variables, and wait for it to exit
+///
+/// # Errors
+///
+/// If the shell can't be started.
+pub fn open_shell(target: &ForeachTarget, root: &Path) -> std::io::Result<ExitStatus> {
+    let shell = std::env::var_os(if cfg!(windows) { "COMSPEC" } else { "SHELL" })
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| (if cfg!(windows) { "cmd" } else { "sh" }).into());
+    in_submodule(&mut Command::new(shell), target, root).status()
+}
+
+/// Runs `cmd` from the submodule `target`'s directory, with the `SUBMOD_*` variables set
+fn in_submodule<'a>(cmd: &'a mut Command, target: &ForeachTarget, root: &Path) -> &'a mut Command {
+    cmd.current_dir(root.join(&target.path))
+        .env("SUBMOD_NAME", &target.name)
+        .env("SUBMOD_PATH", &target.path)
+        .env("SUBMOD_ROOT", root);
+    match &target.url {
+        Some(url) => cmd.env("SUBMOD_URL", url),
+        None => cmd.env_remove("SUBMOD_URL"),
+    }
+}
+
+/// Run `command` in every target, up to `jobs` at a time.
+///
+/// `on_outcome` sees each outcome in the order of `targets`, as soon as it and every
+/// outcome before it are done. Returns the outcomes in that same order.
+pub fn run_all(
+    command: &[String],
+    targets: &[ForeachTarget],
+    root: &Path,
+    jobs: usize,
+    mut on_outcome: impl FnMut(&ForeachOutcome),
+) -> Vec<ForeachOutcome> {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut done: Vec<Option<ForeachOutcome>> = targets.iter().map(|_| None).collect();
+    let mut reported = 0;
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, targets.len().max(1)) {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(target) = targets.get(index) else {
+                        break;
+                    };
+                    if sender.send((index, run_in(command, target, root))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for (index, outcome) in receiver {
+            done[index] = Some(outcome);
+            while let Some(Some(outcome)) = done.get(reported) {
+                on_outcome(outcome);
+                reported += 1;
+            }
+        }
+    });
+
+    done.into_iter().flatten().collect()
+}
+
+/// A command that runs `command` as given: through the shell for a single argument,
+/// directly otherwise
+fn build_command(command: &[String]) -> Command {
+    match command {
+        [script] => shell_command(script),
+        [program, args @ ..] => {
+            let mut cmd = Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+        // clap requires a command, but an empty one just succeeds
+        [] => shell_command(""),
+    }
+}
+
+/// A command that runs `script` through the platform shell
+#[cfg(unix)]
+fn shell_command(script: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", script]);
+    cmd
+}
+
+/// A command that runs `script` through the platform shell
+#[cfg(not(unix))]
+fn shell_command(script: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", script]);
+    cmd
+}
+
+#[cfg(all(test, unix))]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn targets_in(root: &Path, names: &[&str]) -> Vec<ForeachTarget> {
+        names
+            .iter()
+            .map(|name| {
+                fs::create_dir_all(root.join("lib").join(name)).unwrap();
+                ForeachTarget {
+                    name: (*name).to_string(),
+                    path: format!("lib/{name}"),
+                    url: Some(format!("https://example.com/{name}.git")),
+                }
+            })
+            .collect()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| (*arg).to_string()).collect()
+    }
+
+    #[test]
+    fn test_run_in_sets_env_and_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let targets = targets_in(dir.path(), &["one"]);
+
+        let outcome = run_in(
+            &args(&[
+                "echo \"$SUBMOD_NAME $SUBMOD_PATH $SUBMOD_URL\"; basename \"$PWD\"; echo oops >&2",
+            ]),
+            &targets[0],
+            dir.path(),
+        );
+        assert!(outcome.succeeded());
+        assert_eq!(
+            String::from_utf8_lossy(&outcome.stdout),
+            "one lib/one https://example.com/one.git\none\n"
+        );
+        assert_eq!(String::from_utf8_lossy(&outcome.stderr), "oops\n");
+    }
+
+    #[test]
+    fn test_run_in_runs_several_arguments_without_a_shell() {
+        let dir = tempfile::tempdir().unwrap();
+        let targets = targets_in(dir.path(), &["one"]);
+
+        let outcome = run_in(
+            &args(&["echo", "$SUBMOD_NAME", "a b"]),
+            &targets[0],
+            dir.path(),
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&outcome.stdout),
+            "$SUBMOD_NAME a b\n"
+        );
+
+        let missing = run_in(
+            &args(&["no-such-program-here", "x"]),
+            &targets[0],
+            dir.path(),
+        );
+        assert!(!missing.succeeded());
+        assert!(missing.failure().unwrap().contains("could not start"));
+    }
+
+    #[test]
+    fn test_run_all_reports_in_order_and_collects_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let targets = targets_in(dir.path(), &["a", "b", "c", "d"]);
+
+        // Later targets finish first, but are still reported in order
+        let command = args(&[
+            "case $SUBMOD_NAME in a) sleep 0.3;; b) sleep 0.1; exit 3;; esac; echo $SUBMOD_NAME",
+        ]);
+        let mut seen = Vec::new();
+        let outcomes = run_all(&command, &targets, dir.path(), 4, |outcome| {
+            seen.push(outcome.target.name.clone());
+        });
+        assert_eq!(seen, ["a", "b", "c", "d"]);
+        let failed: Vec<&str> = outcomes
+            .iter()
+            .filter(|outcome| !outcome.succeeded())
+            .map(|outcome| outcome.target.name.as_str())
+            .collect();
+        assert_eq!(failed, ["b"]);
+        assert!(outcomes[1].failure().unwrap().contains("exit status: 3"));
+    }
+}
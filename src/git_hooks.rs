@@ -0,0 +1,313 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Git hooks that keep submodules in sync as the superproject moves.
+
+`submod hooks install` writes managed `post-checkout`, `post-merge`, and `post-rewrite`
+hooks that run `submod sync --only-changed` after a branch switch, a merge or pull, and a
+rebase. A hook the user already had is kept as `<hook>.pre-submod` and runs first, with the
+same arguments and input; `submod hooks uninstall` puts it back.
+"]
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The git hooks `submod hooks install` manages
+pub const MANAGED_HOOKS: [&str; 3] = ["post-checkout", "post-merge", "post-rewrite"];
+
+/// Marks a hook script as written by `submod`
+const MANAGED_MARKER: &str = "# submod-managed-hook";
+
+/// Suffix for a user's own hook, moved aside so the managed hook can chain to it
+pub const CHAINED_SUFFIX: &str = ".pre-submod";
+
+/// What installing a managed hook did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookInstall {
+    /// No hook was there before
+    Installed,
+    /// A managed hook was rewritten
+    Updated,
+    /// The user's hook was moved to `<hook>.pre-submod` and is chained to
+    Chained,
+}
+
+/// What uninstalling a managed hook did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookUninstall {
+    /// The managed hook was removed
+    Removed,
+    /// The managed hook was removed and the user's hook put back
+    Restored,
+    /// There was no managed hook to remove
+    NotInstalled,
+}
+
+/// The hooks directory git uses for the repository at `repo_root`, honoring `core.hooksPath`
+pub fn hooks_dir(repo_root: &Path) -> Result<PathBuf> {
+    let repo = gix::open(repo_root)
+        .with_context(|| format!("Failed to open repository at {}", repo_root.display()))?;
+    let hooks_path = repo
+        .config_snapshot()
+        .trusted_path("core.hooksPath")
+        .context("Failed to read core.hooksPath")?;
+    if let Some(path) = hooks_path {
+        // Like git, resolve a relative hooksPath from the root of the working tree
+        let base = repo.workdir().unwrap_or_else(|| repo.git_dir());
+        return Ok(base.join(path));
+    }
+    Ok(repo.common_dir().join("hooks"))
+}
+
+/// Whether the hook at `path` was written by `submod`
+#[must_use]
+pub fn is_managed(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|script| script.contains(MANAGED_MARKER))
+}
+
+/// The managed script for `hook`.
+///
+/// It runs `submod` from `PATH`, falling back to `submod_exe` (the binary that installed it),
+/// and passes `--config` when `config` is given.
+#[must_use]
+pub fn hook_script(hook: &str, submod_exe: &Path, config: Option<&Path>) -> String {
+    // git only hands input to post-rewrite; reading stdin elsewhere could block on a terminal
+    let (read_input, run_chained) = if hook == "post-rewrite" {
+        (
+            "input=$(cat)\n",
+            format!("    printf '%s\\n' \"$input\" | \"$hook_dir/{hook}{CHAINED_SUFFIX}\" \"$@\""),
+        )
+    } else {
+        (
+            "",
+            format!("    \"$hook_dir/{hook}{CHAINED_SUFFIX}\" \"$@\""),
+        )
+    };
+    // Only sync when the checked-out commits can actually have changed
+    let (event, skip) = match hook {
+        "post-checkout" => (
+            "checkout",
+            "# Nothing to sync after checking out files rather than a branch\n[ \"$3\" = 1 ] || exit \"$status\"\n",
+        ),
+        "post-rewrite" => (
+            "rebase",
+            "# An amend leaves the checkout where it was\n[ \"$1\" = rebase ] || exit \"$status\"\n",
+        ),
+        _ => ("merge", ""),
+    };
+    let config_arg = config.map_or_else(String::new, |config| {
+        format!(" --config {}", shell_quote(&config.to_string_lossy()))
+    });
+    let fallback = shell_quote(&submod_exe.to_string_lossy());
+    format!(
+        r#"#!/bin/sh
+{MANAGED_MARKER}
+# Keeps submodules in sync after `git {event}`. Written by `submod hooks install`;
+# remove it with `submod hooks uninstall`. A hook that was here before lives on as
+# {hook}{CHAINED_SUFFIX} and still runs first.
+hook_dir=$(dirname "$0")
+status=0
+{read_input}if [ -x "$hook_dir/{hook}{CHAINED_SUFFIX}" ]; then
+{run_chained}
+    status=$?
+fi
+{skip}
+# Let submod find the superproject from the working directory, not git's hook environment
+unset GIT_DIR GIT_WORK_TREE GIT_INDEX_FILE
+submod=submod
+command -v "$submod" >/dev/null 2>&1 || submod={fallback}
+"$submod"{config_arg} sync --only-changed ||
+    echo "submod: could not sync submodules after {hook}; run \`submod sync\`" >&2
+exit "$status"
+"#
+    )
+}
+
+/// Write the managed `hook` into `dir`, moving a user's hook aside to chain to it.
+///
+/// Fails rather than overwrite a `<hook>.pre-submod` that is already there.
+pub fn install_hook(dir: &Path, hook: &str, script: &str) -> io::Result<HookInstall> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(hook);
+    let chained = dir.join(format!("{hook}{CHAINED_SUFFIX}"));
+    let outcome = if !path.exists() {
+        HookInstall::Installed
+    } else if is_managed(&path) {
+        HookInstall::Updated
+    } else if chained.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "both {} and {} exist; move one of them out of the way first",
+                path.display(),
+                chained.display()
+            ),
+        ));
+    } else {
+        fs::rename(&path, &chained)?;
+        HookInstall::Chained
+    };
+    fs::write(&path, script)?;
+    make_executable(&path)?;
+    Ok(outcome)
+}
+
+/// Remove the managed `hook` from `dir`, putting back the user's hook if it was chained.
+///
+/// A hook `submod` didn't write is left alone.
+pub fn uninstall_hook(dir: &Path, hook: &str) -> io::Result<HookUninstall> {
+    let path = dir.join(hook);
+    let chained = dir.join(format!("{hook}{CHAINED_SUFFIX}"));
+    let removed = path.exists();
+    if removed {
+        if !is_managed(&path) {
+            return Ok(HookUninstall::NotInstalled);
+        }
+        fs::remove_file(&path)?;
+    }
+    if chained.exists() {
+        fs::rename(&chained, &path)?;
+        return Ok(HookUninstall::Restored);
+    }
+    Ok(if removed {
+        HookUninstall::Removed
+    } else {
+        HookUninstall::NotInstalled
+    })
+}
+
+/// Quote `value` for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+/// Git for Windows runs hooks through its own `sh`, so there is nothing to set
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn make_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_script_conditions_and_config() {
+        let exe = Path::new("/opt/it's/submod");
+        let checkout = hook_script("post-checkout", exe, None);
+        assert!(checkout.contains(MANAGED_MARKER));
+        assert!(checkout.contains("[ \"$3\" = 1 ] || exit"));
+        assert!(checkout.contains("submod='/opt/it'\\''s/submod'"));
+        assert!(checkout.contains("\"$submod\" sync --only-changed"));
+        assert!(!checkout.contains("$(cat)"));
+
+        let rewrite = hook_script("post-rewrite", exe, Some(Path::new("conf/sub mod.toml")));
+        assert!(rewrite.contains("input=$(cat)"));
+        assert!(rewrite.contains("[ \"$1\" = rebase ] || exit"));
+        assert!(rewrite.contains("\"$submod\" --config 'conf/sub mod.toml' sync --only-changed"));
+
+        let merge = hook_script("post-merge", exe, None);
+        assert!(!merge.contains("|| exit \"$status\"\n"));
+    }
+
+    #[test]
+    fn test_install_and_uninstall_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let hooks = dir.path().join("hooks");
+
+        assert_eq!(
+            install_hook(&hooks, "post-merge", "#!/bin/sh\n# submod-managed-hook\n").unwrap(),
+            HookInstall::Installed
+        );
+        assert_eq!(
+            install_hook(&hooks, "post-merge", "#!/bin/sh\n# submod-managed-hook\n").unwrap(),
+            HookInstall::Updated
+        );
+        assert_eq!(
+            uninstall_hook(&hooks, "post-merge").unwrap(),
+            HookUninstall::Removed
+        );
+        assert!(!hooks.join("post-merge").exists());
+        assert_eq!(
+            uninstall_hook(&hooks, "post-merge").unwrap(),
+            HookUninstall::NotInstalled
+        );
+    }
+
+    #[test]
+    fn test_install_chains_to_user_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        let user_hook = "#!/bin/sh\necho mine\n";
+        fs::write(dir.path().join("post-checkout"), user_hook).unwrap();
+
+        let script = hook_script("post-checkout", Path::new("/bin/submod"), None);
+        assert_eq!(
+            install_hook(dir.path(), "post-checkout", &script).unwrap(),
+            HookInstall::Chained
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("post-checkout.pre-submod")).unwrap(),
+            user_hook
+        );
+        // Reinstalling must not move the managed hook over the user's
+        assert_eq!(
+            install_hook(dir.path(), "post-checkout", &script).unwrap(),
+            HookInstall::Updated
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("post-checkout.pre-submod")).unwrap(),
+            user_hook
+        );
+
+        assert_eq!(
+            uninstall_hook(dir.path(), "post-checkout").unwrap(),
+            HookUninstall::Restored
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("post-checkout")).unwrap(),
+            user_hook
+        );
+        assert!(!dir.path().join("post-checkout.pre-submod").exists());
+    }
+
+    #[test]
+    fn test_install_refuses_to_clobber_a_chained_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("post-merge"), "#!/bin/sh\necho new\n").unwrap();
+        fs::write(
+            dir.path().join("post-merge.pre-submod"),
+            "#!/bin/sh\necho old\n",
+        )
+        .unwrap();
+
+        let err = install_hook(dir.path(), "post-merge", "# submod-managed-hook\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("post-merge")).unwrap(),
+            "#!/bin/sh\necho new\n"
+        );
+    }
+
+    #[test]
+    fn test_uninstall_leaves_user_hooks_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("post-rewrite"), "#!/bin/sh\n").unwrap();
+        assert_eq!(
+            uninstall_hook(dir.path(), "post-rewrite").unwrap(),
+            HookUninstall::NotInstalled
+        );
+        assert!(dir.path().join("post-rewrite").exists());
+    }
+}
@@ -51,14 +51,31 @@ All operations return [`SubmoduleError`](src/git_manager.rs:14) for consistent e
 Use this module as the backend for CLI commands to manage submodules in a repository. See the project [README](README.md) for usage examples and configuration details.
 "]
 
-use crate::config::{Config, SubmoduleEntry};
+use crate::absorb;
+use crate::config::{Config, SubmoduleAuth, SubmoduleEntry, SubmoduleHooks};
+use crate::diff::{self as submodule_diff, Gitlink};
+use crate::doctor::{self, Field, Report, Sections, SubmoduleReport, TomlSubmodule};
+use crate::foreach::{self, ForeachOutcome, ForeachTarget};
+use crate::git_hooks::{self, HookInstall, HookUninstall};
 use crate::git_ops::GitOperations;
 use crate::git_ops::GitOpsManager;
+use crate::git_ops::{Backend, BackendError};
+use crate::hooks::{DEFAULT_HOOK_TIMEOUT_SECS, HookContext, HookEvent, run_hook_command};
+use crate::interrupt;
+use crate::manifests::{self, ManifestExport, ManifestImport, ManifestProject, Revision};
 use crate::options::{
+    ConfigSource, DiffFormat, GitmodulesConvert, HookFailurePolicy, ManifestFormat,
     SerializableBranch, SerializableFetchRecurse, SerializableIgnore, SerializableUpdate,
 };
+use crate::progress::Row;
+use crate::report::{ConsoleReporter, Details, Event, EventKind, Failure, Operation, Reporter};
+use crate::vendor::{self, Vendored, VendoredRecord};
+use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::instrument;
 
 /// The deny-all pattern prepended to sparse-checkout files in deny-all-by-default mode.
 ///
@@ -112,6 +129,307 @@ pub enum SubmoduleError {
     /// Submodule path is invalid or escapes repository root
     #[error("Invalid path: {0}")]
     InvalidPath(String),
+
+    /// A lifecycle hook failed under the `abort` failure policy
+    #[error("{event} hook for {name} failed: {reason}")]
+    HookFailed {
+        /// The hook event, e.g. `post_update`.
+        event: String,
+        /// Name of the submodule the hook ran for.
+        name: String,
+        /// Why the hook failed.
+        reason: String,
+    },
+
+    /// A `foreach` command failed in one or more submodules
+    #[error("command failed in {failed} of {total} submodule(s)")]
+    ForeachFailed {
+        /// How many submodules the command failed in.
+        failed: usize,
+        /// How many submodules the command ran in.
+        total: usize,
+    },
+
+    /// An init or update failed in one or more of the submodules it ran on
+    #[error("failed in {} of {total} submodule(s): {}", .failures.len(), failed_names(.failures))]
+    SubmodulesFailed {
+        /// What was being done to them.
+        operation: Operation,
+        /// Each submodule it failed in, and why.
+        failures: Vec<(String, Self)>,
+        /// How many submodules it ran on.
+        total: usize,
+    },
+
+    /// `doctor` found config sources that disagree
+    #[error("found {count} problem(s) with submodule configuration")]
+    DriftDetected {
+        /// How many problems remain.
+        count: usize,
+    },
+
+    /// The run was interrupted, e.g. by Ctrl-C
+    #[error("interrupted")]
+    Interrupted,
+
+    /// submod.toml couldn't be read or isn't valid
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    /// A git operation on a submodule failed
+    #[error("Git operation failed: {message}")]
+    GitOperation {
+        /// Name of the submodule.
+        name: String,
+        /// What was being done to it.
+        operation: Operation,
+        /// The backend that failed, if known.
+        backend: Option<Backend>,
+        /// What went wrong.
+        message: String,
+    },
+
+    /// A submodule's remote couldn't be reached
+    #[error("Network error: {message}")]
+    Network {
+        /// Name of the submodule.
+        name: String,
+        /// What was being done to it.
+        operation: Operation,
+        /// The backend that failed, if known.
+        backend: Option<Backend>,
+        /// What went wrong.
+        message: String,
+    },
+
+    /// Uncommitted changes in a submodule stopped an operation that would have lost them
+    #[error("{message}")]
+    DirtyWorktree {
+        /// Name of the submodule.
+        name: String,
+        /// What was being done to it.
+        operation: Operation,
+        /// The backend that refused, if it was one.
+        backend: Option<Backend>,
+        /// What went wrong.
+        message: String,
+    },
+}
+
+/// The names of the submodules in `failures`, comma-separated
+fn failed_names(failures: &[(String, SubmoduleError)]) -> String {
+    failures
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The kinds of failure scripts can tell apart, each with its own exit code:
+///
+/// | Exit code | Kind |
+/// |-----------|------|
+/// | 0 | Success |
+/// | 1 | [`Other`](Self::Other) |
+/// | 2 | Invalid command-line arguments (from the argument parser, not an `ErrorKind`) |
+/// | 3 | [`ConfigInvalid`](Self::ConfigInvalid) |
+/// | 4 | [`SubmoduleNotFound`](Self::SubmoduleNotFound) |
+/// | 5 | [`Network`](Self::Network) |
+/// | 6 | [`DirtyWorktree`](Self::DirtyWorktree) |
+/// | 7 | [`PartialSuccess`](Self::PartialSuccess) |
+/// | 8 | [`DriftDetected`](Self::DriftDetected) |
+/// | 130 | [`Interrupted`](Self::Interrupted) |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Anything not listed below
+    Other,
+    /// submod.toml couldn't be read or isn't valid
+    ConfigInvalid,
+    /// A submodule named on the command line isn't configured
+    SubmoduleNotFound,
+    /// A remote couldn't be reached
+    Network,
+    /// Uncommitted changes in a submodule stopped the operation
+    DirtyWorktree,
+    /// The operation worked in some submodules and failed in others
+    PartialSuccess,
+    /// `doctor` found config sources that disagree
+    DriftDetected,
+    /// The run was interrupted, e.g. by Ctrl-C
+    Interrupted,
+}
+
+impl ErrorKind {
+    /// The exit code `submod` ends with for this kind of failure
+    #[must_use]
+    pub const fn exit_code(self) -> u8 {
+        match self {
+            Self::Other => 1,
+            Self::ConfigInvalid => 3,
+            Self::SubmoduleNotFound => 4,
+            Self::Network => 5,
+            Self::DirtyWorktree => 6,
+            Self::PartialSuccess => 7,
+            Self::DriftDetected => 8,
+            Self::Interrupted => interrupt::EXIT_CODE,
+        }
+    }
+}
+
+/// Phrases in git's messages that mean a remote couldn't be reached, for errors that only
+/// come as text (the git CLI's) or whose types don't say so
+const NETWORK_FAILURES: &[&str] = &[
+    "could not resolve host",
+    "failed to resolve address",
+    "couldn't connect to server",
+    "failed to connect to",
+    "connection refused",
+    "connection timed out",
+    "network is unreachable",
+    "operation timed out",
+];
+
+/// Phrases in git's messages that mean uncommitted changes were in the way
+const DIRTY_WORKTREE_FAILURES: &[&str] = &[
+    "would be overwritten",
+    "prevents checkout",
+    "prevent checkout",
+    "uncommitted changes",
+];
+
+impl SubmoduleError {
+    /// Wraps an error from the git backends about the submodule `name`: as
+    /// [`Network`](Self::Network) if a remote couldn't be reached,
+    /// [`DirtyWorktree`](Self::DirtyWorktree) if uncommitted changes were in the way, and
+    /// [`GitOperation`](Self::GitOperation) otherwise
+    pub fn from_git(name: &str, operation: Operation) -> impl FnOnce(anyhow::Error) -> Self {
+        let name = name.to_string();
+        move |error| {
+            let message = error.to_string();
+            let (backend, error) = match error.downcast::<BackendError>() {
+                Ok(tagged) => (Some(tagged.backend), tagged.error),
+                Err(error) => (None, error),
+            };
+            let text = format!("{error:#}").to_lowercase();
+            let classes = error
+                .chain()
+                .filter_map(|cause| cause.downcast_ref::<git2::Error>())
+                .map(|e| (e.class(), e.code()))
+                .collect::<Vec<_>>();
+            let network = classes.iter().any(|(class, _)| {
+                matches!(
+                    class,
+                    git2::ErrorClass::Net
+                        | git2::ErrorClass::Http
+                        | git2::ErrorClass::Ssh
+                        | git2::ErrorClass::Ssl
+                )
+            }) || NETWORK_FAILURES.iter().any(|phrase| text.contains(phrase));
+            let dirty = classes.iter().any(|(_, code)| {
+                matches!(
+                    code,
+                    git2::ErrorCode::Conflict | git2::ErrorCode::Uncommitted
+                )
+            }) || DIRTY_WORKTREE_FAILURES
+                .iter()
+                .any(|phrase| text.contains(phrase));
+            if network {
+                Self::Network {
+                    name,
+                    operation,
+                    backend,
+                    message,
+                }
+            } else if dirty {
+                Self::DirtyWorktree {
+                    message: format!("Uncommitted changes in {name} are in the way: {message}"),
+                    name,
+                    operation,
+                    backend,
+                }
+            } else {
+                Self::GitOperation {
+                    name,
+                    operation,
+                    backend,
+                    message,
+                }
+            }
+        }
+    }
+
+    /// What kind of failure this is, which decides `submod`'s exit code. Failing in only some
+    /// of several submodules is a partial success; failing in all of them is the kind of
+    /// failure they share, if they share one.
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::SubmodulesFailed {
+                failures, total, ..
+            } => {
+                let mut kinds = failures.iter().map(|(_, error)| error.kind());
+                let first = kinds.next().unwrap_or(ErrorKind::Other);
+                if failures.len() < *total {
+                    ErrorKind::PartialSuccess
+                } else if kinds.all(|kind| kind == first) {
+                    first
+                } else {
+                    ErrorKind::Other
+                }
+            }
+            Self::InvalidConfig(_) => ErrorKind::ConfigInvalid,
+            Self::SubmoduleNotFound { .. } => ErrorKind::SubmoduleNotFound,
+            Self::Network { .. } => ErrorKind::Network,
+            Self::DirtyWorktree { .. } => ErrorKind::DirtyWorktree,
+            Self::ForeachFailed { failed, total } if *failed < *total => ErrorKind::PartialSuccess,
+            Self::DriftDetected { .. } => ErrorKind::DriftDetected,
+            Self::Interrupted => ErrorKind::Interrupted,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// The submodule the error is about, if it's about one
+    #[must_use]
+    pub fn submodule(&self) -> Option<&str> {
+        match self {
+            Self::SubmoduleNotFound { name }
+            | Self::HookFailed { name, .. }
+            | Self::GitOperation { name, .. }
+            | Self::Network { name, .. }
+            | Self::DirtyWorktree { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The operation that failed, if the error says
+    #[must_use]
+    pub const fn operation(&self) -> Option<Operation> {
+        match self {
+            Self::GitOperation { operation, .. }
+            | Self::Network { operation, .. }
+            | Self::DirtyWorktree { operation, .. }
+            | Self::SubmodulesFailed { operation, .. } => Some(*operation),
+            Self::HookFailed { .. } => Some(Operation::Hook),
+            Self::ForeachFailed { .. } => Some(Operation::Foreach),
+            Self::DriftDetected { .. } => Some(Operation::Doctor),
+            _ => None,
+        }
+    }
+
+    /// The git backend that failed, if one did and it's known which
+    #[must_use]
+    pub const fn backend(&self) -> Option<Backend> {
+        match self {
+            Self::GitOperation { backend, .. }
+            | Self::Network { backend, .. }
+            | Self::DirtyWorktree { backend, .. } => *backend,
+            Self::Git2Error(_) => Some(Backend::Git2),
+            Self::GitoxideError(_) => Some(Backend::Gix),
+            Self::CliError(_) => Some(Backend::Cli),
+            _ => None,
+        }
+    }
 }
 
 /// Status information for a submodule
@@ -138,6 +456,9 @@ pub struct SubmoduleStatus {
 
     /// Whether the submodule has its own submodules
     pub has_submodules: bool,
+
+    /// Current depth if the submodule is a shallow clone
+    pub shallow_depth: Option<u32>,
 }
 
 /// Sparse checkout status
@@ -158,6 +479,44 @@ pub enum SparseStatus {
     },
 }
 
+/// What a clone of a submodule writes, noted before it starts so an interrupted one can be
+/// undone
+struct NewClone {
+    /// The submodule's working directory
+    dir: PathBuf,
+    /// Whether the directory was missing or empty, so everything in it is the clone's
+    dir_was_empty: bool,
+    /// Where under `.git/modules` the clone could create the submodule's git directory,
+    /// of those that don't exist yet
+    module_dirs: Vec<PathBuf>,
+}
+
+impl NewClone {
+    /// Removes what the clone wrote. An empty directory that was there before stays, as
+    /// git leaves one for each submodule that isn't checked out.
+    fn remove(self) {
+        if self.dir_was_empty {
+            let existed = self.dir.exists();
+            let _ = fs::remove_dir_all(&self.dir);
+            if existed {
+                let _ = fs::create_dir_all(&self.dir);
+            }
+        } else {
+            let git = self.dir.join(".git");
+            let _ = fs::remove_dir_all(&git).or_else(|_| fs::remove_file(&git));
+        }
+        for module_dir in self.module_dirs {
+            let _ = fs::remove_dir_all(&module_dir);
+            // A path-keyed directory leaves its parents, which held nothing else
+            for parent in module_dir.ancestors().skip(1) {
+                if parent.ends_with("modules") || fs::remove_dir(parent).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
 /// Main gitoxide-based submodule manager
 pub struct GitManager {
     /// The main git operations manager (gix-first, git2-fallback)
@@ -168,6 +527,12 @@ pub struct GitManager {
     config_path: PathBuf,
     /// Whether to print verbose output
     verbose: bool,
+    /// Whether to run lifecycle hooks declared in the config
+    hooks_enabled: bool,
+    /// Where events about what the manager does go
+    reporter: Arc<dyn Reporter>,
+    /// The progress tree clones and fetches are reported to, if any
+    progress: Option<Arc<prodash::tree::Root>>,
 }
 
 impl GitManager {
@@ -247,13 +612,16 @@ impl GitManager {
 
         let config = Config::default()
             .load(&config_path, Config::default())
-            .map_err(|e| SubmoduleError::ConfigError(format!("Failed to load config: {e}")))?;
+            .map_err(|e| SubmoduleError::InvalidConfig(format!("Failed to load config: {e}")))?;
 
         Ok(Self {
             git_ops,
             config,
             config_path,
             verbose,
+            hooks_enabled: true,
+            reporter: Arc::new(ConsoleReporter::default()),
+            progress: None,
         })
     }
 
@@ -268,16 +636,1170 @@ impl GitManager {
 
         let config = Config::default()
             .load(&config_path, Config::default())
-            .map_err(|e| SubmoduleError::ConfigError(format!("Failed to load config: {e}")))?;
+            .map_err(|e| SubmoduleError::InvalidConfig(format!("Failed to load config: {e}")))?;
 
         Ok(Self {
             git_ops,
             config,
             config_path,
             verbose: false,
+            hooks_enabled: true,
+            reporter: Arc::new(ConsoleReporter::default()),
+            progress: None,
+        })
+    }
+
+    /// Enables or disables the lifecycle hooks declared in the config.
+    #[must_use]
+    pub const fn with_hooks(mut self, enabled: bool) -> Self {
+        self.hooks_enabled = enabled;
+        self
+    }
+
+    /// Sends events about what the manager does to `reporter` instead of printing them.
+    #[must_use]
+    pub fn with_reporter(mut self, reporter: impl Reporter + 'static) -> Self {
+        self.reporter = Arc::new(reporter);
+        self
+    }
+
+    /// Reports the progress of each submodule's clones and fetches to `tree`, in a row named
+    /// after the submodule. See [`progress`](crate::progress).
+    #[must_use]
+    pub fn with_progress(mut self, tree: Arc<prodash::tree::Root>) -> Self {
+        self.progress = Some(tree);
+        self
+    }
+
+    /// Reports `event` to the manager's reporter
+    #[allow(clippy::needless_pass_by_value)]
+    fn emit(&self, event: Event) {
+        self.reporter.report(&event);
+    }
+
+    /// Reports an event of the caller's own to the manager's reporter, so it's shown the way
+    /// the manager's are
+    pub fn report(&self, event: &Event) {
+        self.reporter.report(event);
+    }
+
+    /// Runs `run` with the git backends reporting progress to a row for `name`, if the
+    /// manager reports progress. The row is gone by the time `run` returns.
+    fn with_progress_row<T>(
+        &mut self,
+        name: &str,
+        run: impl FnOnce(&mut Self) -> Result<T, SubmoduleError>,
+    ) -> Result<T, SubmoduleError> {
+        let row = self.progress.as_ref().map(|tree| Row::new(tree, name));
+        self.git_ops.set_progress(row);
+        let result = run(self);
+        self.git_ops.set_progress(None);
+        result
+    }
+
+    /// Runs `run`, a step that may clone the submodule `name` into `path`, with its progress
+    /// on the submodule's row. Once the run is interrupted it doesn't start; if an interrupt
+    /// stops it part way through a clone, what the clone left behind is removed, so the next
+    /// run starts that submodule afresh.
+    fn interruptible<T>(
+        &mut self,
+        name: &str,
+        path: &str,
+        run: impl FnOnce(&mut Self) -> Result<T, SubmoduleError>,
+    ) -> Result<T, SubmoduleError> {
+        if interrupt::is_interrupted() {
+            return Err(SubmoduleError::Interrupted);
+        }
+        let new_clone = self.new_clone(name, path);
+        match self.with_progress_row(name, run) {
+            Err(_) if interrupt::is_interrupted() => {
+                if let Some(new_clone) = new_clone {
+                    new_clone.remove();
+                }
+                Err(SubmoduleError::Interrupted)
+            }
+            result => result,
+        }
+    }
+
+    /// Where a clone of the submodule `name` into `path` would write, if it isn't cloned yet
+    fn new_clone(&self, name: &str, path: &str) -> Option<NewClone> {
+        let root = self.git_ops.workdir()?;
+        let dir = root.join(path);
+        if dir.join(".git").exists() {
+            return None;
+        }
+        let dir_was_empty = dir
+            .read_dir()
+            .map_or(true, |mut entries| entries.next().is_none());
+        // The clone's git directory is named after its `.gitmodules` entry, which may not be
+        // the name submod.toml knows it by, and git2's add keys it by path until it's done
+        let git_name = self
+            .gitmodules_name(name, path)
+            .unwrap_or_else(|| name.to_string());
+        let module_dirs = gix::open(root)
+            .map(|repo| {
+                let modules = repo.common_dir().join("modules");
+                [modules.join(git_name), modules.join(path)]
+                    .into_iter()
+                    .filter(|module_dir| !module_dir.exists())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(NewClone {
+            dir,
+            dir_was_empty,
+            module_dirs,
         })
     }
 
+    /// The checked-out `HEAD` of a configured submodule, if it has one
+    pub fn submodule_head(&self, name: &str) -> Option<String> {
+        let path = self
+            .config
+            .get_submodule(name)
+            .and_then(|entry| entry.path.clone())
+            .unwrap_or_else(|| name.to_string());
+        self.head_at(&path)
+    }
+
+    /// The `HEAD` of the repository at `path`, relative to the superproject root
+    fn head_at(&self, path: &str) -> Option<String> {
+        let root = self.git_ops.workdir().unwrap_or_else(|| Path::new("."));
+        let repo = gix::open(root.join(path)).ok()?;
+        repo.head_id().ok().map(|id| id.to_string())
+    }
+
+    /// The commit the superproject's index records for the submodule at `path`
+    fn recorded_gitlink(&self, path: &str) -> Option<String> {
+        use gix::bstr::ByteSlice;
+        let root = self.git_ops.workdir().unwrap_or_else(|| Path::new("."));
+        let repo = gix::open(root).ok()?;
+        let index = repo.index_or_empty().ok()?;
+        let entry = index.entry_by_path(path.as_bytes().as_bstr())?;
+        (entry.mode == gix::index::entry::Mode::COMMIT).then(|| entry.id.to_string())
+    }
+
+    /// Whether a configured submodule needs syncing: it isn't checked out, or its `HEAD`
+    /// differs from the commit the superproject records for it.
+    pub fn submodule_out_of_sync(&self, name: &str) -> bool {
+        let path = self
+            .config
+            .get_submodule(name)
+            .and_then(|entry| entry.path.clone())
+            .unwrap_or_else(|| name.to_string());
+        let Some(head) = self.head_at(&path) else {
+            return true;
+        };
+        self.recorded_gitlink(&path)
+            .is_some_and(|recorded| recorded != head)
+    }
+
+    /// The checked-out submodules `submod foreach` should run in, sorted by name.
+    ///
+    /// With `groups`, only submodules in at least one of them; with `dirty_only`, only
+    /// submodules with uncommitted changes to tracked files.
+    pub fn foreach_targets(&self, groups: &[String], dirty_only: bool) -> Vec<ForeachTarget> {
+        let root = self.git_ops.workdir().unwrap_or_else(|| Path::new("."));
+        let mut targets = self
+            .config
+            .get_submodules()
+            .filter(|(_, entry)| {
+                groups.is_empty()
+                    || entry
+                        .groups
+                        .as_ref()
+                        .is_some_and(|own| own.iter().any(|group| groups.contains(group)))
+            })
+            .filter_map(|(name, entry)| {
+                let path = entry.path.clone().unwrap_or_else(|| name.clone());
+                let Ok(repo) = gix::open(root.join(&path)) else {
+                    if self.verbose {
+                        self.emit(
+                            Event::progress(
+                                Operation::Foreach,
+                                format!("Skipping {name}: not checked out"),
+                            )
+                            .about(name),
+                        );
+                    }
+                    return None;
+                };
+                // As in `check`, a status that can't be computed counts as dirty
+                if dirty_only && !repo.is_dirty().unwrap_or(true) {
+                    return None;
+                }
+                Some(ForeachTarget {
+                    name: name.clone(),
+                    path,
+                    url: entry.url.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        targets.sort_by(|a, b| a.name.cmp(&b.name));
+        targets
+    }
+
+    /// Runs `command` in each submodule from [`Self::foreach_targets`], up to `jobs` at a
+    /// time, printing each submodule's output under its own heading and then a summary.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::ForeachFailed` if the command failed in any submodule.
+    #[instrument(level = "info", skip_all, fields(command = ?command, groups = ?groups), err(level = "info"))]
+    pub fn foreach(
+        &self,
+        command: &[String],
+        groups: &[String],
+        jobs: usize,
+        dirty_only: bool,
+    ) -> Result<(), SubmoduleError> {
+        let targets = self.foreach_targets(groups, dirty_only);
+        if targets.is_empty() {
+            self.emit(Event::finished(
+                Operation::Foreach,
+                "No submodules to run in",
+            ));
+            return Ok(());
+        }
+        // Drop git's trailing slash so `$SUBMOD_ROOT/$SUBMOD_PATH` matches the command's `$PWD`
+        let root = self.root();
+
+        let outcomes = foreach::run_all(command, &targets, &root, jobs, |outcome| {
+            self.report_outcome(outcome);
+        });
+
+        let failures: Vec<Failure> = outcomes
+            .iter()
+            .filter_map(|outcome| {
+                outcome.failure().map(|reason| Failure {
+                    submodule: outcome.target.name.clone(),
+                    reason,
+                })
+            })
+            .collect();
+        let failed = failures.len();
+        self.emit(Event::detailed(
+            EventKind::Finished,
+            Operation::Foreach,
+            Details::RanAll {
+                total: outcomes.len(),
+                failures,
+            },
+        ));
+        if failed == 0 {
+            return Ok(());
+        }
+        Err(SubmoduleError::ForeachFailed {
+            failed,
+            total: outcomes.len(),
+        })
+    }
+
+    /// Reports one submodule's `foreach` outcome, with everything the command wrote
+    fn report_outcome(&self, outcome: &ForeachOutcome) {
+        self.emit(
+            Event::detailed(
+                EventKind::Progress,
+                Operation::Foreach,
+                Details::Ran {
+                    path: outcome.target.path.clone(),
+                    succeeded: outcome.succeeded(),
+                },
+            )
+            .about(&outcome.target.name)
+            .with_output(&outcome.stdout, &outcome.stderr),
+        );
+    }
+
+    /// Summarizes how submodules changed between two superproject revisions.
+    ///
+    /// `from` defaults to `HEAD`; without `to`, the working tree is compared, using each
+    /// submodule's checked-out commit. Returns the report in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::ConfigError` if a revision can't be resolved or read.
+    #[instrument(level = "info", skip_all, fields(from = ?from, to = ?to), err(level = "info"))]
+    pub fn diff(
+        &self,
+        from: Option<&str>,
+        to: Option<&str>,
+        format: DiffFormat,
+    ) -> Result<String, SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?;
+        let repo = gix::open(root).map_err(|_| SubmoduleError::RepositoryError)?;
+        let from = from.unwrap_or("HEAD");
+        let old = submodule_diff::gitlinks_at_rev(&repo, from).map_err(Self::map_git_ops_error)?;
+        let new = to
+            .map_or_else(
+                || submodule_diff::gitlinks_in_worktree(&repo),
+                |to| submodule_diff::gitlinks_at_rev(&repo, to),
+            )
+            .map_err(Self::map_git_ops_error)?;
+
+        let mut changes =
+            submodule_diff::pair_gitlinks(&self.named_by_config(old), &self.named_by_config(new));
+        for change in &mut changes {
+            submodule_diff::describe(change, root);
+        }
+
+        let to = to.unwrap_or("the working tree");
+        match format {
+            DiffFormat::Text => Ok(submodule_diff::render_text(&changes, from, to)),
+            DiffFormat::Markdown => Ok(submodule_diff::render_markdown(&changes, from, to)),
+            DiffFormat::Json => {
+                submodule_diff::render_json(&changes, from, to).map_err(Self::map_git_ops_error)
+            }
+        }
+    }
+
+    /// Renames gitlinks whose path is configured in submod.toml to their configured name
+    fn named_by_config(&self, links: Vec<Gitlink>) -> Vec<Gitlink> {
+        links
+            .into_iter()
+            .map(|mut link| {
+                if let Some((name, _)) = self
+                    .config
+                    .get_submodules()
+                    .find(|(_, entry)| entry.path.as_deref() == Some(link.path.as_str()))
+                {
+                    link.name.clone_from(name);
+                }
+                link
+            })
+            .collect()
+    }
+
+    /// Checks that `submod.toml`, `.gitmodules`, and `.git/config` agree about every
+    /// submodule, and that each has a gitlink and no module directory is left over.
+    ///
+    /// With `fix`, sources that disagree with `source` are rewritten to match it, and
+    /// submodules missing from `.gitmodules` or `submod.toml` are added from it where it
+    /// has them. Problems that can't be fixed by editing config are reported with advice.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::DriftDetected` if any problem remains, or another error if
+    /// a source can't be read or written.
+    #[instrument(level = "info", skip_all, fields(fix = fix), err(level = "info"))]
+    pub fn doctor(&mut self, fix: bool, source: ConfigSource) -> Result<(), SubmoduleError> {
+        let report = self.examine_config()?;
+        self.report_findings(&report, false);
+        let report = if fix && report.problem_count() > 0 {
+            self.emit(Event::started(
+                Operation::Doctor,
+                format!("\n🔧 Fixing with {source} as the source of truth"),
+            ));
+            self.fix_drift(&report, source)?;
+            let report = self.examine_config()?;
+            self.report_findings(&report, true);
+            report
+        } else {
+            report
+        };
+
+        let count = report.problem_count();
+        if count == 0 {
+            return Ok(());
+        }
+        if !fix {
+            self.emit(Event::progress(
+                Operation::Doctor,
+                "Run `submod doctor --fix` to make the config files agree (`--source` picks which one is right)",
+            ));
+        }
+        Err(SubmoduleError::DriftDetected { count })
+    }
+
+    /// Gathers every source `doctor` compares and examines them
+    fn examine_config(&self) -> Result<Report, SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?;
+        let repo = gix::open(root).map_err(|_| SubmoduleError::RepositoryError)?;
+        let gitmodules =
+            doctor::read_sections(&root.join(".gitmodules")).map_err(Self::map_git_ops_error)?;
+        // Linked worktrees share the main worktree's config and `.git/modules`
+        let git_config = doctor::read_sections(&repo.common_dir().join("config"))
+            .map_err(Self::map_git_ops_error)?;
+        let module_dirs = doctor::module_dirs(&repo.common_dir().join("modules"));
+        let gitlinks = submodule_diff::gitlinks_in_worktree(&repo)
+            .map_err(Self::map_git_ops_error)?
+            .into_iter()
+            .map(|link| link.path)
+            .collect();
+        let toml = self.toml_submodules();
+        Ok(doctor::examine(&doctor::Sources {
+            toml: &toml,
+            gitmodules: &gitmodules,
+            git_config: &git_config,
+            module_dirs: &module_dirs,
+            gitlinks: &gitlinks,
+        }))
+    }
+
+    /// The submodules in submod.toml, with `[defaults]` applied
+    fn toml_submodules(&self) -> Vec<TomlSubmodule> {
+        let defaults = &self.config.defaults;
+        self.config
+            .get_submodules()
+            .map(|(name, entry)| {
+                let values = [
+                    (Field::Url, entry.url.clone()),
+                    (
+                        Field::Branch,
+                        entry.branch.as_ref().map(GitmodulesConvert::to_gitmodules),
+                    ),
+                    (
+                        Field::Ignore,
+                        entry
+                            .ignore
+                            .as_ref()
+                            .or(defaults.ignore.as_ref())
+                            .map(GitmodulesConvert::to_gitmodules),
+                    ),
+                    (
+                        Field::Update,
+                        entry
+                            .update
+                            .as_ref()
+                            .or(defaults.update.as_ref())
+                            .map(GitmodulesConvert::to_gitmodules),
+                    ),
+                    (
+                        Field::FetchRecurse,
+                        entry
+                            .fetch_recurse
+                            .as_ref()
+                            .or(defaults.fetch_recurse.as_ref())
+                            .map(GitmodulesConvert::to_gitmodules),
+                    ),
+                    (
+                        Field::Active,
+                        Some(entry.active.unwrap_or(true).to_string()),
+                    ),
+                ]
+                .into_iter()
+                .filter_map(|(field, value)| value.map(|value| (field, value)))
+                .collect();
+                TomlSubmodule {
+                    name: name.clone(),
+                    path: entry.path.clone().unwrap_or_else(|| name.clone()),
+                    values,
+                }
+            })
+            .collect()
+    }
+
+    /// Reports what `doctor` found: one event per submodule with problems, then a count.
+    /// `after_fix` sets the report apart from the fixes before it.
+    fn report_findings(&self, report: &Report, after_fix: bool) {
+        let mut events = Vec::new();
+        for submodule in &report.submodules {
+            let mut message = format!("❌ {} ({})", submodule.name, submodule.path);
+            for source in &submodule.missing_from {
+                let _ = write!(message, "\n   missing from {source}");
+            }
+            for drift in &submodule.drifts {
+                let values: Vec<String> = drift
+                    .values
+                    .iter()
+                    .map(|(source, value)| format!("{source} = {}", doctor::show(value.as_deref())))
+                    .collect();
+                let _ = write!(message, "\n   {}: {}", drift.field, values.join(", "));
+            }
+            for problem in &submodule.problems {
+                let _ = write!(message, "\n   {problem}");
+            }
+            events.push(Event::progress(Operation::Doctor, message).about(&submodule.name));
+        }
+        for dir in &report.orphaned_module_dirs {
+            events.push(Event::progress(
+                Operation::Doctor,
+                format!(
+                    "❌ .git/modules/{dir} belongs to no submodule; delete it if you no longer need its history"
+                ),
+            ));
+        }
+        for key in &report.stale_git_config {
+            events.push(Event::progress(
+                Operation::Doctor,
+                format!(
+                    "❌ .git/config has settings for \"{key}\", which is no longer a submodule; remove them with `git config --remove-section submodule.{key}`"
+                ),
+            ));
+        }
+        events.push(Event::finished(
+            Operation::Doctor,
+            match report.problem_count() {
+                0 => "✅ submod.toml, .gitmodules, and .git/config agree".to_string(),
+                1 => "Found 1 problem".to_string(),
+                count => format!("Found {count} problems"),
+            },
+        ));
+        if after_fix {
+            events[0].message.insert(0, '\n');
+        }
+        for event in events {
+            self.emit(event);
+        }
+    }
+
+    /// Rewrites every source that disagrees with `truth`, as far as config edits can
+    fn fix_drift(&mut self, report: &Report, truth: ConfigSource) -> Result<(), SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?
+            .to_path_buf();
+        let git_dir = gix::open(&root)
+            .map_err(|_| SubmoduleError::RepositoryError)?
+            .common_dir()
+            .to_path_buf();
+        let gitmodules_path = root.join(".gitmodules");
+        let gitmodules =
+            doctor::read_sections(&gitmodules_path).map_err(Self::map_git_ops_error)?;
+        let mut toml_changed = false;
+
+        for submodule in &report.submodules {
+            for drift in &submodule.drifts {
+                let value = drift.resolved(truth);
+                for source in drift.sources_to_fix(truth) {
+                    match source {
+                        ConfigSource::SubmodToml => {
+                            toml_changed |=
+                                self.set_toml_field(&submodule.name, drift.field, value.as_deref());
+                        }
+                        ConfigSource::Gitmodules => Self::set_git_field(
+                            &gitmodules_path,
+                            submodule.gitmodules_key.as_deref(),
+                            drift.field,
+                            value.as_deref(),
+                        )?,
+                        ConfigSource::GitConfig => Self::set_git_field(
+                            &git_dir.join("config"),
+                            submodule.git_config_key.as_deref(),
+                            drift.field,
+                            value.as_deref(),
+                        )?,
+                    }
+                    self.emit(
+                        Event::progress(
+                            Operation::Doctor,
+                            format!(
+                                "   {} ({}): set {} in {source} to {}",
+                                submodule.name,
+                                submodule.path,
+                                drift.field,
+                                doctor::show(value.as_deref())
+                            ),
+                        )
+                        .about(&submodule.name),
+                    );
+                }
+            }
+            toml_changed |= self.fill_missing(submodule, truth, &gitmodules_path, &gitmodules)?;
+        }
+
+        if toml_changed {
+            self.write_full_config()?;
+        }
+        Ok(())
+    }
+
+    /// Adds a submodule missing from `.gitmodules` or submod.toml from the source of
+    /// truth. Returns whether submod.toml changed.
+    fn fill_missing(
+        &mut self,
+        submodule: &SubmoduleReport,
+        truth: ConfigSource,
+        gitmodules_path: &Path,
+        gitmodules: &Sections,
+    ) -> Result<bool, SubmoduleError> {
+        let mut toml_changed = false;
+        for missing in &submodule.missing_from {
+            match (missing, truth) {
+                (ConfigSource::Gitmodules, ConfigSource::SubmodToml) => {
+                    let Some(entry) = self.config.get_submodule(&submodule.name).cloned() else {
+                        continue;
+                    };
+                    let mut file = git2::Config::open(gitmodules_path)?;
+                    let key = &submodule.path;
+                    file.set_str(&format!("submodule.{key}.path"), key)?;
+                    let settings = [
+                        (Field::Url, entry.url.clone()),
+                        (
+                            Field::Branch,
+                            entry.branch.as_ref().map(GitmodulesConvert::to_gitmodules),
+                        ),
+                        (
+                            Field::Ignore,
+                            entry.ignore.as_ref().map(GitmodulesConvert::to_gitmodules),
+                        ),
+                        (
+                            Field::Update,
+                            entry
+                                .update
+                                .as_ref()
+                                .filter(|u| !u.is_command())
+                                .map(GitmodulesConvert::to_gitmodules),
+                        ),
+                        (
+                            Field::FetchRecurse,
+                            entry
+                                .fetch_recurse
+                                .as_ref()
+                                .map(GitmodulesConvert::to_gitmodules),
+                        ),
+                    ];
+                    for (field, value) in settings {
+                        if let Some(value) = value {
+                            file.set_str(&format!("submodule.{key}.{}", field.git_key()), &value)?;
+                        }
+                    }
+                    self.emit(
+                        Event::progress(
+                            Operation::Doctor,
+                            format!(
+                                "   {} ({}): added to .gitmodules",
+                                submodule.name, submodule.path
+                            ),
+                        )
+                        .about(&submodule.name),
+                    );
+                }
+                (ConfigSource::SubmodToml, ConfigSource::Gitmodules) => {
+                    let Some(section) = submodule
+                        .gitmodules_key
+                        .as_ref()
+                        .and_then(|key| gitmodules.get(key))
+                    else {
+                        continue;
+                    };
+                    let get = |field: Field| section.get(&field.git_key().to_ascii_lowercase());
+                    let entry = SubmoduleEntry::new(
+                        get(Field::Url).cloned(),
+                        Some(submodule.path.clone()),
+                        get(Field::Branch)
+                            .and_then(|v| SerializableBranch::from_gitmodules(v).ok()),
+                        get(Field::Ignore)
+                            .and_then(|v| SerializableIgnore::from_gitmodules(v).ok()),
+                        get(Field::Update)
+                            .and_then(|v| SerializableUpdate::from_gitmodules(v).ok()),
+                        get(Field::FetchRecurse)
+                            .and_then(|v| SerializableFetchRecurse::from_gitmodules(v).ok()),
+                        Some(true),
+                        None,
+                        None,
+                    );
+                    self.config.add_submodule(submodule.name.clone(), entry);
+                    toml_changed = true;
+                    self.emit(
+                        Event::progress(
+                            Operation::Doctor,
+                            format!(
+                                "   {} ({}): added to submod.toml",
+                                submodule.name, submodule.path
+                            ),
+                        )
+                        .about(&submodule.name),
+                    );
+                }
+                (ConfigSource::GitConfig, _) => self.emit(
+                    Event::progress(
+                        Operation::Doctor,
+                        format!(
+                            "   {} ({}): not registered in .git/config; run `submod init` to set it up",
+                            submodule.name, submodule.path
+                        ),
+                    )
+                    .about(&submodule.name),
+                ),
+                (missing, truth) => self.emit(
+                    Event::progress(
+                        Operation::Doctor,
+                        format!(
+                            "   {} ({}): missing from {missing}, but {truth} doesn't have it either to copy from",
+                            submodule.name, submodule.path
+                        ),
+                    )
+                    .about(&submodule.name),
+                ),
+            }
+        }
+        Ok(toml_changed)
+    }
+
+    /// Sets one field of a submod.toml entry from its `.gitmodules` spelling. Returns
+    /// whether the entry changed.
+    fn set_toml_field(&mut self, name: &str, field: Field, value: Option<&str>) -> bool {
+        let Some(mut entry) = self.config.get_submodule(name).cloned() else {
+            return false;
+        };
+        match field {
+            // submod.toml needs a URL, so one that's unset elsewhere stays
+            Field::Url => match value {
+                Some(url) => entry.url = Some(url.to_string()),
+                None => return false,
+            },
+            Field::Branch => {
+                entry.branch = value.and_then(|v| SerializableBranch::from_gitmodules(v).ok());
+            }
+            Field::Ignore => {
+                entry.ignore = value.and_then(|v| SerializableIgnore::from_gitmodules(v).ok());
+            }
+            Field::Update => {
+                entry.update = value.and_then(|v| SerializableUpdate::from_gitmodules(v).ok());
+            }
+            Field::FetchRecurse => {
+                entry.fetch_recurse =
+                    value.and_then(|v| SerializableFetchRecurse::from_gitmodules(v).ok());
+            }
+            Field::Active => entry.active = Some(value != Some("false")),
+        }
+        self.config.submodules.update_entry(name.to_string(), entry);
+        true
+    }
+
+    /// Sets or unsets `submodule.<key>.<field>` in the git config file at `path`
+    fn set_git_field(
+        path: &Path,
+        key: Option<&str>,
+        field: Field,
+        value: Option<&str>,
+    ) -> Result<(), SubmoduleError> {
+        let Some(key) = key else {
+            return Ok(());
+        };
+        let mut file = git2::Config::open(path)?;
+        let name = format!("submodule.{key}.{}", field.git_key());
+        match value {
+            Some(value) => file.set_str(&name, value)?,
+            None => match file.remove(&name) {
+                Err(e) if e.code() != git2::ErrorCode::NotFound => return Err(e.into()),
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    /// Imports the projects of the Android `repo` manifest at `manifest` into submod.toml,
+    /// reporting manifest features it couldn't carry over. With `init`, the imported
+    /// submodules are then added and cloned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::ConfigError` if the manifest can't be read, or an error
+    /// from writing the config or initializing a submodule.
+    #[instrument(level = "info", skip_all, fields(manifest = %manifest.display()), err(level = "info"))]
+    pub fn import_repo_manifest(
+        &mut self,
+        manifest: &Path,
+        manifest_url: Option<&str>,
+        init: bool,
+    ) -> Result<(), SubmoduleError> {
+        let import =
+            manifests::repo::read(manifest, manifest_url).map_err(Self::map_git_ops_error)?;
+        self.import_manifest(&import, &manifest.display().to_string(), init)
+    }
+
+    /// Imports the repositories of the vcstool `.repos` file at `file` into submod.toml,
+    /// as [`Self::import_repo_manifest`] does for `repo` manifests.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::ConfigError` if the file can't be read, or an error from
+    /// writing the config or initializing a submodule.
+    #[instrument(level = "info", skip_all, fields(file = %file.display()), err(level = "info"))]
+    pub fn import_vcstool(&mut self, file: &Path, init: bool) -> Result<(), SubmoduleError> {
+        let import = manifests::vcstool::read(file).map_err(Self::map_git_ops_error)?;
+        self.import_manifest(&import, &file.display().to_string(), init)
+    }
+
+    /// Imports the projects of the west manifest at `file` into submod.toml, as
+    /// [`Self::import_repo_manifest`] does for `repo` manifests.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::ConfigError` if the file can't be read, or an error from
+    /// writing the config or initializing a submodule.
+    #[instrument(level = "info", skip_all, fields(file = %file.display()), err(level = "info"))]
+    pub fn import_west(&mut self, file: &Path, init: bool) -> Result<(), SubmoduleError> {
+        let import = manifests::west::read(file).map_err(Self::map_git_ops_error)?;
+        self.import_manifest(&import, &file.display().to_string(), init)
+    }
+
+    /// Writes the configured submodules as a manifest in `format`.
+    ///
+    /// Each submodule is locked to the commit it has checked out, or the commit the
+    /// superproject records if it isn't checked out; with `rev`, to the commit recorded at
+    /// that superproject revision. Submodules with no commit there keep their branch.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::ConfigError` if `rev` can't be read or the manifest can't
+    /// be written.
+    #[instrument(level = "info", skip_all, fields(rev = ?rev), err(level = "info"))]
+    pub fn export_manifest(
+        &self,
+        format: ManifestFormat,
+        rev: Option<&str>,
+    ) -> Result<ManifestExport, SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?;
+        let repo = gix::open(root).map_err(|_| SubmoduleError::RepositoryError)?;
+        let gitlinks = rev
+            .map_or_else(
+                || submodule_diff::gitlinks_in_worktree(&repo),
+                |rev| submodule_diff::gitlinks_at_rev(&repo, rev),
+            )
+            .map_err(Self::map_git_ops_error)?;
+        let locked: HashMap<String, String> = gitlinks
+            .into_iter()
+            .map(|link| (link.path, link.oid))
+            .collect();
+
+        let mut skipped = Vec::new();
+        let mut entries: Vec<(&String, &SubmoduleEntry)> = self.config.get_submodules().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        let projects: Vec<ManifestProject> = entries
+            .into_iter()
+            .filter_map(|(name, entry)| {
+                let Some(url) = entry.url.clone() else {
+                    skipped.push(name.clone());
+                    return None;
+                };
+                let path = entry.path.clone().unwrap_or_else(|| name.clone());
+                let revision = locked
+                    .get(&path)
+                    .map(|oid| Revision::Commit(oid.clone()))
+                    .or_else(|| match &entry.branch {
+                        Some(SerializableBranch::Name(branch)) => {
+                            Some(Revision::Branch(branch.clone()))
+                        }
+                        _ => None,
+                    });
+                Some(ManifestProject {
+                    name: name.clone(),
+                    path,
+                    url,
+                    revision,
+                    upstream: None,
+                    depth: entry.depth.filter(|_| entry.shallow == Some(true)),
+                    groups: entry.groups.clone().unwrap_or_default(),
+                    active: entry.active.unwrap_or(true),
+                })
+            })
+            .collect();
+
+        let mut export = match format {
+            ManifestFormat::Vcstool => manifests::vcstool::write(&projects),
+            ManifestFormat::West => manifests::west::write(&projects),
+        }
+        .map_err(Self::map_git_ops_error)?;
+        for name in skipped {
+            export
+                .unsupported
+                .add("submodule with no URL (not exported)", name);
+        }
+        Ok(export)
+    }
+
+    /// Adds a manifest's projects to submod.toml, skipping any whose name or path is
+    /// already configured, then initializes them if asked
+    fn import_manifest(
+        &mut self,
+        import: &ManifestImport,
+        source: &str,
+        init: bool,
+    ) -> Result<(), SubmoduleError> {
+        let mut imported = Vec::new();
+        for project in &import.projects {
+            if let Some((existing, _)) = self.config.get_submodules().find(|(name, entry)| {
+                **name == project.name || entry.path.as_deref() == Some(project.path.as_str())
+            }) {
+                self.emit(
+                    Event::progress(
+                        Operation::Import,
+                        format!(
+                            "⏭️  {} ({}): already configured as {existing}",
+                            project.name, project.path
+                        ),
+                    )
+                    .about(&project.name),
+                );
+                continue;
+            }
+            let tracking = project
+                .branch()
+                .map_or_else(String::new, |branch| format!(" on {branch}"));
+            let mut message = format!(
+                "➕ {} ({}): {}{tracking}",
+                project.name, project.path, project.url
+            );
+            if let Some(pin @ (Revision::Tag(_) | Revision::Commit(_))) = &project.revision {
+                let _ = write!(
+                    message,
+                    "\n   pinned to {pin} in the manifest; submod.toml tracks branches, so this tracks {} instead",
+                    project.branch().unwrap_or("the remote's default branch")
+                );
+            }
+            if !project.active {
+                message.push_str(
+                    "\n   not checked out by default in the manifest, so imported inactive",
+                );
+            }
+            self.emit(Event::progress(Operation::Import, message).about(&project.name));
+            self.config
+                .add_submodule(project.name.clone(), project.to_entry());
+            imported.push(project.name.clone());
+        }
+
+        if !import.unsupported.is_empty() {
+            let mut message = format!("⚠️  Not imported from {source}:");
+            for line in import.unsupported.lines() {
+                let _ = write!(message, "\n   {line}");
+            }
+            self.emit(Event::progress(Operation::Import, message));
+        }
+
+        if imported.is_empty() {
+            self.emit(Event::finished(
+                Operation::Import,
+                "No submodules to import",
+            ));
+            return Ok(());
+        }
+        self.write_full_config()?;
+        self.emit(Event::detailed(
+            EventKind::Finished,
+            Operation::Import,
+            Details::Imported {
+                count: imported.len(),
+                config: self.config_path.clone(),
+            },
+        ));
+
+        if init {
+            for name in &imported {
+                if self
+                    .config
+                    .get_submodule(name)
+                    .and_then(|entry| entry.active)
+                    == Some(false)
+                {
+                    continue;
+                }
+                self.init_submodule(name)?;
+                let path = self.configured_path(name)?;
+                self.emit(
+                    Event::detailed(
+                        EventKind::Finished,
+                        Operation::Init,
+                        Details::Initialized { path },
+                    )
+                    .about(name),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Installs the managed `post-checkout`, `post-merge`, and `post-rewrite` git hooks,
+    /// which run `submod sync --only-changed`. Existing hooks are kept and chained to.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::IoError` if a hook cannot be written, or if a hook and a
+    /// previously chained `<hook>.pre-submod` would collide.
+    #[instrument(level = "info", skip_all, err(level = "info"))]
+    pub fn install_git_hooks(&self) -> Result<(), SubmoduleError> {
+        let dir = self.git_hooks_dir()?;
+        let exe = std::env::current_exe()?;
+        let config = self.hook_config_path()?;
+        for hook in git_hooks::MANAGED_HOOKS {
+            let script = git_hooks::hook_script(hook, &exe, config.as_deref());
+            let message = match git_hooks::install_hook(&dir, hook, &script)? {
+                HookInstall::Installed => format!("✅ Installed {hook} hook"),
+                HookInstall::Updated => format!("✅ Updated {hook} hook"),
+                HookInstall::Chained => format!(
+                    "✅ Installed {hook} hook (your existing hook is now {hook}{} and still runs first)",
+                    git_hooks::CHAINED_SUFFIX
+                ),
+            };
+            self.emit(Event::progress(Operation::GitHooks, message));
+        }
+        if self.verbose {
+            self.emit(Event::progress(
+                Operation::GitHooks,
+                format!("Hooks directory: {}", dir.display()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Removes the managed git hooks, restoring any hooks they were chained to.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::IoError` if a hook cannot be removed or restored.
+    #[instrument(level = "info", skip_all, err(level = "info"))]
+    pub fn uninstall_git_hooks(&self) -> Result<(), SubmoduleError> {
+        let dir = self.git_hooks_dir()?;
+        for hook in git_hooks::MANAGED_HOOKS {
+            let message = match git_hooks::uninstall_hook(&dir, hook)? {
+                HookUninstall::Removed => format!("✅ Removed {hook} hook"),
+                HookUninstall::Restored => format!("✅ Removed {hook} hook and restored yours"),
+                HookUninstall::NotInstalled if self.verbose => {
+                    format!("{hook} hook is not managed by submod; left as is")
+                }
+                HookUninstall::NotInstalled => continue,
+            };
+            self.emit(Event::progress(Operation::GitHooks, message));
+        }
+        Ok(())
+    }
+
+    /// The config path for the managed hooks to pass along, or `None` for the default.
+    ///
+    /// git runs hooks from the top of the worktree, so a config inside it is given
+    /// relative to that; one outside it is given in full.
+    fn hook_config_path(&self) -> Result<Option<PathBuf>, SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?
+            .canonicalize()?;
+        let config = self
+            .config_path
+            .canonicalize()
+            .or_else(|_| std::path::absolute(&self.config_path))?;
+        match config.strip_prefix(&root) {
+            Ok(relative) if relative == Path::new("submod.toml") => Ok(None),
+            Ok(relative) => Ok(Some(relative.to_path_buf())),
+            Err(_) => Ok(Some(config)),
+        }
+    }
+
+    /// The hooks directory of the superproject
+    fn git_hooks_dir(&self) -> Result<PathBuf, SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?;
+        git_hooks::hooks_dir(root).map_err(Self::map_git_ops_error)
+    }
+
+    /// Runs the `event` hooks for a configured submodule.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::HookFailed` if a hook fails and the effective
+    /// `on_failure` policy is `abort`. Under `warn`, failures are reported and skipped.
+    #[instrument(level = "info", skip_all, fields(event = %event, submodule = name), err(level = "info"))]
+    pub fn run_hooks(
+        &self,
+        event: HookEvent,
+        name: &str,
+        old_oid: Option<&str>,
+    ) -> Result<(), SubmoduleError> {
+        let entry = self.config.get_submodule(name);
+        let path = entry
+            .and_then(|entry| entry.path.clone())
+            .unwrap_or_else(|| name.to_string());
+        let url = entry.and_then(|entry| entry.url.clone());
+        let hooks = self.effective_hooks(entry.and_then(|entry| entry.hooks.as_ref()));
+        self.run_hooks_at(event, name, &path, url.as_deref(), &hooks, old_oid)
+    }
+
+    /// A submodule's own hooks layered over `[defaults.hooks]`
+    fn effective_hooks(&self, own: Option<&SubmoduleHooks>) -> SubmoduleHooks {
+        let defaults = self.config.defaults.hooks.clone().unwrap_or_default();
+        match own {
+            Some(own) => own.merged_over(&defaults),
+            None => defaults,
+        }
+    }
+
+    /// Runs the `event` commands from `hooks` for a submodule whose path and URL are known.
+    fn run_hooks_at(
+        &self,
+        event: HookEvent,
+        name: &str,
+        path: &str,
+        url: Option<&str>,
+        hooks: &SubmoduleHooks,
+        old_oid: Option<&str>,
+    ) -> Result<(), SubmoduleError> {
+        if !self.hooks_enabled {
+            return Ok(());
+        }
+        let commands = hooks.commands(event);
+        if commands.is_empty() {
+            return Ok(());
+        }
+
+        let root = self
+            .git_ops
+            .workdir()
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+        let new_oid = if event.is_pre() {
+            None
+        } else {
+            self.head_at(path)
+        };
+        let ctx = HookContext {
+            name,
+            path,
+            url,
+            root: &root,
+            old_oid,
+            new_oid: new_oid.as_deref(),
+        };
+        let timeout =
+            std::time::Duration::from_secs(hooks.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+
+        for command in commands {
+            if self.verbose {
+                self.emit(
+                    Event::started(
+                        Operation::Hook,
+                        format!("🪝 Running {event} hook for {name}: {command}"),
+                    )
+                    .about(name),
+                );
+            }
+            if let Err(reason) = run_hook_command(command, event, &ctx, timeout) {
+                match hooks.on_failure.unwrap_or_default() {
+                    HookFailurePolicy::Abort => {
+                        return Err(SubmoduleError::HookFailed {
+                            event: event.to_string(),
+                            name: name.to_string(),
+                            reason,
+                        });
+                    }
+                    HookFailurePolicy::Warn => {
+                        self.emit(
+                            Event::warning(
+                                Operation::Hook,
+                                format!("⚠️  {event} hook for {name} failed: {reason}"),
+                            )
+                            .about(name),
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Check submodule repository status using gix APIs
     pub fn check_submodule_repository_status(
         &self,
@@ -317,6 +1839,8 @@ impl GitManager {
             } else {
                 SparseStatus::NotEnabled
             };
+        let shallow_depth = crate::utilities::shallow_depth(&submodule_repo);
+
         // Check if submodule has its own submodules
         let has_submodules = submodule_repo
             .submodules()
@@ -331,6 +1855,7 @@ impl GitManager {
             is_active,
             sparse_status,
             has_submodules,
+            shallow_depth,
         })
     }
 
@@ -391,6 +1916,7 @@ impl GitManager {
 
     /// Add a submodule using the fallback chain: gitoxide -> git2 -> CLI
     #[allow(clippy::too_many_arguments, clippy::needless_pass_by_value)]
+    #[instrument(level = "info", skip_all, fields(submodule = %name, path = %path, url = %url), err(level = "info"))]
     pub fn add_submodule(
         &mut self,
         name: String,
@@ -402,6 +1928,11 @@ impl GitManager {
         fetch_recurse: Option<SerializableFetchRecurse>,
         update: Option<SerializableUpdate>,
         shallow: Option<bool>,
+        depth: Option<u32>,
+        shallow_since: Option<String>,
+        auth: SubmoduleAuth,
+        hooks: Option<SubmoduleHooks>,
+        groups: Option<Vec<String>>,
         no_init: bool,
         use_git_default_sparse_checkout: Option<bool>,
     ) -> Result<(), SubmoduleError> {
@@ -415,30 +1946,56 @@ impl GitManager {
             return Err(SubmoduleError::InvalidPath(e.to_string()));
         }
 
+        // Update commands only go in .git/config, which git_ops writes for a new submodule
+        let toml_update = update.clone().filter(|u| !u.is_command());
+
         if no_init {
+            if let Some(command) = update.as_ref().filter(|u| u.is_command()) {
+                self.set_local_update(&name, command)?;
+            }
             self.update_toml_config(
                 name,
                 SubmoduleEntry {
                     path: Some(path.clone()),
                     url: Some(url),
                     branch,
+                    fallback_branch: None,
                     ignore,
-                    update,
+                    update: toml_update,
                     fetch_recurse,
                     active: Some(!no_init),
                     shallow,
+                    depth,
+                    shallow_since,
+                    ssh_key: auth.ssh_key,
+                    credential_helper: auth.credential_helper,
+                    http_extra_header_env: auth.http_extra_header_env,
                     no_init: Some(no_init),
                     sparse_paths: None,
                     use_git_default_sparse_checkout,
+                    groups,
+                    hooks,
                 },
                 sparse_paths,
             )?;
             // When requested, only update configuration without touching repository state.
+            // Nothing is added to the repository, so `pre_add`/`post_add` don't run; the init
+            // hooks run once it's initialized.
             return Ok(());
         }
 
+        let effective_hooks = self.effective_hooks(hooks.as_ref());
+        self.run_hooks_at(
+            HookEvent::PreAdd,
+            &name,
+            &path,
+            Some(&url),
+            &effective_hooks,
+            None,
+        )?;
+
         // Clean up any existing submodule state using git commands
-        self.cleanup_existing_submodule(&path);
+        self.cleanup_existing_submodule(&name, &path);
 
         let opts = crate::config::SubmoduleAddOptions {
             name: name.clone(),
@@ -449,12 +2006,15 @@ impl GitManager {
             update: update.clone(),
             fetch_recurse,
             shallow: shallow.unwrap_or(false),
+            depth,
+            shallow_since: shallow_since.clone(),
+            auth: auth.clone(),
             no_init,
         };
         match self
             .git_ops
             .add_submodule(&opts)
-            .map_err(Self::map_git_ops_error)
+            .map_err(SubmoduleError::from_git(&name, Operation::Add))
         {
             Ok(()) => {
                 // Store the opt-out flag in config before configuring sparse checkout
@@ -464,14 +2024,22 @@ impl GitManager {
                         path: Some(path.clone()),
                         url: Some(url.clone()),
                         branch: branch.clone(),
+                        fallback_branch: None,
                         ignore,
-                        update: update.clone(),
+                        update: toml_update.clone(),
                         fetch_recurse,
                         active: Some(!no_init),
                         shallow,
+                        depth,
+                        shallow_since: shallow_since.clone(),
+                        ssh_key: auth.ssh_key.clone(),
+                        credential_helper: auth.credential_helper.clone(),
+                        http_extra_header_env: auth.http_extra_header_env.clone(),
                         no_init: Some(no_init),
                         sparse_paths: None,
                         use_git_default_sparse_checkout,
+                        groups: groups.clone(),
+                        hooks: hooks.clone(),
                     };
                     self.config.add_submodule(name.clone(), entry);
                 }
@@ -480,37 +2048,71 @@ impl GitManager {
                 self.update_toml_config(
                     name.clone(),
                     SubmoduleEntry {
-                        path: Some(path),
-                        url: Some(url),
+                        path: Some(path.clone()),
+                        url: Some(url.clone()),
                         branch,
+                        fallback_branch: None,
                         ignore,
-                        update,
+                        update: toml_update,
                         fetch_recurse,
                         active: Some(!no_init),
                         shallow,
+                        depth,
+                        shallow_since,
+                        ssh_key: auth.ssh_key,
+                        credential_helper: auth.credential_helper,
+                        http_extra_header_env: auth.http_extra_header_env,
                         no_init: Some(no_init),
                         sparse_paths: None, // stored separately via configure_submodule_post_creation
                         use_git_default_sparse_checkout,
+                        groups,
+                        hooks,
                     },
                     sparse_paths,
                 )?;
-                println!("Added submodule {name}");
-                Ok(())
+                self.emit(
+                    Event::detailed(
+                        EventKind::Finished,
+                        Operation::Add,
+                        Details::Added { path, url },
+                    )
+                    .about(&name),
+                );
+                self.run_hooks(HookEvent::PostAdd, &name, None)
             }
             Err(e) => Err(e),
         }
     }
 
     /// Clean up existing submodule state using git commands only
-    fn cleanup_existing_submodule(&mut self, path: &str) {
+    fn cleanup_existing_submodule(&mut self, name: &str, path: &str) {
         // Best-effort cleanup of any existing submodule state
         // These operations may fail if the submodule doesn't exist yet, which is fine,
         // but other errors (permissions, corruption, etc.) should at least be visible.
         if let Err(e) = self.git_ops.deinit_submodule(path, true) {
-            eprintln!("Warning: failed to deinit submodule at '{path}': {e:?}");
+            self.emit(
+                Event::warning(
+                    Operation::Add,
+                    format!("Warning: failed to deinit submodule at '{path}': {e:?}"),
+                )
+                .about(name),
+            );
         }
         if let Err(e) = self.git_ops.delete_submodule(path) {
-            eprintln!("Warning: failed to delete submodule at '{path}': {e:?}");
+            self.emit(
+                Event::warning(
+                    Operation::Add,
+                    format!("Warning: failed to delete submodule at '{path}': {e:?}"),
+                )
+                .about(name),
+            );
+        }
+        // A stale `.git/config` section would keep the new submodule from taking its name
+        if let Some(workdir) = self.git_ops.workdir() {
+            let _ = std::process::Command::new("git")
+                .args(["config", "--remove-section", &format!("submodule.{name}")])
+                .current_dir(workdir)
+                .output();
         }
     }
 
@@ -553,37 +2155,40 @@ impl GitManager {
             // Normalize to the deny-all-by-default model.
             let normalized = Self::build_deny_all_sparse_patterns(patterns);
             if !normalized.is_empty() {
-                eprintln!(
+                self.emit(Event::warning(
+                    Operation::SparseCheckout,
                     "ℹ️  submod uses a deny-all-by-default sparse-checkout model: `!/*` is \
                      automatically prepended so only the paths you list are checked out.\n\
                      To use git's default behavior instead, set \
                      `use_git_default_sparse_checkout = true` in your submod.toml (globally \
                      under `[defaults]` or per submodule) or pass \
-                     `--use-git-default-sparse-checkout`."
-                );
+                     `--use-git-default-sparse-checkout`.",
+                ));
             }
             normalized
         };
 
+        let name = self
+            .config
+            .submodules
+            .name_for_path(submodule_path)
+            .map_or(submodule_path, String::as_str);
         self.git_ops
             .enable_sparse_checkout(submodule_path)
-            .map_err(|e| {
-                SubmoduleError::GitoxideError(format!("Enable sparse checkout failed: {e}"))
-            })?;
+            .map_err(SubmoduleError::from_git(name, Operation::SparseCheckout))?;
 
         self.git_ops
             .set_sparse_patterns(submodule_path, &effective_patterns)
-            .map_err(|e| {
-                SubmoduleError::GitoxideError(format!("Set sparse patterns failed: {e}"))
-            })?;
+            .map_err(SubmoduleError::from_git(name, Operation::SparseCheckout))?;
 
         self.git_ops
             .apply_sparse_checkout(submodule_path)
-            .map_err(|e| {
-                SubmoduleError::GitoxideError(format!("Apply sparse checkout failed: {e}"))
-            })?;
+            .map_err(SubmoduleError::from_git(name, Operation::SparseCheckout))?;
 
-        println!("Configured sparse checkout");
+        self.emit(Event::finished(
+            Operation::SparseCheckout,
+            "Configured sparse checkout",
+        ));
 
         Ok(())
     }
@@ -685,6 +2290,7 @@ impl GitManager {
     // Removed: apply_sparse_checkout_cli is obsolete; sparse checkout is handled by GitOpsManager abstraction.
 
     /// Update submodule using CLI fallback (gix remote operations are complex for this use case)
+    #[instrument(level = "info", skip_all, fields(submodule = name), err(level = "info"))]
     pub fn update_submodule(&mut self, name: &str) -> Result<(), SubmoduleError> {
         let config =
             self.config
@@ -698,23 +2304,588 @@ impl GitManager {
             SubmoduleError::ConfigError("No path configured for submodule".to_string())
         })?;
 
-        // Prepare update options (use defaults for now)
-        let update_opts = crate::config::SubmoduleUpdateOptions::default();
+        // Prepare update options (the configured strategy and clone depth)
+        let update_opts = crate::config::SubmoduleUpdateOptions::default()
+            .with_strategy(self.update_strategy(name, config)?)
+            .with_shallow_from(config)
+            .with_auth_from(config);
+        let submodule_path = submodule_path.clone();
+
+        self.emit(Event::started(Operation::Update, "").about(name));
+        let old_oid = self.head_at(&submodule_path);
+        self.run_hooks(HookEvent::PreUpdate, name, old_oid.as_deref())?;
+        let cloned = self
+            .git_ops
+            .workdir()
+            .is_some_and(|root| root.join(&submodule_path).join(".git").exists());
 
-        self.git_ops
-            .update_submodule(submodule_path, &update_opts)
-            .map_err(|e| {
-                SubmoduleError::GitoxideError(format!("GitOpsManager update failed: {e}"))
-            })?;
+        // Merge and rebase should land on the followed branch; a checkout would detach
+        // from it again, so it switches afterwards
+        let switch_first = matches!(
+            update_opts.strategy,
+            SerializableUpdate::Merge | SerializableUpdate::Rebase
+        );
+        if update_opts.strategy != SerializableUpdate::None {
+            self.checkout_from_main_worktree(name, &submodule_path)?;
+        }
+        if switch_first {
+            self.follow_superproject_branch(name, &submodule_path)?;
+        }
+        self.interruptible(name, &submodule_path, |manager| {
+            manager
+                .git_ops
+                .update_submodule(&submodule_path, &update_opts)
+                .map_err(SubmoduleError::from_git(name, Operation::Update))
+        })?;
+        if !cloned {
+            self.absorb_new_clone(&submodule_path)?;
+        }
+        if !switch_first && update_opts.strategy != SerializableUpdate::None {
+            self.follow_superproject_branch(name, &submodule_path)?;
+        }
 
         // Name every submodule that was updated, not just a trailing count: with
         // only the count, a multi-submodule `update` gives no way to tell which
         // ones it actually touched.
-        println!("✅ Updated {name}");
+        let new_oid = self.head_at(&submodule_path);
+        self.emit(
+            Event::detailed(
+                EventKind::Finished,
+                Operation::Update,
+                Details::Updated {
+                    path: submodule_path,
+                    old: old_oid.clone(),
+                    new: new_oid,
+                },
+            )
+            .about(name),
+        );
+        self.run_hooks(HookEvent::PostUpdate, name, old_oid.as_deref())
+    }
+
+    /// Switch a submodule that tracks the superproject's branch (`branch = "."`) to the
+    /// branch the superproject is on now, creating a local branch that tracks the
+    /// remote's if needed. An existing local branch is fast-forwarded first; one that has
+    /// diverged from the checked-out commit is left alone, with a warning. When the remote
+    /// doesn't have that branch, or the superproject is on a detached HEAD, the submodule's
+    /// `fallback_branch` is used instead.
+    fn follow_superproject_branch(&self, name: &str, path: &str) -> Result<(), SubmoduleError> {
+        let Some(entry) = self.config.submodules.get(name) else {
+            return Ok(());
+        };
+        if entry.branch != Some(SerializableBranch::CurrentInSuperproject) {
+            return Ok(());
+        }
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?
+            .to_path_buf();
+        let dir = root.join(path);
+        if !dir.join(".git").exists() {
+            return Ok(());
+        }
+        let branch_at = |dir: &Path| {
+            Self::run_git(dir, &["symbolic-ref", "--quiet", "--short", "HEAD"], None)
+                .ok()
+                .map(|branch| branch.trim().to_string())
+        };
+        let has_ref = |reference: String| {
+            Self::run_git(
+                &dir,
+                &["rev-parse", "--verify", "--quiet", &reference],
+                None,
+            )
+            .is_ok()
+        };
+        let has_branch = |branch: &str| {
+            has_ref(format!("refs/heads/{branch}"))
+                || has_ref(format!("refs/remotes/origin/{branch}"))
+        };
+
+        let wanted = branch_at(&root);
+        let fallback = entry.fallback_branch.as_ref().or(self
+            .config
+            .defaults
+            .fallback_branch
+            .as_ref());
+        let Some(target) = wanted
+            .iter()
+            .chain(fallback)
+            .find(|branch| has_branch(branch))
+        else {
+            let reason = wanted.as_ref().map_or_else(
+                || "the superproject is on a detached HEAD".to_string(),
+                |branch| format!("its remote has no '{branch}' branch"),
+            );
+            let fallback_note = fallback.map_or_else(
+                || "no fallback_branch is set".to_string(),
+                |branch| format!("nor the fallback '{branch}'"),
+            );
+            self.emit(
+                Event::warning(
+                    Operation::Update,
+                    format!("⚠️  {name}: {reason}, and {fallback_note}; not switching branches"),
+                )
+                .about(name),
+            );
+            return Ok(());
+        };
+        if branch_at(&dir).as_ref() == Some(target) {
+            return Ok(());
+        }
+
+        let local = format!("refs/heads/{target}");
+        if has_ref(local.clone()) {
+            // The branch moves up to the checked-out commit, and on to its remote's tip, as
+            // far as either is a fast-forward; switching to an older tip would undo the update
+            let rev = |reference: &str| {
+                Self::run_git(&dir, &["rev-parse", "--verify", "--quiet", reference], None)
+                    .ok()
+                    .map(|oid| oid.trim().to_string())
+            };
+            let contains = |ancestor: &str, descendant: &str| {
+                Self::run_git(
+                    &dir,
+                    &["merge-base", "--is-ancestor", ancestor, descendant],
+                    None,
+                )
+                .is_ok()
+            };
+            let head = rev("HEAD").ok_or(SubmoduleError::RepositoryError)?;
+            let mut tip = rev(&local).ok_or(SubmoduleError::RepositoryError)?;
+            for candidate in [
+                Some(head.clone()),
+                rev(&format!("refs/remotes/origin/{target}")),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                if contains(&tip, &candidate) {
+                    tip = candidate;
+                }
+            }
+            if !contains(&head, &tip) {
+                self.emit(
+                    Event::warning(
+                        Operation::Update,
+                        format!(
+                            "⚠️  {name}: its '{target}' branch can't be fast-forwarded to the checked-out commit; staying detached"
+                        ),
+                    )
+                    .about(name),
+                );
+                return Ok(());
+            }
+            Self::run_git(&dir, &["checkout", "--quiet", "-B", target, &tip], None)?;
+        } else {
+            let upstream = format!("origin/{target}");
+            Self::run_git(
+                &dir,
+                &["checkout", "--quiet", "--track", "-b", target, &upstream],
+                None,
+            )?;
+        }
+        let target = target.clone();
+        let wanted = wanted.filter(|wanted| *wanted != target);
+        self.emit(
+            Event::detailed(
+                EventKind::Progress,
+                Operation::Update,
+                Details::SwitchedBranch {
+                    branch: target,
+                    wanted,
+                },
+            )
+            .about(name),
+        );
+        Ok(())
+    }
+
+    /// Moves the embedded `.git` directories of the named submodules (all of them if
+    /// `names` is empty), and of the submodules nested in them, into `.git/modules`, like
+    /// `git submodule absorbgitdirs`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::SubmoduleNotFound` for an unknown name, and
+    /// `SubmoduleError::GitoxideError` if a git directory can't be moved; nothing moves
+    /// unless all of them can, and the ones moved before a failure are put back.
+    #[instrument(level = "info", skip_all, fields(submodules = ?names), err(level = "info"))]
+    pub fn absorb_submodules(&mut self, names: &[String]) -> Result<(), SubmoduleError> {
+        let paths = names
+            .iter()
+            .map(|name| {
+                let entry = self
+                    .config
+                    .get_submodule(name)
+                    .ok_or_else(|| SubmoduleError::SubmoduleNotFound { name: name.clone() })?;
+                Ok(entry.path.clone().unwrap_or_else(|| name.clone()))
+            })
+            .collect::<Result<Vec<_>, SubmoduleError>>()?;
+        let (root, plan) = self.absorb_plan((!names.is_empty()).then_some(paths.as_slice()))?;
+        if !plan.iter().any(|relocation| relocation.embedded) {
+            self.emit(Event::finished(
+                Operation::Absorb,
+                "Nothing to absorb; no submodule has a .git directory of its own",
+            ));
+            return Ok(());
+        }
+        absorb::absorb(&plan).map_err(Self::map_git_ops_error)?;
+        for relocation in plan.iter().filter(|relocation| relocation.embedded) {
+            self.emit(Event::detailed(
+                EventKind::Progress,
+                Operation::Absorb,
+                Details::Absorbed {
+                    path: relocation.path.clone(),
+                    git_dir: crate::utilities::relative_path(&root, &relocation.to),
+                },
+            ));
+        }
+        if let Err(e) = self.git_ops.reopen() {
+            self.emit(Event::warning(
+                Operation::Absorb,
+                format!("Warning: failed to refresh git repository state after absorbing: {e}"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The canonical superproject root, and the git directories that absorbing the
+    /// submodules at `paths` (all of them for `None`) would relocate
+    fn absorb_plan(
+        &self,
+        paths: Option<&[String]>,
+    ) -> Result<(PathBuf, Vec<absorb::Relocation>), SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?
+            .canonicalize()?;
+        // From a linked worktree, submodules still belong in the main `.git/modules`
+        let git_dir = gix::open(&root)
+            .map_err(|_| SubmoduleError::RepositoryError)?
+            .common_dir()
+            .canonicalize()?;
+        let plan = absorb::plan(&root, &git_dir, paths).map_err(Self::map_git_ops_error)?;
+        Ok((root, plan))
+    }
+
+    /// Absorb a submodule submod just cloned, if the clone kept its `.git` directory inside
+    fn absorb_new_clone(&self, path: &str) -> Result<(), SubmoduleError> {
+        let (_, plan) = self.absorb_plan(Some(&[path.to_string()]))?;
+        absorb::absorb(&plan).map_err(Self::map_git_ops_error)
+    }
+
+    /// In a linked worktree of the superproject (`git worktree add`), check a submodule out
+    /// as a worktree of the main worktree's clone, detached at the recorded commit, so the
+    /// two share one object store rather than cloning it again. Returns whether it did:
+    /// nothing happens in the main worktree, for a submodule already checked out here, or
+    /// when the main worktree has no clone of it.
+    fn checkout_from_main_worktree(&self, name: &str, path: &str) -> Result<bool, SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?
+            .to_path_buf();
+        let dir = root.join(path);
+        if dir.join(".git").exists() {
+            return Ok(false);
+        }
+        let repo = gix::open(&root).map_err(|_| SubmoduleError::RepositoryError)?;
+        let Some(main_root) = crate::utilities::main_worktree_root(&repo) else {
+            return Ok(false);
+        };
+        // The main worktree's clone is in `.git/modules/<its .gitmodules name>`, or in the
+        // submodule itself if it was cloned without one
+        let common_dir = repo.common_dir().canonicalize()?;
+        let git_name = self
+            .gitmodules_name(name, path)
+            .unwrap_or_else(|| name.to_string());
+        let shared = common_dir.join("modules").join(git_name);
+        let Some(module_dir) = shared.is_dir().then_some(shared).or_else(|| {
+            Some(
+                gix::open(main_root.join(path))
+                    .ok()?
+                    .git_dir()
+                    .to_path_buf(),
+            )
+        }) else {
+            return Ok(false);
+        };
+        drop(repo);
+        let Ok(recorded) = Self::run_git(
+            &root,
+            &["rev-parse", "--verify", "--quiet", &format!(":{path}")],
+            None,
+        ) else {
+            return Ok(false);
+        };
+        let recorded = recorded.trim();
+
+        let has_commit = Self::run_git(
+            &module_dir,
+            &["cat-file", "-e", &format!("{recorded}^{{commit}}")],
+            None,
+        )
+        .is_ok();
+        if !has_commit {
+            let auth_overrides = self
+                .configured_auth(name)
+                .config_overrides(&root)
+                .map_err(Self::map_git_ops_error)?;
+            Self::run_git_with_auth(
+                &module_dir,
+                &["fetch", "--quiet", "origin"],
+                None,
+                &auth_overrides,
+            )?;
+        }
+        // A deleted worktree recreated at the same path is still registered there
+        Self::run_git(&module_dir, &["worktree", "prune"], None)?;
+        Self::run_git(
+            &module_dir,
+            &[
+                "worktree",
+                "add",
+                "--quiet",
+                "--detach",
+                &dir.to_string_lossy(),
+                recorded,
+            ],
+            None,
+        )?;
+        if self.verbose {
+            self.emit(
+                Event::progress(
+                    Operation::Init,
+                    format!(
+                        "🌿 {name}: checked out from the main worktree's clone in {}",
+                        module_dir.display()
+                    ),
+                )
+                .about(name),
+            );
+        }
+        Ok(true)
+    }
+
+    /// Removes what deleted worktrees of the superproject left behind: git's records of
+    /// the worktrees themselves, and of the submodule checkouts they held as worktrees
+    /// of the clones in `.git/modules`. With `dry_run`, only reports what would go.
+    #[instrument(level = "info", skip_all, fields(dry_run = dry_run), err(level = "info"))]
+    pub fn prune_worktrees(&self, dry_run: bool) -> Result<(), SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?;
+        let common_dir = gix::open(root)
+            .map_err(|_| SubmoduleError::RepositoryError)?
+            .common_dir()
+            .to_path_buf();
+        let modules_root = common_dir.join("modules");
+        let repos = std::iter::once(("superproject".to_string(), common_dir)).chain(
+            doctor::module_dirs(&modules_root)
+                .into_iter()
+                .map(|dir| (dir.clone(), modules_root.join(dir))),
+        );
+
+        let mut pruned = 0;
+        for (label, git_dir) in repos {
+            if !git_dir.join("worktrees").is_dir() {
+                continue;
+            }
+            let mut args = vec!["worktree", "prune", "--verbose"];
+            if dry_run {
+                args.push("--dry-run");
+            }
+            // `git worktree prune --verbose` reports on stderr
+            let output = std::process::Command::new("git")
+                .args(&args)
+                .current_dir(&git_dir)
+                .output()?;
+            if !output.status.success() {
+                return Err(SubmoduleError::CliError(format!(
+                    "git {} in {}: {}",
+                    args.join(" "),
+                    git_dir.display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            for line in String::from_utf8_lossy(&output.stderr).lines() {
+                // "Removing worktrees/<id>: gitdir file points to non-existent location"
+                let Some(entry) = line.strip_prefix("Removing worktrees/") else {
+                    continue;
+                };
+                let (id, reason) = entry.split_once(": ").unwrap_or((entry, ""));
+                let verb = if dry_run { "Would prune" } else { "Pruned" };
+                self.emit(Event::progress(
+                    Operation::Worktree,
+                    format!("🧹 {verb} {label} worktree '{id}' ({reason})"),
+                ));
+                pruned += 1;
+            }
+        }
+        if pruned == 0 {
+            self.emit(Event::finished(Operation::Worktree, "No stale worktrees"));
+        }
+        Ok(())
+    }
+
+    /// Fetch `by` more commits of history into a shallow submodule. The resulting depth is
+    /// recorded in the config so later updates don't cut the history back.
+    #[instrument(level = "info", skip_all, fields(submodule = name, by = by), err(level = "info"))]
+    pub fn deepen_submodule(&mut self, name: &str, by: u32) -> Result<(), SubmoduleError> {
+        let submodule_path = self.configured_path(name)?;
+
+        self.git_ops
+            .deepen_submodule(&submodule_path, by, &self.configured_auth(name))
+            .map_err(SubmoduleError::from_git(name, Operation::Deepen))?;
+
+        let depth = self.current_shallow_depth(&submodule_path);
+        let mut entry = self.config.submodules.get(name).cloned().ok_or_else(|| {
+            SubmoduleError::SubmoduleNotFound {
+                name: name.to_string(),
+            }
+        })?;
+        entry.depth = depth;
+        entry.shallow = Some(depth.is_some());
+        entry.shallow_since = None;
+        self.config.submodules.update_entry(name.to_string(), entry);
+        self.write_full_config()?;
+
+        self.emit(
+            Event::detailed(
+                EventKind::Finished,
+                Operation::Deepen,
+                Details::Deepened { by, depth },
+            )
+            .about(name),
+        );
+        Ok(())
+    }
+
+    /// Fetch the complete history of a shallow submodule and drop its depth settings.
+    #[instrument(level = "info", skip_all, fields(submodule = name), err(level = "info"))]
+    pub fn unshallow_submodule(&mut self, name: &str) -> Result<(), SubmoduleError> {
+        let submodule_path = self.configured_path(name)?;
+
+        self.git_ops
+            .unshallow_submodule(&submodule_path, &self.configured_auth(name))
+            .map_err(SubmoduleError::from_git(name, Operation::Unshallow))?;
+
+        let mut entry = self.config.submodules.get(name).cloned().ok_or_else(|| {
+            SubmoduleError::SubmoduleNotFound {
+                name: name.to_string(),
+            }
+        })?;
+        entry.depth = None;
+        entry.shallow = Some(false);
+        entry.shallow_since = None;
+        self.config.submodules.update_entry(name.to_string(), entry);
+        self.write_full_config()?;
+
+        self.emit(
+            Event::detailed(
+                EventKind::Finished,
+                Operation::Unshallow,
+                Details::Unshallowed,
+            )
+            .about(name),
+        );
         Ok(())
     }
 
+    /// Look up the configured path of a submodule by name
+    fn configured_path(&self, name: &str) -> Result<String, SubmoduleError> {
+        self.config
+            .submodules
+            .get(name)
+            .ok_or_else(|| SubmoduleError::SubmoduleNotFound {
+                name: name.to_string(),
+            })?
+            .path
+            .clone()
+            .ok_or_else(|| {
+                SubmoduleError::ConfigError("No path configured for submodule".to_string())
+            })
+    }
+
+    /// The credential settings configured for a submodule
+    fn configured_auth(&self, name: &str) -> SubmoduleAuth {
+        self.config
+            .submodules
+            .get(name)
+            .map(SubmoduleAuth::from_entry)
+            .unwrap_or_default()
+    }
+
+    /// The update strategy for a submodule. Like git, submod only runs a `!command` set in
+    /// the superproject's git config: one in submod.toml is refused, or cloning a repository
+    /// and running `update` would run whatever it committed.
+    fn update_strategy(
+        &self,
+        name: &str,
+        entry: &SubmoduleEntry,
+    ) -> Result<SerializableUpdate, SubmoduleError> {
+        if let Some(SerializableUpdate::Command(command)) = &entry.update {
+            return Err(SubmoduleError::ConfigError(format!(
+                "submod.toml sets update = \"!{command}\" for '{name}', but update commands only run from .git/config; set it there with `git config submodule.{name}.update '!{command}'`"
+            )));
+        }
+        Ok(self
+            .local_update_command(name)
+            .or_else(|| entry.update.clone())
+            .unwrap_or_default())
+    }
+
+    /// The `!command` git config sets as a submodule's update strategy, if any
+    fn local_update_command(&self, name: &str) -> Option<SerializableUpdate> {
+        let repo = gix::open(self.git_ops.workdir()?).ok()?;
+        let value = repo
+            .config_snapshot()
+            .string(format!("submodule.{name}.update").as_str())?;
+        SerializableUpdate::from_gitmodules(&value.to_string())
+            .ok()
+            .filter(SerializableUpdate::is_command)
+    }
+
+    /// Sets `submodule.<name>.update` in the superproject's git config, where update
+    /// commands live instead of submod.toml
+    fn set_local_update(
+        &self,
+        name: &str,
+        update: &SerializableUpdate,
+    ) -> Result<(), SubmoduleError> {
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?;
+        let git_dir = gix::open(root)
+            .map_err(|_| SubmoduleError::RepositoryError)?
+            .common_dir()
+            .to_path_buf();
+        Self::set_git_field(
+            &git_dir.join("config"),
+            Some(name),
+            Field::Update,
+            Some(&update.to_gitmodules()),
+        )
+    }
+
+    /// Current depth of a checked-out submodule, or `None` if it has its full history
+    fn current_shallow_depth(&self, submodule_path: &str) -> Option<u32> {
+        let full_path = self
+            .git_ops
+            .workdir()
+            .map_or_else(|| PathBuf::from(submodule_path), |w| w.join(submodule_path));
+        gix::open(full_path)
+            .ok()
+            .and_then(|repo| crate::utilities::shallow_depth(&repo))
+    }
+
     /// Reset submodule using CLI operations
+    #[instrument(level = "info", skip_all, fields(submodule = name), err(level = "info"))]
     pub fn reset_submodule(&self, name: &str) -> Result<(), SubmoduleError> {
         let config =
             self.config
@@ -728,38 +2899,60 @@ impl GitManager {
             SubmoduleError::ConfigError("No path configured for submodule".to_string())
         })?;
 
-        println!("🔄 Hard resetting {name}...");
+        let step = |message: &str| {
+            self.emit(Event::progress(Operation::Reset, message).about(name));
+        };
+        self.emit(
+            Event::started(Operation::Reset, format!("🔄 Hard resetting {name}...")).about(name),
+        );
 
         // Step 1: Stash changes
-        println!("  📦 Stashing working changes...");
-        match self.git_ops.stash_submodule(submodule_path, true) {
-            Ok(()) => {}
-            Err(e) => println!("  ⚠️  Stash warning: {e}"),
+        step("  📦 Stashing working changes...");
+        if let Err(e) = self.git_ops.stash_submodule(submodule_path, true) {
+            self.emit(
+                Event::warning(Operation::Reset, format!("  ⚠️  Stash warning: {e}")).about(name),
+            );
         }
 
         // Step 2: Hard reset
-        println!("  🔄 Resetting to HEAD...");
+        step("  🔄 Resetting to HEAD...");
         self.git_ops
             .reset_submodule(submodule_path, true)
-            .map_err(|e| {
-                SubmoduleError::GitoxideError(format!("GitOpsManager reset failed: {e}"))
-            })?;
+            .map_err(SubmoduleError::from_git(name, Operation::Reset))?;
 
         // Step 3: Clean untracked files
-        println!("  🧹 Cleaning untracked files...");
+        step("  🧹 Cleaning untracked files...");
         self.git_ops
             .clean_submodule(submodule_path, true, true)
-            .map_err(|e| {
-                SubmoduleError::GitoxideError(format!("GitOpsManager clean failed: {e}"))
-            })?;
-
-        println!("✅ {name} reset complete");
+            .map_err(SubmoduleError::from_git(name, Operation::Reset))?;
+
+        self.emit(
+            Event::detailed(
+                EventKind::Finished,
+                Operation::Reset,
+                Details::Reset {
+                    path: submodule_path.clone(),
+                },
+            )
+            .about(name),
+        );
         Ok(())
     }
 
     /// Initialize submodule - add it first if not registered, then initialize
+    #[instrument(level = "info", skip_all, fields(submodule = name), err(level = "info"))]
     pub fn init_submodule(&mut self, name: &str) -> Result<(), SubmoduleError> {
-        let (path_str, url_str, branch, ignore, update, fetch_recurse, shallow, sparse_paths_opt) = {
+        let (
+            path_str,
+            url_str,
+            branch,
+            ignore,
+            update,
+            fetch_recurse,
+            shallow,
+            sparse_paths_opt,
+            clone_opts,
+        ) = {
             let config = self.config.get_submodule(name).ok_or_else(|| {
                 SubmoduleError::SubmoduleNotFound {
                     name: name.to_string(),
@@ -793,10 +2986,13 @@ impl GitManager {
                 url_str,
                 config.branch.clone(),
                 config.ignore,
-                config.update.clone(),
+                config.update.clone().filter(|u| !u.is_command()),
                 config.fetch_recurse,
                 config.shallow.unwrap_or(false),
                 sparse_paths_opt,
+                crate::config::SubmoduleUpdateOptions::default()
+                    .with_shallow_from(config)
+                    .with_auth_from(config),
             )
         };
 
@@ -804,7 +3000,10 @@ impl GitManager {
 
         if submodule_path.exists() && submodule_path.join(".git").exists() {
             if self.verbose {
-                println!("✅ {name} already initialized");
+                self.emit(
+                    Event::finished(Operation::Init, format!("✅ {name} already initialized"))
+                        .about(name),
+                );
             }
             // Even if already initialized, check if we need to configure sparse checkout
             if let Some(sparse_paths) = sparse_paths_opt {
@@ -814,51 +3013,89 @@ impl GitManager {
             return Ok(());
         }
 
-        if self.verbose {
-            println!("🔄 Initializing {name}...");
-        }
-
-        let workdir = std::path::Path::new(".");
-
-        // First check if submodule is registered in .gitmodules
-        let gitmodules_path = workdir.join(".gitmodules");
-        let needs_add = if gitmodules_path.exists() {
-            let gitmodules_content = fs::read_to_string(&gitmodules_path)?;
-            !gitmodules_content.contains(&format!("path = {path_str}"))
+        self.run_hooks(HookEvent::PreInit, name, None)?;
+        let message = if self.verbose {
+            format!("🔄 Initializing {name}...")
         } else {
-            true
+            String::new()
         };
+        self.emit(Event::started(Operation::Init, message).about(name));
 
-        if needs_add {
-            // Submodule not registered yet, add it first via GitOpsManager
-            let opts = crate::config::SubmoduleAddOptions {
-                name: name.to_string(),
-                path: std::path::PathBuf::from(&path_str),
-                url: url_str,
-                branch,
-                ignore,
-                update,
-                fetch_recurse,
-                shallow,
-                no_init: false,
-            };
-            self.git_ops
-                .add_submodule(&opts)
-                .map_err(Self::map_git_ops_error)?;
-        } else {
-            // Submodule is registered, just initialize and update using GitOperations
-            self.git_ops
-                .init_submodule(&path_str)
-                .map_err(Self::map_git_ops_error)?;
+        let workdir = std::path::Path::new(".");
 
-            let update_opts = crate::config::SubmoduleUpdateOptions::default();
-            self.git_ops
-                .update_submodule(&path_str, &update_opts)
-                .map_err(Self::map_git_ops_error)?;
-        }
+        // First check if submodule is registered in .gitmodules, under any name
+        let needs_add = !workdir.join(".gitmodules").exists()
+            || self
+                .git_ops
+                .read_gitmodules()
+                .map_err(SubmoduleError::from_git(name, Operation::Init))?
+                .name_for_path(&path_str)
+                .is_none();
+
+        self.interruptible(name, &path_str, |manager| {
+            if needs_add {
+                // Submodule not registered yet, add it first via GitOpsManager
+                let opts = crate::config::SubmoduleAddOptions {
+                    name: name.to_string(),
+                    path: std::path::PathBuf::from(&path_str),
+                    url: url_str,
+                    branch,
+                    ignore,
+                    update,
+                    fetch_recurse,
+                    shallow,
+                    depth: clone_opts.depth,
+                    shallow_since: clone_opts.shallow_since,
+                    auth: clone_opts.auth,
+                    no_init: false,
+                };
+                manager
+                    .git_ops
+                    .add_submodule(&opts)
+                    .map_err(SubmoduleError::from_git(name, Operation::Init))?;
+            } else if manager.checkout_from_main_worktree(name, &path_str)? {
+                // Checked out in this linked worktree; only the config is left to record
+                manager
+                    .git_ops
+                    .init_submodule(&path_str, &clone_opts.auth)
+                    .map_err(SubmoduleError::from_git(name, Operation::Init))?;
+            } else if clone_opts.is_shallow() {
+                // `.gitmodules` only knows `shallow = true`, so initializing first would
+                // clone with the wrong depth. Updating clones with the configured depth and
+                // initializes the submodule; the init that follows only records config.
+                manager
+                    .git_ops
+                    .update_submodule(&path_str, &clone_opts)
+                    .map_err(SubmoduleError::from_git(name, Operation::Init))?;
+                manager
+                    .git_ops
+                    .init_submodule(&path_str, &clone_opts.auth)
+                    .map_err(SubmoduleError::from_git(name, Operation::Init))?;
+            } else {
+                // Submodule is registered, just initialize and update using GitOperations
+                manager
+                    .git_ops
+                    .init_submodule(&path_str, &clone_opts.auth)
+                    .map_err(SubmoduleError::from_git(name, Operation::Init))?;
+
+                manager
+                    .git_ops
+                    .update_submodule(&path_str, &clone_opts)
+                    .map_err(SubmoduleError::from_git(name, Operation::Init))?;
+            }
+            Ok(())
+        })?;
+
+        self.absorb_new_clone(&path_str)?;
 
         if self.verbose {
-            println!("  ✅ Initialized using git submodule commands: {path_str}");
+            self.emit(
+                Event::progress(
+                    Operation::Init,
+                    format!("  ✅ Initialized using git submodule commands: {path_str}"),
+                )
+                .about(name),
+            );
         }
 
         // Configure sparse checkout if specified
@@ -867,30 +3104,45 @@ impl GitManager {
             self.configure_sparse_checkout(&path_str, &sparse_paths, use_git_default)?;
         }
 
-        if self.verbose {
-            println!("✅ {name} initialized");
-        }
-        Ok(())
+        let message = if self.verbose {
+            format!("✅ {name} initialized")
+        } else {
+            String::new()
+        };
+        self.emit(Event::finished(Operation::Init, message).about(name));
+        self.run_hooks(HookEvent::PostInit, name, None)
     }
 
     /// Check all submodules using gitoxide APIs where possible
     #[allow(clippy::unnecessary_wraps)]
+    #[instrument(level = "info", skip_all, err(level = "info"))]
     pub fn check_all_submodules(&self) -> Result<(), SubmoduleError> {
         if self.verbose {
-            println!("Checking submodule configurations...");
+            self.emit(Event::started(
+                Operation::Check,
+                "Checking submodule configurations...",
+            ));
         }
 
         for (submodule_name, submodule) in self.config.get_submodules() {
+            let report = |lines: &[String]| {
+                if !lines.is_empty() {
+                    self.emit(
+                        Event::progress(Operation::Check, lines.join("\n")).about(submodule_name),
+                    );
+                }
+            };
+
             // Handle missing path gracefully - report but don't fail
             let Some(path_str) = submodule.path.as_ref() else {
                 // Always show errors regardless of verbosity
-                println!("  ❌ {submodule_name}: No path configured");
+                report(&[format!("  ❌ {submodule_name}: No path configured")]);
                 continue;
             };
 
             // Handle missing URL gracefully - report but don't fail
             if submodule.url.is_none() {
-                println!("  ❌ {submodule_name}: No URL configured");
+                report(&[format!("  ❌ {submodule_name}: No URL configured")]);
                 continue;
             }
 
@@ -898,98 +3150,155 @@ impl GitManager {
             let git_path = submodule_path.join(".git");
 
             if !submodule_path.exists() {
-                println!("  ❌ {submodule_name}: Folder missing ({path_str})");
+                report(&[format!(
+                    "  ❌ {submodule_name}: Folder missing ({path_str})"
+                )]);
                 continue;
             }
 
             if !git_path.exists() {
-                println!("  ❌ {submodule_name}: Not a git repository");
+                report(&[format!("  ❌ {submodule_name}: Not a git repository")]);
                 continue;
             }
 
             // GITOXIDE API: Use gix::open and status check
+            let mut lines = Vec::new();
             match self.check_submodule_repository_status(path_str, submodule_name) {
                 Ok(status) => {
                     if self.verbose {
-                        println!("\n📁 {submodule_name}");
-                        println!("  ✅ Git repository exists");
+                        lines.push(format!("\n📁 {submodule_name}"));
+                        lines.push("  ✅ Git repository exists".to_string());
 
                         if status.is_clean {
-                            println!("  ✅ Working tree is clean");
+                            lines.push("  ✅ Working tree is clean".to_string());
                         } else {
-                            println!("  ⚠️  Working tree has changes");
+                            lines.push("  ⚠️  Working tree has changes".to_string());
                         }
 
                         if let Some(commit) = &status.current_commit {
-                            println!("  ✅ Current commit: {}", &commit[..8]);
+                            lines.push(format!("  ✅ Current commit: {}", &commit[..8]));
                         }
 
                         if status.has_remotes {
-                            println!("  ✅ Has remotes configured");
+                            lines.push("  ✅ Has remotes configured".to_string());
                         } else {
-                            println!("  ⚠️  No remotes configured");
+                            lines.push("  ⚠️  No remotes configured".to_string());
+                        }
+
+                        if let Some(depth) = status.shallow_depth {
+                            lines.push(format!("  ✅ Shallow clone (depth {depth})"));
                         }
 
                         match &status.sparse_status {
                             SparseStatus::NotEnabled => {}
                             SparseStatus::NotConfigured => {
-                                println!("  ❌ Sparse checkout not configured");
+                                lines.push("  ❌ Sparse checkout not configured".to_string());
                             }
                             SparseStatus::Correct => {
-                                println!("  ✅ Sparse checkout configured correctly");
+                                lines.push("  ✅ Sparse checkout configured correctly".to_string());
                             }
                             SparseStatus::Mismatch { expected, actual } => {
-                                println!("  ❌ Sparse checkout mismatch");
-                                println!("    Expected: {expected:?}");
-                                println!("    Current: {actual:?}");
+                                lines.push("  ❌ Sparse checkout mismatch".to_string());
+                                lines.push(format!("    Expected: {expected:?}"));
+                                lines.push(format!("    Current: {actual:?}"));
                             }
                         }
 
                         // Show effective settings
-                        self.show_effective_settings(submodule_name, submodule);
+                        lines.extend(self.effective_settings(submodule));
                     } else {
-                        // Non-verbose: only print warnings/problems
+                        // Non-verbose: only report warnings/problems
                         if !status.is_clean {
-                            println!("  ⚠️  {submodule_name}: Working tree has changes");
+                            lines.push(format!("  ⚠️  {submodule_name}: Working tree has changes"));
                         }
                         if !status.has_remotes {
-                            println!("  ⚠️  {submodule_name}: No remotes configured");
+                            lines.push(format!("  ⚠️  {submodule_name}: No remotes configured"));
+                        }
+                        if let Some(depth) = status.shallow_depth {
+                            lines.push(format!(
+                                "  📏 {submodule_name}: Shallow clone (depth {depth})"
+                            ));
                         }
                         match &status.sparse_status {
                             SparseStatus::NotEnabled | SparseStatus::Correct => {}
                             SparseStatus::NotConfigured => {
-                                println!("  ❌ {submodule_name}: Sparse checkout not configured");
+                                lines.push(format!(
+                                    "  ❌ {submodule_name}: Sparse checkout not configured"
+                                ));
                             }
                             SparseStatus::Mismatch { expected, actual } => {
-                                println!("  ❌ {submodule_name}: Sparse checkout mismatch");
-                                println!("    Expected: {expected:?}");
-                                println!("    Current: {actual:?}");
+                                lines.push(format!(
+                                    "  ❌ {submodule_name}: Sparse checkout mismatch"
+                                ));
+                                lines.push(format!("    Expected: {expected:?}"));
+                                lines.push(format!("    Current: {actual:?}"));
                             }
                         }
                     }
                 }
                 Err(e) => {
-                    println!("  ❌ {submodule_name}: Cannot analyze repository: {e}");
+                    lines.push(format!(
+                        "  ❌ {submodule_name}: Cannot analyze repository: {e}"
+                    ));
                 }
             }
+            report(&lines);
+        }
+
+        // A git directory inside the submodule is deleted along with it
+        if let Ok((_, plan)) = self.absorb_plan(None) {
+            for relocation in plan.iter().filter(|relocation| relocation.embedded) {
+                self.emit(Event::progress(
+                    Operation::Check,
+                    format!(
+                        "  ⚠️  {}: Has its own .git directory; run `submod absorb` to move it into .git/modules",
+                        relocation.path
+                    ),
+                ));
+            }
         }
 
         Ok(())
     }
 
-    #[allow(clippy::unused_self)]
-    fn show_effective_settings(&self, _name: &str, config: &SubmoduleEntry) {
-        println!("  📋 Effective settings:");
+    /// The lines `check --verbose` shows for a submodule's settings
+    fn effective_settings(&self, config: &SubmoduleEntry) -> Vec<String> {
+        let mut lines = vec!["  📋 Effective settings:".to_string()];
 
         if let Some(ignore) = &config.ignore {
-            println!("     ignore = {ignore:?}");
+            lines.push(format!("     ignore = {ignore:?}"));
         }
         if let Some(update) = &config.update {
-            println!("     update = {update:?}");
+            lines.push(format!("     update = {update:?}"));
         }
         if let Some(branch) = &config.branch {
-            println!("     branch = {branch:?}");
+            lines.push(format!("     branch = {branch:?}"));
+        }
+        if let Some(depth) = config.effective_depth() {
+            lines.push(format!("     depth = {depth}"));
+        }
+        if let Some(since) = &config.shallow_since {
+            lines.push(format!("     shallow_since = {since}"));
+        }
+        if let Some(key) = &config.ssh_key {
+            lines.push(format!("     ssh_key = {key}"));
+        }
+        if let Some(helper) = &config.credential_helper {
+            lines.push(format!("     credential_helper = {helper}"));
+        }
+        if let Some(var) = &config.http_extra_header_env {
+            lines.push(format!("     http_extra_header_env = {var}"));
+        }
+        if let Some(groups) = config.groups.as_ref().filter(|groups| !groups.is_empty()) {
+            lines.push(format!("     groups = {}", groups.join(", ")));
+        }
+        let hooks = self.effective_hooks(config.hooks.as_ref());
+        for event in HookEvent::ALL {
+            for command in hooks.commands(event) {
+                lines.push(format!("     hooks.{event} = {command}"));
+            }
         }
+        lines
     }
     /// Get reference to the underlying config
     pub const fn config(&self) -> &Config {
@@ -1008,6 +3317,13 @@ impl GitManager {
         self.config.clone()
     }
 
+    /// The superproject's working directory, which submodule paths are relative to
+    pub fn root(&self) -> PathBuf {
+        self.git_ops
+            .workdir()
+            .map_or_else(|| PathBuf::from("."), |root| root.components().collect())
+    }
+
     /// Extract the submodule name from a TOML section header line, e.g. `[my-sub]` → `my-sub`.
     /// Returns `None` if the line does not look like a section header.
     fn section_name_from_header(header: &str) -> Option<String> {
@@ -1026,6 +3342,52 @@ impl GitManager {
         }
     }
 
+    /// Extract the owner of a hooks table header, e.g. `[my-sub.hooks]` → `my-sub` and
+    /// `[defaults.hooks]` → `defaults`. Returns `None` for any other header.
+    fn hooks_owner_from_header(header: &str) -> Option<String> {
+        let inner = header.trim().strip_prefix('[')?.strip_suffix(']')?;
+        let owner = inner.trim().strip_suffix(".hooks")?.trim_end();
+        if owner.is_empty() || inner.starts_with('[') {
+            return None;
+        }
+        Self::section_name_from_header(&format!("[{owner}]"))
+    }
+
+    /// Format `name` as a TOML table key, quoting it when it isn't a bare key.
+    fn table_key(name: &str) -> String {
+        let needs_quoting = name
+            .chars()
+            .any(|c| !c.is_alphanumeric() && c != '-' && c != '_');
+        if needs_quoting {
+            format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Serialize a hooks table to a list of key = value lines (no section header).
+    fn hooks_to_kv_lines(hooks: &SubmoduleHooks) -> Vec<(String, String)> {
+        let mut kv: Vec<(String, String)> = Vec::new();
+        for event in HookEvent::ALL {
+            // An empty list is kept: it switches off the defaults' commands for the event
+            if let Some(commands) = hooks.get(event) {
+                let joined = commands
+                    .iter()
+                    .map(|c| format!("\"{}\"", c.replace('\\', "\\\\").replace('"', "\\\"")))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                kv.push((event.as_str().into(), format!("[{joined}]")));
+            }
+        }
+        if let Some(timeout) = hooks.timeout {
+            kv.push(("timeout".into(), timeout.to_string()));
+        }
+        if let Some(on_failure) = hooks.on_failure {
+            kv.push(("on_failure".into(), format!("\"{on_failure}\"")));
+        }
+        kv
+    }
+
     /// Serialize the given `SubmoduleEntry` to a list of key = value lines (no section header).
     fn entry_to_kv_lines(entry: &SubmoduleEntry) -> Vec<(String, String)> {
         let mut kv: Vec<(String, String)> = Vec::new();
@@ -1050,6 +3412,15 @@ impl GitManager {
                 ));
             }
         }
+        if let Some(fallback) = &entry.fallback_branch {
+            kv.push((
+                "fallback_branch".into(),
+                format!(
+                    "\"{}\"",
+                    fallback.replace('\\', "\\\\").replace('"', "\\\"")
+                ),
+            ));
+        }
         if let Some(ignore) = &entry.ignore {
             let val = ignore.to_string();
             if !val.is_empty() {
@@ -1071,11 +3442,31 @@ impl GitManager {
         if let Some(active) = entry.active {
             kv.push(("active".into(), active.to_string()));
         }
-        if let Some(shallow) = entry.shallow
+        if let Some(depth) = entry.depth {
+            kv.push(("depth".into(), depth.to_string()));
+        } else if let Some(shallow) = entry.shallow
             && shallow
         {
             kv.push(("shallow".into(), "true".into()));
         }
+        if let Some(since) = &entry.shallow_since {
+            kv.push((
+                "shallow_since".into(),
+                format!("\"{}\"", since.replace('\\', "\\\\").replace('"', "\\\"")),
+            ));
+        }
+        for (key, value) in [
+            ("ssh_key", &entry.ssh_key),
+            ("credential_helper", &entry.credential_helper),
+            ("http_extra_header_env", &entry.http_extra_header_env),
+        ] {
+            if let Some(value) = value {
+                kv.push((
+                    key.into(),
+                    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")),
+                ));
+            }
+        }
         if let Some(sparse_paths) = &entry.sparse_paths
             && !sparse_paths.is_empty()
         {
@@ -1086,6 +3477,16 @@ impl GitManager {
                 .join(", ");
             kv.push(("sparse_paths".into(), format!("[{joined}]")));
         }
+        if let Some(groups) = &entry.groups
+            && !groups.is_empty()
+        {
+            let joined = groups
+                .iter()
+                .map(|g| format!("\"{}\"", g.replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            kv.push(("groups".into(), format!("[{joined}]")));
+        }
         kv
     }
 
@@ -1094,18 +3495,44 @@ impl GitManager {
         "path",
         "url",
         "branch",
+        "fallback_branch",
         "ignore",
         "fetchRecurse",
         "fetch",
         "update",
         "active",
         "shallow",
+        "depth",
+        "shallow_since",
+        "ssh_key",
+        "credential_helper",
+        "http_extra_header_env",
         "sparse_paths",
+        "groups",
+    ];
+
+    /// Known hooks table key names.
+    const KNOWN_HOOK_KEYS: &'static [&'static str] = &[
+        "pre_add",
+        "post_add",
+        "pre_init",
+        "post_init",
+        "pre_update",
+        "post_update",
+        "pre_sync",
+        "post_sync",
+        "timeout",
+        "on_failure",
     ];
 
     /// Known [defaults] key names.
-    const KNOWN_DEFAULTS_KEYS: &'static [&'static str] =
-        &["ignore", "fetchRecurse", "fetch", "update"];
+    const KNOWN_DEFAULTS_KEYS: &'static [&'static str] = &[
+        "ignore",
+        "fetchRecurse",
+        "fetch",
+        "update",
+        "fallback_branch",
+    ];
 
     /// Return the key name if `line` is a key = value assignment for one of `known_keys`, else None.
     fn line_key<'a>(line: &str, known_keys: &[&'a str]) -> Option<&'a str> {
@@ -1154,6 +3581,7 @@ impl GitManager {
         // Track which names appeared in the existing file (so we know what to append)
         let mut seen_names: std::collections::HashSet<String> = std::collections::HashSet::new();
         let mut seen_defaults = false;
+        let mut seen_hooks: std::collections::HashSet<String> = std::collections::HashSet::new();
 
         // Parse the file into sections.
         // Each element: (header_line, body_lines)
@@ -1212,6 +3640,15 @@ impl GitManager {
                     kv.push(("update".into(), format!("\"{val}\"")));
                 }
             }
+            if let Some(fallback) = &defaults.fallback_branch {
+                kv.push((
+                    "fallback_branch".into(),
+                    format!(
+                        "\"{}\"",
+                        fallback.replace('\\', "\\\\").replace('"', "\\\"")
+                    ),
+                ));
+            }
             kv
         };
 
@@ -1227,6 +3664,28 @@ impl GitManager {
                 continue;
             }
 
+            if let Some(owner) = Self::hooks_owner_from_header(header) {
+                let hooks = if owner == "defaults" {
+                    defaults.hooks.as_ref()
+                } else {
+                    current_entries
+                        .get(owner.as_str())
+                        .and_then(|entry| entry.hooks.as_ref())
+                };
+                // A hooks table whose owner or hooks are gone is omitted
+                if let Some(hooks) = hooks {
+                    output.push_str(header);
+                    output.push('\n');
+                    let kv = Self::hooks_to_kv_lines(hooks);
+                    for line in &Self::merge_section_body(body, &kv, Self::KNOWN_HOOK_KEYS) {
+                        output.push_str(line);
+                        output.push('\n');
+                    }
+                }
+                seen_hooks.insert(owner);
+                continue;
+            }
+
             let sec_name = Self::section_name_from_header(header).unwrap_or_default();
 
             if sec_name == "defaults" {
@@ -1271,19 +3730,28 @@ impl GitManager {
         // Append submodule sections that weren't in the existing file (sorted for determinism)
         for (name, entry) in &current_entries {
             if !seen_names.contains(name.as_str()) {
-                let needs_quoting = name
-                    .chars()
-                    .any(|c| !c.is_alphanumeric() && c != '-' && c != '_');
-                let escaped_name = name.replace('\\', "\\\\").replace('"', "\\\"");
-                let section_header = if needs_quoting {
-                    format!("[\"{escaped_name}\"]")
-                } else {
-                    format!("[{name}]")
-                };
-                output.push_str(&section_header);
-                output.push('\n');
+                use std::fmt::Write as _;
+                let _ = writeln!(output, "[{}]", Self::table_key(name));
                 for (key, val) in Self::entry_to_kv_lines(entry) {
-                    use std::fmt::Write as _;
+                    let _ = writeln!(output, "{key} = {val}");
+                }
+                output.push('\n');
+            }
+        }
+
+        // Append hooks tables that weren't in the existing file
+        let hook_tables = std::iter::once(("defaults", defaults.hooks.as_ref())).chain(
+            current_entries
+                .iter()
+                .map(|(name, entry)| (name.as_str(), entry.hooks.as_ref())),
+        );
+        for (owner, hooks) in hook_tables {
+            if let Some(hooks) = hooks
+                && !seen_hooks.contains(owner)
+            {
+                use std::fmt::Write as _;
+                let _ = writeln!(output, "[{}.hooks]", Self::table_key(owner));
+                for (key, val) in Self::hooks_to_kv_lines(hooks) {
                     let _ = writeln!(output, "{key} = {val}");
                 }
                 output.push('\n');
@@ -1313,9 +3781,19 @@ impl GitManager {
 
         let mut emitted_keys: std::collections::HashSet<&str> = std::collections::HashSet::new();
         let mut result: Vec<String> = Vec::new();
+        // Open brackets left by a known key's multi-line array value, whose
+        // continuation lines are replaced along with it
+        let mut open_brackets = 0usize;
 
         for line in body {
+            if open_brackets > 0 {
+                open_brackets = Self::bracket_depth_after(line, open_brackets);
+                continue;
+            }
             if let Some(key) = Self::line_key(line, known_keys) {
+                open_brackets = line
+                    .split_once('=')
+                    .map_or(0, |(_, value)| Self::bracket_depth_after(value, 0));
                 if let Some(new_val) = kv_map.get(key) {
                     // Replace existing key line with new value, preserving inline comment if any
                     let comment_part = Self::extract_inline_comment(line);
@@ -1333,16 +3811,41 @@ impl GitManager {
             }
         }
 
-        // Append any new keys (from new_kv) that were not already in the body
+        // Append any new keys (from new_kv) that were not already in the body, ahead of
+        // the blank lines that separate this section from the next
+        let mut insert_at = result
+            .iter()
+            .rposition(|line| !line.trim().is_empty())
+            .map_or(0, |last| last + 1);
         for (key, val) in new_kv {
             if !emitted_keys.contains(key.as_str()) {
-                result.push(format!("{key} = {val}"));
+                result.insert(insert_at, format!("{key} = {val}"));
+                insert_at += 1;
             }
         }
 
         result
     }
 
+    /// The array nesting depth after reading `text`, starting from `depth`. Brackets inside
+    /// quoted strings and comments don't count.
+    fn bracket_depth_after(text: &str, mut depth: usize) -> usize {
+        let mut in_quote = false;
+        let mut escaped = false;
+        for ch in text.chars() {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' if in_quote => escaped = true,
+                '"' => in_quote = !in_quote,
+                '#' if !in_quote => break,
+                '[' if !in_quote => depth += 1,
+                ']' if !in_quote => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        depth
+    }
+
     /// Extract an inline comment (e.g. `# ...`) from a TOML value line, if any.
     /// Returns the comment portion including `#`, or an empty string.
     fn extract_inline_comment(line: &str) -> &str {
@@ -1368,26 +3871,37 @@ impl GitManager {
     /// List all submodules from the config. If `recursive` is true, also lists
     /// submodules found in the git repository (which may include nested ones).
     #[allow(clippy::unnecessary_wraps)]
+    #[instrument(level = "info", skip_all, err(level = "info"))]
     pub fn list_submodules(&self, recursive: bool) -> Result<(), SubmoduleError> {
         let submodules: Vec<_> = self.config.get_submodules().collect();
 
         if submodules.is_empty() && !recursive {
-            println!("No submodules configured.");
+            self.emit(Event::finished(
+                Operation::List,
+                "No submodules configured.",
+            ));
             return Ok(());
         }
 
         if submodules.is_empty() {
-            println!("No submodules configured.");
+            self.emit(Event::progress(
+                Operation::List,
+                "No submodules configured.",
+            ));
         } else {
-            println!("Submodules:");
+            self.emit(Event::progress(Operation::List, "Submodules:"));
             for (name, entry) in &submodules {
                 let path = entry.path.as_deref().unwrap_or("<no path>");
                 let url = entry.url.as_deref().unwrap_or("<no url>");
                 let active = entry.active.unwrap_or(true);
                 let active_str = if active { "active" } else { "disabled" };
-                println!("  {name} [{active_str}]");
-                println!("    path: {path}");
-                println!("    url:  {url}");
+                self.emit(
+                    Event::progress(
+                        Operation::List,
+                        format!("  {name} [{active_str}]\n    path: {path}\n    url:  {url}"),
+                    )
+                    .about(*name),
+                );
             }
         }
 
@@ -1404,14 +3918,19 @@ impl GitManager {
                         .filter(|p| !config_paths.contains(*p))
                         .collect();
                     if !extra.is_empty() {
-                        println!("\nAdditional submodules found in git (not in config):");
+                        let mut message =
+                            "\nAdditional submodules found in git (not in config):".to_string();
                         for path in extra {
-                            println!("  {path}");
+                            let _ = write!(message, "\n  {path}");
                         }
+                        self.emit(Event::progress(Operation::List, message));
                     }
                 }
                 Err(e) => {
-                    eprintln!("Warning: could not list git submodules: {e}");
+                    self.emit(Event::warning(
+                        Operation::List,
+                        format!("Warning: could not list git submodules: {e}"),
+                    ));
                 }
             }
         }
@@ -1420,6 +3939,7 @@ impl GitManager {
     }
 
     /// Update global default settings and save the config.
+    #[instrument(level = "info", skip_all, err(level = "info"))]
     pub fn update_global_defaults(
         &mut self,
         ignore: Option<SerializableIgnore>,
@@ -1436,6 +3956,12 @@ impl GitManager {
                 "No settings provided to change.".to_string(),
             ));
         }
+        // Update commands live in .git/config, one submodule at a time
+        if update.as_ref().is_some_and(SerializableUpdate::is_command) {
+            return Err(SubmoduleError::ConfigError(
+                "Update commands can't be defaults; set one per submodule with `submod change <name> --update '!<command>'`".to_string(),
+            ));
+        }
         if let Some(i) = ignore {
             self.config.defaults.ignore = Some(i);
         }
@@ -1452,6 +3978,7 @@ impl GitManager {
     }
 
     /// Disable a submodule by setting `active = false` in the config and deinitializing it.
+    #[instrument(level = "info", skip_all, fields(submodule = name), err(level = "info"))]
     pub fn disable_submodule(&mut self, name: &str) -> Result<(), SubmoduleError> {
         let entry = self
             .config
@@ -1475,15 +4002,11 @@ impl GitManager {
 
         // Update .gitmodules
         if let Ok(mut entries) = self.git_ops.read_gitmodules() {
-            // Find by name, or fall back to finding by path
-            let gitmodules_name = if entries.get(name).is_some() {
-                Some(name.to_string())
-            } else {
-                entries
-                    .submodule_iter()
-                    .find(|(_, e)| e.path.as_deref() == Some(path.as_str()))
-                    .map(|(n, _)| n.clone())
-            };
+            // Find by path, or fall back to finding by name
+            let gitmodules_name = entries
+                .name_for_path(&path)
+                .cloned()
+                .or_else(|| entries.contains_key(name).then(|| name.to_string()));
 
             if let Some(gm_name) = gitmodules_name {
                 let mut gitmodules_entry = entries.get(&gm_name).cloned().unwrap();
@@ -1494,11 +4017,14 @@ impl GitManager {
         }
 
         self.write_full_config()?;
-        println!("Disabled submodule '{name}'.");
+        self.emit(
+            Event::detailed(EventKind::Finished, Operation::Disable, Details::Disabled).about(name),
+        );
         Ok(())
     }
 
     /// Delete a submodule: deinit, remove from filesystem, and remove from config.
+    #[instrument(level = "info", skip_all, fields(submodule = name), err(level = "info"))]
     pub fn delete_submodule_by_name(&mut self, name: &str) -> Result<(), SubmoduleError> {
         let entry = self
             .config
@@ -1509,12 +4035,20 @@ impl GitManager {
             .clone();
 
         let path = entry.path.as_deref().unwrap_or(name).to_string();
+        // Read before the cleanups below take it out of .gitmodules
+        let git_name = self.gitmodules_name(name, &path);
 
         // Deinit (best-effort — submodule may not be registered in .gitmodules)
         let _ = self.git_ops.deinit_submodule(&path, true);
         // Git-layer delete (best-effort — submodule may only be in our config, not .gitmodules)
         if let Err(e) = self.git_ops.delete_submodule(&path) {
-            eprintln!("Note: git cleanup for '{name}' skipped: {e}");
+            self.emit(
+                Event::warning(
+                    Operation::Delete,
+                    format!("Note: git cleanup for '{name}' skipped: {e}"),
+                )
+                .about(name),
+            );
             // Still try to remove the directory from the filesystem directly
             let dir = std::path::Path::new(&path);
             if dir.exists() {
@@ -1522,34 +4056,22 @@ impl GitManager {
             }
         }
 
-        // Ensure thorough cleanup of git state left behind by git2 (which uses the *path*
-        // as the submodule key rather than the *name*).  The gix/git2 cleanups above key
-        // on the name, so path-based artifacts may linger and prevent a clean re-add.
+        // Ensure thorough cleanup of the git state keyed by the submodule's git name, which
+        // the best-effort cleanups above may leave behind and would prevent a clean re-add.
         if let Some(workdir) = self.git_ops.workdir() {
             let workdir = workdir.to_path_buf();
-
-            // Remove path-based git config section (created by git2 during add)
-            if path != name {
-                let _ = std::process::Command::new("git")
-                    .args(["config", "--remove-section", &format!("submodule.{path}")])
-                    .current_dir(&workdir)
-                    .output();
-            }
-            // Also ensure name-based config section is gone
+            let git_name = git_name.unwrap_or_else(|| name.to_string());
             let _ = std::process::Command::new("git")
-                .args(["config", "--remove-section", &format!("submodule.{name}")])
+                .args([
+                    "config",
+                    "--remove-section",
+                    &format!("submodule.{git_name}"),
+                ])
                 .current_dir(&workdir)
                 .output();
-
-            // Remove path-based .git/modules directory (created by git2 using path as key)
-            let path_modules_dir = workdir.join(".git").join("modules").join(&path);
-            if path_modules_dir.exists() {
-                let _ = fs::remove_dir_all(&path_modules_dir);
-            }
-            // Also ensure name-based .git/modules directory is gone
-            let name_modules_dir = workdir.join(".git").join("modules").join(name);
-            if name_modules_dir.exists() {
-                let _ = fs::remove_dir_all(&name_modules_dir);
+            let modules_dir = workdir.join(".git").join("modules").join(&git_name);
+            if modules_dir.exists() {
+                let _ = fs::remove_dir_all(&modules_dir);
             }
         }
 
@@ -1557,21 +4079,655 @@ impl GitManager {
         let _ = self.config.submodules.remove_submodule(name);
         self.write_full_config()?;
 
-        // Reopen the git repository to flush any cached state (git2 caches internal state
-        // about submodules and will fail on subsequent add_submodule calls if not refreshed).
-        if let Err(e) = self.git_ops.reopen() {
-            eprintln!(
-                "Warning: failed to refresh git repository state after deleting submodule '{name}': {e}"
-            );
+        // Reopen the git repository to flush any cached state (git2 caches internal state
+        // about submodules and will fail on subsequent add_submodule calls if not refreshed).
+        if let Err(e) = self.git_ops.reopen() {
+            self.emit(
+                Event::warning(
+                    Operation::Delete,
+                    format!(
+                        "Warning: failed to refresh git repository state after deleting submodule '{name}': {e}"
+                    ),
+                )
+                .about(name),
+            );
+        }
+
+        self.emit(
+            Event::detailed(EventKind::Finished, Operation::Delete, Details::Deleted).about(name),
+        );
+        Ok(())
+    }
+
+    /// Replace a submodule with its checked-out files: the gitlink, `.gitmodules` entry,
+    /// `.git/config` section, clone, and submod.toml entry are removed, the files the
+    /// submodule has checked out (honoring sparse checkout) are added to the superproject's
+    /// index, and the URL and commit are recorded in `VENDORED.toml`. Nothing is committed.
+    /// Unless `force` is set, the checked-out commit must be on a remote-tracking branch, so
+    /// the recorded commit can be fetched again. If a step fails, everything is put back.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::SubmoduleNotFound` if `name` isn't configured, and
+    /// `SubmoduleError::ConfigError` if it has no URL, isn't checked out, has uncommitted
+    /// changes, has a commit no remote has (without `force`), or has submodules of its own.
+    /// Returns `SubmoduleError::CliError` if a git command fails.
+    #[instrument(level = "info", skip_all, fields(submodule = name), err(level = "info"))]
+    pub fn vendor_submodule(&mut self, name: &str, force: bool) -> Result<(), SubmoduleError> {
+        let entry = self
+            .config
+            .get_submodule(name)
+            .ok_or_else(|| SubmoduleError::SubmoduleNotFound {
+                name: name.to_string(),
+            })?
+            .clone();
+        let path = entry.path.clone().unwrap_or_else(|| name.to_string());
+        let url = entry.url.clone().ok_or_else(|| {
+            SubmoduleError::ConfigError(format!(
+                "Submodule '{name}' has no URL to record as where its files came from"
+            ))
+        })?;
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?
+            .to_path_buf();
+        let superproject = gix::open(&root).map_err(|_| SubmoduleError::RepositoryError)?;
+        let super_git_dir = superproject.git_dir().to_path_buf();
+        let index_path = superproject.index_path();
+        drop(superproject);
+
+        let dir = root.join(&path);
+        let Ok(repo) = gix::open(&dir) else {
+            return Err(SubmoduleError::ConfigError(format!(
+                "Submodule '{name}' isn't checked out at {path}; run `submod init` first"
+            )));
+        };
+        // Vendoring uncommitted changes would make the recorded commit a lie
+        if repo.is_dirty().unwrap_or(true) {
+            return Err(SubmoduleError::DirtyWorktree {
+                name: name.to_string(),
+                operation: Operation::Vendor,
+                backend: None,
+                message: format!(
+                    "Submodule '{name}' has uncommitted changes; commit or discard them first"
+                ),
+            });
+        }
+        let commit = repo
+            .head_id()
+            .map_err(|e| {
+                SubmoduleError::ConfigError(format!("Submodule '{name}' has no commit: {e}"))
+            })?
+            .to_string();
+        let files = vendor::checked_out_files(&repo).map_err(|e| {
+            SubmoduleError::ConfigError(format!("Can't vendor submodule '{name}': {e}"))
+        })?;
+        let module_dir = repo.git_dir().to_path_buf();
+        // Its git directory goes, and with it any commit no remote has; the one recorded in
+        // VENDORED.toml has to stay fetchable for `submod adopt` to undo this
+        if !force {
+            let pushed = Self::run_git(
+                &dir,
+                &[
+                    "for-each-ref",
+                    "--count=1",
+                    "--contains",
+                    "HEAD",
+                    "refs/remotes/",
+                ],
+                None,
+            )
+            .is_ok_and(|refs| !refs.trim().is_empty());
+            if !pushed {
+                return Err(SubmoduleError::ConfigError(format!(
+                    "Submodule '{name}' is at {}, which no remote-tracking branch contains, so it couldn't be fetched again; push it first, or pass --force to vendor it anyway",
+                    &commit[..commit.len().min(12)]
+                )));
+            }
+        }
+        drop(repo);
+
+        // Until every step has worked, the clone's `.git` is only moved aside, and the
+        // index and the files submod edits can be put back
+        let index = fs::read(&index_path)?;
+        let gitmodules = fs::read(root.join(".gitmodules")).ok();
+        let vendored_file = fs::read(root.join(vendor::VENDORED_FILE)).ok();
+        let dot_git = dir.join(".git");
+        let aside = super_git_dir.join(format!("vendor-{}", std::process::id()));
+        let record = VendoredRecord {
+            path: path.clone(),
+            url: url.clone(),
+            commit: commit.clone(),
+            branch: match &entry.branch {
+                Some(SerializableBranch::Name(branch)) => Some(branch.clone()),
+                _ => None,
+            },
+            sparse_paths: entry.sparse_paths.clone().filter(|paths| !paths.is_empty()),
+        };
+        let gitmodules_name = self.gitmodules_name(name, &path);
+        let staged = Self::stage_vendored_files(
+            &root,
+            name,
+            &path,
+            &files,
+            (&dot_git, &aside),
+            gitmodules_name.as_deref(),
+            record,
+        )
+        .and_then(|()| {
+            let _ = self.config.submodules.remove_submodule(name);
+            self.write_full_config()
+        });
+        if let Err(e) = staged {
+            self.config.add_submodule(name.to_string(), entry);
+            let _ = fs::write(&index_path, &index);
+            for (file, contents) in [
+                (".gitmodules", gitmodules),
+                (vendor::VENDORED_FILE, vendored_file),
+            ] {
+                let _ = contents.map_or_else(
+                    || fs::remove_file(root.join(file)),
+                    |contents| fs::write(root.join(file), contents),
+                );
+            }
+            if aside.exists() {
+                let _ = fs::rename(&aside, &dot_git);
+            }
+            return Err(e);
+        }
+
+        // Only now do the clone's git directories go for good
+        if aside.is_dir() {
+            fs::remove_dir_all(&aside)?;
+        } else {
+            fs::remove_file(&aside)?;
+        }
+        if module_dir.starts_with(super_git_dir.join("modules")) {
+            fs::remove_dir_all(&module_dir)?;
+        }
+        if let Some(gm_name) = &gitmodules_name {
+            let section = format!("submodule.{gm_name}");
+            let _ = Self::run_git(&root, &["config", "--remove-section", &section], None);
+        }
+        if let Err(e) = self.git_ops.reopen() {
+            self.emit(
+                Event::warning(
+                    Operation::Vendor,
+                    format!(
+                        "Warning: failed to refresh git repository state after vendoring submodule '{name}': {e}"
+                    ),
+                )
+                .about(name),
+            );
+        }
+
+        self.emit(
+            Event::detailed(
+                EventKind::Finished,
+                Operation::Vendor,
+                Details::Vendored {
+                    path,
+                    url,
+                    commit,
+                    files: files.len(),
+                    record: vendor::VENDORED_FILE.to_string(),
+                },
+            )
+            .about(name),
+        );
+        Ok(())
+    }
+
+    /// The index side of vendoring: drops the gitlink, moves the clone's `.git` from
+    /// `dot_git` to `aside` so git sees plain files, stages the checked-out `files` under
+    /// `path`, drops the `.gitmodules` entry, and records `record` in `VENDORED.toml`
+    fn stage_vendored_files(
+        root: &Path,
+        name: &str,
+        path: &str,
+        files: &[String],
+        (dot_git, aside): (&Path, &Path),
+        gitmodules_name: Option<&str>,
+        record: VendoredRecord,
+    ) -> Result<(), SubmoduleError> {
+        Self::run_git(root, &["rm", "--cached", "-q", "--", path], None)?;
+        fs::rename(dot_git, aside)?;
+        let pathspecs = files.iter().fold(String::new(), |mut list, file| {
+            list.push_str(path);
+            list.push('/');
+            list.push_str(file);
+            list.push('\0');
+            list
+        });
+        Self::run_git(
+            root,
+            &["add", "-f", "--pathspec-from-file=-", "--pathspec-file-nul"],
+            Some(&pathspecs),
+        )?;
+
+        // `.gitmodules` may know the submodule by a different name than submod.toml
+        if let Some(gm_name) = gitmodules_name {
+            let section = format!("submodule.{gm_name}");
+            Self::run_git(
+                root,
+                &["config", "-f", ".gitmodules", "--remove-section", &section],
+                None,
+            )?;
+            let emptied = fs::read_to_string(root.join(".gitmodules"))
+                .is_ok_and(|text| text.trim().is_empty());
+            if emptied {
+                Self::run_git(root, &["rm", "-f", "-q", "--", ".gitmodules"], None)?;
+            } else {
+                Self::run_git(root, &["add", "--", ".gitmodules"], None)?;
+            }
+        }
+
+        let mut vendored = Vendored::read(root).map_err(Self::map_git_ops_error)?;
+        vendored.submodules.insert(name.to_string(), record);
+        vendored.write(root).map_err(Self::map_git_ops_error)?;
+        Self::run_git(root, &["add", "--", vendor::VENDORED_FILE], None)?;
+        Ok(())
+    }
+
+    /// Make the directory at `path` a submodule without cloning it again.
+    ///
+    /// A nested clone keeps its history, branches, and checked-out commit; its `.git` is
+    /// moved into `.git/modules`. A plain copy of an upstream repository gets a new clone
+    /// there, fetched from `url` (or the `VENDORED.toml` record for `path`), checked out at
+    /// the upstream commit whose files match the copy. Either way the working tree is left
+    /// as it is, and the gitlink and `.gitmodules` entry are staged but not committed. `auth`
+    /// is used for the fetch, and recorded for the submodule.
+    ///
+    /// If staging the submodule fails, the index and `.gitmodules` are put back, and so is
+    /// the clone: a nested one's `.git` returns, and one made for a plain copy is removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::InvalidPath` if `path` isn't a directory inside the
+    /// superproject, and `SubmoduleError::ConfigError` if it's already a submodule, no URL
+    /// is known, or no upstream commit matches a plain copy. Returns
+    /// `SubmoduleError::CliError` if a git command fails.
+    #[instrument(level = "info", skip_all, fields(path = path), err(level = "info"))]
+    pub fn adopt_submodule(
+        &mut self,
+        path: &str,
+        url: Option<String>,
+        name: Option<String>,
+        branch: Option<String>,
+        auth: SubmoduleAuth,
+    ) -> Result<(), SubmoduleError> {
+        let path = path.trim_end_matches('/').to_string();
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?
+            .to_path_buf();
+        crate::utilities::validate_submodule_path(&root, Path::new(&path))
+            .map_err(|e| SubmoduleError::InvalidPath(e.to_string()))?;
+        let dir = root.join(&path);
+        if !dir.is_dir() {
+            return Err(SubmoduleError::InvalidPath(format!(
+                "{path} is not a directory"
+            )));
+        }
+        if let Some((existing, _)) = self
+            .config
+            .get_submodules()
+            .find(|(_, entry)| entry.path.as_deref() == Some(path.as_str()))
+        {
+            return Err(SubmoduleError::ConfigError(format!(
+                "{path} is already the submodule '{existing}'"
+            )));
+        }
+        let dot_git = dir.join(".git");
+        if dot_git.is_file() {
+            return Err(SubmoduleError::ConfigError(format!(
+                "{path} is already a submodule or worktree"
+            )));
+        }
+
+        let mut vendored = Vendored::read(&root).map_err(Self::map_git_ops_error)?;
+        let record = vendored
+            .submodules
+            .iter()
+            .find(|(_, record)| record.path == path)
+            .map(|(name, record)| (name.clone(), record.clone()));
+        let name = match name {
+            Some(name) => name,
+            None => match &record {
+                Some((name, _)) => name.clone(),
+                None => crate::utilities::get_name(None, None, Some(path.clone().into()))
+                    .map_err(Self::map_git_ops_error)?,
+            },
+        };
+        if self.config.get_submodule(&name).is_some() {
+            return Err(SubmoduleError::ConfigError(format!(
+                "A submodule named '{name}' already exists; pick another with --name"
+            )));
+        }
+
+        let nested_clone = dot_git.is_dir();
+        let (url, branch, sparse_paths) = if nested_clone {
+            let url = match url {
+                Some(url) => url,
+                None => Self::origin_url(&dir)?.ok_or_else(|| {
+                    SubmoduleError::ConfigError(format!(
+                        "The clone at {path} has no remote; pass its upstream with --url"
+                    ))
+                })?,
+            };
+            let branch = branch.or_else(|| {
+                Self::run_git(&dir, &["symbolic-ref", "--short", "-q", "HEAD"], None)
+                    .ok()
+                    .map(|out| out.trim().to_string())
+                    .filter(|branch| !branch.is_empty())
+            });
+            (url, branch, None)
+        } else {
+            let record = record.map(|(_, record)| record);
+            let url = url
+                .or_else(|| record.as_ref().map(|record| record.url.clone()))
+                .ok_or_else(|| {
+                    SubmoduleError::ConfigError(format!(
+                        "{path} isn't a git clone; pass the upstream it was copied from with --url"
+                    ))
+                })?;
+            let auth_overrides = auth
+                .config_overrides(&root)
+                .map_err(Self::map_git_ops_error)?;
+            // A failed match leaves no half-made clone behind
+            if let Err(e) = Self::clone_in_place(&dir, &url, record.as_ref(), &auth_overrides) {
+                let _ = fs::remove_dir_all(&dot_git);
+                return Err(e);
+            }
+            let branch = branch.or_else(|| record.as_ref().and_then(|r| r.branch.clone()));
+            (url, branch, record.and_then(|record| record.sparse_paths))
+        };
+        let commit = Self::run_git(&dir, &["rev-parse", "HEAD"], None)
+            .map_err(|_| {
+                SubmoduleError::ConfigError(format!("The clone at {path} has no commits"))
+            })?
+            .trim()
+            .to_string();
+
+        // Until the submodule is staged, the index and `.gitmodules` can be put back
+        let superproject = gix::open(&root).map_err(|_| SubmoduleError::RepositoryError)?;
+        let index_path = superproject.index_path();
+        let module_dir = superproject.common_dir().join("modules").join(&name);
+        drop(superproject);
+        let index = fs::read(&index_path)?;
+        let gitmodules = fs::read(root.join(".gitmodules")).ok();
+        let staged = Self::stage_adopted(&root, &name, &path, &url, branch.as_deref(), &commit);
+        if let Err(e) = staged {
+            let _ = fs::write(&index_path, &index);
+            let _ = gitmodules.map_or_else(
+                || fs::remove_file(root.join(".gitmodules")),
+                |contents| fs::write(root.join(".gitmodules"), contents),
+            );
+            let _ = Self::run_git(
+                &root,
+                &["config", "--remove-section", &format!("submodule.{name}")],
+                None,
+            );
+            // Once absorbed, the clone's `.git` is a file pointing into `.git/modules`
+            if dot_git.is_file() && module_dir.is_dir() {
+                let _ = fs::remove_file(&dot_git);
+                let _ = fs::rename(&module_dir, &dot_git);
+                let _ = Self::run_git(&dir, &["config", "--unset", "core.worktree"], None);
+            }
+            if !nested_clone {
+                let _ = fs::remove_dir_all(&dot_git);
+            }
+            return Err(e);
+        }
+
+        if vendored.submodules.remove(&name).is_some() {
+            if vendored.submodules.is_empty() {
+                Self::run_git(
+                    &root,
+                    &["rm", "-f", "-q", "--", vendor::VENDORED_FILE],
+                    None,
+                )?;
+            } else {
+                vendored.write(&root).map_err(Self::map_git_ops_error)?;
+                Self::run_git(&root, &["add", "--", vendor::VENDORED_FILE], None)?;
+            }
+        }
+        if let Err(e) = self.git_ops.reopen() {
+            self.emit(Event::warning(
+                Operation::Adopt,
+                format!(
+                    "Warning: failed to refresh git repository state after adopting '{path}': {e}"
+                ),
+            ));
+        }
+
+        self.update_toml_config(
+            name.clone(),
+            SubmoduleEntry {
+                ssh_key: auth.ssh_key,
+                credential_helper: auth.credential_helper,
+                http_extra_header_env: auth.http_extra_header_env,
+                ..SubmoduleEntry::new(
+                    Some(url.clone()),
+                    Some(path.clone()),
+                    branch.map(SerializableBranch::Name),
+                    None,
+                    None,
+                    None,
+                    Some(true),
+                    None,
+                    None,
+                )
+            },
+            sparse_paths.clone(),
+        )?;
+        if let Some(patterns) = sparse_paths {
+            let use_git_default = self.effective_use_git_default_sparse_checkout(&name);
+            self.configure_sparse_checkout(&path, &patterns, use_git_default)?;
+        }
+
+        self.emit(
+            Event::detailed(
+                EventKind::Finished,
+                Operation::Adopt,
+                Details::Adopted {
+                    path,
+                    url,
+                    commit,
+                    absorbed_clone: nested_clone,
+                },
+            )
+            .about(&name),
+        );
+        Ok(())
+    }
+
+    /// The index side of adopting: replaces any files the superproject tracks at `path` with
+    /// a gitlink to `commit`, adds the `.gitmodules` entry, and moves the clone's `.git` into
+    /// `.git/modules`
+    fn stage_adopted(
+        root: &Path,
+        name: &str,
+        path: &str,
+        url: &str,
+        branch: Option<&str>,
+        commit: &str,
+    ) -> Result<(), SubmoduleError> {
+        Self::run_git(
+            root,
+            &["rm", "-r", "--cached", "-q", "--ignore-unmatch", "--", path],
+            None,
+        )?;
+        let key = format!("submodule.{name}");
+        Self::run_git(
+            root,
+            &["config", "-f", ".gitmodules", &format!("{key}.path"), path],
+            None,
+        )?;
+        Self::run_git(
+            root,
+            &["config", "-f", ".gitmodules", &format!("{key}.url"), url],
+            None,
+        )?;
+        if let Some(branch) = branch {
+            Self::run_git(
+                root,
+                &[
+                    "config",
+                    "-f",
+                    ".gitmodules",
+                    &format!("{key}.branch"),
+                    branch,
+                ],
+                None,
+            )?;
+        }
+        Self::run_git(
+            root,
+            &[
+                "update-index",
+                "--add",
+                "--cacheinfo",
+                &format!("160000,{commit},{path}"),
+            ],
+            None,
+        )?;
+        Self::run_git(root, &["add", "--", ".gitmodules"], None)?;
+        Self::run_git(root, &["submodule", "absorbgitdirs", "--", path], None)?;
+        Self::run_git(root, &["submodule", "init", "--", path], None)?;
+        Ok(())
+    }
+
+    /// The URL of `origin`, or of the first remote, of the clone at `dir`
+    fn origin_url(dir: &Path) -> Result<Option<String>, SubmoduleError> {
+        let remotes = Self::run_git(dir, &["remote"], None)?;
+        let Some(remote) = remotes
+            .lines()
+            .find(|remote| *remote == "origin")
+            .or_else(|| remotes.lines().next())
+        else {
+            return Ok(None);
+        };
+        let url = Self::run_git(dir, &["remote", "get-url", remote], None)?;
+        Ok(Some(url.trim().to_string()))
+    }
+
+    /// Make the plain copy at `dir` a clone of `url`, checked out at the upstream commit
+    /// whose files match it, without touching the files. With a `VENDORED.toml` record,
+    /// the recorded commit only has to match within its sparse paths. The fetch uses the
+    /// submodule's credentials, given as `auth_overrides`.
+    fn clone_in_place(
+        dir: &Path,
+        url: &str,
+        record: Option<&VendoredRecord>,
+        auth_overrides: &[(String, String)],
+    ) -> Result<(), SubmoduleError> {
+        Self::run_git(dir, &["init", "-q"], None)?;
+        Self::run_git(dir, &["remote", "add", "origin", url], None)?;
+        Self::run_git_with_auth(
+            dir,
+            &["fetch", "-q", "--tags", "origin"],
+            None,
+            auth_overrides,
+        )?;
+        // Stage the copy's files in the new clone's index to compare them to upstream
+        Self::run_git(dir, &["add", "-A"], None)?;
+        let tree = Self::run_git(dir, &["write-tree"], None)?
+            .trim()
+            .to_string();
+
+        let recorded = record.filter(|record| {
+            let mut args = vec!["diff-index", "--cached", "--quiet", &record.commit, "--"];
+            if let Some(paths) = &record.sparse_paths {
+                args.extend(paths.iter().map(String::as_str));
+            }
+            Self::run_git(dir, &args, None).is_ok()
+        });
+        let commit = if let Some(record) = recorded {
+            record.commit.clone()
+        } else {
+            let log = Self::run_git(dir, &["log", "--all", "--format=%H %T"], None)?;
+            log.lines()
+                .find_map(|line| {
+                    line.split_once(' ')
+                        .filter(|(_, commit_tree)| *commit_tree == tree)
+                        .map(|(commit, _)| commit.to_string())
+                })
+                .ok_or_else(|| {
+                    SubmoduleError::ConfigError(format!(
+                        "No commit of {url} has exactly the files in {}",
+                        dir.display()
+                    ))
+                })?
+        };
+        Self::run_git(dir, &["update-ref", "--no-deref", "HEAD", &commit], None)?;
+        Self::run_git(dir, &["reset", "-q"], None)?;
+        Ok(())
+    }
+
+    /// The name `.gitmodules` knows the submodule at `path` by, which needn't be its name in
+    /// submod.toml, or `name` if no entry has that path
+    fn gitmodules_name(&self, name: &str, path: &str) -> Option<String> {
+        let entries = self.git_ops.read_gitmodules().ok()?;
+        entries
+            .name_for_path(path)
+            .cloned()
+            .or_else(|| entries.contains_key(name).then(|| name.to_string()))
+    }
+
+    /// Runs git in `dir` with literal pathspecs, feeding it `stdin` if given, and returns
+    /// its output or its error message
+    fn run_git(dir: &Path, args: &[&str], stdin: Option<&str>) -> Result<String, SubmoduleError> {
+        Self::run_git_with_auth(dir, args, stdin, &[])
+    }
+
+    /// [`run_git`](Self::run_git), with a submodule's credential settings as config
+    /// overrides
+    fn run_git_with_auth(
+        dir: &Path,
+        args: &[&str],
+        stdin: Option<&str>,
+        auth_overrides: &[(String, String)],
+    ) -> Result<String, SubmoduleError> {
+        use std::io::Write as _;
+        use std::process::{Command, Stdio};
+
+        let mut cmd = Command::new("git");
+        cmd.arg("--literal-pathspecs")
+            .args(args)
+            .current_dir(dir)
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        crate::git_ops::apply_auth_env(&mut cmd, auth_overrides);
+        let mut child = cmd.spawn()?;
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            pipe.write_all(input.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(SubmoduleError::CliError(format!(
+                "git {}: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
         }
-
-        println!("Deleted submodule '{name}'.");
-        Ok(())
     }
 
-    /// Change settings of an existing submodule. If `path` changes, the submodule is
-    /// deleted and re-cloned at the new location.
+    /// Change settings of an existing submodule. If `path` changes, the submodule is moved
+    /// there with [`Self::move_submodule`]; if `url` changes, it's switched in place with
+    /// [`Self::set_submodule_url`], fetching from the new URL first if `fetch_url` is set.
+    /// A `new_name` is applied last, with [`Self::rename_submodule`].
     #[allow(clippy::too_many_arguments, clippy::needless_pass_by_value)]
+    #[instrument(level = "info", skip_all, fields(submodule = name), err(level = "info"))]
     pub fn change_submodule(
         &mut self,
         name: &str,
@@ -1583,9 +4739,13 @@ impl GitManager {
         fetch: Option<SerializableFetchRecurse>,
         update: Option<SerializableUpdate>,
         shallow: Option<bool>,
+        depth: Option<u32>,
+        shallow_since: Option<String>,
         url: Option<String>,
+        fetch_url: bool,
         active: Option<bool>,
         use_git_default_sparse_checkout: Option<bool>,
+        new_name: Option<String>,
     ) -> Result<(), SubmoduleError> {
         let entry = self
             .config
@@ -1595,66 +4755,24 @@ impl GitManager {
             })?
             .clone();
 
-        let new_path = path.as_ref().map(|p| p.to_string_lossy().to_string());
+        let new_path = path
+            .as_ref()
+            .map(|p| p.to_string_lossy().trim_end_matches('/').to_string());
 
-        // If path is changing, delete and re-add
+        // A new path moves the existing clone and records it; the other fields are then
+        // updated in place
         if let Some(ref np) = new_path {
             let old_path = entry.path.as_deref().unwrap_or(name);
             if np != old_path {
-                let sub_url = url
-                    .as_deref()
-                    .or(entry.url.as_deref())
-                    .ok_or_else(|| {
-                        SubmoduleError::ConfigError(
-                            "Cannot re-clone submodule: no URL available.".to_string(),
-                        )
-                    })?
-                    .to_string();
-
-                // Delete old then re-add at new path
-                self.delete_submodule_by_name(name)?;
-
-                // Compute effective branch: caller's value if provided, else preserve existing
-                let effective_branch = if branch.is_some() {
-                    SerializableBranch::set_branch(branch.clone())
-                        .map_err(|e| SubmoduleError::ConfigError(e.to_string()))?
-                } else {
-                    entry.branch.clone().unwrap_or_default()
-                };
-
-                // Compute effective sparse paths: caller's value if provided, else preserve existing
-                let effective_sparse = if let Some(ref sp) = sparse_paths {
-                    let paths: Vec<String> =
-                        sp.iter().map(|p| p.to_string_lossy().to_string()).collect();
-                    if paths.is_empty() { None } else { Some(paths) }
-                } else {
-                    entry.sparse_paths.clone().filter(|v| !v.is_empty())
-                };
-
-                let effective_ignore = ignore.or(entry.ignore);
-                let effective_fetch = fetch.or(entry.fetch_recurse);
-                let effective_update = update.or(entry.update);
-                // Preserve shallow/active from entry unless caller explicitly set them
-                let effective_shallow = shallow.or(entry.shallow);
-                let effective_git_default =
-                    use_git_default_sparse_checkout.or(entry.use_git_default_sparse_checkout);
-
-                self.add_submodule(
-                    name.to_string(),
-                    np.clone(),
-                    sub_url,
-                    effective_sparse,
-                    Some(effective_branch),
-                    effective_ignore,
-                    effective_fetch,
-                    effective_update,
-                    effective_shallow,
-                    false,
-                    effective_git_default,
-                )?;
-                return Ok(());
+                self.move_submodule(name, np)?;
             }
         }
+        // So does a new URL, after checking it has the recorded commit
+        if let Some(ref new_url) = url
+            && entry.url.as_deref() != Some(new_url.as_str())
+        {
+            self.set_submodule_url(name, new_url, fetch_url)?;
+        }
 
         // Otherwise update fields in place
         {
@@ -1666,9 +4784,6 @@ impl GitManager {
                 })?
                 .clone();
             let mut updated = entry;
-            if let Some(np) = new_path {
-                updated.path = Some(np);
-            }
             if let Some(b) = branch {
                 updated.branch = SerializableBranch::set_branch(Some(b))
                     .map(Some)
@@ -1680,8 +4795,13 @@ impl GitManager {
             if let Some(f) = fetch {
                 updated.fetch_recurse = Some(f);
             }
+            // A command is only recorded in .git/config, and a named strategy replaces one
+            // there
             if let Some(u) = update {
-                updated.update = Some(u);
+                if u.is_command() || self.local_update_command(name).is_some() {
+                    self.set_local_update(name, &u)?;
+                }
+                updated.update = (!u.is_command()).then_some(u);
             }
             if let Some(new_url) = url {
                 updated.url = Some(new_url);
@@ -1692,6 +4812,13 @@ impl GitManager {
             if let Some(s) = shallow {
                 updated.shallow = Some(s);
             }
+            // A depth of 0 or an empty date clears the setting (back to a full clone)
+            if let Some(d) = depth {
+                updated.depth = (d > 0).then_some(d);
+            }
+            if let Some(since) = shallow_since {
+                updated.shallow_since = (!since.is_empty()).then_some(since);
+            }
             if let Some(v) = use_git_default_sparse_checkout {
                 updated.use_git_default_sparse_checkout = Some(v);
             }
@@ -1721,12 +4848,483 @@ impl GitManager {
         }
 
         self.write_full_config()?;
-        println!("Updated submodule '{name}'.");
+        self.emit(
+            Event::finished(Operation::Change, format!("Updated submodule '{name}'.")).about(name),
+        );
+        match new_name {
+            Some(new_name) if new_name != name => self.rename_submodule(name, &new_name),
+            _ => Ok(()),
+        }
+    }
+
+    /// Give a submodule a new name, in submod.toml and in git: its `.gitmodules` and
+    /// `.git/config` sections and its git directory under `.git/modules` move to the new
+    /// name, whatever git called it before. Its path and working tree stay where they are.
+    /// submod.toml is updated last, and the git rename is undone if that fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::SubmoduleNotFound` if `name` isn't configured, and
+    /// `SubmoduleError::ConfigError` if `new_name` isn't a valid submodule name, another
+    /// submodule already has it, or git can't rename it; nothing is changed in that case.
+    #[instrument(level = "info", skip_all, fields(submodule = name, new_name = new_name), err(level = "info"))]
+    pub fn rename_submodule(&mut self, name: &str, new_name: &str) -> Result<(), SubmoduleError> {
+        let entry = self
+            .config
+            .get_submodule(name)
+            .ok_or_else(|| SubmoduleError::SubmoduleNotFound {
+                name: name.to_string(),
+            })?
+            .clone();
+        if new_name == name {
+            return Ok(());
+        }
+        // Git refuses names that could escape `.git/modules`
+        if new_name.is_empty()
+            || new_name.starts_with('/')
+            || new_name.split(['/', '\\']).any(|part| part == "..")
+        {
+            return Err(SubmoduleError::ConfigError(format!(
+                "'{new_name}' isn't a valid submodule name"
+            )));
+        }
+        if self.config.get_submodule(new_name).is_some() {
+            return Err(SubmoduleError::ConfigError(format!(
+                "There's already a submodule named '{new_name}'"
+            )));
+        }
+        let path = entry.path.unwrap_or_else(|| name.to_string());
+        let git_name = self.gitmodules_name(name, &path);
+        if git_name.is_some() {
+            self.git_ops
+                .rename_submodule(&path, new_name)
+                .map_err(SubmoduleError::from_git(name, Operation::Rename))?;
+        }
+
+        self.config
+            .submodules
+            .rename_submodule(name, new_name.to_string());
+        if let Err(e) = self.write_full_config() {
+            // Put everything back so submod.toml and git agree
+            self.config
+                .submodules
+                .rename_submodule(new_name, name.to_string());
+            if let Some(git_name) = &git_name {
+                let _ = self.git_ops.rename_submodule(&path, git_name);
+            }
+            return Err(e);
+        }
+        if let Err(e) = self.git_ops.reopen() {
+            self.emit(
+                Event::warning(
+                    Operation::Rename,
+                    format!(
+                        "Warning: failed to refresh git repository state after renaming submodule '{name}': {e}"
+                    ),
+                )
+                .about(name),
+            );
+        }
+
+        self.emit(
+            Event::detailed(
+                EventKind::Finished,
+                Operation::Rename,
+                Details::Renamed {
+                    old_name: name.to_string(),
+                    staged: git_name.is_some(),
+                },
+            )
+            .about(new_name),
+        );
+        Ok(())
+    }
+
+    /// Move a submodule to `new_path` without cloning it again, keeping its local branches,
+    /// stashes, and uncommitted changes.
+    ///
+    /// `git mv` relocates the working tree and updates the gitlink, the `.gitmodules` path,
+    /// the submodule's `.git` file, and `core.worktree` in its git directory; submod.toml is
+    /// updated last, and the move is undone if that fails. A submodule with no gitlink yet
+    /// (one that was never initialized) just has its directory and `.gitmodules` path moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::SubmoduleNotFound` if `name` isn't configured, and
+    /// `SubmoduleError::InvalidPath` if `new_path` escapes the superproject, already exists,
+    /// or belongs to another submodule. Returns `SubmoduleError::CliError` if git can't move
+    /// it; nothing is changed in that case.
+    #[instrument(level = "info", skip_all, fields(submodule = name, new_path = new_path), err(level = "info"))]
+    pub fn move_submodule(&mut self, name: &str, new_path: &str) -> Result<(), SubmoduleError> {
+        let entry = self
+            .config
+            .get_submodule(name)
+            .ok_or_else(|| SubmoduleError::SubmoduleNotFound {
+                name: name.to_string(),
+            })?
+            .clone();
+        let old_path = entry.path.clone().unwrap_or_else(|| name.to_string());
+        let new_path = new_path.trim_end_matches('/').to_string();
+        if new_path == old_path {
+            return Ok(());
+        }
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?
+            .to_path_buf();
+        crate::utilities::validate_submodule_path(&root, Path::new(&new_path))
+            .map_err(|e| SubmoduleError::InvalidPath(e.to_string()))?;
+        if root.join(&new_path).exists() {
+            return Err(SubmoduleError::InvalidPath(format!(
+                "{new_path} already exists"
+            )));
+        }
+        if let Some((other, _)) = self
+            .config
+            .get_submodules()
+            .find(|(_, e)| e.path.as_deref() == Some(new_path.as_str()))
+        {
+            return Err(SubmoduleError::InvalidPath(format!(
+                "{new_path} is already the path of submodule '{other}'"
+            )));
+        }
+
+        // Like `git mv`, create the destination's parents; remove them again on failure
+        let mut created = Vec::new();
+        let mut parent = Path::new(&new_path).parent();
+        while let Some(dir) = parent.filter(|dir| !dir.as_os_str().is_empty()) {
+            if root.join(dir).exists() {
+                break;
+            }
+            created.push(root.join(dir));
+            parent = dir.parent();
+        }
+        if let Some(dir) = created.first() {
+            fs::create_dir_all(dir)?;
+        }
+        let remove_created = || {
+            for dir in &created {
+                let _ = fs::remove_dir(dir);
+            }
+        };
+
+        let tracked = Self::run_git(&root, &["ls-files", "--stage", "--", &old_path], None)
+            .is_ok_and(|staged| staged.starts_with("160000 "));
+        let gitmodules_name = self.gitmodules_name(name, &old_path);
+        if let Err(e) = Self::relocate_submodule(
+            &root,
+            tracked,
+            &old_path,
+            &new_path,
+            gitmodules_name.as_deref(),
+        ) {
+            remove_created();
+            return Err(e);
+        }
+
+        let mut updated = entry.clone();
+        updated.path = Some(new_path.clone());
+        self.config
+            .submodules
+            .update_entry(name.to_string(), updated);
+        if let Err(e) = self.write_full_config() {
+            // Put everything back so submod.toml and git agree
+            self.config.submodules.update_entry(name.to_string(), entry);
+            let _ = Self::relocate_submodule(
+                &root,
+                tracked,
+                &new_path,
+                &old_path,
+                gitmodules_name.as_deref(),
+            );
+            remove_created();
+            return Err(e);
+        }
+        if let Err(e) = self.git_ops.reopen() {
+            self.emit(
+                Event::warning(
+                    Operation::Move,
+                    format!(
+                        "Warning: failed to refresh git repository state after moving submodule '{name}': {e}"
+                    ),
+                )
+                .about(name),
+            );
+        }
+
+        self.emit(
+            Event::detailed(
+                EventKind::Finished,
+                Operation::Move,
+                Details::Moved {
+                    old_path,
+                    new_path,
+                    staged: tracked,
+                },
+            )
+            .about(name),
+        );
+        Ok(())
+    }
+
+    /// Point a submodule at a new URL without cloning it again: `.gitmodules`, the
+    /// superproject's `.git/config`, the clone's remote, and submod.toml are all updated.
+    ///
+    /// First the new URL must have the commit the superproject records: at the tip of a
+    /// branch or tag, or, with `fetch`, anywhere in the history fetched from it. Nothing is
+    /// changed if it doesn't, and the changes made so far are undone if a step fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SubmoduleError::SubmoduleNotFound` if `name` isn't configured, and
+    /// `SubmoduleError::ConfigError` if the new URL doesn't have the recorded commit or can't
+    /// be reached. Returns `SubmoduleError::CliError` if a git command fails.
+    #[instrument(level = "info", skip_all, fields(submodule = name, url = url), err(level = "info"))]
+    pub fn set_submodule_url(
+        &mut self,
+        name: &str,
+        url: &str,
+        fetch: bool,
+    ) -> Result<(), SubmoduleError> {
+        let entry = self
+            .config
+            .get_submodule(name)
+            .ok_or_else(|| SubmoduleError::SubmoduleNotFound {
+                name: name.to_string(),
+            })?
+            .clone();
+        let path = entry.path.clone().unwrap_or_else(|| name.to_string());
+        let root = self
+            .git_ops
+            .workdir()
+            .ok_or(SubmoduleError::RepositoryError)?
+            .to_path_buf();
+        let dir = root.join(&path);
+        let cloned = gix::open(&dir).is_ok();
+
+        let recorded = Self::run_git(&root, &["ls-files", "--stage", "--", &path], None)
+            .ok()
+            .and_then(|staged| {
+                staged
+                    .strip_prefix("160000 ")
+                    .and_then(|rest| rest.split_whitespace().next())
+                    .map(str::to_string)
+            });
+        if let Some(commit) = &recorded {
+            let auth_overrides = self
+                .configured_auth(name)
+                .config_overrides(&root)
+                .map_err(Self::map_git_ops_error)?;
+            Self::verify_remote_has_commit(&dir, cloned, url, commit, fetch, &auth_overrides)?;
+        }
+
+        // Each change made so far, as the git command that reverses it
+        let mut undo: Vec<(PathBuf, Vec<String>)> = Vec::new();
+        let applied = self.apply_submodule_url(name, &path, &root, url, &mut undo);
+        let applied = applied.and_then(|()| {
+            let mut updated = entry.clone();
+            updated.url = Some(url.to_string());
+            self.config
+                .submodules
+                .update_entry(name.to_string(), updated);
+            self.write_full_config()
+        });
+        if let Err(e) = applied {
+            self.config.submodules.update_entry(name.to_string(), entry);
+            for (dir, args) in undo.iter().rev() {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                let _ = Self::run_git(dir, &args, None);
+            }
+            return Err(e);
+        }
+
+        self.emit(
+            Event::detailed(
+                EventKind::Finished,
+                Operation::SetUrl,
+                Details::UrlChanged {
+                    path: path.clone(),
+                    old_url: entry.url,
+                    new_url: url.to_string(),
+                    recorded,
+                },
+            )
+            .about(name),
+        );
+        Ok(())
+    }
+
+    /// Fails unless `url` has `commit`: at a branch or tag tip, or with `fetch`, anywhere in
+    /// the history fetched from it into the clone at `dir`. The submodule's credentials come
+    /// in as `auth_overrides`.
+    fn verify_remote_has_commit(
+        dir: &Path,
+        cloned: bool,
+        url: &str,
+        commit: &str,
+        fetch: bool,
+        auth_overrides: &[(String, String)],
+    ) -> Result<(), SubmoduleError> {
+        let short = &commit[..commit.len().min(12)];
+        let found = if fetch {
+            if !cloned {
+                return Err(SubmoduleError::ConfigError(format!(
+                    "{} isn't checked out, so there's nothing to fetch into; drop --fetch-url to check {url}'s branches and tags",
+                    dir.display()
+                )));
+            }
+            // Fetch into a scratch namespace so the clone's own remote refs are untouched
+            Self::run_git_with_auth(
+                dir,
+                &[
+                    "fetch",
+                    "-q",
+                    "--no-tags",
+                    url,
+                    "+refs/heads/*:refs/submod-verify/heads/*",
+                    "+refs/tags/*:refs/submod-verify/tags/*",
+                ],
+                None,
+                auth_overrides,
+            )
+            .map_err(|e| SubmoduleError::ConfigError(format!("Couldn't fetch {url}: {e}")))?;
+            let containing = Self::run_git(
+                dir,
+                &["for-each-ref", "--contains", commit, "refs/submod-verify/"],
+                None,
+            );
+            let scratch = Self::run_git(
+                dir,
+                &[
+                    "for-each-ref",
+                    "--format=delete %(refname)",
+                    "refs/submod-verify/",
+                ],
+                None,
+            )?;
+            Self::run_git(dir, &["update-ref", "--stdin"], Some(&scratch))?;
+            containing.is_ok_and(|refs| !refs.trim().is_empty())
+        } else {
+            let refs = Self::run_git_with_auth(
+                dir.ancestors().find(|d| d.is_dir()).unwrap_or(dir),
+                &["ls-remote", url],
+                None,
+                auth_overrides,
+            )
+            .map_err(|e| SubmoduleError::ConfigError(format!("Couldn't reach {url}: {e}")))?;
+            refs.lines().any(|line| line.starts_with(commit))
+        };
+        if found {
+            Ok(())
+        } else if fetch {
+            Err(SubmoduleError::ConfigError(format!(
+                "{url} doesn't have the recorded commit {short}; nothing was changed"
+            )))
+        } else {
+            Err(SubmoduleError::ConfigError(format!(
+                "No branch or tag of {url} points at the recorded commit {short}; pass --fetch-url to search its history. Nothing was changed"
+            )))
+        }
+    }
+
+    /// Sets `url` in `.gitmodules`, `.git/config`, and the clone's remote, pushing the
+    /// command that reverses each change onto `undo`
+    fn apply_submodule_url(
+        &self,
+        name: &str,
+        path: &str,
+        root: &Path,
+        url: &str,
+        undo: &mut Vec<(PathBuf, Vec<String>)>,
+    ) -> Result<(), SubmoduleError> {
+        let gitmodules_name = self.gitmodules_name(name, path);
+        let to_args = |args: &[&str]| args.iter().map(|arg| (*arg).to_string()).collect();
+
+        if let Some(gm_name) = gitmodules_name {
+            // Replayed last, so the index gets back the `.gitmodules` it had, whatever
+            // restoring the file left in the worktree
+            let staged = Self::run_git(root, &["ls-files", "--stage", "--", ".gitmodules"], None)?;
+            let restage = staged.split_whitespace().take(2).collect::<Vec<_>>();
+            undo.push((
+                root.to_path_buf(),
+                match restage[..] {
+                    [mode, oid] => to_args(&[
+                        "update-index",
+                        "--cacheinfo",
+                        &format!("{mode},{oid},.gitmodules"),
+                    ]),
+                    _ => to_args(&["rm", "--cached", "--quiet", "--", ".gitmodules"]),
+                },
+            ));
+
+            let key = format!("submodule.{gm_name}.url");
+            let old = Self::run_git(root, &["config", "-f", ".gitmodules", "--get", &key], None)
+                .map(|old| old.trim().to_string());
+            Self::run_git(root, &["config", "-f", ".gitmodules", &key, url], None)?;
+            let restore = old.as_deref().map_or_else(
+                |_| to_args(&["config", "-f", ".gitmodules", "--unset", &key]),
+                |old| to_args(&["config", "-f", ".gitmodules", &key, old]),
+            );
+            undo.push((root.to_path_buf(), restore));
+            Self::run_git(root, &["add", "--", ".gitmodules"], None)?;
+
+            // Like `git submodule sync`, only an initialized submodule's URL is updated
+            if let Ok(old) = Self::run_git(root, &["config", "--get", &key], None) {
+                Self::run_git(root, &["config", &key, url], None)?;
+                undo.push((root.to_path_buf(), to_args(&["config", &key, old.trim()])));
+            }
+        }
+
+        let dir = root.join(path);
+        if gix::open(&dir).is_ok() {
+            let remotes = Self::run_git(&dir, &["remote"], None)?;
+            let remote = remotes
+                .lines()
+                .find(|remote| *remote == "origin")
+                .or_else(|| remotes.lines().next());
+            if let Some(remote) = remote {
+                let old = Self::run_git(&dir, &["remote", "get-url", remote], None)?;
+                Self::run_git(&dir, &["remote", "set-url", remote, url], None)?;
+                undo.push((dir, to_args(&["remote", "set-url", remote, old.trim()])));
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves a submodule from `from` to `to` in git: with `git mv` if it has a gitlink, or
+    /// else its directory, if it has one, and its `.gitmodules` path, if it's listed there
+    fn relocate_submodule(
+        root: &Path,
+        tracked: bool,
+        from: &str,
+        to: &str,
+        gitmodules_name: Option<&str>,
+    ) -> Result<(), SubmoduleError> {
+        if tracked {
+            return Self::run_git(root, &["mv", "--", from, to], None).map(|_| ());
+        }
+        let dir = root.join(from);
+        if dir.exists() {
+            fs::rename(&dir, root.join(to))?;
+        }
+        if let Some(gm_name) = gitmodules_name {
+            let key = format!("submodule.{gm_name}.path");
+            let updated = Self::run_git(root, &["config", "-f", ".gitmodules", &key, to], None)
+                .and_then(|_| Self::run_git(root, &["add", "--", ".gitmodules"], None));
+            if let Err(e) = updated {
+                if root.join(to).exists() {
+                    let _ = fs::rename(root.join(to), &dir);
+                }
+                return Err(e);
+            }
+        }
         Ok(())
     }
 
     /// Nuke (deinit + delete + remove from config) all or specific submodules.
     /// If `kill` is false, reinitializes them after deletion.
+    #[instrument(level = "info", skip_all, fields(all = all, submodules = ?names, kill = kill), err(level = "info"))]
     pub fn nuke_submodules(
         &mut self,
         all: bool,
@@ -1762,7 +5360,10 @@ impl GitManager {
         }
 
         for name in &targets {
-            println!("💥 Nuking submodule '{name}'...");
+            self.emit(
+                Event::started(Operation::Nuke, format!("💥 Nuking submodule '{name}'..."))
+                    .about(name),
+            );
             self.delete_submodule_by_name(name)?;
         }
 
@@ -1772,13 +5373,26 @@ impl GitManager {
                 let url = match entry.url.clone() {
                     Some(u) if !u.is_empty() => u,
                     _ => {
-                        eprintln!("Skipping reinit of '{name}': no URL in config entry.");
+                        self.emit(
+                            Event::warning(
+                                Operation::Nuke,
+                                format!("Skipping reinit of '{name}': no URL in config entry."),
+                            )
+                            .about(&name),
+                        );
                         continue;
                     }
                 };
-                println!("🔄 Reinitializing submodule '{name}'...");
+                self.emit(
+                    Event::progress(
+                        Operation::Nuke,
+                        format!("🔄 Reinitializing submodule '{name}'..."),
+                    )
+                    .about(&name),
+                );
                 let path = entry.path.as_deref().unwrap_or(&name).to_string();
                 let sparse = entry.sparse_paths.clone().filter(|paths| !paths.is_empty());
+                let auth = SubmoduleAuth::from_entry(&entry);
                 self.add_submodule(
                     name.clone(),
                     path,
@@ -1789,6 +5403,11 @@ impl GitManager {
                     entry.fetch_recurse,
                     entry.update,
                     entry.shallow,
+                    entry.depth,
+                    entry.shallow_since.clone(),
+                    auth,
+                    entry.hooks.clone(),
+                    entry.groups.clone(),
                     false,
                     entry.use_git_default_sparse_checkout,
                 )?;
@@ -1801,11 +5420,14 @@ impl GitManager {
     /// Generate a config file. If `from_setup` is true, reads `.gitmodules` from the repo.
     /// If `template` is true, writes an annotated sample config.
     /// If the output file exists and `force` is false, returns an error.
+    /// What was written is reported to `reporter`.
+    #[instrument(level = "info", skip_all, fields(output = %output.display()), err(level = "info"))]
     pub fn generate_config(
         output: &std::path::Path,
         from_setup: bool,
         template: bool,
         force: bool,
+        reporter: &dyn Reporter,
     ) -> Result<(), SubmoduleError> {
         if output.exists() && !force {
             return Err(SubmoduleError::ConfigError(format!(
@@ -1818,7 +5440,10 @@ impl GitManager {
             // Write an annotated sample config
             let sample = include_str!("../sample_config/submod.toml");
             std::fs::write(output, sample).map_err(SubmoduleError::IoError)?;
-            println!("Generated template config at '{}'.", output.display());
+            reporter.report(&Event::finished(
+                Operation::GenerateConfig,
+                format!("Generated template config at '{}'.", output.display()),
+            ));
             return Ok(());
         }
 
@@ -1857,19 +5482,28 @@ impl GitManager {
                 config,
                 config_path: output.to_path_buf(),
                 verbose: false,
+                hooks_enabled: false,
+                reporter: Arc::new(ConsoleReporter::default()),
+                progress: None,
             };
             tmp_manager.write_full_config()?;
-            println!(
-                "Generated config from .gitmodules at '{}'.",
-                output.display()
-            );
+            reporter.report(&Event::finished(
+                Operation::GenerateConfig,
+                format!(
+                    "Generated config from .gitmodules at '{}'.",
+                    output.display()
+                ),
+            ));
             return Ok(());
         }
 
         // Neither template nor from-setup: write an empty config
         let empty = "[defaults]\n";
         std::fs::write(output, empty).map_err(SubmoduleError::IoError)?;
-        println!("Generated empty config at '{}'.", output.display());
+        reporter.report(&Event::finished(
+            Operation::GenerateConfig,
+            format!("Generated empty config at '{}'.", output.display()),
+        ));
         Ok(())
     }
 }
@@ -2003,6 +5637,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_write_full_config_depth_replaces_shallow_alias() {
+        // A configured depth supersedes the `shallow = true` alias: rewriting an
+        // existing section must drop the alias line and keep `shallow_since`.
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("submod.toml");
+        let mut manager = create_test_manager(temp_dir.path(), config_path.clone());
+        fs::write(
+            &config_path,
+            "[mymod]\npath = \"libs/mymod\"\nurl = \"https://example.com/repo.git\"\nshallow = true\n",
+        )
+        .unwrap();
+
+        let mut entry = SubmoduleEntry::new(
+            Some("https://example.com/repo.git".to_string()),
+            Some("libs/mymod".to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            None,
+        );
+        entry.depth = Some(3);
+        entry.shallow_since = Some("2024-01-01".to_string());
+        manager.config.add_submodule("mymod".to_string(), entry);
+
+        manager.write_full_config().expect("write_full_config");
+
+        let written = fs::read_to_string(&config_path).unwrap();
+        assert!(
+            !written.contains("shallow = true"),
+            "alias line should be replaced; file:\n{written}"
+        );
+        let reloaded: Config = toml::from_str(&written).expect("written config must be valid TOML");
+        let reloaded_entry = reloaded.submodules.get("mymod").unwrap();
+        assert_eq!(reloaded_entry.depth, Some(3), "file:\n{written}");
+        assert_eq!(
+            reloaded_entry.shallow_since.as_deref(),
+            Some("2024-01-01"),
+            "file:\n{written}"
+        );
+    }
+
     #[test]
     fn test_save_config_persists_edits_to_existing_section() {
         // `save_config` (the writer used by `add`) was append-only: once a
@@ -2068,6 +5747,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_write_full_config_keeps_hooks_tables() {
+        // Hooks live in nested `[<owner>.hooks]` tables, which the section-by-section
+        // writer would otherwise drop as unknown sections.
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("submod.toml");
+        let mut manager = create_test_manager(temp_dir.path(), config_path.clone());
+        fs::write(
+            &config_path,
+            r#"[defaults]
+
+[defaults.hooks]
+# regenerate bindings after every update
+post_update = [
+    "make gen",
+    "make fmt",
+]
+timeout = 60
+
+[mymod]
+path = "libs/mymod"
+url = "https://example.com/repo.git"
+
+[mymod.hooks]
+pre_init = ["echo \"hi\""]
+"#,
+        )
+        .unwrap();
+        manager.config = toml::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(
+            manager.config.defaults.hooks.as_ref().unwrap().post_update,
+            Some(vec!["make gen".to_string(), "make fmt".to_string()])
+        );
+
+        // Edit the defaults' hooks and add a submodule with hooks of its own
+        let defaults = manager.config.defaults.hooks.as_mut().unwrap();
+        defaults.post_update = Some(vec!["make all".to_string()]);
+        defaults.on_failure = Some(HookFailurePolicy::Abort);
+        let mut other = SubmoduleEntry::new(
+            Some("https://example.com/other.git".to_string()),
+            Some("libs/other".to_string()),
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            None,
+            None,
+        );
+        other.hooks = Some(SubmoduleHooks {
+            post_sync: Some(Vec::new()),
+            ..SubmoduleHooks::default()
+        });
+        manager.config.add_submodule("other.lib".to_string(), other);
+        // Dropping a submodule's hooks drops its table
+        let mut mymod = manager.config.get_submodule("mymod").unwrap().clone();
+        mymod.hooks = None;
+        manager.config.add_submodule("mymod".to_string(), mymod);
+        manager.write_full_config().expect("write_full_config");
+
+        let written = fs::read_to_string(&config_path).unwrap();
+        let reloaded: Config = toml::from_str(&written).expect("written config must parse");
+        let defaults = reloaded.defaults.hooks.expect("defaults hooks kept");
+        assert_eq!(defaults.post_update, Some(vec!["make all".to_string()]));
+        assert_eq!(defaults.timeout, Some(60));
+        assert_eq!(defaults.on_failure, Some(HookFailurePolicy::Abort));
+        assert!(
+            written.contains("# regenerate bindings"),
+            "file:\n{written}"
+        );
+        assert!(!written.contains("make fmt"), "file:\n{written}");
+        assert!(
+            written.contains("timeout = 60\non_failure = \"abort\"\n\n[mymod]"),
+            "new keys go before the section's trailing blank line; file:\n{written}"
+        );
+        assert_eq!(reloaded.submodules.get("mymod").unwrap().hooks, None);
+        assert!(!written.contains("[mymod.hooks]"), "file:\n{written}");
+        assert_eq!(
+            reloaded.submodules.get("other.lib").unwrap().hooks,
+            Some(SubmoduleHooks {
+                post_sync: Some(Vec::new()),
+                ..SubmoduleHooks::default()
+            }),
+            "an empty list must be kept; file:\n{written}"
+        );
+    }
+
     #[test]
     fn test_sparse_checkout_mismatch() {
         let temp_dir = tempdir().unwrap();
@@ -2099,4 +5865,107 @@ mod tests {
             _ => panic!("Expected Mismatch, got {status:?}"),
         }
     }
+
+    #[test]
+    fn test_with_reporter_sends_events_instead_of_printing() {
+        use crate::report::EventKind;
+        use std::sync::Mutex;
+
+        let temp_dir = tempdir().unwrap();
+        let config_path = temp_dir.path().join("submod.toml");
+        git2::Repository::init(temp_dir.path()).expect("Failed to init git repo");
+        fs::write(
+            &config_path,
+            "[lib]\npath = \"libs/lib\"\nurl = \"https://example.com/lib.git\"\n",
+        )
+        .unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&events);
+        let manager = GitManager::with_repo_path(config_path, temp_dir.path())
+            .unwrap()
+            .with_reporter(move |event: &Event| seen.lock().unwrap().push(event.clone()));
+
+        manager.list_submodules(false).unwrap();
+
+        let events = events.lock().unwrap().clone();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].message, "Submodules:");
+        assert_eq!(events[1].kind, EventKind::Progress);
+        assert_eq!(events[1].operation, Operation::List);
+        assert_eq!(events[1].submodule.as_deref(), Some("lib"));
+        assert_eq!(
+            events[1].message,
+            "  lib [active]\n    path: libs/lib\n    url:  https://example.com/lib.git"
+        );
+    }
+
+    #[test]
+    fn test_from_git_classifies_failures_for_exit_codes() {
+        let classify =
+            |error: anyhow::Error| SubmoduleError::from_git("lib", Operation::Update)(error);
+
+        let network = classify(BackendError::tag(Backend::Git2)(anyhow::anyhow!(
+            "Failed to fetch: Could not resolve host: example.invalid"
+        )));
+        assert_eq!(network.kind(), ErrorKind::Network);
+        assert_eq!(network.kind().exit_code(), 5);
+        assert_eq!(network.submodule(), Some("lib"));
+        assert_eq!(network.operation(), Some(Operation::Update));
+        assert_eq!(network.backend(), Some(Backend::Git2));
+
+        let dirty = classify(anyhow::Error::new(git2::Error::new(
+            git2::ErrorCode::Conflict,
+            git2::ErrorClass::Checkout,
+            "1 conflict prevents checkout",
+        )));
+        assert_eq!(dirty.kind(), ErrorKind::DirtyWorktree);
+        assert_eq!(dirty.kind().exit_code(), 6);
+        assert_eq!(dirty.backend(), None);
+
+        let other = classify(anyhow::anyhow!("reference 'refs/heads/nope' not found"));
+        assert_eq!(other.kind(), ErrorKind::Other);
+        assert_eq!(other.kind().exit_code(), 1);
+        assert_eq!(
+            other.to_string(),
+            "Git operation failed: reference 'refs/heads/nope' not found"
+        );
+
+        let partial = SubmoduleError::ForeachFailed {
+            failed: 1,
+            total: 3,
+        };
+        assert_eq!(partial.kind(), ErrorKind::PartialSuccess);
+
+        let not_found = |name: &str| {
+            (
+                name.to_string(),
+                SubmoduleError::SubmoduleNotFound {
+                    name: name.to_string(),
+                },
+            )
+        };
+        let some = SubmoduleError::SubmodulesFailed {
+            operation: Operation::Update,
+            failures: vec![not_found("b")],
+            total: 2,
+        };
+        assert_eq!(some.kind(), ErrorKind::PartialSuccess);
+        assert_eq!(some.to_string(), "failed in 1 of 2 submodule(s): b");
+        let all_alike = SubmoduleError::SubmodulesFailed {
+            operation: Operation::Update,
+            failures: vec![not_found("a"), not_found("b")],
+            total: 2,
+        };
+        assert_eq!(all_alike.kind(), ErrorKind::SubmoduleNotFound);
+        let all_unlike = SubmoduleError::SubmodulesFailed {
+            operation: Operation::Init,
+            failures: vec![
+                ("a".to_string(), SubmoduleError::Interrupted),
+                not_found("b"),
+            ],
+            total: 2,
+        };
+        assert_eq!(all_unlike.kind(), ErrorKind::Other);
+        assert_eq!(all_unlike.operation(), Some(Operation::Init));
+    }
 }
@@ -4,18 +4,22 @@
 
 use super::{DetailedSubmoduleStatus, GitConfig, GitOperations, SubmoduleStatusFlags};
 use crate::config::{
-    SubmoduleAddOptions, SubmoduleEntries, SubmoduleEntry, SubmoduleUpdateOptions,
+    SubmoduleAddOptions, SubmoduleAuth, SubmoduleEntries, SubmoduleEntry, SubmoduleUpdateOptions,
 };
 use crate::options::{
     ConfigLevel, GitmodulesConvert, SerializableBranch, SerializableFetchRecurse,
     SerializableIgnore, SerializableUpdate,
 };
+use crate::progress::Row;
+use crate::utilities::{depth_to_shallow_boundary, relative_path};
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 /// Git2 implementation providing complete fallback coverage
 pub struct Git2Operations {
     repo: git2::Repository,
+    /// Where fetches report what they've received, if anywhere
+    progress: Option<Row>,
 }
 impl Git2Operations {
     /// Create a new `Git2Operations` instance
@@ -26,13 +30,102 @@ impl Git2Operations {
             None => git2::Repository::open_from_env()
                 .with_context(|| "Failed to open repository from environment")?,
         };
-        Ok(Self { repo })
+        Ok(Self {
+            repo,
+            progress: None,
+        })
+    }
+
+    /// Report what fetches receive under `row`, or nowhere
+    pub fn set_progress(&mut self, row: Option<Row>) {
+        self.progress = row;
     }
 
     /// Return the working directory of the repository, if any.
     pub(super) fn workdir(&self) -> Option<&std::path::Path> {
         self.repo.workdir()
     }
+
+    /// Find the submodule at `path`. libgit2 looks submodules up by name; given the path of
+    /// one whose name differs, it finds only the gitlink, with no URL or config.
+    fn find_submodule(&self, path: &str) -> Result<git2::Submodule<'_>> {
+        if let Some(submodule) = self
+            .repo
+            .submodules()?
+            .into_iter()
+            .find(|submodule| submodule.path() == Path::new(path))
+        {
+            return Ok(submodule);
+        }
+        self.repo
+            .find_submodule(path)
+            .with_context(|| format!("Submodule not found: {path}"))
+    }
+
+    /// Callbacks that report what a transfer receives to the progress row, if there is one,
+    /// and cancel it when the run is interrupted
+    fn transfer_callbacks(&self) -> git2::RemoteCallbacks<'static> {
+        let mut callbacks = git2::RemoteCallbacks::new();
+        let counters = self
+            .progress
+            .as_ref()
+            .map(|row| (row.received_objects(), row.received_bytes()));
+        callbacks.transfer_progress(move |stats| {
+            if let Some((objects, bytes)) = &counters {
+                objects.set_max(Some(stats.total_objects()));
+                objects.set(stats.received_objects());
+                bytes.set(stats.received_bytes());
+            }
+            !crate::interrupt::is_interrupted()
+        });
+        callbacks
+    }
+
+    /// Fetch options that authenticate with a submodule's configured credentials, and
+    /// report what they receive to the progress row, if there is one
+    fn auth_fetch_options(&self, auth: &SubmoduleAuth) -> Result<git2::FetchOptions<'static>> {
+        let mut fetch_opts = git2::FetchOptions::new();
+        if let Some(header) = auth.http_extra_header()? {
+            fetch_opts.custom_headers(&[header.as_str()]);
+        }
+        let mut callbacks = self.transfer_callbacks();
+        if auth.ssh_key.is_none() && auth.credential_helper.is_none() {
+            fetch_opts.remote_callbacks(callbacks);
+            return Ok(fetch_opts);
+        }
+        let root = self.repo.workdir().unwrap_or_else(|| Path::new("."));
+        let ssh_key = auth.ssh_key_path(root);
+        let helper = auth.helper()?.map(str::to_string);
+        let mut attempted = false;
+        callbacks.credentials(move |url, username, allowed| {
+            if allowed.contains(git2::CredentialType::USERNAME) {
+                return git2::Cred::username(username.unwrap_or("git"));
+            }
+            // libgit2 keeps asking for as long as the remote rejects us; one try is enough
+            if std::mem::replace(&mut attempted, true) {
+                return Err(git2::Error::from_str(
+                    "the configured credentials were rejected",
+                ));
+            }
+            if allowed.contains(git2::CredentialType::SSH_KEY)
+                && let Some(key) = &ssh_key
+            {
+                return git2::Cred::ssh_key(username.unwrap_or("git"), None, key, None);
+            }
+            if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT)
+                && let Some(helper) = &helper
+            {
+                let (user, password) = credential_fill(helper, url)
+                    .map_err(|e| git2::Error::from_str(&e.to_string()))?;
+                return git2::Cred::userpass_plaintext(&user, &password);
+            }
+            Err(git2::Error::from_str(
+                "no configured credential matches what the remote asked for",
+            ))
+        });
+        fetch_opts.remote_callbacks(callbacks);
+        Ok(fetch_opts)
+    }
     /// Convert git2 submodule to our `SubmoduleEntry` format
     fn convert_git2_submodule_to_entry(
         &self,
@@ -54,20 +147,28 @@ impl Git2Operations {
         let fetch_recurse = self.get_submodule_fetch_recurse(&name)?;
         // Check if submodule is active
         let active = self.is_submodule_active(&name)?;
-        // Check if submodule is shallow
-        let shallow = self.is_submodule_shallow(&path)?;
+        // Check if submodule is shallow, and how deep
+        let depth = self.submodule_shallow_depth(&path)?;
         let entry = SubmoduleEntry {
             path: Some(path),
             url: Some(url),
             branch,
+            fallback_branch: None,
             ignore,
             update,
             fetch_recurse,
             active: Some(active),
-            shallow: Some(shallow),
+            shallow: Some(depth.is_some()),
+            depth,
+            shallow_since: None,
+            ssh_key: None,
+            credential_helper: None,
+            http_extra_header_env: None,
             no_init: Some(false), // not used here
             sparse_paths: None,
             use_git_default_sparse_checkout: None,
+            groups: None,
+            hooks: None,
         };
         Ok((name, entry))
     }
@@ -109,20 +210,42 @@ impl Git2Operations {
 
         config.get_bool(&key).map_or(Ok(true), Ok)
     }
-    /// Check if a submodule is shallow
-    fn is_submodule_shallow(&self, path: &str) -> Result<bool> {
+    /// Get the current depth of a shallow submodule, or `None` if it has full history
+    /// (or is not checked out).
+    fn submodule_shallow_depth(&self, path: &str) -> Result<Option<u32>> {
         let submodule_path = self
             .repo
             .workdir()
             .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?
             .join(path);
 
-        if !submodule_path.exists() {
-            return Ok(false);
+        if !submodule_path.join(".git").exists() {
+            return Ok(None);
         }
-        // Check if .git/shallow exists in the submodule
-        let shallow_file = submodule_path.join(".git").join("shallow");
-        Ok(shallow_file.exists())
+        // Open the submodule so a `.git` gitlink file resolves to its real git dir,
+        // where the `shallow` file lives.
+        let Ok(sub_repo) = git2::Repository::open(&submodule_path) else {
+            return Ok(None);
+        };
+        if !sub_repo.is_shallow() {
+            return Ok(None);
+        }
+        // git2 has no API for the shallow boundary, so read it from the `shallow` file
+        let Ok(shallow) = std::fs::read_to_string(sub_repo.commondir().join("shallow")) else {
+            return Ok(None);
+        };
+        let boundary: HashSet<git2::Oid> = shallow
+            .lines()
+            .filter_map(|line| git2::Oid::from_str(line.trim()).ok())
+            .collect();
+        let Some(head) = sub_repo.head().ok().and_then(|head| head.target()) else {
+            return Ok(None);
+        };
+        Ok(depth_to_shallow_boundary(head, &boundary, |id| {
+            sub_repo
+                .find_commit(id)
+                .map_or_else(|_| Vec::new(), |commit| commit.parent_ids().collect())
+        }))
     }
     /// Convert git2 status flags to our status flags
     #[allow(dead_code, clippy::unused_self)]
@@ -237,7 +360,8 @@ impl GitOperations for Git2Operations {
             if let Some(branch) = &entry.branch {
                 gitmodules_config.set_str(&format!("{section}.branch"), &branch.to_string())?;
             }
-            if let Some(update) = &entry.update {
+            // git refuses `!command` updates from .gitmodules; they only go in .git/config
+            if let Some(update) = entry.update.as_ref().filter(|u| !u.is_command()) {
                 gitmodules_config.set_str(&format!("{section}.update"), &update.to_gitmodules())?;
             }
             if let Some(ignore) = &entry.ignore {
@@ -257,10 +381,7 @@ impl GitOperations for Git2Operations {
             }
 
             // 2. Sync to local repository config (.git/config) if the submodule exists in the repository
-            if let Ok(mut submodule) = self
-                .repo
-                .find_submodule(entry.path.as_deref().unwrap_or(name))
-            {
+            if let Ok(mut submodule) = self.repo.find_submodule(name) {
                 let mut repo_config = self.repo.config()?;
                 if let Some(ignore) = &entry.ignore {
                     let ignore_str = match ignore {
@@ -272,15 +393,14 @@ impl GitOperations for Git2Operations {
                     };
                     repo_config.set_str(&format!("submodule.{name}.ignore"), ignore_str)?;
                 }
-                if let Some(update) = &entry.update {
-                    let update_str = match update {
-                        SerializableUpdate::Checkout => "checkout",
-                        SerializableUpdate::Rebase => "rebase",
-                        SerializableUpdate::Merge => "merge",
-                        SerializableUpdate::None => "none",
-                        SerializableUpdate::Unspecified => continue,
-                    };
-                    repo_config.set_str(&format!("submodule.{name}.update"), update_str)?;
+                // Commands are never copied from submod.toml; `submod change` sets them here
+                if let Some(update) = entry
+                    .update
+                    .as_ref()
+                    .filter(|u| !matches!(u, SerializableUpdate::Unspecified) && !u.is_command())
+                {
+                    repo_config
+                        .set_str(&format!("submodule.{name}.update"), &update.to_gitmodules())?;
                 }
                 if let Some(active) = entry.active {
                     let active_str = if active { "true" } else { "false" };
@@ -335,9 +455,15 @@ impl GitOperations for Git2Operations {
 
         // 2. Configure clone options
         let mut update_opts = git2::SubmoduleUpdateOptions::new();
-        let mut fetch_opts = git2::FetchOptions::new();
-        if opts.shallow {
-            fetch_opts.depth(1);
+        if opts.shallow_since.is_some() && opts.effective_depth().is_none() {
+            // libgit2 can only limit history by depth
+            return Err(anyhow::anyhow!(
+                "git2 cannot clone with a shallow-since date; falling back to the git CLI"
+            ));
+        }
+        let mut fetch_opts = self.auth_fetch_options(&opts.auth)?;
+        if let Some(depth) = opts.effective_depth() {
+            fetch_opts.depth(i32::try_from(depth).unwrap_or(i32::MAX));
         }
         update_opts.fetch(fetch_opts);
 
@@ -354,10 +480,16 @@ impl GitOperations for Git2Operations {
             .with_context(|| format!("Failed to add submodule '{}' to index", opts.name))?;
         sub.add_finalize()
             .with_context(|| format!("Failed to finalize submodule '{}'", opts.name))?;
+        drop(sub);
 
-        // 5. Apply optional configuration via git config.
-        // git2's submodule() keys the submodule by path; use the path as the config key.
+        // 5. git2's submodule() names the submodule after its path; give it its own name
         let path_str = opts.path.to_string_lossy();
+        if opts.name != path_str {
+            self.rename_submodule(&path_str, &opts.name)?;
+        }
+        let name = &opts.name;
+
+        // 6. Apply optional configuration via git config.
         let mut config = self
             .repo
             .config()
@@ -365,7 +497,7 @@ impl GitOperations for Git2Operations {
 
         // Set branch if specified
         if let Some(branch) = &opts.branch {
-            let branch_key = format!("submodule.{path_str}.branch");
+            let branch_key = format!("submodule.{name}.branch");
             config
                 .set_str(&branch_key, &branch.to_string())
                 .with_context(|| format!("Failed to set branch for submodule '{}'", opts.name))?;
@@ -375,7 +507,7 @@ impl GitOperations for Git2Operations {
         if let Some(ignore) = &opts.ignore
             && !matches!(ignore, SerializableIgnore::Unspecified)
         {
-            let ignore_key = format!("submodule.{path_str}.ignore");
+            let ignore_key = format!("submodule.{name}.ignore");
             config
                 .set_str(&ignore_key, &ignore.to_string())
                 .with_context(|| format!("Failed to set ignore for submodule '{}'", opts.name))?;
@@ -385,7 +517,7 @@ impl GitOperations for Git2Operations {
         if let Some(fetch_recurse) = &opts.fetch_recurse
             && !matches!(fetch_recurse, SerializableFetchRecurse::Unspecified)
         {
-            let fetch_key = format!("submodule.{path_str}.fetchRecurseSubmodules");
+            let fetch_key = format!("submodule.{name}.fetchRecurseSubmodules");
             config
                 .set_str(&fetch_key, &fetch_recurse.to_string())
                 .with_context(|| {
@@ -397,7 +529,7 @@ impl GitOperations for Git2Operations {
         if let Some(update) = &opts.update
             && !matches!(update, SerializableUpdate::Unspecified)
         {
-            let update_key = format!("submodule.{path_str}.update");
+            let update_key = format!("submodule.{name}.update");
             config
                 .set_str(&update_key, &update.to_string())
                 .with_context(|| format!("Failed to set update for submodule '{}'", opts.name))?;
@@ -405,36 +537,46 @@ impl GitOperations for Git2Operations {
 
         Ok(())
     }
-    fn init_submodule(&mut self, path: &str) -> Result<()> {
-        let mut submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+    fn init_submodule(&mut self, path: &str, _auth: &SubmoduleAuth) -> Result<()> {
+        let mut submodule = self.find_submodule(path)?;
 
+        // Only records config; the clone happens when the submodule is updated
         submodule.init(false)?; // false = don't overwrite existing config
         Ok(())
     }
     fn update_submodule(&mut self, path: &str, opts: &SubmoduleUpdateOptions) -> Result<()> {
-        let mut submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
-        // Create update options
-        let mut update_opts = git2::SubmoduleUpdateOptions::new();
-        update_opts.allow_fetch(true);
-        // Set update strategy (git2 has limited support for different strategies)
-        match opts.strategy {
-            SerializableUpdate::Rebase | SerializableUpdate::Merge => {
-                // git2 doesn't support rebase/merge directly, use checkout
-                eprintln!(
-                    "Warning: git2 doesn't support rebase/merge update strategies, using checkout"
-                );
-            }
+        let mut submodule = self.find_submodule(path)?;
+        let mut fetch_opts = self.auth_fetch_options(&opts.auth)?;
+        if let Some(depth) = opts.depth {
+            fetch_opts.depth(i32::try_from(depth).unwrap_or(i32::MAX));
+        } else if opts.shallow_since.is_some() {
+            tracing::warn!(
+                submodule = path,
+                "git2 doesn't support shallow-since, fetching full history"
+            );
+        }
+        match &opts.strategy {
             SerializableUpdate::None => return Ok(()),
-            SerializableUpdate::Checkout | SerializableUpdate::Unspecified => {
-                // Default behavior / Use default
+            SerializableUpdate::Checkout | SerializableUpdate::Unspecified => {}
+            strategy => {
+                // Like git, a submodule that isn't cloned yet is cloned and checked out
+                if let Ok(sub_repo) = submodule.open() {
+                    let commit = submodule.index_id().ok_or_else(|| {
+                        anyhow::anyhow!("The superproject records no commit for submodule '{path}'")
+                    })?;
+                    return integrate_recorded_commit(
+                        &sub_repo,
+                        path,
+                        commit,
+                        strategy,
+                        &mut fetch_opts,
+                    );
+                }
             }
         }
+        let mut update_opts = git2::SubmoduleUpdateOptions::new();
+        update_opts.allow_fetch(true);
+        update_opts.fetch(fetch_opts);
         submodule.update(true, Some(&mut update_opts))?;
         Ok(())
     }
@@ -463,10 +605,7 @@ impl GitOperations for Git2Operations {
         Ok(())
     }
     fn deinit_submodule(&mut self, path: &str, force: bool) -> Result<()> {
-        let submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+        let submodule = self.find_submodule(path)?;
         // git2 doesn't have a direct deinit method, so we need to:
         // 1. Remove the submodule's config entries
         // 2. Remove the submodule's working directory if force is true
@@ -497,11 +636,102 @@ impl GitOperations for Git2Operations {
         }
         Ok(())
     }
-    fn get_submodule_status(&self, path: &str) -> Result<DetailedSubmoduleStatus> {
-        let submodule = self
+    fn rename_submodule(&mut self, path: &str, new_name: &str) -> Result<()> {
+        let (name, sub_path) = {
+            let submodule = self.find_submodule(path)?;
+            (
+                submodule.name().unwrap_or(path).to_string(),
+                submodule.path().to_path_buf(),
+            )
+        };
+        if name == new_name {
+            return Ok(());
+        }
+        let workdir = self
             .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+            .workdir()
+            .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?
+            .to_path_buf();
+        let gitmodules = workdir.join(".gitmodules");
+        let local_config = self.repo.config()?.open_level(git2::ConfigLevel::Local)?;
+        let modules = self.repo.path().join("modules");
+        let (old_dir, new_dir) = (modules.join(&name), modules.join(new_name));
+
+        // Check everything before changing anything
+        let in_gitmodules =
+            gitmodules.exists() && has_submodule_section(&git2::Config::open(&gitmodules)?, &name);
+        if gitmodules.exists() && has_submodule_section(&git2::Config::open(&gitmodules)?, new_name)
+        {
+            return Err(anyhow::anyhow!(
+                "A submodule named '{new_name}' is already in .gitmodules"
+            ));
+        }
+        if has_submodule_section(&local_config, new_name) {
+            return Err(anyhow::anyhow!(
+                "A submodule named '{new_name}' is already in .git/config"
+            ));
+        }
+        // `lib/zlib` can become `lib`: its directory only holds the old git directory
+        if new_dir.exists() && !(old_dir.starts_with(&new_dir) && only_holds(&new_dir, &old_dir)) {
+            return Err(anyhow::anyhow!("{} already exists", new_dir.display()));
+        }
+
+        // 1. Move the git directory, and repoint the two links between it and the worktree
+        if old_dir.exists() {
+            // Stage it beside `modules` so either name may sit inside the other
+            let staged = self
+                .repo
+                .path()
+                .join(format!("modules-rename-{}", std::process::id()));
+            let move_dir = |from: &Path, to: &Path| {
+                std::fs::rename(from, to).with_context(|| {
+                    format!("Failed to move {} to {}", from.display(), to.display())
+                })
+            };
+            move_dir(&old_dir, &staged)?;
+            // A name with slashes leaves empty directories behind
+            let mut parent = old_dir.parent();
+            while let Some(dir) = parent.filter(|dir| *dir != modules) {
+                if std::fs::remove_dir(dir).is_err() {
+                    break;
+                }
+                parent = dir.parent();
+            }
+            if let Some(parent) = new_dir.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            move_dir(&staged, &new_dir)?;
+            let worktree = workdir.join(&sub_path);
+            let gitfile = worktree.join(".git");
+            if gitfile.is_file() {
+                std::fs::write(
+                    &gitfile,
+                    format!("gitdir: {}\n", relative_path(&worktree, &new_dir).display()),
+                )?;
+            }
+            let mut module_config = git2::Config::open(&new_dir.join("config"))?;
+            if module_config.get_string("core.worktree").is_ok() {
+                module_config.set_str(
+                    "core.worktree",
+                    &relative_path(&new_dir, &worktree).to_string_lossy(),
+                )?;
+            }
+        }
+
+        // 2. Rename the config sections, staging .gitmodules as `git mv` would
+        if in_gitmodules {
+            rename_submodule_section(&workdir, Some(&gitmodules), &name, new_name)?;
+            let mut index = self.repo.index()?;
+            index.add_path(Path::new(".gitmodules"))?;
+            index.write()?;
+        }
+        if has_submodule_section(&local_config, &name) {
+            rename_submodule_section(&workdir, None, &name, new_name)?;
+        }
+        Ok(())
+    }
+    fn get_submodule_status(&self, path: &str) -> Result<DetailedSubmoduleStatus> {
+        let submodule = self.find_submodule(path)?;
         let name = submodule.name().unwrap_or(path).to_string();
         let url = submodule
             .url()
@@ -512,7 +742,7 @@ impl GitOperations for Git2Operations {
         // Get status
         let status = self
             .repo
-            .submodule_status(path, git2::SubmoduleIgnore::Unspecified)?;
+            .submodule_status(&name, git2::SubmoduleIgnore::Unspecified)?;
         let status_flags = self.convert_git2_status_to_flags(status);
         // Get OIDs
         let head_oid = submodule.head_id().map(|oid| oid.to_string());
@@ -560,11 +790,8 @@ impl GitOperations for Git2Operations {
             .collect();
         Ok(paths)
     }
-    fn fetch_submodule(&self, path: &str) -> Result<()> {
-        let submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+    fn fetch_submodule(&self, path: &str, auth: &SubmoduleAuth) -> Result<()> {
+        let submodule = self.find_submodule(path)?;
         // Open the submodule repository
         let sub_repo = submodule
             .open()
@@ -574,16 +801,36 @@ impl GitOperations for Git2Operations {
             .find_remote("origin")
             .with_context(|| format!("Failed to find origin remote for submodule: {path}"))?;
         // Fetch from origin
+        let mut fetch_opts = self.auth_fetch_options(auth)?;
         remote
-            .fetch(&[] as &[&str], None, None)
+            .fetch(&[] as &[&str], Some(&mut fetch_opts), None)
             .with_context(|| format!("Failed to fetch submodule: {path}"))?;
         Ok(())
     }
+    fn deepen_submodule(&self, path: &str, _by: u32, _auth: &SubmoduleAuth) -> Result<()> {
+        // libgit2 only knows depths counted from the remote tips, not from our boundary
+        Err(anyhow::anyhow!(
+            "git2 cannot deepen a shallow submodule relative to its boundary: {path}"
+        ))
+    }
+    fn unshallow_submodule(&self, path: &str, auth: &SubmoduleAuth) -> Result<()> {
+        let submodule = self.find_submodule(path)?;
+        let sub_repo = submodule
+            .open()
+            .with_context(|| format!("Failed to open submodule repository: {path}"))?;
+        let mut remote = sub_repo
+            .find_remote("origin")
+            .with_context(|| format!("Failed to find origin remote for submodule: {path}"))?;
+        // libgit2 treats i32::MAX as "unshallow" (GIT_FETCH_DEPTH_UNSHALLOW)
+        let mut fetch_opts = self.auth_fetch_options(auth)?;
+        fetch_opts.depth(i32::MAX);
+        remote
+            .fetch(&[] as &[&str], Some(&mut fetch_opts), None)
+            .with_context(|| format!("Failed to unshallow submodule: {path}"))?;
+        Ok(())
+    }
     fn reset_submodule(&self, path: &str, hard: bool) -> Result<()> {
-        let submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+        let submodule = self.find_submodule(path)?;
         // Open the submodule repository
         let sub_repo = submodule
             .open()
@@ -603,10 +850,7 @@ impl GitOperations for Git2Operations {
         Ok(())
     }
     fn clean_submodule(&self, path: &str, force: bool, remove_directories: bool) -> Result<()> {
-        let submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+        let submodule = self.find_submodule(path)?;
         // Open the submodule repository
         let sub_repo = submodule
             .open()
@@ -641,10 +885,7 @@ impl GitOperations for Git2Operations {
         Ok(())
     }
     fn stash_submodule(&self, path: &str, include_untracked: bool) -> Result<()> {
-        let submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+        let submodule = self.find_submodule(path)?;
         // Open the submodule repository
         let mut sub_repo = submodule
             .open()
@@ -663,10 +904,7 @@ impl GitOperations for Git2Operations {
         Ok(())
     }
     fn enable_sparse_checkout(&self, path: &str) -> Result<()> {
-        let submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+        let submodule = self.find_submodule(path)?;
         // Open the submodule repository
         let sub_repo = submodule
             .open()
@@ -679,10 +917,7 @@ impl GitOperations for Git2Operations {
         Ok(())
     }
     fn set_sparse_patterns(&self, path: &str, patterns: &[String]) -> Result<()> {
-        let submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+        let submodule = self.find_submodule(path)?;
         // Open the submodule repository
         let sub_repo = submodule
             .open()
@@ -704,10 +939,7 @@ impl GitOperations for Git2Operations {
         Ok(())
     }
     fn get_sparse_patterns(&self, path: &str) -> Result<Vec<String>> {
-        let submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+        let submodule = self.find_submodule(path)?;
         // Open the submodule repository
         let sub_repo = submodule
             .open()
@@ -742,10 +974,7 @@ impl Git2Operations {
     /// Get sparse checkout information for a submodule
     #[allow(dead_code)]
     fn get_sparse_checkout_info(&self, path: &str) -> Result<(bool, Vec<String>)> {
-        let submodule = self
-            .repo
-            .find_submodule(path)
-            .with_context(|| format!("Submodule not found: {path}"))?;
+        let submodule = self.find_submodule(path)?;
         // Open the submodule repository
         let sub_repo = submodule
             .open()
@@ -767,3 +996,208 @@ impl From<super::GitOpsManager> for Git2Operations {
         ops.git2_ops
     }
 }
+
+/// Ask a git credential helper for a username and password.
+///
+/// libgit2 can only run helpers it finds in a config file, so this goes through
+/// `git credential fill`, which understands every `credential.helper` form (`store`,
+/// absolute paths, `!shell` snippets) and never prompts.
+fn credential_fill(helper: &str, url: &str) -> Result<(String, String)> {
+    use std::io::Write;
+    let mut child = std::process::Command::new("git")
+        .args(["-c", "credential.helper="])
+        .arg("-c")
+        .arg(format!("credential.helper={helper}"))
+        .args(["credential", "fill"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .context("Failed to run git credential fill")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "url={url}\n")?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Credential helper '{helper}' gave no credentials for {url}"
+        ));
+    }
+    let reply = String::from_utf8_lossy(&output.stdout);
+    let field = |name: &str| {
+        reply
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+            .map(str::to_string)
+    };
+    match (field("username"), field("password")) {
+        (Some(user), Some(password)) => Ok((user, password)),
+        _ => Err(anyhow::anyhow!(
+            "Credential helper '{helper}' gave no credentials for {url}"
+        )),
+    }
+}
+
+/// Whether `config` has any `submodule.<name>.*` keys
+fn has_submodule_section(config: &git2::Config, name: &str) -> bool {
+    let prefix = format!("submodule.{name}.");
+    config.entries(None).is_ok_and(|mut entries| {
+        let mut found = false;
+        while let Some(Ok(entry)) = entries.next() {
+            if entry.name().is_ok_and(|key| key.starts_with(&prefix)) {
+                found = true;
+                break;
+            }
+        }
+        found
+    })
+}
+
+/// Bring the commit the superproject records into a cloned submodule by merging it,
+/// rebasing onto it, or running the submodule's update command with it, as
+/// `git submodule update` does. Conflicts stop the update and are left for the user.
+fn integrate_recorded_commit(
+    sub_repo: &git2::Repository,
+    path: &str,
+    commit: git2::Oid,
+    strategy: &SerializableUpdate,
+    fetch_opts: &mut git2::FetchOptions<'_>,
+) -> Result<()> {
+    if sub_repo.head().ok().and_then(|head| head.target()) == Some(commit) {
+        return Ok(());
+    }
+    if sub_repo.find_commit(commit).is_err() {
+        let mut remote = if let Ok(remote) = sub_repo.find_remote("origin") {
+            remote
+        } else {
+            let remotes = sub_repo.remotes()?;
+            let name = remotes
+                .get(0)?
+                .ok_or_else(|| anyhow::anyhow!("Submodule '{path}' has no remote"))?;
+            sub_repo.find_remote(name)?
+        };
+        remote
+            .fetch(&[] as &[&str], Some(fetch_opts), None)
+            .with_context(|| format!("Failed to fetch submodule '{path}'"))?;
+        sub_repo.find_commit(commit).map_err(|_| {
+            anyhow::anyhow!("Fetched submodule '{path}', but its remote doesn't have {commit}")
+        })?;
+    }
+    let workdir = sub_repo
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("Submodule '{path}' has no working directory"))?;
+    let sha = commit.to_string();
+    let short = &sha[..sha.len().min(7)];
+
+    if let SerializableUpdate::Command(command) = strategy {
+        // git runs the command through the shell with the commit as its last argument
+        let status = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{command} \"$@\""))
+            .arg(command)
+            .arg(&sha)
+            .current_dir(workdir)
+            .status()
+            .with_context(|| format!("Failed to run '{command}' in submodule '{path}'"))?;
+        return if status.success() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Execution of '{command} {sha}' failed in submodule path '{path}' ({status})"
+            ))
+        };
+    }
+
+    let (verb, args): (&str, &[&str]) = match strategy {
+        SerializableUpdate::Merge => ("merge", &["merge", "--quiet", "--no-edit"]),
+        SerializableUpdate::Rebase => ("rebase", &["rebase", "--quiet"]),
+        _ => return Ok(()),
+    };
+    let output = std::process::Command::new("git")
+        .args(args)
+        .arg(&sha)
+        .current_dir(workdir)
+        .output()
+        .with_context(|| format!("Failed to run git {verb} in submodule '{path}'"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // Reopen so the index reflects what git just wrote
+    let conflicts: Vec<String> = git2::Repository::open(workdir)?
+        .index()?
+        .conflicts()?
+        .filter_map(|conflict| {
+            let conflict = conflict.ok()?;
+            let entry = conflict.our.or(conflict.their).or(conflict.ancestor)?;
+            Some(String::from_utf8_lossy(&entry.path).into_owned())
+        })
+        .collect();
+    if conflicts.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "Unable to {verb} '{sha}' in submodule path '{path}': {}",
+            stderr.trim()
+        ));
+    }
+    let files = conflicts.join(", ");
+    Err(match strategy {
+        SerializableUpdate::Merge => anyhow::anyhow!(
+            "Merging {short} into submodule '{path}' stopped on conflicts in: {files}. \
+             Resolve them in {path} and commit, or run `git merge --abort` there."
+        ),
+        _ => anyhow::anyhow!(
+            "Rebasing submodule '{path}' onto {short} stopped on conflicts in: {files}. \
+             Resolve them and run `git rebase --continue` in {path}, or `git rebase --abort` there."
+        ),
+    })
+}
+
+/// Whether `dir` holds nothing but the directories leading down to `inner`
+fn only_holds(dir: &Path, inner: &Path) -> bool {
+    let mut current = inner;
+    while let Some(parent) = current.parent() {
+        let single = std::fs::read_dir(parent).is_ok_and(|entries| entries.count() == 1);
+        if !single {
+            return false;
+        }
+        if parent == dir {
+            return true;
+        }
+        current = parent;
+    }
+    false
+}
+
+/// Rename `[submodule "<from>"]` to `[submodule "<to>"]` in `file`, or in the repository's
+/// own config if there's no `file`.
+///
+/// libgit2 can only rename a remote's section, so this goes through
+/// `git config --rename-section`.
+fn rename_submodule_section(
+    workdir: &Path,
+    file: Option<&Path>,
+    from: &str,
+    to: &str,
+) -> Result<()> {
+    let mut cmd = std::process::Command::new("git");
+    cmd.current_dir(workdir).arg("config");
+    if let Some(file) = file {
+        cmd.arg("--file").arg(file);
+    }
+    let output = cmd
+        .arg("--rename-section")
+        .arg(format!("submodule.{from}"))
+        .arg(format!("submodule.{to}"))
+        .output()
+        .context("Failed to run git config")?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Failed to rename submodule '{from}' to '{to}': {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
@@ -4,10 +4,12 @@
 // TODO: This module is very not-DRY...but it's low priority right now.
 use anyhow::{Context, Result};
 use gix::bstr::ByteSlice;
+use gix::features::progress::DoOrDiscard;
 use std::collections::HashMap;
 use std::path::Path;
 
-use crate::git_ops::simple_gix::fetch_repo;
+use crate::git_ops::simple_gix::{fetch_repo, shallow_mode};
+use crate::progress::Row;
 
 /// Parse a gix config file from raw bytes
 fn gix_file_from_bytes(bytes: Vec<u8>) -> Result<gix::config::File> {
@@ -21,9 +23,8 @@ fn gix_file_from_bytes(bytes: Vec<u8>) -> Result<gix::config::File> {
 }
 
 use super::{DetailedSubmoduleStatus, GitConfig, GitOperations, SubmoduleStatusFlags};
-use crate::config::{SubmoduleAddOptions, SubmoduleEntries, SubmoduleUpdateOptions};
+use crate::config::{SubmoduleAddOptions, SubmoduleAuth, SubmoduleEntries, SubmoduleUpdateOptions};
 use crate::options::{ConfigLevel, GitmodulesConvert};
-use crate::utilities;
 
 /// Primary implementation using gix (gitoxide)
 #[derive(Debug, Clone, PartialEq)]
@@ -31,6 +32,8 @@ pub struct GixOperations {
     repo: gix::Repository,
     /// Whether to surface the underlying gitoxide fetch report on stderr.
     verbose: bool,
+    /// Where clones and fetches report their progress, if anywhere
+    progress: Option<Row>,
 }
 impl GixOperations {
     /// Create a new `GixOperations` instance. Quiet by default; see
@@ -45,6 +48,7 @@ impl GixOperations {
         Ok(Self {
             repo,
             verbose: false,
+            progress: None,
         })
     }
 
@@ -55,6 +59,16 @@ impl GixOperations {
         self
     }
 
+    /// Report the progress of clones and fetches under `row`, or nowhere
+    pub fn set_progress(&mut self, row: Option<Row>) {
+        self.progress = row;
+    }
+
+    /// A phase of the current submodule's progress, if it's being reported
+    fn phase(&self, name: &str) -> Option<gix::progress::prodash::tree::Item> {
+        self.progress.as_ref().map(|row| row.phase(name))
+    }
+
     /// Try to perform operation with gix, return error if not supported
     fn try_gix_operation<T, F>(&self, operation: F) -> Result<T>
     where
@@ -100,6 +114,45 @@ impl GixOperations {
             .ok_or_else(|| anyhow::anyhow!("HEAD is detached, not on a branch"))
     }
 
+    /// Open a submodule's repository by its path relative to the superproject worktree, with
+    /// `auth` applied to the transports it opens
+    fn open_submodule_repo(&self, path: &str, auth: &SubmoduleAuth) -> Result<gix::Repository> {
+        let workdir = self
+            .repo
+            .workdir()
+            .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
+        let repo = gix::open(workdir.join(path))
+            .with_context(|| format!("Failed to open submodule repository: {path}"))?;
+        self.with_auth(repo, auth)
+    }
+
+    /// A submodule's credential settings as gix config overrides (`key=value`)
+    fn auth_overrides(&self, auth: &SubmoduleAuth) -> Result<Vec<String>> {
+        let root = self.repo.workdir().unwrap_or_else(|| Path::new("."));
+        Ok(auth
+            .config_overrides(root)?
+            .into_iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect())
+    }
+
+    /// `repo` with `auth` applied on top of its config. Like a clone's in-memory overrides,
+    /// they go in last, so they win over `GIT_SSH_COMMAND` and the like, which the overrides
+    /// given when opening a repository don't.
+    fn with_auth(
+        &self,
+        mut repo: gix::Repository,
+        auth: &SubmoduleAuth,
+    ) -> Result<gix::Repository> {
+        let overrides = self.auth_overrides(auth)?;
+        if !overrides.is_empty() {
+            let mut config = repo.config_snapshot_mut();
+            config.append_config(&overrides, gix::config::Source::Api)?;
+            config.commit()?;
+        }
+        Ok(repo)
+    }
+
     /// Convert gix submodule status to our status flags
     #[allow(dead_code, clippy::unused_self)]
     fn convert_gix_status_to_flags(&self, status: &gix::submodule::Status) -> SubmoduleStatusFlags {
@@ -128,10 +181,12 @@ impl GitOperations for GixOperations {
                 return Ok(SubmoduleEntries::default());
             }
 
+            // Without the repository's config: gix would overlay its `submodule.<name>`
+            // sections, which have no path and would hide the one in `.gitmodules`
             let content = std::fs::read(&gitmodules_path)?;
-            let config = repo.config_snapshot();
+            let no_overrides = gix::config::File::new(gix::config::file::Metadata::api());
             let submodule_file =
-                gix_submodule::File::from_bytes(&content, Some(gitmodules_path), &config)?;
+                gix_submodule::File::from_bytes(&content, Some(gitmodules_path), &no_overrides)?;
 
             Ok(mutable_self.convert_gitmodules_to_entries(submodule_file))
         })
@@ -171,7 +226,8 @@ impl GitOperations for GixOperations {
                         value.as_bytes().as_bstr(),
                     )?;
                 }
-                if let Some(update) = &entry.update {
+                // git refuses `!command` updates from .gitmodules; they only go in .git/config
+                if let Some(update) = entry.update.as_ref().filter(|u| !u.is_command()) {
                     let value = update.to_gitmodules();
                     git_config.set_raw_value_by(
                         "submodule",
@@ -281,7 +337,8 @@ impl GitOperations for GixOperations {
 
         self.try_gix_operation(|repo| {
             let config_path = match level {
-                ConfigLevel::Local | ConfigLevel::Worktree => repo.git_dir().join("config"),
+                // Shared by every worktree; a linked worktree's git dir has no config
+                ConfigLevel::Local | ConfigLevel::Worktree => repo.common_dir().join("config"),
                 _ => {
                     return Err(anyhow::anyhow!(
                         "Only local config writing is supported with gix"
@@ -325,7 +382,7 @@ impl GitOperations for GixOperations {
     }
 
     /// Initialize a submodule by reading its configuration and setting it up
-    fn init_submodule(&mut self, path: &str) -> Result<()> {
+    fn init_submodule(&mut self, path: &str, auth: &SubmoduleAuth) -> Result<()> {
         // 1. Read .gitmodules to get submodule configuration
         let entries = self.read_gitmodules()?;
 
@@ -341,6 +398,7 @@ impl GitOperations for GixOperations {
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Submodule '{name}' has no URL configured"))?;
 
+        let auth_overrides = self.auth_overrides(auth)?;
         self.try_gix_operation(|repo| {
             // 3. Set up submodule configuration in .git/config
             let config_snapshot = repo.config_snapshot();
@@ -380,16 +438,18 @@ impl GitOperations for GixOperations {
             // 5. Clone the submodule if it doesn't exist yet
             if !submodule_path.join(".git").exists() {
                 // Clone the submodule repository using gix
-                let mut prepare = gix::prepare_clone(url.clone(), &submodule_path)?;
-                if entry.shallow == Some(true) {
-                    prepare = prepare
-                        .with_shallow(gix::remote::fetch::Shallow::DepthAtRemote(1.try_into()?));
-                }
-                let should_interrupt =
-                    std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-                let progress = gix::progress::Discard;
-                let (_checkout, _outcome) =
-                    prepare.fetch_then_checkout(progress, &should_interrupt)?;
+                let mut prepare = gix::prepare_clone(url.clone(), &submodule_path)?
+                    .with_shallow(shallow_mode(entry.effective_depth(), None)?)
+                    .with_in_memory_config_overrides(auth_overrides);
+                let (mut checkout, _outcome) = prepare.fetch_then_checkout(
+                    DoOrDiscard::from(self.phase("clone")),
+                    crate::interrupt::flag(),
+                )?;
+                // An unfinished checkout deletes the clone when it's dropped
+                checkout.main_worktree(
+                    DoOrDiscard::from(self.phase("checkout")),
+                    crate::interrupt::flag(),
+                )?;
             }
 
             Ok(())
@@ -416,16 +476,26 @@ impl GitOperations for GixOperations {
 
         if !submodule_path.exists() || !submodule_path.join(".git").exists() {
             // Use gix::prepare_clone for proper remote operations
-            let mut prepare = gix::prepare_clone(url.clone(), &submodule_path)?;
-            if entry.shallow == Some(true) {
-                prepare =
-                    prepare.with_shallow(gix::remote::fetch::Shallow::DepthAtRemote(1.try_into()?));
-            }
-            let should_interrupt = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-            let progress = gix::progress::Discard;
-            let (checkout, _outcome) = prepare.fetch_then_checkout(progress, &should_interrupt)?;
+            // `.gitmodules` only carries `shallow = true`; a configured depth or date wins
+            let shallow = if opts.is_shallow() {
+                shallow_mode(opts.depth, opts.shallow_since.as_deref())?
+            } else {
+                shallow_mode(entry.effective_depth(), None)?
+            };
+            let mut prepare = gix::prepare_clone(url.clone(), &submodule_path)?
+                .with_shallow(shallow)
+                .with_in_memory_config_overrides(self.auth_overrides(&opts.auth)?);
+            let (mut checkout, _outcome) = prepare.fetch_then_checkout(
+                DoOrDiscard::from(self.phase("clone")),
+                crate::interrupt::flag(),
+            )?;
+            // An unfinished checkout deletes the clone when it's dropped
+            let (repo, _outcome) = checkout.main_worktree(
+                DoOrDiscard::from(self.phase("checkout")),
+                crate::interrupt::flag(),
+            )?;
             if let Some(branch) = &entry.branch {
-                let mut config_file = checkout.repo().config_snapshot().to_owned();
+                let mut config_file = repo.config_snapshot().to_owned();
                 match branch {
                     crate::options::SerializableBranch::Name(branch_name) => {
                         config_file.set_raw_value_by(
@@ -465,12 +535,18 @@ impl GitOperations for GixOperations {
             // Submodule exists — fetch updates using sync fetch_repo
             // Pass None to let gix resolve the default remote (which has refspecs configured).
             // Passing the URL string would create a bare remote without refspecs.
-            let submodule_repo = gix::open(&submodule_path)?;
+            let submodule_repo = self.with_auth(gix::open(&submodule_path)?, &opts.auth)?;
+            let shallow = if opts.is_shallow() {
+                shallow_mode(opts.depth, opts.shallow_since.as_deref())?
+            } else {
+                shallow_mode(entry.effective_depth(), None)?
+            };
             fetch_repo(
                 submodule_repo,
                 None,
-                entry.shallow == Some(true),
+                shallow,
                 self.verbose,
+                self.phase("fetch"),
             )
             .map_err(|e| anyhow::anyhow!("Failed to fetch submodule: {e}"))?;
             match opts.strategy {
@@ -487,14 +563,11 @@ impl GitOperations for GixOperations {
                         "gix cannot checkout submodule to its recorded commit; falling back to git2"
                     ));
                 }
-                crate::options::SerializableUpdate::Merge => {
+                crate::options::SerializableUpdate::Merge
+                | crate::options::SerializableUpdate::Rebase
+                | crate::options::SerializableUpdate::Command(_) => {
                     return Err(anyhow::anyhow!(
-                        "Merge strategy not yet implemented with gix"
-                    ));
-                }
-                crate::options::SerializableUpdate::Rebase => {
-                    return Err(anyhow::anyhow!(
-                        "Rebase strategy not yet implemented with gix"
+                        "gix cannot merge, rebase, or run update commands; falling back to git2"
                     ));
                 }
                 crate::options::SerializableUpdate::None => {
@@ -689,6 +762,12 @@ impl GitOperations for GixOperations {
             Ok(())
         })
     }
+    /// Rename a submodule (not yet implemented with gix)
+    fn rename_submodule(&mut self, _path: &str, _new_name: &str) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "rename_submodule not yet implemented with gix"
+        ))
+    }
     /// Get the status of a submodule
     fn get_submodule_status(&self, _path: &str) -> Result<DetailedSubmoduleStatus> {
         Err(anyhow::anyhow!(
@@ -707,11 +786,41 @@ impl GitOperations for GixOperations {
             Ok(submodule_paths)
         })
     }
-    fn fetch_submodule(&self, path: &str) -> Result<()> {
+    fn fetch_submodule(&self, path: &str, auth: &SubmoduleAuth) -> Result<()> {
         // Pass None to let gix resolve the default remote (which has refspecs configured).
-        let submodule_repo = utilities::repo_from_path(&std::path::PathBuf::from(path))?;
-        fetch_repo(submodule_repo, None, false, self.verbose)
-            .map_err(|e| anyhow::anyhow!("Failed to fetch submodule: {e}"))
+        let submodule_repo = self.open_submodule_repo(path, auth)?;
+        fetch_repo(
+            submodule_repo,
+            None,
+            gix::remote::fetch::Shallow::NoChange,
+            self.verbose,
+            self.phase("fetch"),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to fetch submodule: {e}"))
+    }
+
+    fn deepen_submodule(&self, path: &str, by: u32, auth: &SubmoduleAuth) -> Result<()> {
+        let submodule_repo = self.open_submodule_repo(path, auth)?;
+        fetch_repo(
+            submodule_repo,
+            None,
+            gix::remote::fetch::Shallow::Deepen(by),
+            self.verbose,
+            self.phase("fetch"),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to deepen submodule: {e}"))
+    }
+
+    fn unshallow_submodule(&self, path: &str, auth: &SubmoduleAuth) -> Result<()> {
+        let submodule_repo = self.open_submodule_repo(path, auth)?;
+        fetch_repo(
+            submodule_repo,
+            None,
+            gix::remote::fetch::Shallow::undo(),
+            self.verbose,
+            self.phase("fetch"),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to unshallow submodule: {e}"))
     }
 
     fn reset_submodule(&self, _path: &str, _hard: bool) -> Result<()> {
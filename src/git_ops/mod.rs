@@ -2,7 +2,8 @@
 //
 // SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
 #![doc = r"
-This module provides a unified interface for performing git operations using both `gix` and `git2` libraries. 
+This module provides a unified interface for performing git operations using both `gix` and `git2` libraries.
+
 It implements a gix-first, git2-fallback strategy to ensure robust functionality across different environments and use cases. 
 
 The `GitOpsManager` struct manages the operations and automatically falls back to `git2` if a `gix` operation fails, 
@@ -22,12 +23,15 @@ use anyhow::{Context, Result};
 use bitflags::bitflags;
 use std::collections::HashMap;
 use std::path::Path;
+use tracing::field::Empty;
+use tracing::instrument;
 
-use crate::config::{SubmoduleAddOptions, SubmoduleEntries, SubmoduleUpdateOptions};
+use crate::config::{SubmoduleAddOptions, SubmoduleAuth, SubmoduleEntries, SubmoduleUpdateOptions};
 use crate::options::{
     ConfigLevel, SerializableBranch, SerializableFetchRecurse, SerializableIgnore,
     SerializableUpdate,
 };
+use crate::progress::Row;
 
 /// Represents git configuration state
 #[allow(dead_code)]
@@ -37,6 +41,56 @@ pub struct GitConfig {
     pub entries: HashMap<String, String>,
 }
 
+/// The git backend that ran an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// gitoxide
+    Gix,
+    /// libgit2
+    Git2,
+    /// The `git` command
+    Cli,
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Gix => "gix",
+            Self::Git2 => "git2",
+            Self::Cli => "git",
+        })
+    }
+}
+
+/// An error from one of the backends, noting which. It reads the same as the error it wraps.
+#[derive(Debug, thiserror::Error)]
+#[error("{error}")]
+pub struct BackendError {
+    /// The backend the error came from; the last one tried, when one fell back to another
+    pub backend: Backend,
+    /// What went wrong
+    pub error: anyhow::Error,
+}
+
+impl BackendError {
+    /// Notes that `error` came from `backend`, unless it already says where it came from
+    pub fn tag(backend: Backend) -> impl FnOnce(anyhow::Error) -> anyhow::Error {
+        move |error| {
+            if error.is::<Self>() {
+                error
+            } else {
+                Self { backend, error }.into()
+            }
+        }
+    }
+}
+
+/// Notes on the current git operation's span that `backend` served it
+fn record_backend(backend: Backend) {
+    tracing::Span::current().record("backend", tracing::field::display(backend));
+}
+
 bitflags! {
     /// Submodule status flags (mirrors git2::SubmoduleStatus)
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -130,14 +184,17 @@ pub trait GitOperations {
     // Submodule operations
     /// Add a new submodule
     fn add_submodule(&mut self, opts: &SubmoduleAddOptions) -> Result<()>;
-    /// Initialize a submodule
-    fn init_submodule(&mut self, path: &str) -> Result<()>;
+    /// Initialize a submodule, cloning it with `auth` if it isn't cloned yet
+    fn init_submodule(&mut self, path: &str, auth: &SubmoduleAuth) -> Result<()>;
     /// Update a submodule
     fn update_submodule(&mut self, path: &str, opts: &SubmoduleUpdateOptions) -> Result<()>;
     /// Delete a submodule completely
     fn delete_submodule(&mut self, path: &str) -> Result<()>;
     /// Deinitialize a submodule
     fn deinit_submodule(&mut self, path: &str, force: bool) -> Result<()>;
+    /// Give the submodule at `path` a new name: its `.gitmodules` and `.git/config`
+    /// sections and its git directory under `.git/modules` all move to the new name
+    fn rename_submodule(&mut self, path: &str, new_name: &str) -> Result<()>;
     /// Get detailed status of a submodule
     #[allow(dead_code)]
     fn get_submodule_status(&self, path: &str) -> Result<DetailedSubmoduleStatus>;
@@ -147,7 +204,11 @@ pub trait GitOperations {
     // Repository operations
     /// Fetch a submodule
     #[allow(dead_code)]
-    fn fetch_submodule(&self, path: &str) -> Result<()>;
+    fn fetch_submodule(&self, path: &str, auth: &SubmoduleAuth) -> Result<()>;
+    /// Fetch `by` more commits of history into a shallow submodule
+    fn deepen_submodule(&self, path: &str, by: u32, auth: &SubmoduleAuth) -> Result<()>;
+    /// Fetch the complete history of a shallow submodule
+    fn unshallow_submodule(&self, path: &str, auth: &SubmoduleAuth) -> Result<()>;
     /// Reset a submodule
     fn reset_submodule(&self, path: &str, hard: bool) -> Result<()>;
     /// Clean a submodule
@@ -172,6 +233,7 @@ pub struct GitOpsManager {
     git2_ops: Git2Operations,
     verbose: bool,
     force_cli_add: bool,
+    progress: Option<Row>,
 }
 
 /// Implement `GitOperations` for `GitOpsManager`, using gix first and falling back to git2 if gix fails
@@ -189,6 +251,7 @@ impl GitOpsManager {
             git2_ops,
             verbose,
             force_cli_add: false,
+            progress: None,
         })
     }
 
@@ -209,6 +272,7 @@ impl GitOpsManager {
             git2_ops,
             verbose,
             force_cli_add: false,
+            progress: None,
         })
     }
 
@@ -242,6 +306,16 @@ impl GitOpsManager {
         self.force_cli_add
     }
 
+    /// Report the progress of both backends' clones and fetches under `row`, or stop
+    /// reporting it with `None`
+    pub fn set_progress(&mut self, row: Option<Row>) {
+        if let Some(gix) = &mut self.gix_ops {
+            gix.set_progress(row.clone());
+        }
+        self.git2_ops.set_progress(row.clone());
+        self.progress = row;
+    }
+
     /// Return the working directory of the underlying git repository, if any.
     pub fn workdir(&self) -> Option<&std::path::Path> {
         self.git2_ops.workdir()
@@ -271,37 +345,47 @@ impl GitOpsManager {
                 self.gix_ops = Some(new_gix.with_verbose(self.verbose));
             }
             Err(e) => {
-                if self.verbose {
-                    eprintln!(
-                        "Warning: failed to reopen gix repository at {}: {}",
-                        workdir.display(),
-                        e
-                    );
-                }
+                tracing::info!(
+                    error = %e,
+                    path = %workdir.display(),
+                    "failed to reopen the gix repository"
+                );
             }
         }
 
+        // The new backends keep reporting where the old ones did
+        self.set_progress(self.progress.clone());
         Ok(())
     }
 
-    /// Try gix first, fall back to git2
+    /// Try gix first, fall back to git2. Each backend tried runs in a span of its own, and
+    /// the operation's span records the one that had the last word.
     fn try_with_fallback<T, F1, F2>(&self, gix_op: F1, git2_op: F2) -> Result<T>
     where
         F1: FnOnce(&GixOperations) -> Result<T>,
         F2: FnOnce(&Git2Operations) -> Result<T>,
     {
         if let Some(ref gix) = self.gix_ops {
-            match gix_op(gix) {
-                Ok(result) => return Ok(result),
+            match tracing::debug_span!("gix").in_scope(|| gix_op(gix)) {
+                Ok(result) => {
+                    record_backend(Backend::Gix);
+                    return Ok(result);
+                }
+                // Stopped on purpose; git2 would only start the work over
+                Err(e) if crate::interrupt::is_interrupted() => {
+                    record_backend(Backend::Gix);
+                    return Err(BackendError::tag(Backend::Gix)(e));
+                }
                 Err(e) => {
-                    if self.verbose {
-                        eprintln!("gix operation failed, falling back to git2: {e}");
-                    }
+                    tracing::info!(error = %format!("{e:#}"), "gix failed, falling back to git2");
                 }
             }
         }
 
-        git2_op(&self.git2_ops)
+        record_backend(Backend::Git2);
+        tracing::debug_span!("git2")
+            .in_scope(|| git2_op(&self.git2_ops))
+            .map_err(BackendError::tag(Backend::Git2))
     }
 
     /// Try gix first, fall back to git2 (mutable version)
@@ -311,21 +395,31 @@ impl GitOpsManager {
         F2: FnOnce(&mut Git2Operations) -> Result<T>,
     {
         if let Some(ref mut gix) = self.gix_ops {
-            match gix_op(gix) {
-                Ok(result) => return Ok(result),
+            match tracing::debug_span!("gix").in_scope(|| gix_op(gix)) {
+                Ok(result) => {
+                    record_backend(Backend::Gix);
+                    return Ok(result);
+                }
+                // Stopped on purpose; git2 would only start the work over
+                Err(e) if crate::interrupt::is_interrupted() => {
+                    record_backend(Backend::Gix);
+                    return Err(BackendError::tag(Backend::Gix)(e));
+                }
                 Err(e) => {
-                    if self.verbose {
-                        eprintln!("gix operation failed, falling back to git2: {e}");
-                    }
+                    tracing::info!(error = %format!("{e:#}"), "gix failed, falling back to git2");
                 }
             }
         }
-        git2_op(&mut self.git2_ops)
+        record_backend(Backend::Git2);
+        tracing::debug_span!("git2")
+            .in_scope(|| git2_op(&mut self.git2_ops))
+            .map_err(BackendError::tag(Backend::Git2))
     }
 }
 
 /// Implement `GitOperations` for `GitOpsManager`, using gix first and falling back to git2 if gix fails
 impl GitOperations for GitOpsManager {
+    #[instrument(level = "debug", skip_all, fields(backend = Empty))]
     fn read_gitmodules(&self) -> Result<SubmoduleEntries> {
         self.try_with_fallback(
             GitOperations::read_gitmodules,
@@ -333,6 +427,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(backend = Empty))]
     fn write_gitmodules(&mut self, config: &SubmoduleEntries) -> Result<()> {
         self.try_with_fallback_mut(
             |gix| gix.write_gitmodules(config),
@@ -340,6 +435,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(backend = Empty))]
     fn read_git_config(&self, level: ConfigLevel) -> Result<GitConfig> {
         self.try_with_fallback(
             |gix| gix.read_git_config(level),
@@ -347,6 +443,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(backend = Empty))]
     fn write_git_config(&self, config: &GitConfig, level: ConfigLevel) -> Result<()> {
         self.try_with_fallback(
             |gix| gix.write_git_config(config, level),
@@ -354,6 +451,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(backend = Empty))]
     fn set_config_value(&self, key: &str, value: &str, level: ConfigLevel) -> Result<()> {
         self.try_with_fallback(
             |gix| gix.set_config_value(key, value, level),
@@ -361,6 +459,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(submodule = %opts.name, path = %opts.path.display(), backend = Empty))]
     fn add_submodule(&mut self, opts: &SubmoduleAddOptions) -> Result<()> {
         // Try gix first (not yet implemented → falls through), then git2 which now uses
         // the correct `submodule.clone() + add_finalize()` sequence.
@@ -380,135 +479,191 @@ impl GitOperations for GitOpsManager {
                 |git2| git2.add_submodule(opts),
             )
         };
-        in_process.or_else(|git2_err| {
-            let workdir = self
-                .git2_ops
-                .workdir()
-                .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
-
-            // Clean up potentially partially initialized submodule path before fallback
-            let sub_path = workdir.join(&opts.path);
-            if sub_path.exists() {
-                let _ = std::fs::remove_dir_all(&sub_path);
-            }
+        in_process
+            .or_else(|git2_err| {
+                if crate::interrupt::is_interrupted() {
+                    return Err(git2_err);
+                }
+                tracing::info!(error = %format!("{git2_err:#}"), "git2 failed, falling back to the git CLI");
+                record_backend(Backend::Cli);
+                let _cli = tracing::debug_span!("git").entered();
+                let workdir = self
+                    .git2_ops
+                    .workdir()
+                    .ok_or_else(|| anyhow::anyhow!("Repository has no working directory"))?;
+
+                // Clean up potentially partially initialized submodule path before fallback
+                let sub_path = workdir.join(&opts.path);
+                if sub_path.exists() {
+                    let _ = std::fs::remove_dir_all(&sub_path);
+                }
 
-            // git2 also adds the submodule to .gitmodules, which will cause CLI to fail
-            // if we don't clean it up.
-            let gitmodules_path = workdir.join(".gitmodules");
-            if gitmodules_path.exists() {
-                // If it fails to read or write we just ignore it as it's a fallback cleanup
-                if let Ok(content) = std::fs::read_to_string(&gitmodules_path) {
-                    let mut new_content = String::new();
-                    let mut in_target_section = false;
-                    // git2 may key the `.gitmodules` section by either the name or the
-                    // path (it uses the path as the section key when name != path), so
-                    // match both to avoid leaving a stale entry behind.
-                    let target_name = format!("\"{}\"", opts.name);
-                    let target_path = format!("\"{}\"", opts.path.display());
-                    for line in content.lines() {
-                        if line.starts_with("[submodule \"") {
-                            in_target_section =
-                                line.contains(&target_name) || line.contains(&target_path);
-                        }
-                        if !in_target_section {
-                            new_content.push_str(line);
-                            new_content.push('\n');
+                // git2 also adds the submodule to .gitmodules, which will cause CLI to fail
+                // if we don't clean it up.
+                let gitmodules_path = workdir.join(".gitmodules");
+                if gitmodules_path.exists() {
+                    // If it fails to read or write we just ignore it as it's a fallback cleanup
+                    if let Ok(content) = std::fs::read_to_string(&gitmodules_path) {
+                        let mut new_content = String::new();
+                        let mut in_target_section = false;
+                        // git2 may key the `.gitmodules` section by either the name or the
+                        // path (it keys by path until its add renames the submodule), so
+                        // match both to avoid leaving a stale entry behind.
+                        let target_name = format!("\"{}\"", opts.name);
+                        let target_path = format!("\"{}\"", opts.path.display());
+                        for line in content.lines() {
+                            if line.starts_with("[submodule \"") {
+                                in_target_section =
+                                    line.contains(&target_name) || line.contains(&target_path);
+                            }
+                            if !in_target_section {
+                                new_content.push_str(line);
+                                new_content.push('\n');
+                            }
                         }
+                        let _ = std::fs::write(&gitmodules_path, new_content);
                     }
-                    let _ = std::fs::write(&gitmodules_path, new_content);
                 }
-            }
 
-            // Also git2 might have added it to .git/config
-            let gitconfig_path = workdir.join(".git").join("config");
-            if gitconfig_path.exists() {
-                // Remove by name (our submodule name)
-                let _ = std::process::Command::new("git")
-                    .args([
-                        "config",
-                        "--remove-section",
-                        &format!("submodule.{}", opts.name),
-                    ])
-                    .current_dir(workdir)
-                    .output();
-                // Remove by path (git2 uses path as key when name != path)
-                let path_key = opts.path.display().to_string();
-                if path_key != opts.name {
+                // Also git2 might have added it to .git/config
+                let gitconfig_path = workdir.join(".git").join("config");
+                if gitconfig_path.exists() {
+                    // Remove by name (our submodule name)
                     let _ = std::process::Command::new("git")
                         .args([
                             "config",
                             "--remove-section",
-                            &format!("submodule.{path_key}"),
+                            &format!("submodule.{}", opts.name),
                         ])
                         .current_dir(workdir)
                         .output();
+                    // Remove by path (git2 keys by path until its add renames the submodule)
+                    let path_key = opts.path.display().to_string();
+                    if path_key != opts.name {
+                        let _ = std::process::Command::new("git")
+                            .args([
+                                "config",
+                                "--remove-section",
+                                &format!("submodule.{path_key}"),
+                            ])
+                            .current_dir(workdir)
+                            .output();
+                    }
                 }
-            }
 
-            // Also git2 might have created the internal git directory
-            let internal_git_dir = workdir.join(".git").join("modules").join(&opts.name);
-            if internal_git_dir.exists() {
-                let _ = std::fs::remove_dir_all(&internal_git_dir);
-            }
+                // Also git2 might have created the internal git directory
+                let internal_git_dir = workdir.join(".git").join("modules").join(&opts.name);
+                if internal_git_dir.exists() {
+                    let _ = std::fs::remove_dir_all(&internal_git_dir);
+                }
 
-            // git2's repo.submodule() uses the *path* (not the name) as the key for the
-            // internal modules directory, so ".git/modules/lib/reinit" may exist even when
-            // ".git/modules/<name>" has already been cleaned up.  Remove both.
-            let path_internal_git_dir = workdir.join(".git").join("modules").join(&opts.path);
-            if path_internal_git_dir.exists() {
-                let _ = std::fs::remove_dir_all(&path_internal_git_dir);
-            }
+                // git2's repo.submodule() uses the *path* (not the name) as the key for the
+                // internal modules directory until its add renames the submodule, so a failed
+                // add may leave ".git/modules/lib/reinit" instead of ".git/modules/<name>".
+                // Remove both.
+                let path_internal_git_dir = workdir.join(".git").join("modules").join(&opts.path);
+                if path_internal_git_dir.exists() {
+                    let _ = std::fs::remove_dir_all(&path_internal_git_dir);
+                }
+
+                // And removed from index
+                let _ = std::process::Command::new("git")
+                    .args(["rm", "--cached", "-r", "--ignore-unmatch", "--"])
+                    .arg(&opts.path)
+                    .current_dir(workdir)
+                    .output();
 
-            // And removed from index
-            let _ = std::process::Command::new("git")
-                .args(["rm", "--cached", "-r", "--ignore-unmatch", "--"])
-                .arg(&opts.path)
-                .current_dir(workdir)
-                .output();
-
-            let mut cmd = std::process::Command::new("git");
-            cmd.args(["-c", "protocol.file.allow=always"])
-                .current_dir(workdir)
-                .arg("submodule")
-                .arg("add")
-                .arg("--name")
-                .arg(&opts.name);
-            if let Some(branch) = &opts.branch {
-                let branch_str = branch.to_string();
-                // "." is the gitmodules/git-config token meaning "track the same branch as
-                // the superproject" (SerializableBranch::CurrentInSuperproject).  It is only
-                // meaningful as a stored config value; passing it as `--branch .` to
-                // `git submodule add` is invalid and causes:
-                //   fatal: 'HEAD' is not a valid branch name
-                // Skip the flag so git resolves the remote's default branch automatically.
-                if branch_str != "." {
-                    cmd.arg("--branch").arg(&branch_str);
+                let auth_overrides = opts.auth.config_overrides(workdir)?;
+                let mut cmd = std::process::Command::new("git");
+                apply_auth_env(&mut cmd, &auth_overrides);
+                cmd.args(["-c", "protocol.file.allow=always"])
+                    .current_dir(workdir)
+                    .arg("submodule")
+                    .arg("add")
+                    .arg("--name")
+                    .arg(&opts.name);
+                if let Some(branch) = &opts.branch {
+                    let branch_str = branch.to_string();
+                    // "." is the gitmodules/git-config token meaning "track the same branch as
+                    // the superproject" (SerializableBranch::CurrentInSuperproject).  It is only
+                    // meaningful as a stored config value; passing it as `--branch .` to
+                    // `git submodule add` is invalid and causes:
+                    //   fatal: 'HEAD' is not a valid branch name
+                    // Skip the flag so git resolves the remote's default branch automatically.
+                    if branch_str != "." {
+                        cmd.arg("--branch").arg(&branch_str);
+                    }
                 }
-            }
-            if opts.shallow {
-                cmd.arg("--depth").arg("1");
-            }
-            cmd.arg("--").arg(&opts.url).arg(&opts.path);
-            let output = cmd.output().context("Failed to run git submodule add")?;
-            if output.status.success() {
-                Ok(())
-            } else {
-                Err(anyhow::anyhow!(
-                    "Failed to add submodule (git2 failed with: {}). CLI output: {}",
-                    git2_err,
-                    String::from_utf8_lossy(&output.stderr).trim()
-                ))
-            }
-        })
+                if let Some(depth) = opts.effective_depth() {
+                    cmd.arg("--depth").arg(depth.to_string());
+                } else if let Some(since) = &opts.shallow_since {
+                    // `git submodule add` has no `--shallow-since`, but it adopts a repository
+                    // that already exists at the path, so clone it with the cutoff first.
+                    let mut clone = std::process::Command::new("git");
+                    apply_auth_env(&mut clone, &auth_overrides);
+                    clone
+                        .args(["-c", "protocol.file.allow=always", "clone"])
+                        .arg(format!("--shallow-since={since}"))
+                        .current_dir(workdir);
+                    if let Some(branch) = &opts.branch {
+                        let branch_str = branch.to_string();
+                        if branch_str != "." {
+                            clone.arg("--branch").arg(&branch_str);
+                        }
+                    }
+                    clone.arg("--").arg(&opts.url).arg(&opts.path);
+                    let output = clone.output().context("Failed to run git clone")?;
+                    if !output.status.success() {
+                        return Err(anyhow::anyhow!(
+                            "Failed to add submodule (git2 failed with: {}). CLI output: {}",
+                            git2_err,
+                            String::from_utf8_lossy(&output.stderr).trim()
+                        ));
+                    }
+                }
+                cmd.arg("--").arg(&opts.url).arg(&opts.path);
+                let output = cmd.output().context("Failed to run git submodule add")?;
+                if output.status.success()
+                    && opts.effective_depth().is_none()
+                    && opts.shallow_since.is_some()
+                {
+                    // Move the adopted clone's git dir under .git/modules like any other submodule
+                    let absorb = std::process::Command::new("git")
+                        .args(["submodule", "absorbgitdirs", "--"])
+                        .arg(&opts.path)
+                        .current_dir(workdir)
+                        .output()
+                        .context("Failed to run git submodule absorbgitdirs")?;
+                    if !absorb.status.success() {
+                        return Err(anyhow::anyhow!(
+                            "Added submodule '{}', but failed to move its git directory under .git/modules: {}",
+                            opts.name,
+                            String::from_utf8_lossy(&absorb.stderr).trim()
+                        ));
+                    }
+                }
+                if output.status.success() {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!(
+                        "Failed to add submodule (git2 failed with: {}). CLI output: {}",
+                        git2_err,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ))
+                }
+            })
+            .map_err(BackendError::tag(Backend::Cli))
     }
 
-    fn init_submodule(&mut self, path: &str) -> Result<()> {
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
+    fn init_submodule(&mut self, path: &str, auth: &SubmoduleAuth) -> Result<()> {
         self.try_with_fallback_mut(
-            |gix| gix.init_submodule(path),
-            |git2| git2.init_submodule(path),
+            |gix| gix.init_submodule(path, auth),
+            |git2| git2.init_submodule(path, auth),
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn update_submodule(&mut self, path: &str, opts: &SubmoduleUpdateOptions) -> Result<()> {
         self.try_with_fallback_mut(
             |gix| gix.update_submodule(path, opts),
@@ -516,6 +671,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn delete_submodule(&mut self, path: &str) -> Result<()> {
         self.try_with_fallback_mut(
             |gix| gix.delete_submodule(path),
@@ -523,6 +679,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn deinit_submodule(&mut self, path: &str, force: bool) -> Result<()> {
         self.try_with_fallback_mut(
             |gix| gix.deinit_submodule(path, force),
@@ -530,6 +687,16 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
+    fn rename_submodule(&mut self, path: &str, new_name: &str) -> Result<()> {
+        // gix can't rewrite config sections, so there is nothing to try first
+        record_backend(Backend::Git2);
+        tracing::debug_span!("git2")
+            .in_scope(|| self.git2_ops.rename_submodule(path, new_name))
+            .map_err(BackendError::tag(Backend::Git2))
+    }
+
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn get_submodule_status(&self, path: &str) -> Result<DetailedSubmoduleStatus> {
         self.try_with_fallback(
             |gix| gix.get_submodule_status(path),
@@ -537,6 +704,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(backend = Empty))]
     fn list_submodules(&self) -> Result<Vec<String>> {
         self.try_with_fallback(
             GitOperations::list_submodules,
@@ -544,13 +712,31 @@ impl GitOperations for GitOpsManager {
         )
     }
 
-    fn fetch_submodule(&self, path: &str) -> Result<()> {
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
+    fn fetch_submodule(&self, path: &str, auth: &SubmoduleAuth) -> Result<()> {
+        self.try_with_fallback(
+            |gix| gix.fetch_submodule(path, auth),
+            |git2| git2.fetch_submodule(path, auth),
+        )
+    }
+
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
+    fn deepen_submodule(&self, path: &str, by: u32, auth: &SubmoduleAuth) -> Result<()> {
+        self.try_with_fallback(
+            |gix| gix.deepen_submodule(path, by, auth),
+            |git2| git2.deepen_submodule(path, by, auth),
+        )
+    }
+
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
+    fn unshallow_submodule(&self, path: &str, auth: &SubmoduleAuth) -> Result<()> {
         self.try_with_fallback(
-            |gix| gix.fetch_submodule(path),
-            |git2| git2.fetch_submodule(path),
+            |gix| gix.unshallow_submodule(path, auth),
+            |git2| git2.unshallow_submodule(path, auth),
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn reset_submodule(&self, path: &str, hard: bool) -> Result<()> {
         self.try_with_fallback(
             |gix| gix.reset_submodule(path, hard),
@@ -558,6 +744,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn clean_submodule(&self, path: &str, force: bool, remove_directories: bool) -> Result<()> {
         self.try_with_fallback(
             |gix| gix.clean_submodule(path, force, remove_directories),
@@ -565,6 +752,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn stash_submodule(&self, path: &str, include_untracked: bool) -> Result<()> {
         self.try_with_fallback(
             |gix| gix.stash_submodule(path, include_untracked),
@@ -572,6 +760,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn enable_sparse_checkout(&self, path: &str) -> Result<()> {
         self.try_with_fallback(
             |gix| gix.enable_sparse_checkout(path),
@@ -579,6 +768,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn set_sparse_patterns(&self, path: &str, patterns: &[String]) -> Result<()> {
         self.try_with_fallback(
             |gix| gix.set_sparse_patterns(path, patterns),
@@ -586,6 +776,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn get_sparse_patterns(&self, path: &str) -> Result<Vec<String>> {
         self.try_with_fallback(
             |gix| gix.get_sparse_patterns(path),
@@ -593,6 +784,7 @@ impl GitOperations for GitOpsManager {
         )
     }
 
+    #[instrument(level = "debug", skip_all, fields(path = path, backend = Empty))]
     fn apply_sparse_checkout(&self, path: &str) -> Result<()> {
         self.try_with_fallback(
             |gix| gix.apply_sparse_checkout(path),
@@ -616,3 +808,27 @@ impl GitOperations for GitOpsManager {
         })
     }
 }
+
+/// Hand per-submodule credential settings to a `git` child process.
+///
+/// They travel as `GIT_CONFIG_COUNT`/`GIT_CONFIG_KEY_<n>`/`GIT_CONFIG_VALUE_<n>` rather than
+/// `-c`, so a header read from the environment never appears in the process list, and git
+/// passes them on to the clone it spawns for the submodule. `GIT_SSH_COMMAND` is set too,
+/// since it outranks `core.sshCommand`.
+pub fn apply_auth_env(cmd: &mut std::process::Command, overrides: &[(String, String)]) {
+    if overrides.is_empty() {
+        return;
+    }
+    let base = std::env::var("GIT_CONFIG_COUNT")
+        .ok()
+        .and_then(|count| count.parse::<usize>().ok())
+        .unwrap_or(0);
+    for (i, (key, value)) in overrides.iter().enumerate() {
+        if key == "core.sshCommand" {
+            cmd.env("GIT_SSH_COMMAND", value);
+        }
+        cmd.env(format!("GIT_CONFIG_KEY_{}", base + i), key)
+            .env(format!("GIT_CONFIG_VALUE_{}", base + i), value);
+    }
+    cmd.env("GIT_CONFIG_COUNT", (base + overrides.len()).to_string());
+}
@@ -11,9 +11,10 @@ use anyhow::Result;
 use gitoxide_core::repository::fetch::{
     Options as FetchOptions, PROGRESS_RANGE as FetchProgressRange,
 };
+use gix::remote::fetch::Shallow;
 use gix::{features::progress, progress::prodash};
 use prodash::render::line;
-use std::io::stderr;
+use std::io::{IsTerminal, stderr};
 
 /// A standard range for line renderer.
 pub fn setup_line_renderer_range(
@@ -68,30 +69,58 @@ pub fn get_progress<T>(
     let progress = progress_tree(false);
     let sub_progress = progress.add_child(func_name);
 
-    let handle = setup_line_renderer_range(&progress, range);
+    // The renderer clears its line when it shuts down, even when nothing was drawn; keep
+    // that out of pipes and files
+    let handle = stderr()
+        .is_terminal()
+        .then(|| setup_line_renderer_range(&progress, range));
+    let outcome = report_progress(sub_progress, run);
+    if let Some(handle) = handle {
+        handle.shutdown_and_wait();
+    }
+    outcome
+}
 
+/// Like [`get_progress`], but reporting to `progress`, an item of a tree someone else renders
+pub fn report_progress<T>(
+    progress: prodash::tree::Item,
+    run: impl FnOnce(
+        progress::DoOrDiscard<prodash::tree::Item>,
+        &mut dyn std::io::Write,
+        &mut dyn std::io::Write,
+    ) -> T,
+) -> (T, Vec<u8>, Vec<u8>) {
     let mut out = Vec::<u8>::new();
     let mut err = Vec::<u8>::new();
 
     let result = gix::trace::coarse!("run").into_scope(|| {
         run(
-            progress::DoOrDiscard::from(Some(sub_progress)),
+            progress::DoOrDiscard::from(Some(progress)),
             &mut out,
             &mut err,
         )
     });
-
-    handle.shutdown_and_wait();
     (result, out, err)
 }
 
-/// Fetch options for the `fetch` command, with an option for shallow fetching.
-const fn fetch_options(remote: Option<String>, shallow: bool) -> FetchOptions {
-    let shallow = if shallow {
-        gix::remote::fetch::Shallow::DepthAtRemote(std::num::NonZeroU32::new(1).unwrap())
-    } else {
-        gix::remote::fetch::Shallow::NoChange
-    };
+/// Map configured clone limits onto a gix [`Shallow`] mode.
+///
+/// Mirrors how the `gix` CLI handles its `--depth`/`--shallow-since` flags. A depth takes
+/// precedence over a date; with neither set the shallow boundary is left unchanged.
+pub fn shallow_mode(depth: Option<u32>, since: Option<&str>) -> Result<Shallow> {
+    if let Some(depth) = depth.and_then(std::num::NonZeroU32::new) {
+        return Ok(Shallow::DepthAtRemote(depth));
+    }
+    if let Some(since) = since {
+        let cutoff = gix::date::parse(since, Some(std::time::SystemTime::now()))
+            .map_err(|e| anyhow::anyhow!("Invalid shallow_since date '{since}': {e:?}"))?;
+        return Ok(Shallow::Since { cutoff });
+    }
+    Ok(Shallow::NoChange)
+}
+
+/// Fetch options for the `fetch` command, with the shallow mode to apply.
+const fn fetch_options(remote: Option<String>, shallow: Shallow) -> FetchOptions {
     FetchOptions {
         format: gitoxide_core::OutputFormat::Human,
         dry_run: false,
@@ -110,22 +139,23 @@ const fn fetch_options(remote: Option<String>, shallow: bool) -> FetchOptions {
 /// carries `submod`'s own output, and callers parse it. The report is written to
 /// stderr when the caller asked for verbose output, or when the fetch failed and
 /// it is the only detail available to explain why.
+///
+/// Progress goes to `progress` when it's given, and is otherwise drawn on stderr.
 pub fn fetch_repo(
     repo: gix::Repository,
     remote: Option<String>,
-    shallow: bool,
+    shallow: Shallow,
     verbose: bool,
+    progress: Option<prodash::tree::Item>,
 ) -> Result<()> {
-    let (inner_result, out, err) =
-        get_progress("fetch", Some(FetchProgressRange), |progress, out, err| {
-            gitoxide_core::repository::fetch(
-                repo,
-                progress,
-                out,
-                err,
-                fetch_options(remote, shallow),
-            )
-        });
+    // gitoxide-core stops when gix's interrupt flag is set, which is the one Ctrl-C sets
+    let fetch = |progress, out: &mut dyn std::io::Write, err: &mut dyn std::io::Write| {
+        gitoxide_core::repository::fetch(repo, progress, out, err, fetch_options(remote, shallow))
+    };
+    let (inner_result, out, err) = match progress {
+        Some(progress) => report_progress(progress, fetch),
+        None => get_progress("fetch", Some(FetchProgressRange), fetch),
+    };
 
     if verbose || inner_result.is_err() {
         let mut sink = stderr();
@@ -0,0 +1,266 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Lifecycle hooks declared in `submod.toml`.
+
+Hooks are shell commands listed under `[defaults.hooks]` or `[<name>.hooks]`, keyed by
+event (`pre_add`, `post_update`, ...). Each command runs through `sh -c` (`cmd /C` on
+Windows) from the superproject root, with the submodule described in `SUBMOD_*`
+environment variables:
+
+- `SUBMOD_HOOK`: the event, e.g. `post_update`
+- `SUBMOD_NAME`, `SUBMOD_PATH`, `SUBMOD_URL`: the submodule's name, path and URL
+- `SUBMOD_ROOT`: the superproject's working directory
+- `SUBMOD_OLD_OID`, `SUBMOD_NEW_OID`: the submodule's `HEAD` before and after the
+  operation, when known
+
+A hook's stdout is sent to stderr, so `submod`'s own stdout stays parseable.
+"]
+
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a hook command may run when no `timeout` is configured, in seconds.
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 300;
+
+/// The points in a submodule operation where hooks run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookEvent {
+    /// Before a submodule is added
+    PreAdd,
+    /// After a submodule is added
+    PostAdd,
+    /// Before a submodule is initialized
+    PreInit,
+    /// After a submodule is initialized
+    PostInit,
+    /// Before a submodule is updated
+    PreUpdate,
+    /// After a submodule is updated
+    PostUpdate,
+    /// Before `sync` processes a submodule
+    PreSync,
+    /// After `sync` has processed a submodule
+    PostSync,
+}
+
+impl HookEvent {
+    /// Every event, in the order they are written to a `hooks` table
+    pub const ALL: [Self; 8] = [
+        Self::PreAdd,
+        Self::PostAdd,
+        Self::PreInit,
+        Self::PostInit,
+        Self::PreUpdate,
+        Self::PostUpdate,
+        Self::PreSync,
+        Self::PostSync,
+    ];
+
+    /// The event's key in a `hooks` table
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PreAdd => "pre_add",
+            Self::PostAdd => "post_add",
+            Self::PreInit => "pre_init",
+            Self::PostInit => "post_init",
+            Self::PreUpdate => "pre_update",
+            Self::PostUpdate => "post_update",
+            Self::PreSync => "pre_sync",
+            Self::PostSync => "post_sync",
+        }
+    }
+
+    /// Whether the event fires before its operation
+    #[must_use]
+    pub const fn is_pre(self) -> bool {
+        matches!(
+            self,
+            Self::PreAdd | Self::PreInit | Self::PreUpdate | Self::PreSync
+        )
+    }
+}
+
+impl std::fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The submodule a hook runs for, exposed to the command as `SUBMOD_*` variables
+#[derive(Debug, Clone)]
+pub struct HookContext<'a> {
+    /// Submodule name
+    pub name: &'a str,
+    /// Submodule path, relative to the superproject root
+    pub path: &'a str,
+    /// Submodule URL, if configured
+    pub url: Option<&'a str>,
+    /// Superproject working directory; hooks run from here
+    pub root: &'a Path,
+    /// Submodule `HEAD` before the operation
+    pub old_oid: Option<&'a str>,
+    /// Submodule `HEAD` after the operation
+    pub new_oid: Option<&'a str>,
+}
+
+/// Run one hook command for `event`, stopping it once `timeout` has passed.
+///
+/// Returns a description of the failure when the command cannot be started, exits
+/// unsuccessfully, or times out.
+pub fn run_hook_command(
+    command: &str,
+    event: HookEvent,
+    ctx: &HookContext<'_>,
+    timeout: Duration,
+) -> Result<(), String> {
+    let mut cmd = shell_command(command);
+    cmd.current_dir(ctx.root)
+        .env("SUBMOD_HOOK", event.as_str())
+        .env("SUBMOD_NAME", ctx.name)
+        .env("SUBMOD_PATH", ctx.path)
+        .env("SUBMOD_ROOT", ctx.root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(std::io::stderr()))
+        .stderr(Stdio::inherit());
+    for (var, value) in [
+        ("SUBMOD_URL", ctx.url),
+        ("SUBMOD_OLD_OID", ctx.old_oid),
+        ("SUBMOD_NEW_OID", ctx.new_oid),
+    ] {
+        match value {
+            Some(value) => cmd.env(var, value),
+            None => cmd.env_remove(var),
+        };
+    }
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("could not start `{command}`: {e}"))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(format!("`{command}` exited with {status}")),
+            Ok(None) if Instant::now() >= deadline => {
+                kill_hook(&mut child);
+                let _ = child.wait();
+                return Err(format!(
+                    "`{command}` timed out after {}s",
+                    timeout.as_secs()
+                ));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("could not wait for `{command}`: {e}")),
+        }
+    }
+}
+
+/// A command that runs `command` through the platform shell
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    use std::os::unix::process::CommandExt;
+    let mut cmd = Command::new("sh");
+    // Its own process group, so a timeout can stop everything the hook started
+    cmd.args(["-c", command]).process_group(0);
+    cmd
+}
+
+/// A command that runs `command` through the platform shell
+#[cfg(not(unix))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+/// Stop a hook that ran past its timeout, along with any processes it started
+fn kill_hook(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let group = format!("-{}", child.id());
+        let killed = Command::new("kill")
+            .args(["-KILL", "--", &group])
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if killed {
+            return;
+        }
+    }
+    let _ = child.kill();
+}
+
+#[cfg(all(test, unix))]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    const fn ctx(root: &Path) -> HookContext<'_> {
+        HookContext {
+            name: "vendor-foo",
+            path: "vendor/foo",
+            url: Some("https://example.com/foo.git"),
+            root,
+            old_oid: Some("1111111111111111111111111111111111111111"),
+            new_oid: None,
+        }
+    }
+
+    #[test]
+    fn test_hook_sees_submod_env() {
+        let dir = tempfile::tempdir().unwrap();
+        run_hook_command(
+            "printf '%s|%s|%s|%s|%s' \"$SUBMOD_HOOK\" \"$SUBMOD_NAME\" \"$SUBMOD_PATH\" \"$SUBMOD_OLD_OID\" \"${SUBMOD_NEW_OID-unset}\" > env.txt",
+            HookEvent::PostUpdate,
+            &ctx(dir.path()),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        let seen = std::fs::read_to_string(dir.path().join("env.txt")).unwrap();
+        assert_eq!(
+            seen,
+            "post_update|vendor-foo|vendor/foo|1111111111111111111111111111111111111111|unset"
+        );
+    }
+
+    #[test]
+    fn test_hook_failure_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = run_hook_command(
+            "exit 3",
+            HookEvent::PreInit,
+            &ctx(dir.path()),
+            Duration::from_secs(10),
+        )
+        .unwrap_err();
+        assert!(err.contains("exit status: 3"), "{err}");
+    }
+
+    #[test]
+    fn test_hook_timeout_stops_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let start = Instant::now();
+        // The shell's own child must be stopped too, or it keeps the shell's pipes open
+        let err = run_hook_command(
+            "sleep 5; true",
+            HookEvent::PreSync,
+            &ctx(dir.path()),
+            Duration::from_millis(200),
+        )
+        .unwrap_err();
+        assert!(err.contains("timed out"), "{err}");
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_event_names() {
+        assert_eq!(HookEvent::PostUpdate.to_string(), "post_update");
+        assert!(HookEvent::PreAdd.is_pre());
+        assert!(!HookEvent::PostSync.is_pre());
+    }
+}
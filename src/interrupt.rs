@@ -0,0 +1,83 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Stopping cleanly on Ctrl-C.
+
+There is one interrupt flag per process. The clones and checkouts gix runs for us watch it
+directly, and fetches gitoxide-core runs watch gix's own flag, which is set along with it.
+git2 transfers check it from their progress callbacks. Once it's set, a
+[`GitManager`](crate::GitManager) removes a clone it was part way through and returns
+[`SubmoduleError::Interrupted`](crate::SubmoduleError::Interrupted) instead of starting
+anything new.
+
+[`install_handler`] makes Ctrl-C (and `SIGTERM`) set the flag. A second Ctrl-C exits at once,
+with [`EXIT_CODE`].
+"]
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use signal_hook::consts::TERM_SIGNALS;
+
+/// The exit code of a run that was interrupted: 128 plus `SIGINT`, as shells report it
+pub const EXIT_CODE: u8 = 130;
+
+/// How often the handler's thread looks for a signal
+const POLL: Duration = Duration::from_millis(50);
+
+/// Set once the run is interrupted. It's an `Arc` so the signal handlers can set it themselves.
+static INTERRUPTED: LazyLock<Arc<AtomicBool>> = LazyLock::new(Arc::default);
+
+/// The flag that says the run has been interrupted, for the APIs that take one
+#[must_use]
+pub fn flag() -> &'static AtomicBool {
+    &INTERRUPTED
+}
+
+/// Whether the run has been interrupted
+#[must_use]
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Interrupts the run, as a Ctrl-C would
+pub fn trigger() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    gix::interrupt::trigger();
+}
+
+/// Clears the flag, so work can start again after an interrupt
+pub fn reset() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    gix::interrupt::reset();
+}
+
+/// Makes the first Ctrl-C (or `SIGTERM`) interrupt the run, and a second one exit right away.
+///
+/// # Errors
+///
+/// If the signal handlers can't be registered.
+pub fn install_handler() -> io::Result<()> {
+    for &signal in TERM_SIGNALS {
+        // Registered first, so the first signal only arms it and the second one exits
+        signal_hook::flag::register_conditional_shutdown(
+            signal,
+            i32::from(EXIT_CODE),
+            Arc::clone(&INTERRUPTED),
+        )?;
+        signal_hook::flag::register(signal, Arc::clone(&INTERRUPTED))?;
+    }
+    // A signal handler can only set a flag; setting gix's, and saying so, happens here
+    std::thread::spawn(|| {
+        while !is_interrupted() {
+            std::thread::sleep(POLL);
+        }
+        trigger();
+        eprintln!("\n⚠️  Interrupted: cleaning up. Press Ctrl-C again to exit now.");
+    });
+    Ok(())
+}
@@ -9,13 +9,31 @@
 //! A Rust CLI tool for managing Git submodules with enhanced features and user-friendly configuration.
 //! This module is exposed for integration testing; it is not intended for public use and may contain unstable APIs.
 
+pub mod absorb;
+/// Completing submodule names, groups, branches, and sparse paths as you type
+pub mod completions;
 pub mod config;
+pub mod diff;
+pub mod doctor;
+pub mod foreach;
+pub mod hooks;
+pub mod interrupt;
+pub mod logging;
+pub mod manifests;
 /// Configuration management for submodules
 pub mod options;
+/// Live progress rows for the submodules `GitManager` clones and fetches
+pub mod progress;
+/// Structured events for what `GitManager` does, and reporters for them
+pub mod report;
 /// Shell completion generation support
 pub mod shells;
+/// `submod tui`, a terminal dashboard of the submodules
+pub mod tui;
 pub mod utilities;
+pub mod vendor;
 
+pub mod git_hooks;
 /// Gitoxide-based submodule management implementation
 pub mod git_manager;
 /// Git operations layer with gix-first, git2-fallback strategy
@@ -25,8 +43,9 @@ pub use config::{
     Config, SubmoduleAddOptions, SubmoduleDefaults, SubmoduleEntry, SubmoduleGitOptions,
     SubmoduleUpdateOptions,
 };
-pub use git_manager::{GitManager, SparseStatus, SubmoduleError, SubmoduleStatus};
-pub use git_ops::{Git2Operations, GixOperations};
+pub use git_manager::{ErrorKind, GitManager, SparseStatus, SubmoduleError, SubmoduleStatus};
+pub use git_ops::{Backend, Git2Operations, GixOperations};
+pub use report::{ConsoleReporter, ErrorReport, Event, JsonLinesReporter, Reporter};
 
 /// Version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,103 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Logs of what `submod` does, for diagnosing it rather than for reading every day.
+
+They're [`tracing`] spans and events. Each [`GitManager`](crate::GitManager) command is a span
+named for it, noting the submodule it works on. Each git operation it runs through
+[`GitOpsManager`](crate::git_ops::GitOpsManager) is a span inside that one, which records the
+backend that served it, with a span for each backend it tried. When gix fails and git2 takes
+over, gix's error is logged. A span logs how long it took when it closes.
+
+How much is logged comes from [`Logging::verbosity`], the number of `-v`s: warnings by
+default, then info (the commands and their timings), debug (each git operation), and trace
+(everything, including gix's own spans). [`ENV_VAR`] overrides it with [`EnvFilter`]
+directives, like `SUBMOD_LOG=submod=debug,gix=trace`. Logs go to stderr, or to
+[`Logging::file`] if it's set.
+"]
+
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// The environment variable that says what to log, as [`EnvFilter`] directives
+pub const ENV_VAR: &str = "SUBMOD_LOG";
+
+/// What `submod` logs, and where
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Logging {
+    /// How many `-v`s were given
+    pub verbosity: u8,
+    /// The file to append logs to, instead of writing them to stderr
+    pub file: Option<PathBuf>,
+}
+
+impl Logging {
+    /// The directives for [`verbosity`](Self::verbosity), for when [`ENV_VAR`] isn't set
+    #[must_use]
+    pub const fn directives(&self) -> &'static str {
+        match self.verbosity {
+            0 => "warn",
+            1 => "warn,submod=info",
+            2 => "warn,submod=debug",
+            _ => "trace",
+        }
+    }
+
+    /// What to log: [`ENV_VAR`]'s directives if it's set, or else
+    /// [`directives`](Self::directives)
+    ///
+    /// # Errors
+    ///
+    /// If [`ENV_VAR`] isn't a valid filter.
+    pub fn filter(&self) -> Result<EnvFilter> {
+        std::env::var(ENV_VAR).map_or_else(
+            |_| Ok(EnvFilter::new(self.directives())),
+            |directives| {
+                EnvFilter::try_new(&directives)
+                    .with_context(|| format!("Invalid {ENV_VAR} filter '{directives}'"))
+            },
+        )
+    }
+
+    /// Whether more than warnings will be written to stderr, where they would run through
+    /// the live progress rows
+    #[must_use]
+    pub fn on_stderr(&self) -> bool {
+        self.file.is_none() && (self.verbosity > 0 || std::env::var_os(ENV_VAR).is_some())
+    }
+
+    /// Starts logging for the rest of the process
+    ///
+    /// # Errors
+    ///
+    /// If [`ENV_VAR`] isn't a valid filter, the log file can't be opened, or logging was
+    /// already started.
+    pub fn init(&self) -> Result<()> {
+        let logs = tracing_subscriber::fmt()
+            .with_env_filter(self.filter()?)
+            .with_span_events(FmtSpan::CLOSE);
+        let started = if let Some(path) = &self.file {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open log file {}", path.display()))?;
+            logs.with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .try_init()
+        } else {
+            logs.with_ansi(io::stderr().is_terminal())
+                .with_writer(io::stderr)
+                .try_init()
+        };
+        started.map_err(|e| anyhow::anyhow!("Failed to start logging: {e}"))
+    }
+}
@@ -29,4 +29,142 @@ Usage:
     - Zsh: `submod completeme zsh > ~/.zsh/completions/_submod` or `submod completeme zsh > ~/.zfunc/_submod`
 
     - Nushell: `submod completeme nu > "$NUSHELL_CONFIG_DIR/scripts/completions/submod.nu" && echo 'use completions/submod.nu' >> "$NU_CONFIG_PATH"`
+
+Dynamic completions:
+    These scripts complete commands and options. For submodule names, groups, branches (`--branch`), and sparse paths (`--sparse-paths`) too, load the script `submod` prints with `COMPLETE` set to your shell instead, e.g. `source <(COMPLETE=bash submod)`. It works for all six shells; see the README for each one's setup.
 "#;
+
+pub const FOREACH: &str = r"
+Runs a command in each checked-out submodule, from the submodule's directory.
+
+Put the command after `--`. A single argument runs through the shell (`sh -c`, or `cmd /C` on Windows), so pipes and `&&` work; several arguments run as a program and its arguments, with no shell.
+
+The command sees the submodule in these environment variables:
+- `SUBMOD_NAME`: the submodule's name in your submod.toml
+- `SUBMOD_PATH`: its path, relative to the superproject root
+- `SUBMOD_URL`: its URL
+- `SUBMOD_ROOT`: the superproject's working directory
+
+Each submodule's output is collected and printed under its name, sorted by name, even with `--jobs`. A summary of failures comes last, and `submod` exits non-zero if the command failed in any submodule.
+
+Examples:
+    submod foreach -- git status --short
+    submod foreach --group vendored --jobs 4 -- 'git fetch && git log --oneline -1 origin/HEAD'
+    submod foreach --dirty-only -- git diff --stat
+";
+
+pub const DOCTOR: &str = r"
+Checks that everywhere a submodule is recorded agrees:
+- `submod.toml`, this tool's config
+- `.gitmodules`, the settings committed with the superproject
+- `.git/config`, the settings git actually uses in this clone
+
+For each submodule, every field that differs is listed with each file's value: `url`, `branch`, `ignore`, `update`, `fetchRecurse`, and `active`. Equivalent spellings (`yes` and `true`) count as the same, and unset `ignore`, `update`, and `fetchRecurse` count as git's defaults. `.git/config` only overrides `.gitmodules`, so its values are compared only when set. `.gitmodules` has no say in `active`.
+
+It also reports submodules missing from a file, `.git/config` sections under a different key than `.gitmodules` uses (git ignores those), directories under `.git/modules` that no submodule uses, and submodules with no gitlink in the superproject's index. `submod` exits non-zero if it finds anything.
+
+With `--fix`, each file that disagrees is rewritten to match `--source` (default `toml`). When the source of truth doesn't set a field, the value git uses wins. Submodules missing from `.gitmodules` or `submod.toml` are copied over from the source of truth. Anything a config edit can't fix is listed with advice.
+
+Examples:
+    submod doctor
+    submod doctor --fix
+    submod doctor --fix --source gitmodules
+";
+
+pub const IMPORT_REPO_MANIFEST: &str = r"
+Imports the projects of an Android `repo` manifest into submod.toml, one submodule per project.
+
+Each project becomes a submodule named after the project, at its `path`, cloned from its remote's `fetch` URL plus its name. `revision` becomes the tracked branch; a tag or commit revision tracks the project's `upstream` branch instead, since submod.toml tracks branches. `clone-depth` makes a shallow clone of that depth, `groups` become submodule groups, and projects in the `notdefault` group are imported inactive. `<include>`, `<extend-project>`, and `<remove-project>` are applied as `repo` would.
+
+Everything else — `<copyfile>`, `<linkfile>`, `sync-c`, a remote's `review` server, and so on — is listed as not imported. Projects already in submod.toml, by name or path, are skipped.
+
+Remotes with a relative `fetch` (like `..`) are resolved against the URL the manifest repository was cloned from, which `repo` knows but the manifest doesn't; pass it with `--manifest-url`.
+
+Examples:
+    submod import repo-manifest .repo/manifests/default.xml
+    submod import repo-manifest default.xml --manifest-url https://android.googlesource.com/platform/manifest --init
+";
+
+pub const IMPORT_VCSTOOL: &str = r"
+Imports the git repositories of a vcstool `.repos` file into submod.toml, one submodule per repository.
+
+Each repository becomes a submodule at its path, named after the path's last component (or the whole path, if two repositories share a last component), cloned from its `url`. A `version` that's a full commit ID is a pin, which submod.toml can't express, so it's reported and the submodule tracks its default branch. Any other `version` becomes the tracked branch: `.repos` files don't say whether a name is a branch or a tag, so tags are imported as branches too.
+
+Repositories of any `type` but `git` are listed as not imported, as are keys vcstool doesn't define. Repositories already in submod.toml, by name or path, are skipped.
+
+Examples:
+    submod import vcstool ros2.repos
+    submod import vcstool deps.repos --init
+";
+
+pub const IMPORT_WEST: &str = r"
+Imports the projects of a Zephyr west manifest (west.yml) into submod.toml, one submodule per project.
+
+Each project becomes a submodule named after the project, at its `path` (or its name), cloned from its `url`, or from its remote's `url-base` plus its `repo-path` (or name). `defaults` supplies the remote and revision; as in west, the revision otherwise defaults to `master`. A full commit ID is a pin, which is reported, and anything else becomes the tracked branch, so tags are imported as branches. `clone-depth` makes a shallow clone of that depth, `groups` become submodule groups, and projects whose groups are all disabled by `group-filter` are imported inactive.
+
+Manifest `import`s, `west-commands`, `self`, project `submodules` and `userdata`, and anything else submodules can't express are listed as not imported. Projects already in submod.toml, by name or path, are skipped.
+
+Examples:
+    submod import west west.yml
+    submod import west zephyr/west.yml --init
+";
+
+pub const EXPORT: &str = r"
+Writes the submodules in submod.toml as a vcstool `.repos` file or a Zephyr west manifest.
+
+Each submodule is locked to a commit: the one it has checked out, or the one the superproject records if it isn't checked out. With `--rev`, the commits recorded at that superproject revision are used instead. Submodules with no commit to lock to keep their tracked branch.
+
+The manifest goes to stdout, or to the `--output` file. Settings the format has no place for are listed on stderr: `.repos` files have no clone depth, groups, or inactive repositories, and west marks inactive submodules with an `inactive` group disabled by `group-filter`, replacing their other groups. Submodules with no URL aren't exported.
+
+Examples:
+    submod export vcstool > deps.repos
+    submod export west --output west.yml
+    submod export west --rev v1.2.0
+";
+
+pub const ADOPT: &str = r"
+Makes a directory already in the superproject a submodule, without downloading it again or changing its files.
+
+A nested clone (a directory with its own `.git`) keeps its history, branches, stashes, and checked-out commit. Its `.git` is moved to `.git/modules/<name>`, as git does for submodules. The URL defaults to its `origin` remote, and the branch to the one it has checked out.
+
+A plain copy of an upstream repository, like a directory made by `submod vendor`, gets a clone fetched from `--url`, or from the URL recorded for it in VENDORED.toml. It's checked out at the upstream commit whose files exactly match the copy; a directory vendored with sparse paths only has to match its recorded commit within them. The copy's files are left as they are, and the record is removed from VENDORED.toml.
+
+Either way, the gitlink and `.gitmodules` entry are staged for you to commit, and the submodule is added to submod.toml.
+
+Examples:
+    submod adopt third_party/zlib
+    submod adopt lib/vendored-json --url https://github.com/nlohmann/json.git --branch develop
+";
+
+pub const VENDOR: &str = r"
+Turns a submodule into plain files in the superproject, for when you want its code without the submodule, like on a release branch.
+
+The files the submodule has checked out are added to the superproject's index as ordinary files. With sparse checkout, only the checked-out paths are kept. The gitlink, the `.gitmodules` entry, the `.git/config` section, the submodule's clone under `.git/modules`, and its submod.toml entry are removed. Local branches and stashes in the submodule go with the clone.
+
+The submodule's URL, commit, tracked branch, and sparse paths are recorded in VENDORED.toml at the superproject root, so `submod adopt` can make it a submodule again at the same commit. The changes are staged, not committed.
+
+The submodule must be checked out, with no uncommitted changes, and without submodules of its own. Its checked-out commit must be on a remote-tracking branch, so the commit recorded in VENDORED.toml can be fetched again; `--force` vendors it anyway. If any step fails, the clone, the index, and the files submod changed are put back.
+
+Examples:
+    submod vendor my-lib
+    submod vendor my-lib && git commit -m 'Vendor my-lib'
+";
+
+pub const TUI: &str = r"
+Opens a dashboard of the configured submodules in the terminal.
+
+Each submodule is listed with its path, tracked branch, whether its working tree is clean, the commit it has checked out, whether its sparse checkout matches its sparse paths, and whether it's active. Commands run in the background, with their output and progress in the log pane below.
+
+Keys:
+    ↑/↓, j/k    Select a submodule
+    i           Initialize it, cloning it if needed
+    u           Update it
+    r           Reset it, discarding local changes (asks first)
+    s           Open your shell in it; exit the shell to come back
+    e           Edit its sparse paths, comma-separated, and apply them
+    a           Toggle whether it's active
+    Ctrl-C      Stop the running command
+    q, Esc      Quit
+
+Hooks run as they do for the matching commands, unless `--no-hooks` is given.
+";
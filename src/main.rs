@@ -19,31 +19,148 @@ and syncing submodules with features like sparse checkout.
 - `update`: Update all submodules.
 - `reset`: Reset specified or all submodules.
 - `sync`: Run check, init, and update in sequence.
+- `foreach`: Run a command in each submodule.
+- `diff`: Summarize submodule changes between superproject revisions.
+- `doctor`: Find and fix drift between submod.toml, .gitmodules, and .git/config.
+- `import`: Import submodules from another tool's manifest.
+- `export`: Write the submodules as a vcstool or west manifest.
+- `vendor`: Replace a submodule with its checked-out files.
+- `adopt`: Make an existing nested clone or vendored copy a submodule.
+- `tui`: Browse and manage the submodules in a terminal dashboard.
 
 Exits with an error if any operation fails.
 "]
+mod absorb;
 mod commands;
+mod completions;
 mod config;
+mod diff;
+mod doctor;
+mod foreach;
+mod git_hooks;
 mod git_manager;
 mod git_ops;
+mod hooks;
+mod interrupt;
+mod logging;
 mod long_abouts;
+mod manifests;
 mod options;
+mod progress;
+mod report;
 mod shells;
+mod tui;
 mod utilities;
+mod vendor;
 
-use crate::commands::{Cli, Commands};
-use crate::git_manager::GitManager;
+use crate::commands::{Cli, Commands, HooksAction, ImportFormat, WorktreeAction};
+use crate::config::SubmoduleAuth;
+use crate::git_manager::{ErrorKind, GitManager, SubmoduleError};
+use crate::hooks::HookEvent;
+use crate::logging::Logging;
+use crate::options::OutputFormat;
 use crate::options::SerializableBranch as Branch;
+use crate::progress::LiveProgress;
+use crate::report::{
+    ConsoleReporter, Details, ErrorReport, Event, EventKind, JsonLinesReporter, Operation, Reporter,
+};
 use crate::utilities::{get_name, get_sparse_paths, set_path};
-use anyhow::Result;
-use clap::Parser;
-use clap_complete::generate;
+use anyhow::{Context, Result};
+use clap::{CommandFactory, Parser};
+use clap_complete::{CompleteEnv, generate};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
 #[cfg_attr(coverage_nightly, coverage(off))]
-fn main() -> Result<()> {
+fn main() -> ExitCode {
+    // `COMPLETE=<shell> submod` prints the shell's completion script, which calls back here
+    CompleteEnv::with_factory(Cli::command)
+        .shells(shells::DYNAMIC_SHELLS)
+        .complete();
     let cli = Cli::parse();
+    let format = cli.format;
+    let Err(error) = run(cli) else {
+        // An interrupt that came too late to stop anything still says the run was cut short
+        return if interrupt::is_interrupted() {
+            ExitCode::from(interrupt::EXIT_CODE)
+        } else {
+            ExitCode::SUCCESS
+        };
+    };
+    let cause = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<SubmoduleError>());
+    let mut report = ErrorReport::new(error_message(&error), cause);
+    // What finished and what didn't has been reported; whatever else failed, this is why
+    if interrupt::is_interrupted() {
+        report.kind = ErrorKind::Interrupted;
+        report.exit_code = ErrorKind::Interrupted.exit_code();
+    }
+    if format == OutputFormat::Json {
+        eprintln!("{}", serde_json::json!({ "error": report }));
+    } else {
+        eprintln!("Error: {}", report.message);
+    }
+    ExitCode::from(report.exit_code)
+}
+
+/// An error as `submod` prints it: what failed, then the [`SubmoduleError`] that caused it,
+/// whose message already covers its own causes
+fn error_message(error: &anyhow::Error) -> String {
+    let mut parts = Vec::new();
+    for cause in error.chain() {
+        parts.push(cause.to_string());
+        if cause.is::<SubmoduleError>() {
+            break;
+        }
+    }
+    parts.join(": ")
+}
+
+#[cfg_attr(coverage_nightly, coverage(off))]
+fn run(cli: Cli) -> Result<()> {
     // config-path is always set because it has a default value, "submod.toml"
     let config_path = cli.config.clone();
-    let verbose = cli.verbose;
+    let verbose = cli.verbose > 0;
+    let hooks = !cli.no_hooks;
+    let quiet = cli.quiet;
+    let format = cli.format;
+    let json = format == OutputFormat::Json;
+    let logging = Logging {
+        verbosity: cli.verbose,
+        file: cli.log_file.clone(),
+    };
+    logging.init()?;
+    // Logs written to the terminal would run through the rows
+    let no_rows = quiet || json || logging.on_stderr();
+    let console = if quiet {
+        ConsoleReporter::quiet()
+    } else {
+        ConsoleReporter::default()
+    };
+    let new_manager = |config_path: PathBuf| {
+        GitManager::with_verbose(config_path, verbose).map(|manager| {
+            if json {
+                manager.with_reporter(JsonLinesReporter::stdout())
+            } else {
+                manager.with_reporter(console)
+            }
+        })
+    };
+
+    // The commands that clone and fetch stop cleanly on Ctrl-C; the rest keep the default
+    if matches!(
+        cli.command,
+        Commands::Add { .. }
+            | Commands::Init
+            | Commands::Update
+            | Commands::Sync { .. }
+            | Commands::Deepen { .. }
+            | Commands::Unshallow { .. }
+            | Commands::NukeItFromOrbit { .. }
+    ) {
+        interrupt::install_handler()?;
+    }
 
     match cli.command {
         Commands::Add {
@@ -57,6 +174,12 @@ fn main() -> Result<()> {
             update,
             fetch,
             shallow,
+            depth,
+            shallow_since,
+            ssh_key,
+            credential_helper,
+            http_extra_header_env,
+            groups,
             no_init,
         } => {
             // Validate sparse paths for null bytes
@@ -73,8 +196,9 @@ fn main() -> Result<()> {
             let set_branch = Branch::set_branch(branch)
                 .map_err(|e| anyhow::anyhow!("Failed to set branch: {e}"))?;
 
-            let mut manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let mut manager = new_manager(config_path)
+                .map(|manager| manager.with_hooks(hooks))
+                .context("Failed to create manager")?;
 
             manager
                 .add_submodule(
@@ -87,21 +211,30 @@ fn main() -> Result<()> {
                     fetch,
                     update,
                     Some(shallow),
+                    depth,
+                    shallow_since,
+                    SubmoduleAuth {
+                        ssh_key,
+                        credential_helper,
+                        http_extra_header_env,
+                    },
+                    None,
+                    groups,
                     no_init,
                     use_git_default_sparse_checkout,
                 )
-                .map_err(|e| anyhow::anyhow!("Failed to add submodule: {e}"))?;
+                .context("Failed to add submodule")?;
         }
         Commands::Check => {
-            let manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let manager = new_manager(config_path).context("Failed to create manager")?;
             manager
                 .check_all_submodules()
-                .map_err(|e| anyhow::anyhow!("Failed to check submodules: {e}"))?;
+                .context("Failed to check submodules")?;
         }
         Commands::Init => {
-            let mut manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let mut manager = new_manager(config_path)
+                .map(|manager| with_live_progress(manager.with_hooks(hooks), console, no_rows))
+                .context("Failed to create manager")?;
 
             // Collect names first to avoid borrow conflict
             let names: Vec<String> = manager
@@ -109,15 +242,17 @@ fn main() -> Result<()> {
                 .get_submodules()
                 .map(|(n, _)| n.clone())
                 .collect();
-            for name in &names {
-                manager
-                    .init_submodule(name)
-                    .map_err(|e| anyhow::anyhow!("Failed to init submodule {name}: {e}"))?;
-            }
+            for_each_submodule(
+                &mut manager,
+                &names,
+                Operation::Init,
+                GitManager::init_submodule,
+            )?;
         }
         Commands::Update => {
-            let mut manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let mut manager = new_manager(config_path)
+                .map(|manager| with_live_progress(manager.with_hooks(hooks), console, no_rows))
+                .context("Failed to create manager")?;
 
             // Collect names first to avoid borrow conflict
             let names: Vec<String> = manager
@@ -126,20 +261,39 @@ fn main() -> Result<()> {
                 .map(|(n, _)| n.clone())
                 .collect();
             if names.is_empty() {
-                println!("No submodules configured");
+                manager.report(&Event::finished(
+                    Operation::Update,
+                    "No submodules configured",
+                ));
             } else {
                 let count = names.len();
-                for name in &names {
-                    manager
-                        .update_submodule(name)
-                        .map_err(|e| anyhow::anyhow!("Failed to update submodule {name}: {e}"))?;
-                }
-                println!("Updated {count} submodule(s)");
+                for_each_submodule(
+                    &mut manager,
+                    &names,
+                    Operation::Update,
+                    GitManager::update_submodule,
+                )?;
+                manager.report(&Event::detailed(
+                    EventKind::Finished,
+                    Operation::Update,
+                    Details::UpdatedAll { count },
+                ));
             }
         }
+        Commands::Deepen { name, by } => {
+            let mut manager = new_manager(config_path).context("Failed to create manager")?;
+            manager
+                .deepen_submodule(&name, by)
+                .with_context(|| format!("Failed to deepen submodule {name}"))?;
+        }
+        Commands::Unshallow { name } => {
+            let mut manager = new_manager(config_path).context("Failed to create manager")?;
+            manager
+                .unshallow_submodule(&name)
+                .with_context(|| format!("Failed to unshallow submodule {name}"))?;
+        }
         Commands::Reset { all, names } => {
-            let manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let manager = new_manager(config_path).context("Failed to create manager")?;
 
             let submodules_to_reset: Vec<String> = if all {
                 manager
@@ -160,53 +314,217 @@ fn main() -> Result<()> {
             for name in submodules_to_reset {
                 manager
                     .reset_submodule(&name)
-                    .map_err(|e| anyhow::anyhow!("Failed to reset submodule {name}: {e}"))?;
+                    .with_context(|| format!("Failed to reset submodule {name}"))?;
             }
         }
-        Commands::Sync => {
-            let mut manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+        Commands::Sync { only_changed } => {
+            let mut manager = new_manager(config_path)
+                .map(|manager| with_live_progress(manager.with_hooks(hooks), console, no_rows))
+                .context("Failed to create manager")?;
 
             let start = std::time::Instant::now();
 
             // Collect names first to avoid borrow conflict
-            let names: Vec<String> = manager
+            let mut names: Vec<String> = manager
                 .config()
                 .get_submodules()
                 .map(|(n, _)| n.clone())
                 .collect();
 
-            if names.is_empty() {
-                println!("No submodules configured");
+            if only_changed {
+                // Stay quiet when there is nothing to do; this runs from git hooks
+                names.retain(|name| manager.submodule_out_of_sync(name));
+                if names.is_empty() {
+                    if verbose {
+                        manager.report(&Event::finished(
+                            Operation::Sync,
+                            "✅ All submodules match the superproject",
+                        ));
+                    }
+                    return Ok(());
+                }
+            } else if names.is_empty() {
+                manager.report(&Event::finished(
+                    Operation::Sync,
+                    "No submodules configured",
+                ));
                 return Ok(());
             }
 
-            let name_list = names.join(", ");
-            if verbose {
-                println!("🔄 Running full sync: check, init, update");
+            let message = if verbose {
+                "🔄 Running full sync: check, init, update".to_string()
             } else {
-                println!("Syncing submodules: {name_list}");
+                format!("Syncing submodules: {}", names.join(", "))
+            };
+            manager.report(&Event::started(Operation::Sync, message));
+
+            // Remember where each submodule started so `post_sync` hooks see the change
+            let old_heads: Vec<Option<String>> = names
+                .iter()
+                .map(|name| manager.submodule_head(name))
+                .collect();
+            for (name, old_head) in names.iter().zip(&old_heads) {
+                manager
+                    .run_hooks(HookEvent::PreSync, name, old_head.as_deref())
+                    .with_context(|| format!("Failed to sync submodule {name}"))?;
             }
 
             // Run check, init, and update in sequence
-            manager
-                .check_all_submodules()
-                .map_err(|e| anyhow::anyhow!("Failed to check submodules: {e}"))?;
-
-            for name in &names {
+            if !only_changed {
                 manager
-                    .init_submodule(name)
-                    .map_err(|e| anyhow::anyhow!("Failed to init submodule {name}: {e}"))?;
+                    .check_all_submodules()
+                    .context("Failed to check submodules")?;
             }
 
-            for name in &names {
+            for_each_submodule(
+                &mut manager,
+                &names,
+                Operation::Init,
+                GitManager::init_submodule,
+            )?;
+            for_each_submodule(
+                &mut manager,
+                &names,
+                Operation::Update,
+                GitManager::update_submodule,
+            )?;
+
+            for (name, old_head) in names.iter().zip(&old_heads) {
                 manager
-                    .update_submodule(name)
-                    .map_err(|e| anyhow::anyhow!("Failed to update submodule {name}: {e}"))?;
+                    .run_hooks(HookEvent::PostSync, name, old_head.as_deref())
+                    .with_context(|| format!("Failed to sync submodule {name}"))?;
             }
 
             let elapsed = start.elapsed();
-            println!("✅ Sync complete ({:.1}s)", elapsed.as_secs_f64());
+            manager.report(&Event::finished(
+                Operation::Sync,
+                format!("✅ Sync complete ({:.1}s)", elapsed.as_secs_f64()),
+            ));
+        }
+        Commands::Diff { from, to } => {
+            let manager = new_manager(config_path).context("Failed to create manager")?;
+            let report = manager
+                .diff(from.as_deref(), to.as_deref(), format.into())
+                .context("Failed to summarize submodule changes")?;
+            print!("{report}");
+        }
+        Commands::Doctor { fix, source } => {
+            let mut manager = new_manager(config_path).context("Failed to create manager")?;
+            manager.doctor(fix, source).context("doctor")?;
+        }
+        Commands::Import { format } => {
+            let mut manager = new_manager(config_path)
+                .map(|manager| manager.with_hooks(hooks))
+                .context("Failed to create manager")?;
+            match format {
+                ImportFormat::RepoManifest {
+                    manifest,
+                    manifest_url,
+                    init,
+                } => manager
+                    .import_repo_manifest(&manifest, manifest_url.as_deref(), init)
+                    .with_context(|| format!("Failed to import {}", manifest.display()))?,
+                ImportFormat::Vcstool { file, init } => manager
+                    .import_vcstool(&file, init)
+                    .with_context(|| format!("Failed to import {}", file.display()))?,
+                ImportFormat::West { file, init } => manager
+                    .import_west(&file, init)
+                    .with_context(|| format!("Failed to import {}", file.display()))?,
+            }
+        }
+        Commands::Export {
+            manifest,
+            output,
+            rev,
+        } => {
+            let manager = new_manager(config_path).context("Failed to create manager")?;
+            let export = manager
+                .export_manifest(manifest, rev.as_deref())
+                .context("Failed to export submodules")?;
+            if let Some(output) = output {
+                std::fs::write(&output, &export.text)
+                    .map_err(|e| anyhow::anyhow!("Failed to write {}: {e}", output.display()))?;
+                manager.report(&Event::finished(
+                    Operation::Export,
+                    format!("✅ Wrote {}", output.display()),
+                ));
+            } else {
+                print!("{}", export.text);
+            }
+            if !export.unsupported.is_empty() {
+                eprintln!("⚠️  Not exported:");
+                for line in export.unsupported.lines() {
+                    eprintln!("   {line}");
+                }
+            }
+        }
+        Commands::Vendor { name, force } => {
+            let mut manager = new_manager(config_path).context("Failed to create manager")?;
+            manager
+                .vendor_submodule(&name, force)
+                .with_context(|| format!("Failed to vendor submodule '{name}'"))?;
+        }
+        Commands::Adopt {
+            path,
+            url,
+            name,
+            branch,
+            ssh_key,
+            credential_helper,
+            http_extra_header_env,
+        } => {
+            let mut manager = new_manager(config_path).context("Failed to create manager")?;
+            let auth = SubmoduleAuth {
+                ssh_key,
+                credential_helper,
+                http_extra_header_env,
+            };
+            manager
+                .adopt_submodule(&path, url, name, branch, auth)
+                .with_context(|| format!("Failed to adopt {path}"))?;
+        }
+        Commands::Absorb { names } => {
+            let mut manager = new_manager(config_path).context("Failed to create manager")?;
+            manager
+                .absorb_submodules(&names)
+                .context("Failed to absorb submodules")?;
+        }
+        Commands::Foreach {
+            groups,
+            jobs,
+            dirty_only,
+            command,
+        } => {
+            let manager = new_manager(config_path).context("Failed to create manager")?;
+            manager
+                .foreach(&command, &groups, jobs as usize, dirty_only)
+                .context("foreach")?;
+        }
+        Commands::Hooks { action } => {
+            let manager = new_manager(config_path).context("Failed to create manager")?;
+            match action {
+                HooksAction::Install => manager
+                    .install_git_hooks()
+                    .context("Failed to install git hooks")?,
+                HooksAction::Uninstall => manager
+                    .uninstall_git_hooks()
+                    .context("Failed to uninstall git hooks")?,
+            }
+        }
+        Commands::Tui => {
+            // Reports and progress go to the dashboard, not the console
+            let manager = GitManager::with_verbose(config_path, verbose)
+                .map(|manager| manager.with_hooks(hooks))
+                .context("Failed to create manager")?;
+            tui::run(manager)?;
+        }
+        Commands::Worktree { action } => {
+            let manager = new_manager(config_path).context("Failed to create manager")?;
+            match action {
+                WorktreeAction::Prune { dry_run } => manager
+                    .prune_worktrees(dry_run)
+                    .context("Failed to prune worktrees")?,
+            }
         }
         // TODO: Implement missing commands
         Commands::Change {
@@ -220,11 +538,16 @@ fn main() -> Result<()> {
             fetch,
             update,
             shallow,
+            depth,
+            shallow_since,
             url,
+            fetch_url,
             active,
+            rename,
         } => {
-            let mut manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let mut manager = new_manager(config_path)
+                .map(|manager| manager.with_hooks(hooks))
+                .context("Failed to create manager")?;
             manager
                 .change_submodule(
                     &name,
@@ -236,11 +559,15 @@ fn main() -> Result<()> {
                     fetch,
                     update,
                     Some(shallow),
+                    depth,
+                    shallow_since,
                     url,
+                    fetch_url,
                     active,
                     use_git_default_sparse_checkout,
+                    rename,
                 )
-                .map_err(|e| anyhow::anyhow!("Failed to change submodule: {e}"))?;
+                .context("Failed to change submodule")?;
         }
         Commands::ChangeGlobal {
             ignore,
@@ -248,32 +575,28 @@ fn main() -> Result<()> {
             update,
             use_git_default_sparse_checkout,
         } => {
-            let mut manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let mut manager = new_manager(config_path).context("Failed to create manager")?;
             manager
                 .update_global_defaults(ignore, fetch, update, use_git_default_sparse_checkout)
-                .map_err(|e| anyhow::anyhow!("Failed to update global settings: {e}"))?;
+                .context("Failed to update global settings")?;
         }
         Commands::List { recursive } => {
-            let manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let manager = new_manager(config_path).context("Failed to create manager")?;
             manager
                 .list_submodules(recursive)
-                .map_err(|e| anyhow::anyhow!("Failed to list submodules: {e}"))?;
+                .context("Failed to list submodules")?;
         }
         Commands::Delete { name } => {
-            let mut manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let mut manager = new_manager(config_path).context("Failed to create manager")?;
             manager
                 .delete_submodule_by_name(&name)
-                .map_err(|e| anyhow::anyhow!("Failed to delete submodule: {e}"))?;
+                .context("Failed to delete submodule")?;
         }
         Commands::Disable { name } => {
-            let mut manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let mut manager = new_manager(config_path).context("Failed to create manager")?;
             manager
                 .disable_submodule(&name)
-                .map_err(|e| anyhow::anyhow!("Failed to disable submodule: {e}"))?;
+                .context("Failed to disable submodule")?;
         }
         Commands::GenerateConfig {
             output,
@@ -281,15 +604,21 @@ fn main() -> Result<()> {
             force,
             template,
         } => {
-            GitManager::generate_config(&output, from_setup.is_some(), template, force)
-                .map_err(|e| anyhow::anyhow!("Failed to generate config: {e}"))?;
+            let reporter: Box<dyn Reporter> = if json {
+                Box::new(JsonLinesReporter::stdout())
+            } else {
+                Box::new(console)
+            };
+            GitManager::generate_config(&output, from_setup.is_some(), template, force, &*reporter)
+                .context("Failed to generate config")?;
         }
         Commands::NukeItFromOrbit { all, names, kill } => {
-            let mut manager = GitManager::with_verbose(config_path, verbose)
-                .map_err(|e| anyhow::anyhow!("Failed to create manager: {e}"))?;
+            let mut manager = new_manager(config_path)
+                .map(|manager| manager.with_hooks(hooks))
+                .context("Failed to create manager")?;
             manager
                 .nuke_submodules(all, names, kill)
-                .map_err(|e| anyhow::anyhow!("Failed to nuke submodules: {e}"))?;
+                .context("Failed to nuke submodules")?;
         }
         Commands::CompleteMe { shell } => {
             let mut cmd = <Cli as clap::CommandFactory>::command();
@@ -300,3 +629,86 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Shows the clones and fetches `manager` does as live rows, one per submodule, when the
+/// output is a terminal and the rows aren't turned off (by `--quiet`, `--format json`, or
+/// logs written to stderr).
+/// Otherwise its events are reported as they were.
+fn with_live_progress(manager: GitManager, console: ConsoleReporter, no_rows: bool) -> GitManager {
+    if no_rows || !progress::is_supported() {
+        return manager;
+    }
+    let tree = progress::tree();
+    manager
+        .with_reporter(LiveProgress::start(Arc::clone(&tree), console))
+        .with_progress(tree)
+}
+
+/// Runs `run`, an init or an update, on each of `names` in turn. A submodule that fails
+/// doesn't stop the others: each failure is reported as it happens, and together they're
+/// returned at the end. If the run is interrupted, the manager reports which submodules it
+/// finished and which it didn't get to.
+fn for_each_submodule(
+    manager: &mut GitManager,
+    names: &[String],
+    operation: Operation,
+    mut run: impl FnMut(&mut GitManager, &str) -> Result<(), SubmoduleError>,
+) -> Result<()> {
+    let step = if operation == Operation::Init {
+        "init"
+    } else {
+        "update"
+    };
+    let mut failures = Vec::new();
+    for (done, name) in names.iter().enumerate() {
+        let result = if interrupt::is_interrupted() {
+            Err(SubmoduleError::Interrupted)
+        } else {
+            run(manager, name)
+        };
+        match result {
+            Ok(()) => {}
+            Err(_) if interrupt::is_interrupted() => {
+                let list = |names: &[String]| {
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                };
+                manager.report(&Event::warning(
+                    operation,
+                    format!(
+                        "⚠️  Interrupted during {step}. Finished: {}. Not finished: {}.",
+                        list(&names[..done]),
+                        list(&names[done..])
+                    ),
+                ));
+                return Err(anyhow::anyhow!("Interrupted during {step}"));
+            }
+            // With one submodule, its error is the command's
+            Err(e) if names.len() == 1 => {
+                return Err(
+                    anyhow::Error::new(e).context(format!("Failed to {step} submodule {name}"))
+                );
+            }
+            Err(e) => {
+                manager.report(
+                    &Event::warning(operation, format!("❌ Failed to {step} {name}: {e}"))
+                        .about(name),
+                );
+                failures.push((name.clone(), e));
+            }
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::Error::new(SubmoduleError::SubmodulesFailed {
+            operation,
+            failures,
+            total: names.len(),
+        })
+        .context(format!("Failed to {step} submodules")))
+    }
+}
@@ -0,0 +1,246 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Importing submodules from other multi-repo tools' manifests, for `submod import`, and
+writing them back out, for `submod export`.
+
+Each format's parser produces [`ManifestProject`]s, which map onto `submod.toml` entries,
+and a list of what it couldn't carry over; writers take the same projects and report
+what the format has no place for. Manifests can say things submodules can't —
+copying files out of a project, pinning to a tag, syncing only some refs — and those are
+reported rather than dropped without a word.
+"]
+
+/// Android `repo` manifests (`default.xml`)
+pub mod repo;
+/// vcstool `.repos` files
+pub mod vcstool;
+/// Zephyr west manifests (`west.yml`)
+pub mod west;
+
+use crate::config::SubmoduleEntry;
+use crate::options::SerializableBranch;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// What a manifest pins a project to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Revision {
+    /// A branch to track
+    Branch(String),
+    /// A tag
+    Tag(String),
+    /// A commit ID
+    Commit(String),
+}
+
+impl Revision {
+    /// Read a manifest revision: `refs/heads/` and `refs/tags/` prefixes are honored, a
+    /// full hexadecimal object ID is a commit, and anything else names a branch
+    #[must_use]
+    pub fn parse(raw: &str) -> Self {
+        let raw = raw.trim();
+        if let Some(tag) = raw.strip_prefix("refs/tags/") {
+            return Self::Tag(tag.to_string());
+        }
+        if matches!(raw.len(), 40 | 64) && raw.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Self::Commit(raw.to_string());
+        }
+        Self::Branch(raw.strip_prefix("refs/heads/").unwrap_or(raw).to_string())
+    }
+
+    /// The branch, tag, or commit ID, as manifests write it
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Branch(value) | Self::Tag(value) | Self::Commit(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for Revision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Branch(branch) => write!(f, "branch {branch}"),
+            Self::Tag(tag) => write!(f, "tag {tag}"),
+            Self::Commit(commit) => write!(f, "commit {commit}"),
+        }
+    }
+}
+
+/// A repository a manifest checks out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestProject {
+    /// The name to give it in `submod.toml`
+    pub name: String,
+    /// Where it's checked out, relative to the manifest's root
+    pub path: String,
+    /// Where it's cloned from
+    pub url: String,
+    /// What it's pinned to, if anything
+    pub revision: Option<Revision>,
+    /// The branch a tag or commit revision comes from, if the manifest says
+    pub upstream: Option<String>,
+    /// How many commits of history to clone, for shallow clones
+    pub depth: Option<u32>,
+    /// Groups for `submod foreach --group`
+    pub groups: Vec<String>,
+    /// Whether it's checked out by default
+    pub active: bool,
+}
+
+impl ManifestProject {
+    /// The branch to track: the revision if it's a branch, else its upstream
+    #[must_use]
+    pub fn branch(&self) -> Option<&str> {
+        match &self.revision {
+            Some(Revision::Branch(branch)) => Some(branch),
+            _ => self.upstream.as_deref(),
+        }
+    }
+
+    /// The equivalent `submod.toml` entry
+    #[must_use]
+    pub fn to_entry(&self) -> SubmoduleEntry {
+        let mut entry = SubmoduleEntry::new(
+            Some(self.url.clone()),
+            Some(self.path.clone()),
+            self.branch()
+                .map(|branch| SerializableBranch::Name(branch.to_string())),
+            None,
+            None,
+            None,
+            Some(self.active),
+            self.depth.map(|_| true),
+            None,
+        );
+        entry.depth = self.depth;
+        entry.groups = (!self.groups.is_empty()).then(|| self.groups.clone());
+        entry
+    }
+}
+
+/// Manifest features that couldn't be carried over, each with where it's used
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Unsupported(BTreeMap<String, Vec<String>>);
+
+impl Unsupported {
+    /// Record that `feature`, used by `place`, couldn't be carried over
+    pub fn add(&mut self, feature: impl Into<String>, place: impl Into<String>) {
+        self.0.entry(feature.into()).or_default().push(place.into());
+    }
+
+    /// Whether everything was carried over
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// One line per feature, naming up to three places it's used
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|(feature, places)| match places.as_slice() {
+                [] => feature.clone(),
+                [place] => format!("{feature} ({place})"),
+                _ if places.len() <= 3 => format!("{feature} ({})", places.join(", ")),
+                _ => format!(
+                    "{feature} ({}, and {} more)",
+                    places[..3].join(", "),
+                    places.len() - 3
+                ),
+            })
+            .collect()
+    }
+}
+
+/// Everything read from a manifest
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestImport {
+    /// The projects to import, in manifest order
+    pub projects: Vec<ManifestProject>,
+    /// Manifest features that weren't imported
+    pub unsupported: Unsupported,
+}
+
+/// A manifest written from `submod.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestExport {
+    /// The manifest
+    pub text: String,
+    /// Settings the format has no place for
+    pub unsupported: Unsupported,
+}
+
+/// A YAML scalar as a string: unquoted versions like `1.0` are read as numbers
+fn scalar(value: &serde_yaml_ng::Value) -> Option<String> {
+    match value {
+        serde_yaml_ng::Value::String(value) => Some(value.clone()),
+        serde_yaml_ng::Value::Number(value) => Some(value.to_string()),
+        serde_yaml_ng::Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revision_parse() {
+        assert_eq!(
+            Revision::parse("refs/heads/main"),
+            Revision::Branch("main".to_string())
+        );
+        assert_eq!(
+            Revision::parse("android-14"),
+            Revision::Branch("android-14".to_string())
+        );
+        assert_eq!(
+            Revision::parse("refs/tags/v1.0"),
+            Revision::Tag("v1.0".to_string())
+        );
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(Revision::parse(sha), Revision::Commit(sha.to_string()));
+    }
+
+    #[test]
+    fn test_project_to_entry_and_unsupported_lines() {
+        let project = ManifestProject {
+            name: "platform/build".to_string(),
+            path: "build".to_string(),
+            url: "https://example.com/platform/build".to_string(),
+            revision: Some(Revision::Tag("v2".to_string())),
+            upstream: Some("main".to_string()),
+            depth: Some(1),
+            groups: vec!["pdk".to_string()],
+            active: false,
+        };
+        let entry = project.to_entry();
+        assert_eq!(entry.path.as_deref(), Some("build"));
+        assert_eq!(
+            entry.branch,
+            Some(SerializableBranch::Name("main".to_string()))
+        );
+        assert_eq!((entry.shallow, entry.depth), (Some(true), Some(1)));
+        assert_eq!(entry.active, Some(false));
+        assert_eq!(entry.groups, Some(vec!["pdk".to_string()]));
+
+        let mut unsupported = Unsupported::default();
+        for place in ["a", "b", "c", "d", "e"] {
+            unsupported.add("`<copyfile>`", place);
+        }
+        unsupported.add("`<notice>`", "manifest");
+        assert_eq!(
+            unsupported.lines(),
+            [
+                "`<copyfile>` (a, b, c, and 2 more)",
+                "`<notice>` (manifest)"
+            ]
+        );
+    }
+}
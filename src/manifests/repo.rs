@@ -0,0 +1,487 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Reading Android `repo` manifests.
+//!
+//! Supported: `<remote>` (`name`, `fetch`, `revision`), `<default>` (`remote`, `revision`,
+//! `upstream`), `<project>` (`name`, `path`, `remote`, `revision`, `upstream`, `groups`,
+//! `clone-depth`), `<extend-project>`, `<remove-project>`, and `<include>`, resolved
+//! relative to the including manifest. A project in the `notdefault` group is imported
+//! inactive. Everything else is reported as unsupported.
+//!
+//! A relative `fetch` (`..`) is resolved against the manifest repository's URL, the way
+//! `repo` does, so it needs `--manifest-url`.
+
+use super::{ManifestImport, ManifestProject, Revision};
+use anyhow::{Context, Result, bail};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// A `<remote>`
+#[derive(Debug, Clone, Default)]
+struct Remote {
+    fetch: String,
+    revision: Option<String>,
+}
+
+/// A `<project>` before remotes and defaults are applied
+#[derive(Debug, Clone, Default)]
+struct RawProject {
+    name: String,
+    path: Option<String>,
+    remote: Option<String>,
+    revision: Option<String>,
+    upstream: Option<String>,
+    groups: Vec<String>,
+    depth: Option<u32>,
+}
+
+impl RawProject {
+    fn path(&self) -> &str {
+        self.path.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// The manifest as read so far, across includes
+#[derive(Debug, Default)]
+struct Parser<'a> {
+    manifest_url: Option<&'a str>,
+    remotes: BTreeMap<String, Remote>,
+    default_remote: Option<String>,
+    default_revision: Option<String>,
+    default_upstream: Option<String>,
+    projects: Vec<RawProject>,
+    visited: BTreeSet<PathBuf>,
+    import: ManifestImport,
+}
+
+/// Read the manifest at `path`, following its includes.
+///
+/// `manifest_url` is the URL the manifest repository was cloned from, for relative
+/// `fetch` URLs; projects on such remotes are skipped without it.
+///
+/// # Errors
+///
+/// Returns an error if the manifest or an include can't be read or isn't a manifest.
+pub fn read(path: &Path, manifest_url: Option<&str>) -> Result<ManifestImport> {
+    let mut parser = Parser {
+        manifest_url,
+        ..Parser::default()
+    };
+    parser.load(path)?;
+    Ok(parser.finish())
+}
+
+impl Parser<'_> {
+    fn load(&mut self, path: &Path) -> Result<()> {
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+        if !self.visited.insert(canonical) {
+            bail!("Manifest {} includes itself", path.display());
+        }
+        let xml = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+        let doc = roxmltree::Document::parse(&xml)
+            .with_context(|| format!("Failed to parse manifest {}", path.display()))?;
+        let root = doc.root_element();
+        if root.tag_name().name() != "manifest" {
+            bail!(
+                "{} is not a repo manifest: its root element is <{}>, not <manifest>",
+                path.display(),
+                root.tag_name().name()
+            );
+        }
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        for node in root.children().filter(roxmltree::Node::is_element) {
+            self.element(node, dir)?;
+        }
+        Ok(())
+    }
+
+    fn element(&mut self, node: roxmltree::Node<'_, '_>, dir: &Path) -> Result<()> {
+        let tag = node.tag_name().name();
+        let name = node.attribute("name").unwrap_or_default().to_string();
+        match tag {
+            "remote" => {
+                self.report_attributes(node, &["name", "fetch", "revision"], &name);
+                self.remotes.insert(
+                    name,
+                    Remote {
+                        fetch: node.attribute("fetch").unwrap_or_default().to_string(),
+                        revision: node.attribute("revision").map(str::to_string),
+                    },
+                );
+            }
+            "default" => {
+                self.report_attributes(node, &["remote", "revision", "upstream"], "<default>");
+                if let Some(remote) = node.attribute("remote") {
+                    self.default_remote = Some(remote.to_string());
+                }
+                if let Some(revision) = node.attribute("revision") {
+                    self.default_revision = Some(revision.to_string());
+                }
+                if let Some(upstream) = node.attribute("upstream") {
+                    self.default_upstream = Some(upstream.to_string());
+                }
+            }
+            "project" => self.project(node)?,
+            "extend-project" => {
+                self.report_attributes(
+                    node,
+                    &["name", "path", "groups", "revision", "remote", "upstream"],
+                    &name,
+                );
+                let path = node.attribute("path");
+                for project in self
+                    .projects
+                    .iter_mut()
+                    .filter(|project| project.name == name)
+                    .filter(|project| path.is_none_or(|path| project.path() == path))
+                {
+                    project
+                        .groups
+                        .extend(split_groups(node.attribute("groups")));
+                    for (value, attribute) in [
+                        (&mut project.revision, "revision"),
+                        (&mut project.remote, "remote"),
+                        (&mut project.upstream, "upstream"),
+                    ] {
+                        if let Some(new) = node.attribute(attribute) {
+                            *value = Some(new.to_string());
+                        }
+                    }
+                }
+            }
+            "remove-project" => {
+                self.report_attributes(node, &["name", "path", "optional"], &name);
+                let (name, path) = (node.attribute("name"), node.attribute("path"));
+                if name.is_some() || path.is_some() {
+                    self.projects.retain(|project| {
+                        !(name.is_none_or(|name| project.name == name)
+                            && path.is_none_or(|path| project.path() == path))
+                    });
+                }
+            }
+            "include" => {
+                self.report_attributes(node, &["name"], &name);
+                self.load(&dir.join(&name))?;
+            }
+            other => self
+                .import
+                .unsupported
+                .add(format!("`<{other}>`"), "manifest"),
+        }
+        Ok(())
+    }
+
+    fn project(&mut self, node: roxmltree::Node<'_, '_>) -> Result<()> {
+        let Some(name) = node.attribute("name") else {
+            bail!("A <project> has no name");
+        };
+        self.report_attributes(
+            node,
+            &[
+                "name",
+                "path",
+                "remote",
+                "revision",
+                "upstream",
+                "groups",
+                "clone-depth",
+            ],
+            name,
+        );
+        for child in node.children().filter(roxmltree::Node::is_element) {
+            let feature = match child.tag_name().name() {
+                "project" => "nested `<project>` (not imported)".to_string(),
+                other => format!("`<{other}>`"),
+            };
+            self.import.unsupported.add(feature, name);
+        }
+        let depth = node
+            .attribute("clone-depth")
+            .map(|depth| {
+                depth
+                    .parse()
+                    .with_context(|| format!("Invalid clone-depth \"{depth}\" on {name}"))
+            })
+            .transpose()?;
+        self.projects.push(RawProject {
+            name: name.to_string(),
+            path: node.attribute("path").map(str::to_string),
+            remote: node.attribute("remote").map(str::to_string),
+            revision: node.attribute("revision").map(str::to_string),
+            upstream: node.attribute("upstream").map(str::to_string),
+            groups: split_groups(node.attribute("groups")),
+            depth,
+        });
+        Ok(())
+    }
+
+    /// Report every attribute of `node` outside `known`
+    fn report_attributes(&mut self, node: roxmltree::Node<'_, '_>, known: &[&str], place: &str) {
+        let tag = node.tag_name().name();
+        for attribute in node.attributes() {
+            if !known.contains(&attribute.name()) {
+                self.import
+                    .unsupported
+                    .add(format!("`{}` on `<{tag}>`", attribute.name()), place);
+            }
+        }
+    }
+
+    /// Apply remotes and defaults, and name each project
+    fn finish(mut self) -> ManifestImport {
+        let mut names = BTreeSet::new();
+        for raw in std::mem::take(&mut self.projects) {
+            let Some(remote_name) = raw.remote.as_ref().or(self.default_remote.as_ref()) else {
+                self.import
+                    .unsupported
+                    .add("project with no remote (not imported)", &raw.name);
+                continue;
+            };
+            let Some(remote) = self.remotes.get(remote_name) else {
+                self.import.unsupported.add(
+                    format!("undefined remote \"{remote_name}\" (not imported)"),
+                    &raw.name,
+                );
+                continue;
+            };
+            let Some(fetch) = resolve_fetch(&remote.fetch, self.manifest_url) else {
+                self.import.unsupported.add(
+                    "relative `fetch` URL without --manifest-url (not imported)",
+                    &raw.name,
+                );
+                continue;
+            };
+
+            let path = raw.path().to_string();
+            // `repo` allows one project at several paths; submod.toml names must be unique
+            let name = if names.insert(raw.name.clone()) {
+                raw.name.clone()
+            } else {
+                self.import
+                    .unsupported
+                    .add("project checked out more than once (named by path)", &path);
+                names.insert(path.clone());
+                path.clone()
+            };
+            let revision = raw
+                .revision
+                .or_else(|| remote.revision.clone())
+                .or_else(|| self.default_revision.clone())
+                .map(|revision| Revision::parse(&revision));
+            let active = !raw.groups.iter().any(|group| group == "notdefault");
+            self.import.projects.push(ManifestProject {
+                url: format!("{fetch}/{}", raw.name),
+                name,
+                path,
+                revision,
+                upstream: raw.upstream.or_else(|| self.default_upstream.clone()),
+                depth: raw.depth,
+                groups: raw
+                    .groups
+                    .into_iter()
+                    .filter(|group| group != "notdefault")
+                    .collect(),
+                active,
+            });
+        }
+        self.import
+    }
+}
+
+/// Split a `groups` attribute, which separates groups with commas or whitespace
+fn split_groups(groups: Option<&str>) -> Vec<String> {
+    groups
+        .unwrap_or_default()
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|group| !group.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// A remote's base URL, with a relative `fetch` resolved against the manifest repository's
+/// URL as `repo` does: like a relative link from that URL
+fn resolve_fetch(fetch: &str, manifest_url: Option<&str>) -> Option<String> {
+    if !fetch.starts_with('.') {
+        return Some(fetch.trim_end_matches('/').to_string());
+    }
+    let base = manifest_url?.trim_end_matches('/');
+    let mut segments: Vec<&str> = base.split('/').collect();
+    // Keep `scheme://host`
+    let keep = if base.contains("://") { 3 } else { 1 };
+    segments.pop();
+    for part in fetch.split('/') {
+        match part {
+            ".." => {
+                if segments.len() > keep {
+                    segments.pop();
+                }
+            }
+            "." | "" => {}
+            part => segments.push(part),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn write_manifest(dir: &Path, file: &str, body: &str) -> PathBuf {
+        let path = dir.join(file);
+        std::fs::write(
+            &path,
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<manifest>\n{body}\n</manifest>\n"
+            ),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_applies_remotes_and_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = write_manifest(
+            dir.path(),
+            "default.xml",
+            r#"
+  <remote name="aosp" fetch=".." review="https://review.example.com/" />
+  <remote name="github" fetch="https://github.com/" revision="refs/heads/stable" />
+  <default remote="aosp" revision="main" sync-j="4" />
+  <project name="platform/build" path="build/make" groups="pdk,tradefed" clone-depth="1">
+    <copyfile src="core/root.mk" dest="Makefile" />
+  </project>
+  <project name="platform/docs" groups="notdefault" />
+  <project name="org/tool" remote="github" path="tools/tool" />
+  <project name="platform/pinned" revision="0123456789abcdef0123456789abcdef01234567" upstream="release" />
+"#,
+        );
+        let import = read(
+            &manifest,
+            Some("https://android.example.com/platform/manifest"),
+        )
+        .unwrap();
+
+        let build = &import.projects[0];
+        assert_eq!(build.name, "platform/build");
+        assert_eq!(build.path, "build/make");
+        assert_eq!(build.url, "https://android.example.com/platform/build");
+        assert_eq!(build.revision, Some(Revision::Branch("main".to_string())));
+        assert_eq!(build.depth, Some(1));
+        assert_eq!(build.groups, ["pdk", "tradefed"]);
+        assert!(build.active);
+
+        let docs = &import.projects[1];
+        assert_eq!(docs.path, "platform/docs");
+        assert!(!docs.active);
+        assert!(docs.groups.is_empty());
+
+        let tool = &import.projects[2];
+        assert_eq!(tool.url, "https://github.com/org/tool");
+        assert_eq!(tool.branch(), Some("stable"));
+
+        let pinned = &import.projects[3];
+        assert!(matches!(pinned.revision, Some(Revision::Commit(_))));
+        assert_eq!(pinned.branch(), Some("release"));
+
+        assert_eq!(
+            import.unsupported.lines(),
+            [
+                "`<copyfile>` (platform/build)",
+                "`review` on `<remote>` (aosp)",
+                "`sync-j` on `<default>` (<default>)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_follows_includes_and_local_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(
+            dir.path(),
+            "base.xml",
+            r#"
+  <remote name="origin" fetch="https://example.com/" />
+  <default remote="origin" revision="main" />
+  <project name="keep" />
+  <project name="drop" />
+  <project name="grow" path="a" />
+  <project name="grow" path="b" />
+"#,
+        );
+        let manifest = write_manifest(
+            dir.path(),
+            "default.xml",
+            r#"
+  <include name="base.xml" />
+  <remove-project name="drop" />
+  <extend-project name="grow" path="b" groups="extra" revision="refs/tags/v1" />
+  <superproject name="super" />
+"#,
+        );
+        let import = read(&manifest, None).unwrap();
+        let names: Vec<(&str, &str)> = import
+            .projects
+            .iter()
+            .map(|project| (project.name.as_str(), project.path.as_str()))
+            .collect();
+        assert_eq!(names, [("keep", "keep"), ("grow", "a"), ("b", "b")]);
+        assert_eq!(import.projects[2].groups, ["extra"]);
+        assert_eq!(
+            import.projects[2].revision,
+            Some(Revision::Tag("v1".to_string()))
+        );
+        assert_eq!(
+            import.unsupported.lines(),
+            [
+                "`<superproject>` (manifest)",
+                "project checked out more than once (named by path) (b)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relative_fetch_needs_manifest_url() {
+        assert_eq!(
+            resolve_fetch("..", Some("https://host/platform/manifest")),
+            Some("https://host".to_string())
+        );
+        assert_eq!(
+            resolve_fetch("../mirror/", Some("https://host/org/manifest.git/")),
+            Some("https://host/mirror".to_string())
+        );
+        assert_eq!(
+            resolve_fetch("git@host:org/", None),
+            Some("git@host:org".to_string())
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = write_manifest(
+            dir.path(),
+            "default.xml",
+            r#"<remote name="r" fetch=".." /><default remote="r" /><project name="p" />"#,
+        );
+        let import = read(&manifest, None).unwrap();
+        assert!(import.projects.is_empty());
+        assert_eq!(
+            import.unsupported.lines(),
+            ["relative `fetch` URL without --manifest-url (not imported) (p)"]
+        );
+
+        let not_a_manifest = dir.path().join("other.xml");
+        std::fs::write(&not_a_manifest, "<project />").unwrap();
+        assert!(
+            read(&not_a_manifest, None)
+                .unwrap_err()
+                .to_string()
+                .contains("not a repo manifest")
+        );
+    }
+}
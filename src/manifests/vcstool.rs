@@ -0,0 +1,225 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Reading and writing vcstool `.repos` files.
+//!
+//! A `.repos` file maps each checkout path to a `type`, `url`, and optional `version`: a
+//! branch, tag, or commit. Only `git` repositories can become submodules. A submodule
+//! is named after the last component of its path, or the whole path if that's taken.
+//!
+//! `.repos` files have no room for clone depth, groups, or inactive repositories, so
+//! exports report those.
+
+use super::{ManifestExport, ManifestImport, ManifestProject, Revision, scalar};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ReposFile {
+    #[serde(default)]
+    repositories: BTreeMap<String, Repository>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Repository {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<serde_yaml_ng::Value>,
+    #[serde(flatten, skip_serializing)]
+    other: BTreeMap<String, serde_yaml_ng::Value>,
+}
+
+/// Read the `.repos` file at `path`.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read or isn't a `.repos` file.
+pub fn read(path: &Path) -> Result<ManifestImport> {
+    let yaml = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&yaml).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Parse the contents of a `.repos` file.
+///
+/// # Errors
+///
+/// Returns an error if `yaml` isn't a `.repos` file.
+pub fn parse(yaml: &str) -> Result<ManifestImport> {
+    let file: ReposFile = serde_yaml_ng::from_str(yaml)?;
+    let mut import = ManifestImport::default();
+    let mut names = BTreeSet::new();
+    for (path, repository) in file.repositories {
+        for key in repository.other.keys() {
+            import.unsupported.add(format!("`{key}`"), &path);
+        }
+        if repository.kind != "git" {
+            import.unsupported.add(
+                format!("`{}` repository (not imported)", repository.kind),
+                &path,
+            );
+            continue;
+        }
+        let Some(url) = repository.url else {
+            import
+                .unsupported
+                .add("repository with no `url` (not imported)", &path);
+            continue;
+        };
+        let short = path.rsplit('/').next().unwrap_or(&path).to_string();
+        let name = if names.insert(short.clone()) {
+            short
+        } else {
+            names.insert(path.clone());
+            path.clone()
+        };
+        import.projects.push(ManifestProject {
+            name,
+            path,
+            url,
+            revision: repository
+                .version
+                .as_ref()
+                .and_then(scalar)
+                .map(|version| Revision::parse(&version)),
+            upstream: None,
+            depth: None,
+            groups: Vec::new(),
+            active: true,
+        });
+    }
+    Ok(import)
+}
+
+/// Write `projects` as a `.repos` file.
+///
+/// # Errors
+///
+/// Returns an error if the YAML can't be written.
+pub fn write(projects: &[ManifestProject]) -> Result<ManifestExport> {
+    let mut export = ManifestExport::default();
+    let mut file = ReposFile {
+        repositories: BTreeMap::new(),
+    };
+    for project in projects {
+        if project.depth.is_some() {
+            export.unsupported.add("`depth`", &project.name);
+        }
+        if !project.groups.is_empty() {
+            export.unsupported.add("`groups`", &project.name);
+        }
+        if !project.active {
+            export
+                .unsupported
+                .add("`active = false` (exported anyway)", &project.name);
+        }
+        file.repositories.insert(
+            project.path.clone(),
+            Repository {
+                kind: "git".to_string(),
+                url: Some(project.url.clone()),
+                version: project
+                    .revision
+                    .as_ref()
+                    .map(|revision| serde_yaml_ng::Value::from(revision.as_str())),
+                other: BTreeMap::new(),
+            },
+        );
+    }
+    export.text = serde_yaml_ng::to_string(&file)?;
+    Ok(export)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_maps_paths_urls_and_versions() {
+        let import = parse(
+            r"
+repositories:
+  src/ros2/rclcpp:
+    type: git
+    url: https://github.com/ros2/rclcpp.git
+    version: rolling
+  vendor/rclcpp:
+    type: git
+    url: https://example.com/rclcpp.git
+    version: 0123456789abcdef0123456789abcdef01234567
+  old/tool:
+    type: svn
+    url: https://svn.example.com/tool
+  src/extra:
+    type: git
+    url: https://example.com/extra.git
+    version: 2.0
+    recursive: true
+",
+        )
+        .unwrap();
+
+        let names: Vec<(&str, &str)> = import
+            .projects
+            .iter()
+            .map(|project| (project.name.as_str(), project.path.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("extra", "src/extra"),
+                ("rclcpp", "src/ros2/rclcpp"),
+                ("vendor/rclcpp", "vendor/rclcpp")
+            ]
+        );
+        assert_eq!(import.projects[0].branch(), Some("2.0"));
+        assert_eq!(import.projects[1].branch(), Some("rolling"));
+        assert!(matches!(
+            import.projects[2].revision,
+            Some(Revision::Commit(_))
+        ));
+        assert_eq!(
+            import.unsupported.lines(),
+            [
+                "`recursive` (src/extra)",
+                "`svn` repository (not imported) (old/tool)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_round_trips_and_reports_what_it_drops() {
+        let projects = [ManifestProject {
+            name: "rclcpp".to_string(),
+            path: "src/rclcpp".to_string(),
+            url: "https://github.com/ros2/rclcpp.git".to_string(),
+            revision: Some(Revision::Commit(
+                "0123456789abcdef0123456789abcdef01234567".to_string(),
+            )),
+            upstream: None,
+            depth: Some(1),
+            groups: vec!["ros".to_string()],
+            active: true,
+        }];
+        let export = write(&projects).unwrap();
+        assert_eq!(
+            export.text,
+            "repositories:\n  src/rclcpp:\n    type: git\n    url: https://github.com/ros2/rclcpp.git\n    version: 0123456789abcdef0123456789abcdef01234567\n"
+        );
+        assert_eq!(
+            export.unsupported.lines(),
+            ["`depth` (rclcpp)", "`groups` (rclcpp)"]
+        );
+
+        let import = parse(&export.text).unwrap();
+        assert_eq!(import.projects[0].url, projects[0].url);
+        assert_eq!(import.projects[0].revision, projects[0].revision);
+    }
+}
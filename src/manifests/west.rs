@@ -0,0 +1,338 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Reading and writing Zephyr west manifests (`west.yml`).
+//!
+//! Supported: `defaults` (`remote`, `revision`), `remotes` (`name`, `url-base`),
+//! `group-filter`, and each project's `name`, `url` or `remote` and `repo-path`,
+//! `revision`, `path`, `clone-depth`, and `groups`. As in west, a project's revision
+//! defaults to `master`, and a project whose groups are all disabled by `group-filter`
+//! is inactive. Manifest imports, `west-commands`, `submodules`, `userdata`, and `self`
+//! are reported as unsupported.
+//!
+//! Exports put inactive submodules in an `inactive` group that `group-filter` disables,
+//! in place of any other groups, which would make them active.
+
+use super::{ManifestExport, ManifestImport, ManifestProject, Revision, Unsupported, scalar};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// The revision west uses when neither the project nor `defaults` has one
+const WEST_DEFAULT_REVISION: &str = "master";
+
+/// The group exports put inactive submodules in
+const INACTIVE_GROUP: &str = "inactive";
+
+#[derive(Debug, Deserialize, Serialize)]
+struct WestFile {
+    manifest: WestManifest,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct WestManifest {
+    #[serde(default, skip_serializing)]
+    defaults: WestDefaults,
+    #[serde(default, skip_serializing)]
+    remotes: Vec<WestRemote>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    group_filter: Vec<String>,
+    #[serde(default)]
+    projects: Vec<WestProject>,
+    #[serde(flatten, skip_serializing)]
+    other: BTreeMap<String, serde_yaml_ng::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct WestDefaults {
+    remote: Option<String>,
+    revision: Option<serde_yaml_ng::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct WestRemote {
+    name: String,
+    url_base: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct WestProject {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(default, skip_serializing)]
+    remote: Option<String>,
+    #[serde(default, skip_serializing)]
+    repo_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    revision: Option<serde_yaml_ng::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    clone_depth: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    groups: Vec<String>,
+    #[serde(flatten, skip_serializing)]
+    other: BTreeMap<String, serde_yaml_ng::Value>,
+}
+
+/// Read the west manifest at `path`.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read or isn't a west manifest.
+pub fn read(path: &Path) -> Result<ManifestImport> {
+    let yaml = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&yaml).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Parse the contents of a west manifest.
+///
+/// # Errors
+///
+/// Returns an error if `yaml` isn't a west manifest.
+pub fn parse(yaml: &str) -> Result<ManifestImport> {
+    let WestFile { manifest } = serde_yaml_ng::from_str(yaml)?;
+    let mut import = ManifestImport::default();
+    // The schema `version` only says which west can read the manifest
+    for key in manifest.other.keys().filter(|key| *key != "version") {
+        import.unsupported.add(format!("`{key}`"), "manifest");
+    }
+    let disabled: BTreeSet<&str> = manifest
+        .group_filter
+        .iter()
+        .filter_map(|filter| filter.strip_prefix('-'))
+        .collect();
+    let remotes: BTreeMap<&str, &str> = manifest
+        .remotes
+        .iter()
+        .map(|remote| (remote.name.as_str(), remote.url_base.trim_end_matches('/')))
+        .collect();
+    let default_revision = manifest
+        .defaults
+        .revision
+        .as_ref()
+        .and_then(scalar)
+        .unwrap_or_else(|| WEST_DEFAULT_REVISION.to_string());
+
+    for project in manifest.projects {
+        for key in project.other.keys() {
+            import.unsupported.add(format!("`{key}`"), &project.name);
+        }
+        let url = if let Some(url) = project.url {
+            url
+        } else {
+            let Some(remote) = project
+                .remote
+                .as_ref()
+                .or(manifest.defaults.remote.as_ref())
+            else {
+                import.unsupported.add(
+                    "project with no `url` or remote (not imported)",
+                    &project.name,
+                );
+                continue;
+            };
+            let Some(base) = remotes.get(remote.as_str()) else {
+                import.unsupported.add(
+                    format!("undefined remote \"{remote}\" (not imported)"),
+                    &project.name,
+                );
+                continue;
+            };
+            format!(
+                "{base}/{}",
+                project.repo_path.as_deref().unwrap_or(&project.name)
+            )
+        };
+        let revision = project
+            .revision
+            .as_ref()
+            .and_then(scalar)
+            .unwrap_or_else(|| default_revision.clone());
+        let active = project.groups.is_empty()
+            || project
+                .groups
+                .iter()
+                .any(|group| !disabled.contains(group.as_str()));
+        import.projects.push(ManifestProject {
+            path: project.path.unwrap_or_else(|| project.name.clone()),
+            name: project.name,
+            url,
+            revision: Some(Revision::parse(&revision)),
+            upstream: None,
+            depth: project.clone_depth,
+            groups: project.groups,
+            active,
+        });
+    }
+    Ok(import)
+}
+
+/// Write `projects` as a west manifest.
+///
+/// # Errors
+///
+/// Returns an error if the YAML can't be written.
+pub fn write(projects: &[ManifestProject]) -> Result<ManifestExport> {
+    let mut manifest = WestManifest::default();
+    let mut unsupported = Unsupported::default();
+    for project in projects {
+        // A project with any enabled group is active, so an inactive one can only be in
+        // the disabled group
+        let groups = if project.active {
+            project.groups.clone()
+        } else {
+            if !project.groups.is_empty() {
+                unsupported.add(
+                    format!("`groups` of inactive submodules (replaced by `{INACTIVE_GROUP}`)"),
+                    &project.name,
+                );
+            }
+            vec![INACTIVE_GROUP.to_string()]
+        };
+        manifest.projects.push(WestProject {
+            name: project.name.clone(),
+            url: Some(project.url.clone()),
+            revision: project
+                .revision
+                .as_ref()
+                .map(|revision| serde_yaml_ng::Value::from(revision.as_str())),
+            path: (project.path != project.name).then(|| project.path.clone()),
+            clone_depth: project.depth,
+            groups,
+            ..WestProject::default()
+        });
+    }
+    if projects.iter().any(|project| !project.active) {
+        manifest.group_filter = vec![format!("-{INACTIVE_GROUP}")];
+    }
+    Ok(ManifestExport {
+        text: serde_yaml_ng::to_string(&WestFile { manifest })?,
+        unsupported,
+    })
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolves_remotes_defaults_and_group_filter() {
+        let import = parse(
+            r"
+manifest:
+  version: 0.13
+  defaults:
+    remote: upstream
+    revision: main
+  remotes:
+    - name: upstream
+      url-base: https://github.com/zephyrproject-rtos/
+  group-filter: [-optional]
+  projects:
+    - name: zephyr
+      revision: v3.5.0
+      import: true
+    - name: hal_nordic
+      repo-path: hal-nordic
+      path: modules/hal/nordic
+      clone-depth: 1
+      groups: [hal]
+    - name: tools
+      url: https://example.com/tools.git
+      groups: [optional]
+  self:
+    path: app
+",
+        )
+        .unwrap();
+
+        let zephyr = &import.projects[0];
+        assert_eq!(zephyr.url, "https://github.com/zephyrproject-rtos/zephyr");
+        assert_eq!(zephyr.path, "zephyr");
+        assert_eq!(zephyr.branch(), Some("v3.5.0"));
+
+        let nordic = &import.projects[1];
+        assert_eq!(
+            nordic.url,
+            "https://github.com/zephyrproject-rtos/hal-nordic"
+        );
+        assert_eq!(nordic.path, "modules/hal/nordic");
+        assert_eq!(nordic.branch(), Some("main"));
+        assert_eq!(nordic.depth, Some(1));
+        assert!(nordic.active);
+
+        let tools = &import.projects[2];
+        assert_eq!(tools.url, "https://example.com/tools.git");
+        assert!(!tools.active);
+
+        assert_eq!(
+            import.unsupported.lines(),
+            ["`import` (zephyr)", "`self` (manifest)"]
+        );
+    }
+
+    #[test]
+    fn test_write_round_trips_inactive_projects() {
+        let projects = [
+            ManifestProject {
+                name: "zephyr".to_string(),
+                path: "zephyr".to_string(),
+                url: "https://github.com/zephyrproject-rtos/zephyr".to_string(),
+                revision: Some(Revision::Commit(
+                    "0123456789abcdef0123456789abcdef01234567".to_string(),
+                )),
+                upstream: None,
+                depth: None,
+                groups: Vec::new(),
+                active: true,
+            },
+            ManifestProject {
+                name: "docs".to_string(),
+                path: "doc/site".to_string(),
+                url: "https://example.com/docs.git".to_string(),
+                revision: Some(Revision::Branch("main".to_string())),
+                upstream: None,
+                depth: Some(1),
+                groups: vec!["web".to_string()],
+                active: false,
+            },
+        ];
+        let export = write(&projects).unwrap();
+        assert_eq!(
+            export.unsupported.lines(),
+            ["`groups` of inactive submodules (replaced by `inactive`) (docs)"]
+        );
+        assert_eq!(
+            export.text,
+            "manifest:
+  group-filter:
+  - -inactive
+  projects:
+  - name: zephyr
+    url: https://github.com/zephyrproject-rtos/zephyr
+    revision: 0123456789abcdef0123456789abcdef01234567
+  - name: docs
+    url: https://example.com/docs.git
+    revision: main
+    path: doc/site
+    clone-depth: 1
+    groups:
+    - inactive
+"
+        );
+
+        let import = parse(&export.text).unwrap();
+        assert_eq!(import.projects[0].revision, projects[0].revision);
+        assert_eq!(import.projects[1].path, "doc/site");
+        assert!(!import.projects[1].active);
+    }
+}
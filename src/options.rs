@@ -578,10 +578,7 @@ impl GixGit2Convert for SerializableBranch {
 }
 
 /// Serializable enum for [`Update`] config
-#[derive(
-    Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize, ValueEnum,
-)]
-#[serde(rename_all = "kebab-case")]
+#[derive(Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub enum SerializableUpdate {
     /// Update the submodule by checking out the commit specified in the superproject.
     #[default]
@@ -592,12 +589,114 @@ pub enum SerializableUpdate {
     Merge,
     /// Do not update the submodule at all. This is useful if you want to manage submodules manually or if you don't want to update them at all.
     None,
+    /// Run a shell command in the submodule, with the superproject's recorded commit as its last argument. Written `!command` in git's config files.
+    ///
+    /// Git only honors commands from `.git/config`, never from `.gitmodules`, and submod likewise never runs one from submod.toml.
+    Command(String),
     /// Used as a sentinel value internally; do not use in a submod.toml or submod CLI command.
-    #[serde(skip)]
-    #[value(skip)]
     Unspecified,
 }
 
+impl Serialize for SerializableUpdate {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.is_unspecified() {
+            return Err(serde::ser::Error::custom(
+                "an unspecified update strategy can't be serialized",
+            ));
+        }
+        serializer.serialize_str(&self.to_gitmodules())
+    }
+}
+
+impl<'de> Deserialize<'de> for SerializableUpdate {
+    /// Deserialize from `"checkout"`, `"rebase"`, `"merge"`, `"none"`, or `"!command"`.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_gitmodules(&s)
+            .ok()
+            .filter(|update| !update.is_unspecified())
+            .ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "invalid update value: {s:?}; expected \"checkout\", \"rebase\", \"merge\", \"none\", or \"!command\""
+                ))
+            })
+    }
+}
+
+impl SerializableUpdate {
+    /// The strategies with fixed names, as the CLI lists them
+    const NAMED: [(&'static str, &'static str); 4] = [
+        ("checkout", "Check out the recorded commit, detaching HEAD"),
+        (
+            "rebase",
+            "Rebase the submodule's current branch onto the recorded commit",
+        ),
+        (
+            "merge",
+            "Merge the recorded commit into the submodule's current branch",
+        ),
+        ("none", "Leave the submodule alone"),
+    ];
+
+    /// Whether git would refuse this value in `.gitmodules`. Custom commands only count in `.git/config`.
+    #[must_use]
+    pub const fn is_command(&self) -> bool {
+        matches!(self, Self::Command(_))
+    }
+}
+
+/// Parses `--update` values: one of the named strategies, or `!command`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UpdateValueParser;
+
+impl clap::builder::TypedValueParser for UpdateValueParser {
+    type Value = SerializableUpdate;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        if let Some(command) = value.to_str().and_then(|v| v.strip_prefix('!'))
+            && !command.trim().is_empty()
+        {
+            return Ok(SerializableUpdate::Command(command.to_string()));
+        }
+        let named = clap::builder::PossibleValuesParser::new(Self::possible_values_list())
+            .parse_ref(cmd, arg, value)?;
+        SerializableUpdate::from_gitmodules(&named)
+            .map_err(|()| clap::Error::new(clap::error::ErrorKind::InvalidValue).with_cmd(cmd))
+    }
+
+    fn possible_values(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+        Some(Box::new(Self::possible_values_list().into_iter()))
+    }
+}
+
+impl UpdateValueParser {
+    fn possible_values_list() -> Vec<clap::builder::PossibleValue> {
+        SerializableUpdate::NAMED
+            .iter()
+            .map(|(name, help)| clap::builder::PossibleValue::new(*name).help(*help))
+            .chain(std::iter::once(
+                clap::builder::PossibleValue::new("!command")
+                    .help("Run `command <commit>` in the submodule"),
+            ))
+            .collect()
+    }
+}
+
+impl clap::builder::ValueParserFactory for SerializableUpdate {
+    type Parser = UpdateValueParser;
+
+    fn value_parser() -> Self::Parser {
+        UpdateValueParser
+    }
+}
+
 impl OptionsChecks for SerializableUpdate {
     /// Check if the enum is unspecified
     fn is_unspecified(&self) -> bool {
@@ -623,6 +722,7 @@ impl GitmodulesConvert for SerializableUpdate {
             Self::Rebase => "rebase".to_string(),
             Self::Merge => "merge".to_string(),
             Self::None => "none".to_string(),
+            Self::Command(command) => format!("!{command}"),
             Self::Unspecified => String::new(), // Unspecified is treated as an empty string
         }
     }
@@ -635,7 +735,12 @@ impl GitmodulesConvert for SerializableUpdate {
             "merge" => Ok(Self::Merge),
             "none" => Ok(Self::None),    // Default is None
             "" => Ok(Self::Unspecified), // Empty string is treated as unspecified
-            _ => Err(()),                // Handle unsupported options
+            _ => match options.strip_prefix('!') {
+                Some(command) if !command.trim().is_empty() => {
+                    Ok(Self::Command(command.to_string()))
+                }
+                _ => Err(()), // Handle unsupported options
+            },
         }
     }
 
@@ -669,7 +774,7 @@ impl TryFrom<SerializableUpdate> for Git2SubmoduleUpdate {
             SerializableUpdate::Merge => Self::Merge,
             SerializableUpdate::None => Self::None,
             SerializableUpdate::Unspecified => Self::Default,
-            _ => return Err(()), // Handle unsupported variants
+            SerializableUpdate::Command(_) => return Err(()), // git2 has no command variant
         })
     }
 }
@@ -682,8 +787,7 @@ impl TryFrom<Update> for SerializableUpdate {
             Update::Rebase => Self::Rebase,
             Update::Merge => Self::Merge,
             Update::None => Self::None,
-            // Commands are not directly serializable, and can't be defined in .gitmodules, so we use unspecified. `gix` has it as a variant because it can be provided by library call.
-            Update::Command(_cmd) => Self::Unspecified,
+            Update::Command(cmd) => Self::Command(cmd.to_string()),
             _ => return Err(()),
         })
     }
@@ -696,7 +800,7 @@ impl TryFrom<SerializableUpdate> for Update {
             SerializableUpdate::Rebase => Self::Rebase,
             SerializableUpdate::Merge => Self::Merge,
             SerializableUpdate::None => Self::None,
-
+            SerializableUpdate::Command(cmd) => Self::Command(cmd.into()),
             _ => return Err(()), // Handle unsupported variants
         })
     }
@@ -722,6 +826,108 @@ impl GixGit2Convert for SerializableUpdate {
     }
 }
 
+/// What to do when a lifecycle hook exits unsuccessfully or runs past its timeout.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    Ord,
+    PartialOrd,
+    Eq,
+    PartialEq,
+    Hash,
+    Serialize,
+    Deserialize,
+    ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookFailurePolicy {
+    /// Print a warning and carry on with the operation.
+    #[default]
+    Warn,
+    /// Stop the operation and fail the command.
+    Abort,
+}
+
+impl std::fmt::Display for HookFailurePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warn => write!(f, "warn"),
+            Self::Abort => write!(f, "abort"),
+        }
+    }
+}
+
+/// How `submod diff` prints the submodule changes it finds.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, ValueEnum)]
+pub enum DiffFormat {
+    /// Plain text for the terminal.
+    #[default]
+    Text,
+    /// Markdown, e.g. for a pull request description.
+    Markdown,
+    /// JSON, for scripts.
+    Json,
+}
+
+/// How `submod` writes its output, chosen with the global `--format`.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, ValueEnum)]
+pub enum OutputFormat {
+    /// Plain text for the terminal.
+    #[default]
+    Text,
+    /// Markdown, e.g. for a pull request description. Only `diff` has a Markdown form; the
+    /// other commands print text.
+    Markdown,
+    /// JSON, for scripts: one object per line for each event, and an error object on stderr
+    /// if the command fails.
+    Json,
+}
+
+impl From<OutputFormat> for DiffFormat {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Text => Self::Text,
+            OutputFormat::Markdown => Self::Markdown,
+            OutputFormat::Json => Self::Json,
+        }
+    }
+}
+
+/// A manifest format `submod export` can write.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, ValueEnum)]
+pub enum ManifestFormat {
+    /// A vcstool `.repos` file.
+    Vcstool,
+    /// A Zephyr west manifest (`west.yml`).
+    West,
+}
+
+/// One of the places submodule settings are recorded, e.g. the source of truth for
+/// `submod doctor --fix`.
+#[derive(Debug, Default, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, ValueEnum)]
+pub enum ConfigSource {
+    /// `submod.toml`.
+    #[default]
+    #[value(name = "toml")]
+    SubmodToml,
+    /// `.gitmodules`, the settings committed with the superproject.
+    Gitmodules,
+    /// `.git/config`, the settings git actually uses in this clone.
+    GitConfig,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SubmodToml => write!(f, "submod.toml"),
+            Self::Gitmodules => write!(f, ".gitmodules"),
+            Self::GitConfig => write!(f, ".git/config"),
+        }
+    }
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
@@ -1295,6 +1501,10 @@ mod tests {
         assert_eq!(SerializableUpdate::Merge.to_gitmodules(), "merge");
         assert_eq!(SerializableUpdate::None.to_gitmodules(), "none");
         assert_eq!(SerializableUpdate::Unspecified.to_gitmodules(), "");
+        assert_eq!(
+            SerializableUpdate::Command("make sync".to_string()).to_gitmodules(),
+            "!make sync"
+        );
     }
 
     #[test]
@@ -1319,6 +1529,11 @@ mod tests {
             SerializableUpdate::from_gitmodules("").unwrap(),
             SerializableUpdate::Unspecified
         );
+        assert_eq!(
+            SerializableUpdate::from_gitmodules("!make sync").unwrap(),
+            SerializableUpdate::Command("make sync".to_string())
+        );
+        assert!(SerializableUpdate::from_gitmodules("!").is_err());
         assert!(SerializableUpdate::from_gitmodules("invalid").is_err());
     }
 
@@ -1398,11 +1613,49 @@ mod tests {
     }
 
     #[test]
-    fn test_update_gix_command_variant_maps_to_unspecified() {
-        // Update::Command is not serializable; it should map to Unspecified
-        let cmd = Update::Command("!echo hello".into());
-        let ours: SerializableUpdate = cmd.try_into().unwrap();
-        assert_eq!(ours, SerializableUpdate::Unspecified);
+    fn test_update_gix_command_variant_roundtrip() {
+        // gix keeps the command without its leading `!`, as we do
+        let cmd = Update::Command("echo hello".into());
+        let ours: SerializableUpdate = cmd.clone().try_into().unwrap();
+        assert_eq!(ours, SerializableUpdate::Command("echo hello".to_string()));
+        let gix: Update = ours.try_into().unwrap();
+        assert_eq!(gix, cmd);
+    }
+
+    #[test]
+    fn test_update_serde_and_cli_accept_commands() {
+        #[derive(Deserialize, Serialize)]
+        struct Wrapper {
+            update: SerializableUpdate,
+        }
+        let parsed: Wrapper = toml::from_str("update = \"!make sync\"").unwrap();
+        assert_eq!(
+            parsed.update,
+            SerializableUpdate::Command("make sync".to_string())
+        );
+        assert_eq!(
+            toml::to_string(&parsed).unwrap().trim(),
+            "update = \"!make sync\""
+        );
+        assert!(toml::from_str::<Wrapper>("update = \"\"").is_err());
+        assert!(toml::from_str::<Wrapper>("update = \"sometimes\"").is_err());
+
+        let cmd = clap::Command::new("submod").arg(
+            clap::Arg::new("update")
+                .long("update")
+                .value_parser(clap::value_parser!(SerializableUpdate)),
+        );
+        let parse = |value: &str| {
+            cmd.clone()
+                .try_get_matches_from(["submod", "--update", value])
+                .map(|matches| matches.get_one::<SerializableUpdate>("update").cloned())
+        };
+        assert_eq!(parse("rebase").unwrap(), Some(SerializableUpdate::Rebase));
+        assert_eq!(
+            parse("!git checkout").unwrap(),
+            Some(SerializableUpdate::Command("git checkout".to_string()))
+        );
+        assert!(parse("sometimes").is_err());
     }
 
     #[test]
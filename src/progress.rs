@@ -0,0 +1,395 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Live progress for the submodules `submod` clones and fetches.
+
+A [`GitManager`](crate::GitManager) given a progress tree with
+[`GitManager::with_progress`](crate::GitManager::with_progress) adds a [`Row`] to it for each
+submodule it clones or fetches, and the git backends report their phases under that row:
+gix its own nested progress, git2 the objects and bytes it has received.
+
+[`LiveProgress`] draws the tree on stderr as one line per submodule, with the phase it's in,
+the objects and bytes received so far, and the transfer rate. It's also a [`Reporter`], so
+the manager's events print above the rows rather than through them. The rows are redrawn in
+place, which only works on a terminal; [`is_supported`] says whether this is one. [`Rows`]
+reads the same rows as text, for a screen that draws them itself.
+"]
+
+use gix::features::progress::{bytes, count_with_decimals};
+use prodash::progress::{Id, Key, Task};
+use prodash::tree::{Item, Root};
+use std::fmt::Write as _;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::report::{ConsoleReporter, Event, Reporter};
+
+/// The progress id gix gives the bytes of a pack as it reads them. git2's received bytes are
+/// reported under it too, so both backends show up the same way.
+pub const RECEIVED_BYTES: Id = *b"BWRB";
+
+/// The progress id gix gives the objects of a pack as it indexes them, and that git2's received
+/// objects are reported under
+pub const RECEIVED_OBJECTS: Id = *b"IWIO";
+
+/// How often the rows are redrawn
+const FRAME: Duration = Duration::from_millis(100);
+
+/// How long a submodule has to take before it gets a row, so quick local clones don't flicker
+const INITIAL_DELAY: Duration = Duration::from_millis(500);
+
+/// The width to fit rows to when the terminal's can't be found out
+const DEFAULT_WIDTH: usize = 80;
+
+/// A new, empty progress tree
+#[must_use]
+pub fn tree() -> Arc<Root> {
+    crate::git_ops::simple_gix::progress_tree(false)
+}
+
+/// Whether rows can be drawn live: stdout and stderr both go to a terminal that can move
+/// its cursor. Otherwise events should just be printed, one line after another.
+#[must_use]
+pub fn is_supported() -> bool {
+    io::stdout().is_terminal()
+        && io::stderr().is_terminal()
+        && std::env::var_os("TERM").is_none_or(|term| term != "dumb")
+}
+
+/// A submodule's row in a progress tree. The backends add their phases to it; it leaves the
+/// tree when the last clone of it is dropped.
+#[derive(Clone)]
+pub struct Row(Arc<Mutex<Item>>);
+
+impl Row {
+    /// Adds a row for the submodule `name` to `root`
+    #[must_use]
+    pub fn new(root: &Root, name: &str) -> Self {
+        Self(Arc::new(Mutex::new(root.add_child(name))))
+    }
+
+    fn item(&self) -> MutexGuard<'_, Item> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// A phase of work on the submodule, such as a clone or a checkout, for gix to report to
+    #[must_use]
+    pub fn phase(&self, name: &str) -> Item {
+        self.item().add_child(name)
+    }
+
+    /// A counter of the objects received, for a backend that reports transfers itself
+    #[must_use]
+    pub fn received_objects(&self) -> Item {
+        let item = self
+            .item()
+            .add_child_with_id("receiving objects", RECEIVED_OBJECTS);
+        item.init(None, count_with_decimals("objects", 0));
+        item
+    }
+
+    /// A counter of the bytes received, for a backend that reports transfers itself
+    #[must_use]
+    pub fn received_bytes(&self) -> Item {
+        let item = self.item().add_child_with_id("received", RECEIVED_BYTES);
+        item.init(None, bytes());
+        item
+    }
+}
+
+impl std::fmt::Debug for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Row").field(&self.item().name()).finish()
+    }
+}
+
+impl PartialEq for Row {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// What one row shows
+#[derive(Debug, Default)]
+struct Line {
+    submodule: String,
+    phase: String,
+    objects: Option<String>,
+    bytes: Option<String>,
+}
+
+/// The lines to draw for a snapshot of a progress tree, one for each submodule in it.
+///
+/// The phase is the newest task under the submodule. Objects and bytes come from the tasks
+/// with the [`RECEIVED_OBJECTS`] and [`RECEIVED_BYTES`] ids, the bytes with their rate as
+/// `throughput` works it out.
+fn lines(snapshot: &[(Key, Task)], throughput: &mut prodash::Throughput) -> Vec<Line> {
+    let mut lines: Vec<Line> = Vec::new();
+    for (key, task) in snapshot {
+        if key.level() == 1 {
+            lines.push(Line {
+                submodule: task.name.clone(),
+                ..Line::default()
+            });
+            continue;
+        }
+        let Some(line) = lines.last_mut() else {
+            continue;
+        };
+        if task.id != RECEIVED_BYTES {
+            line.phase.clone_from(&task.name);
+        }
+        let Some(value) = &task.progress else {
+            continue;
+        };
+        let step = value.step.load(Ordering::Relaxed);
+        let shown = |throughput| {
+            value.unit.as_ref().map_or_else(
+                || step.to_string(),
+                |unit| unit.display(step, value.done_at, throughput).to_string(),
+            )
+        };
+        match task.id {
+            RECEIVED_BYTES => line.bytes = Some(shown(throughput.update_and_get(key, Some(value)))),
+            RECEIVED_OBJECTS => line.objects = Some(shown(None)),
+            _ => {}
+        }
+    }
+    throughput.reconcile(snapshot);
+    lines
+}
+
+/// Lays out `lines` as rows at most `width` characters wide, with the columns lined up
+fn rows(lines: &[Line], width: usize) -> Vec<String> {
+    let name_width = lines
+        .iter()
+        .map(|line| line.submodule.chars().count())
+        .max()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| {
+            let phase = if line.phase.is_empty() {
+                "starting"
+            } else {
+                &line.phase
+            };
+            let counts: Vec<&str> = [&line.objects, &line.bytes]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect();
+            let row = format!(
+                "  {:<name_width$}  {phase:<18} {}",
+                line.submodule,
+                counts.join(", ")
+            );
+            row.trim_end().chars().take(width).collect()
+        })
+        .collect()
+}
+
+/// Reads a progress tree as rows of text, one per submodule, for drawing it somewhere other
+/// than [`LiveProgress`] does, like in `submod tui`
+pub struct Rows {
+    root: Arc<Root>,
+    snapshot: Vec<(Key, Task)>,
+    throughput: prodash::Throughput,
+}
+
+impl Rows {
+    /// Reads the rows of `root`
+    #[must_use]
+    pub fn new(root: Arc<Root>) -> Self {
+        Self {
+            root,
+            snapshot: Vec::new(),
+            throughput: prodash::Throughput::default(),
+        }
+    }
+
+    /// The lines for the tree as it is now. Byte rates are worked out from how much came in
+    /// since the last call.
+    fn lines(&mut self) -> Vec<Line> {
+        self.root.sorted_snapshot(&mut self.snapshot);
+        self.throughput.update_elapsed();
+        lines(&self.snapshot, &mut self.throughput)
+    }
+
+    /// The rows for the tree as it is now, each at most `width` characters wide. Byte rates
+    /// are worked out from how much came in since the last call.
+    pub fn current(&mut self, width: usize) -> Vec<String> {
+        rows(&self.lines(), width)
+    }
+}
+
+impl std::fmt::Debug for Rows {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rows").finish_non_exhaustive()
+    }
+}
+
+/// The rows on screen, and what's needed to redraw them
+struct Screen {
+    rows: Rows,
+    /// How many rows are on screen, above the cursor
+    drawn: usize,
+    /// When the tree last went from empty to having rows
+    busy_since: Option<Instant>,
+}
+
+impl Screen {
+    /// Erases the rows on screen
+    fn clear(&mut self, out: &mut impl Write) {
+        if self.drawn > 0 {
+            let _ = write!(out, "\r\x1b[{}A\x1b[J", self.drawn);
+            let _ = out.flush();
+            self.drawn = 0;
+        }
+    }
+
+    /// Replaces the rows on screen with the tree as it is now
+    fn redraw(&mut self, out: &mut impl Write) {
+        let lines = self.rows.lines();
+        let started = if lines.is_empty() {
+            self.busy_since = None;
+            None
+        } else {
+            Some(*self.busy_since.get_or_insert_with(Instant::now))
+        };
+        let rows = if started.is_some_and(|started| started.elapsed() >= INITIAL_DELAY) {
+            let width = crossterm::terminal::size()
+                .ok()
+                .filter(|&(columns, _)| columns > 0)
+                .map_or(DEFAULT_WIDTH, |(columns, _)| usize::from(columns) - 1);
+            rows(&lines, width)
+        } else {
+            Vec::new()
+        };
+        // One write, so the rows never show half-drawn
+        let mut frame = String::new();
+        if self.drawn > 0 {
+            let _ = write!(frame, "\r\x1b[{}A\x1b[J", self.drawn);
+        }
+        for row in &rows {
+            frame.push_str(row);
+            frame.push('\n');
+        }
+        if !frame.is_empty() {
+            let _ = out.write_all(frame.as_bytes());
+            let _ = out.flush();
+        }
+        self.drawn = rows.len();
+    }
+}
+
+/// Draws a progress tree on stderr as one live row per submodule, and prints events above
+/// the rows with a [`ConsoleReporter`]. The rows are erased when it's dropped.
+pub struct LiveProgress {
+    screen: Arc<Mutex<Screen>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    console: ConsoleReporter,
+}
+
+impl LiveProgress {
+    /// Starts drawing the rows of `root`, redrawing them several times a second, and
+    /// printing events with `console`
+    #[must_use]
+    pub fn start(root: Arc<Root>, console: ConsoleReporter) -> Self {
+        let screen = Arc::new(Mutex::new(Screen {
+            rows: Rows::new(root),
+            drawn: 0,
+            busy_since: None,
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (screen, stop) = (Arc::clone(&screen), Arc::clone(&stop));
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    lock(&screen).redraw(&mut io::stderr());
+                    std::thread::sleep(FRAME);
+                }
+            })
+        };
+        Self {
+            screen,
+            stop,
+            thread: Some(thread),
+            console,
+        }
+    }
+}
+
+fn lock(screen: &Mutex<Screen>) -> MutexGuard<'_, Screen> {
+    screen.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl std::fmt::Debug for LiveProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LiveProgress").finish_non_exhaustive()
+    }
+}
+
+impl Reporter for LiveProgress {
+    fn report(&self, event: &Event) {
+        let mut screen = lock(&self.screen);
+        screen.clear(&mut io::stderr());
+        self.console.report(event);
+        let _ = io::stdout().flush();
+        screen.redraw(&mut io::stderr());
+    }
+}
+
+impl Drop for LiveProgress {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        lock(&self.screen).clear(&mut io::stderr());
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn snapshot(root: &Root) -> Vec<(Key, Task)> {
+        let mut snapshot = Vec::new();
+        root.sorted_snapshot(&mut snapshot);
+        snapshot
+    }
+
+    #[test]
+    fn test_one_row_per_submodule_with_phase_and_counts() {
+        let root = tree();
+        let lib = Row::new(&root, "lib");
+        let objects = lib.received_objects();
+        let bytes = lib.received_bytes();
+        objects.set(42);
+        bytes.set(2048);
+        let vendor = Row::new(&root, "vendor/tools");
+        let _clone = vendor.phase("clone");
+
+        let lines = lines(&snapshot(&root), &mut prodash::Throughput::default());
+        let rows = rows(&lines, 200);
+        assert_eq!(rows.len(), 2, "{rows:?}");
+        assert_eq!(
+            rows[0],
+            "  lib           receiving objects  42 objects, 2.0kB"
+        );
+        assert_eq!(rows[1], "  vendor/tools  clone");
+
+        // A finished submodule's row leaves with it
+        drop((objects, bytes, lib));
+        let lines = super::lines(&snapshot(&root), &mut prodash::Throughput::default());
+        assert_eq!(super::rows(&lines, 8), ["  vendor"]);
+    }
+}
@@ -0,0 +1,752 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r#"
+Structured events for what [`GitManager`](crate::GitManager) does, and the reporters that show them.
+
+`GitManager` doesn't print. Everything it has to say, from "✅ Updated lib" to a warning that a
+hook failed, is an [`Event`] sent to its [`Reporter`]:
+
+- [`ConsoleReporter`], the default, prints events the way the `submod` CLI does: warnings to
+  stderr, everything else to stdout. [`ConsoleReporter::quiet`] prints only the warnings.
+- [`JsonLinesReporter`] writes each event as one line of JSON, for other tools to read.
+- [`ErrorReport`] is what `submod --format json` writes when a command fails.
+- Anything else that implements [`Reporter`], including a closure, can be plugged in with
+  [`GitManager::with_reporter`](crate::GitManager::with_reporter):
+
+```rust,no_run
+use std::path::PathBuf;
+use submod::report::{Event, EventKind};
+
+let manager = submod::GitManager::new(PathBuf::from("submod.toml"))?
+    .with_reporter(|event: &Event| {
+        if event.kind == EventKind::Warning {
+            eprintln!("{:?}: {}", event.submodule, event.message);
+        }
+    });
+# Ok::<(), submod::SubmoduleError>(())
+```
+
+Each event says which [`Operation`] it belongs to, what stage of it ([`EventKind`]), and which
+submodule it's about, if any. Events that report a result carry it as [`Details`]: the path,
+commits, URL, or counts, which [`ConsoleReporter::render`] turns into the line `submod` prints.
+The rest carry that line as their `message`.
+"#]
+
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::git_manager::{ErrorKind, SubmoduleError};
+use crate::git_ops::Backend;
+
+/// What stage of an operation an [`Event`] marks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// An operation began
+    Started,
+    /// A step of an operation, or a line of what it found
+    Progress,
+    /// Something went wrong that didn't stop the operation
+    Warning,
+    /// An operation ended
+    Finished,
+}
+
+/// The operation an [`Event`] belongs to, named after the `submod` command for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Operation {
+    /// Adding a submodule
+    Add,
+    /// Cloning and checking out a submodule
+    Init,
+    /// Updating a submodule to the commit it should be at
+    Update,
+    /// Discarding a submodule's local changes
+    Reset,
+    /// Checking submodules' status
+    Check,
+    /// Listing the configured submodules
+    List,
+    /// Running a command in each submodule
+    Foreach,
+    /// Comparing submod.toml, `.gitmodules`, and `.git/config`
+    Doctor,
+    /// Importing submodules from a manifest
+    Import,
+    /// Installing or removing the managed git hooks
+    GitHooks,
+    /// Running a lifecycle hook from submod.toml
+    Hook,
+    /// Configuring a submodule's sparse checkout
+    SparseCheckout,
+    /// Moving embedded `.git` directories into `.git/modules`
+    Absorb,
+    /// Cleaning up after deleted worktrees
+    Worktree,
+    /// Fetching more of a shallow submodule's history
+    Deepen,
+    /// Fetching all of a shallow submodule's history
+    Unshallow,
+    /// Disabling a submodule
+    Disable,
+    /// Deleting a submodule
+    Delete,
+    /// Replacing a submodule with its files
+    Vendor,
+    /// Making a directory in the tree a submodule
+    Adopt,
+    /// Changing a submodule's settings
+    Change,
+    /// Renaming a submodule
+    Rename,
+    /// Moving a submodule to a new path
+    Move,
+    /// Changing a submodule's URL
+    SetUrl,
+    /// Deleting and reinitializing submodules
+    Nuke,
+    /// Writing the submodules as another tool's manifest
+    Export,
+    /// Checking, initializing, and updating submodules in one go
+    Sync,
+    /// Writing a new submod.toml
+    GenerateConfig,
+}
+
+/// What a command `submod` ran in a submodule wrote, for `foreach`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommandOutput {
+    /// Everything the command wrote to stdout
+    pub stdout: String,
+    /// Everything the command wrote to stderr
+    pub stderr: String,
+}
+
+/// A submodule a command failed in, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Failure {
+    /// The submodule's name
+    pub submodule: String,
+    /// Why it failed
+    pub reason: String,
+}
+
+/// What an [`Event`] reports, as data. Commits are full object ids.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Details {
+    /// A submodule was added at `path`, cloned from `url`
+    Added {
+        /// Where it was added, relative to the superproject
+        path: String,
+        /// Its remote
+        url: String,
+    },
+    /// A submodule was initialized
+    Initialized {
+        /// Where it's checked out
+        path: String,
+    },
+    /// A submodule was updated, from the `old` commit to the `new` one
+    Updated {
+        /// Where it's checked out
+        path: String,
+        /// What was checked out before, if it was checked out
+        old: Option<String>,
+        /// What's checked out now
+        new: Option<String>,
+    },
+    /// `update` finished with every submodule
+    UpdatedAll {
+        /// How many submodules were updated
+        count: usize,
+    },
+    /// A submodule following the superproject's branch was switched to `branch`
+    SwitchedBranch {
+        /// The branch it's on now
+        branch: String,
+        /// The branch it asked for, when its remote has no such branch
+        wanted: Option<String>,
+    },
+    /// A submodule's local changes were discarded
+    Reset {
+        /// Where it's checked out
+        path: String,
+    },
+    /// A `foreach` command finished in one submodule; its output is the event's `output`
+    Ran {
+        /// Where the command ran
+        path: String,
+        /// Whether it succeeded
+        succeeded: bool,
+    },
+    /// A `foreach` command finished in every submodule
+    RanAll {
+        /// How many submodules it ran in
+        total: usize,
+        /// The ones it failed in
+        failures: Vec<Failure>,
+    },
+    /// Submodules were imported from a manifest
+    Imported {
+        /// How many
+        count: usize,
+        /// The submod.toml they were written to
+        config: PathBuf,
+    },
+    /// A submodule's embedded `.git` directory was moved to `git_dir`
+    Absorbed {
+        /// Where it's checked out
+        path: String,
+        /// Where its git directory is now, relative to the superproject
+        git_dir: PathBuf,
+    },
+    /// A shallow submodule was deepened
+    Deepened {
+        /// How many commits were asked for
+        by: u32,
+        /// Its depth now, or `None` if it has its full history
+        depth: Option<u32>,
+    },
+    /// A shallow submodule was given its full history
+    Unshallowed,
+    /// A submodule was disabled
+    Disabled,
+    /// A submodule was deleted
+    Deleted,
+    /// A submodule was replaced with `files` files at `path`, from `commit` of `url`
+    Vendored {
+        /// Where the files are
+        path: String,
+        /// Where they came from
+        url: String,
+        /// The commit they came from
+        commit: String,
+        /// How many files there are
+        files: usize,
+        /// The file the origin is recorded in
+        record: String,
+    },
+    /// A directory in the tree became a submodule at `commit` of `url`
+    Adopted {
+        /// The directory
+        path: String,
+        /// The submodule's remote
+        url: String,
+        /// The commit it's at
+        commit: String,
+        /// Whether it had a clone of its own, which was absorbed, rather than files that
+        /// were matched to an upstream commit
+        absorbed_clone: bool,
+    },
+    /// A submodule was renamed; the event is about its new name
+    Renamed {
+        /// Its name before
+        old_name: String,
+        /// Whether a `.gitmodules` change is staged
+        staged: bool,
+    },
+    /// A submodule was moved
+    Moved {
+        /// Where it was
+        old_path: String,
+        /// Where it is now
+        new_path: String,
+        /// Whether the new gitlink and `.gitmodules` path are staged
+        staged: bool,
+    },
+    /// A submodule's URL was changed
+    UrlChanged {
+        /// Where it's checked out
+        path: String,
+        /// Its URL before, if it had one
+        old_url: Option<String>,
+        /// Its URL now
+        new_url: String,
+        /// The recorded commit the new URL was checked for, if one is recorded
+        recorded: Option<String>,
+    },
+}
+
+/// Something [`GitManager`](crate::GitManager) did or found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Event {
+    /// What stage of the operation this is
+    #[serde(rename = "event")]
+    pub kind: EventKind,
+    /// The operation it belongs to
+    pub operation: Operation,
+    /// The name of the submodule it's about, if it's about one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submodule: Option<String>,
+    /// What happened, as `submod` prints it, for events without [`Details`]. It may span
+    /// lines, or be empty for an event `submod` doesn't print, such as the start of an update.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub message: String,
+    /// What happened, as data, for events that report a result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Details>,
+    /// The output of a command run in the submodule, if there was one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<CommandOutput>,
+}
+
+impl Event {
+    fn new(kind: EventKind, operation: Operation, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            operation,
+            submodule: None,
+            message: message.into(),
+            details: None,
+            output: None,
+        }
+    }
+
+    /// An event of `kind` that reports `details`
+    #[must_use]
+    pub fn detailed(kind: EventKind, operation: Operation, details: Details) -> Self {
+        Self {
+            details: Some(details),
+            ..Self::new(kind, operation, String::new())
+        }
+    }
+
+    /// An [`EventKind::Started`] event
+    #[must_use]
+    pub fn started(operation: Operation, message: impl Into<String>) -> Self {
+        Self::new(EventKind::Started, operation, message)
+    }
+
+    /// An [`EventKind::Progress`] event
+    #[must_use]
+    pub fn progress(operation: Operation, message: impl Into<String>) -> Self {
+        Self::new(EventKind::Progress, operation, message)
+    }
+
+    /// An [`EventKind::Warning`] event
+    #[must_use]
+    pub fn warning(operation: Operation, message: impl Into<String>) -> Self {
+        Self::new(EventKind::Warning, operation, message)
+    }
+
+    /// An [`EventKind::Finished`] event
+    #[must_use]
+    pub fn finished(operation: Operation, message: impl Into<String>) -> Self {
+        Self::new(EventKind::Finished, operation, message)
+    }
+
+    /// The same event, about the submodule `name`
+    #[must_use]
+    pub fn about(mut self, name: impl Into<String>) -> Self {
+        self.submodule = Some(name.into());
+        self
+    }
+
+    /// The same event, carrying what a command wrote
+    #[must_use]
+    pub fn with_output(mut self, stdout: &[u8], stderr: &[u8]) -> Self {
+        self.output = Some(CommandOutput {
+            stdout: String::from_utf8_lossy(stdout).into_owned(),
+            stderr: String::from_utf8_lossy(stderr).into_owned(),
+        });
+        self
+    }
+}
+
+/// Receives the [`Event`]s a [`GitManager`](crate::GitManager) reports.
+///
+/// Events arrive in order, on the thread the `GitManager` runs on.
+pub trait Reporter: Send + Sync {
+    /// Handles one event
+    fn report(&self, event: &Event);
+}
+
+impl<F: Fn(&Event) + Send + Sync> Reporter for F {
+    fn report(&self, event: &Event) {
+        self(event);
+    }
+}
+
+/// Prints events for a person at a terminal: warnings to stderr, everything else to stdout.
+/// Events with nothing to say, like the start of an update, aren't printed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ConsoleReporter {
+    quiet: bool,
+}
+
+impl ConsoleReporter {
+    /// A reporter for `--quiet`: it prints warnings, and what commands run by `foreach`
+    /// write, but nothing else
+    #[must_use]
+    pub const fn quiet() -> Self {
+        Self { quiet: true }
+    }
+
+    /// What `submod` prints for `event`: its [`Details`] written out, or else its message
+    #[must_use]
+    pub fn render(event: &Event) -> Cow<'_, str> {
+        let Some(details) = &event.details else {
+            return Cow::Borrowed(&event.message);
+        };
+        let name = event.submodule.as_deref().unwrap_or_default();
+        let short = |commit: &str| commit[..commit.len().min(12)].to_string();
+        let submodules = |count: usize| {
+            if count == 1 {
+                "1 submodule".to_string()
+            } else {
+                format!("{count} submodules")
+            }
+        };
+        Cow::Owned(match details {
+            Details::Added { .. } => format!("Added submodule {name}"),
+            Details::Initialized { .. } => format!("✅ Initialized {name}"),
+            Details::Updated { .. } => format!("✅ Updated {name}"),
+            Details::UpdatedAll { count } => format!("Updated {count} submodule(s)"),
+            Details::SwitchedBranch {
+                branch,
+                wanted: Some(wanted),
+            } => format!("🔀 {name}: switched to {branch} (its remote has no '{wanted}' branch)"),
+            Details::SwitchedBranch { branch, .. } => format!("🔀 {name}: switched to {branch}"),
+            Details::Reset { .. } => format!("✅ {name} reset complete"),
+            Details::Ran { path, succeeded } => {
+                let mark = if *succeeded { "📦" } else { "❌" };
+                format!("{mark} {name} ({path})")
+            }
+            Details::RanAll { total, failures } if failures.is_empty() => {
+                format!("\n✅ Command succeeded in {}", submodules(*total))
+            }
+            Details::RanAll { total, failures } => {
+                let mut text = format!(
+                    "\n❌ Command failed in {} of {}:",
+                    failures.len(),
+                    submodules(*total)
+                );
+                for failure in failures {
+                    let _ = write!(text, "\n   {}: {}", failure.submodule, failure.reason);
+                }
+                text
+            }
+            Details::Imported { count, config } => {
+                format!(
+                    "✅ Imported {} into {}",
+                    submodules(*count),
+                    config.display()
+                )
+            }
+            Details::Absorbed { path, git_dir } => format!(
+                "📦 Absorbed {path}: its git directory is now {}",
+                git_dir.display()
+            ),
+            Details::Deepened {
+                by,
+                depth: Some(depth),
+            } => {
+                format!("✅ Deepened {name} by {by} commit(s) (depth {depth})")
+            }
+            Details::Deepened { depth: None, .. } => {
+                format!("✅ Deepened {name}; it now has its full history")
+            }
+            Details::Unshallowed => format!("✅ Unshallowed {name}"),
+            Details::Disabled => format!("Disabled submodule '{name}'."),
+            Details::Deleted => format!("Deleted submodule '{name}'."),
+            Details::Vendored {
+                path,
+                url,
+                commit,
+                files,
+                record,
+            } => {
+                let files = if *files == 1 {
+                    "1 file".to_string()
+                } else {
+                    format!("{files} files")
+                };
+                format!(
+                    "📦 Vendored {name}: {files} at {path}, from {url} at {}\n   Recorded in {record}; the changes are staged for you to commit.",
+                    short(commit)
+                )
+            }
+            Details::Adopted {
+                path,
+                url,
+                commit,
+                absorbed_clone,
+            } => {
+                let how = if *absorbed_clone {
+                    "absorbed its clone"
+                } else {
+                    "matched the files to an upstream commit"
+                };
+                format!(
+                    "🏠 Adopted {name} at {path} ({how}): {url} at {}\n   The gitlink and .gitmodules entry are staged for you to commit.",
+                    short(commit)
+                )
+            }
+            Details::Renamed { old_name, staged } => {
+                let mut text = format!("🏷️  Renamed {old_name} to {name}");
+                if *staged {
+                    text.push_str("\n   The .gitmodules change is staged for you to commit.");
+                }
+                text
+            }
+            Details::Moved {
+                old_path,
+                new_path,
+                staged,
+            } => {
+                let mut text = format!("🚚 Moved {name}: {old_path} → {new_path}");
+                if *staged {
+                    text.push_str(
+                        "\n   The new gitlink and .gitmodules path are staged for you to commit.",
+                    );
+                }
+                text
+            }
+            Details::UrlChanged {
+                path,
+                old_url,
+                new_url,
+                recorded,
+            } => {
+                let checked = recorded.as_deref().map_or_else(
+                    || {
+                        format!(
+                            "No commit is recorded for {path} yet, so there was nothing to check"
+                        )
+                    },
+                    |commit| format!("{new_url} has the recorded commit {}", short(commit)),
+                );
+                format!(
+                    "🔗 Changed {name}'s URL: {} → {new_url}\n   {checked}\n   The new .gitmodules URL is staged for you to commit.",
+                    old_url.as_deref().unwrap_or("(none)")
+                )
+            }
+        })
+    }
+}
+
+impl Reporter for ConsoleReporter {
+    fn report(&self, event: &Event) {
+        let text = Self::render(event);
+        if text.is_empty() {
+            // Nothing to say; the event is for reporters that track progress
+        } else if event.kind == EventKind::Warning {
+            eprintln!("{text}");
+        } else if !self.quiet {
+            println!("{text}");
+        }
+        if let Some(output) = &event.output {
+            let _ = io::stdout().write_all(output.stdout.as_bytes());
+            let _ = io::stderr().write_all(output.stderr.as_bytes());
+        }
+    }
+}
+
+/// Writes each event as a line of JSON, for other programs to read
+pub struct JsonLinesReporter {
+    out: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonLinesReporter {
+    /// A reporter that writes to `out`
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        Self {
+            out: Mutex::new(Box::new(out)),
+        }
+    }
+
+    /// A reporter that writes to stdout
+    #[must_use]
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl std::fmt::Debug for JsonLinesReporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonLinesReporter").finish_non_exhaustive()
+    }
+}
+
+impl Reporter for JsonLinesReporter {
+    fn report(&self, event: &Event) {
+        let mut out = self
+            .out
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // Like printing, reporting is best-effort; a closed pipe shouldn't stop the work
+        if serde_json::to_writer(&mut *out, event).is_ok() {
+            let _ = writeln!(out);
+            let _ = out.flush();
+        }
+    }
+}
+
+/// A command's failure, as `--format json` writes it to stderr
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorReport {
+    /// What kind of failure it was
+    pub kind: ErrorKind,
+    /// The exit code `submod` ends with for it
+    pub exit_code: u8,
+    /// The error, as `submod` prints it
+    pub message: String,
+    /// The submodule it's about, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submodule: Option<String>,
+    /// The operation that failed, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation: Option<Operation>,
+    /// The git backend that failed, if one did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<Backend>,
+}
+
+impl ErrorReport {
+    /// A report of a failure described by `message`. `error` fills in the rest; without one,
+    /// it's [`ErrorKind::Other`].
+    #[must_use]
+    pub fn new(message: impl Into<String>, error: Option<&SubmoduleError>) -> Self {
+        let kind = error.map_or(ErrorKind::Other, SubmoduleError::kind);
+        Self {
+            kind,
+            exit_code: kind.exit_code(),
+            message: message.into(),
+            submodule: error
+                .and_then(SubmoduleError::submodule)
+                .map(str::to_string),
+            operation: error.and_then(SubmoduleError::operation),
+            backend: error.and_then(SubmoduleError::backend),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A writer whose bytes can be read back after the reporter that owns it is done
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_lines_reporter_writes_one_object_per_event() {
+        let buffer = Shared::default();
+        let reporter = JsonLinesReporter::new(buffer.clone());
+        reporter.report(&Event::started(Operation::Init, "🔄 Initializing lib...").about("lib"));
+        reporter.report(&Event::warning(
+            Operation::Hook,
+            "⚠️  post_update hook failed",
+        ));
+        reporter.report(
+            &Event::finished(Operation::Foreach, "📦 lib (libs/lib)")
+                .about("lib")
+                .with_output(b"hello\n", b""),
+        );
+
+        let text = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            lines[0],
+            serde_json::json!({
+                "event": "started",
+                "operation": "init",
+                "submodule": "lib",
+                "message": "🔄 Initializing lib...",
+            })
+        );
+        assert_eq!(
+            lines[1],
+            serde_json::json!({
+                "event": "warning",
+                "operation": "hook",
+                "message": "⚠️  post_update hook failed",
+            })
+        );
+        assert_eq!(lines[2]["output"]["stdout"], "hello\n");
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn test_details_are_data_the_console_reporter_renders() {
+        let event = Event::detailed(
+            EventKind::Finished,
+            Operation::Update,
+            Details::Updated {
+                path: "libs/lib".to_string(),
+                old: Some("a".repeat(40)),
+                new: Some("b".repeat(40)),
+            },
+        )
+        .about("lib");
+        assert_eq!(ConsoleReporter::render(&event), "✅ Updated lib");
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "event": "finished",
+                "operation": "update",
+                "submodule": "lib",
+                "details": {
+                    "type": "updated",
+                    "path": "libs/lib",
+                    "old": "a".repeat(40),
+                    "new": "b".repeat(40),
+                },
+            })
+        );
+
+        let summary = Event::detailed(
+            EventKind::Finished,
+            Operation::Foreach,
+            Details::RanAll {
+                total: 2,
+                failures: vec![Failure {
+                    submodule: "lib".to_string(),
+                    reason: "exited with status 1".to_string(),
+                }],
+            },
+        );
+        assert_eq!(
+            ConsoleReporter::render(&summary),
+            "\n❌ Command failed in 1 of 2 submodules:\n   lib: exited with status 1"
+        );
+        let plain = Event::progress(Operation::Check, "Checking...");
+        assert_eq!(ConsoleReporter::render(&plain), "Checking...");
+    }
+
+    #[test]
+    fn test_closures_are_reporters() {
+        let seen = Mutex::new(Vec::new());
+        let reporter = |event: &Event| seen.lock().unwrap().push(event.kind);
+        reporter.report(&Event::progress(Operation::Check, "Checking..."));
+        reporter.report(&Event::finished(Operation::Check, "Done"));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [EventKind::Progress, EventKind::Finished]
+        );
+    }
+}
@@ -4,9 +4,12 @@
 //
 // SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
 
+use std::ffi::OsString;
+
 use clap::{ValueEnum, builder::PossibleValue};
 use clap_complete::aot::Generator;
 use clap_complete::aot::Shell as AotShell;
+use clap_complete::env::{self, EnvCompleter, Shells};
 use clap_complete_nushell::Nushell as NushellShell;
 
 /// Represents the supported shells for command-line completion.
@@ -202,6 +205,87 @@ impl Generator for Shell {
     }
 }
 
+/// The shells `COMPLETE=<shell> submod` writes completions for: clap's own, and [`NushellEnv`]
+pub const DYNAMIC_SHELLS: Shells<'static> = Shells(&[
+    &env::Bash,
+    &env::Elvish,
+    &env::Fish,
+    &env::Powershell,
+    &env::Zsh,
+    &NushellEnv,
+]);
+
+/// Dynamic completions for Nushell, which clap doesn't provide.
+///
+/// The registration sets Nushell's external completer to one that asks `submod` for
+/// candidates whenever the command line starts with it, and hands anything else to the
+/// completer that was set before. The candidates come back as the JSON list of `value` and
+/// `description` records Nushell expects.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NushellEnv;
+
+impl EnvCompleter for NushellEnv {
+    fn name(&self) -> &'static str {
+        "nushell"
+    }
+
+    fn is(&self, name: &str) -> bool {
+        name == "nushell" || name == "nu"
+    }
+
+    fn write_registration(
+        &self,
+        var: &str,
+        name: &str,
+        bin: &str,
+        completer: &str,
+        buf: &mut dyn std::io::Write,
+    ) -> Result<(), std::io::Error> {
+        let ident = name.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        writeln!(
+            buf,
+            r#"let {ident}_completer = {{|spans: list<string>|
+    with-env {{ {var}: "nushell" }} {{ run-external r#'{completer}'# "--" ...$spans }} | from json
+}}
+let {ident}_previous = $env.config.completions.external.completer?
+$env.config.completions.external.enable = true
+$env.config.completions.external.completer = {{|spans: list<string>|
+    if ($spans | first) == r#'{bin}'# {{
+        do ${ident}_completer $spans
+    }} else if ${ident}_previous != null {{
+        do ${ident}_previous $spans
+    }}
+}}"#
+        )
+    }
+
+    fn write_complete(
+        &self,
+        cmd: &mut clap::Command,
+        args: Vec<OsString>,
+        current_dir: Option<&std::path::Path>,
+        buf: &mut dyn std::io::Write,
+    ) -> Result<(), std::io::Error> {
+        let index = args.len() - 1;
+        let candidates: Vec<serde_json::Value> =
+            clap_complete::engine::complete(cmd, args, index, current_dir)?
+                .into_iter()
+                .filter(|candidate| !candidate.is_hide_set())
+                .map(|candidate| {
+                    let mut record = serde_json::json!({
+                        "value": candidate.get_value().to_string_lossy(),
+                    });
+                    if let Some(help) = candidate.get_help() {
+                        record["description"] = help.to_string().lines().next().into();
+                    }
+                    record
+                })
+                .collect();
+        serde_json::to_writer(&mut *buf, &candidates)?;
+        writeln!(buf)
+    }
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage_nightly, coverage(off))]
 mod tests {
@@ -429,6 +513,50 @@ mod tests {
         assert!(nu_name.contains("submod"));
     }
 
+    // ================================================================
+    // Dynamic completions: NushellEnv
+    // ================================================================
+
+    #[test]
+    fn test_nushell_env_is_found_by_either_name() {
+        for name in ["nushell", "nu"] {
+            assert!(
+                DYNAMIC_SHELLS
+                    .completer(name)
+                    .is_some_and(|shell| shell.name() == "nushell")
+            );
+        }
+        assert_eq!(DYNAMIC_SHELLS.names().count(), 6);
+    }
+
+    #[test]
+    fn test_nushell_env_writes_candidates_as_records() {
+        use clap::{Arg, Command};
+
+        let mut cmd = Command::new("test-cmd")
+            .subcommand(Command::new("sub1").about("The first one"))
+            .subcommand(Command::new("sub2"))
+            .arg(Arg::new("flag").long("flag").hide(true));
+        let mut buf = Vec::new();
+        NushellEnv
+            .write_complete(
+                &mut cmd,
+                vec!["test-cmd".into(), "sub".into()],
+                None,
+                &mut buf,
+            )
+            .unwrap();
+
+        let records: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(
+            records,
+            serde_json::json!([
+                { "value": "sub1", "description": "The first one" },
+                { "value": "sub2" },
+            ])
+        );
+    }
+
     #[test]
     fn test_generator_try_generate_produces_output() {
         use clap::Command;
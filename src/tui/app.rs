@@ -0,0 +1,512 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! What `submod tui` shows, and what its keys do to it
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::foreach::ForeachTarget;
+use crate::git_manager::{GitManager, SparseStatus, SubmoduleStatus};
+use crate::report::{ConsoleReporter, Event, EventKind};
+
+/// How many lines the log pane keeps
+const LOG_LIMIT: usize = 1000;
+
+/// One configured submodule, as the table shows it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmoduleRow {
+    /// The submodule's name in the config
+    pub name: String,
+    /// Where it's checked out, relative to the superproject
+    pub path: String,
+    /// Its URL, if configured
+    pub url: Option<String>,
+    /// The branch it tracks, if configured
+    pub branch: Option<String>,
+    /// Whether it's active
+    pub active: bool,
+    /// Its configured sparse paths; empty if it has none
+    pub sparse_paths: Vec<String>,
+    /// What's checked out
+    pub state: RowState,
+}
+
+/// What's checked out for a submodule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowState {
+    /// Nothing yet; `init` would clone it
+    NotCheckedOut,
+    /// A clone, as [`GitManager::check_submodule_repository_status`] found it
+    CheckedOut(SubmoduleStatus),
+    /// A clone whose status couldn't be read, and why
+    Unreadable(String),
+}
+
+impl SubmoduleRow {
+    /// The rows for every submodule `manager` has configured, sorted by name
+    #[must_use]
+    pub fn load(manager: &GitManager) -> Vec<Self> {
+        let root = manager.root();
+        let mut rows: Vec<Self> = manager
+            .config()
+            .get_submodules()
+            .map(|(name, entry)| {
+                let path = entry.path.clone().unwrap_or_else(|| name.clone());
+                let state = if root.join(&path).join(".git").exists() {
+                    manager
+                        .check_submodule_repository_status(&path, name)
+                        .map_or_else(
+                            |e| RowState::Unreadable(e.to_string()),
+                            RowState::CheckedOut,
+                        )
+                } else {
+                    RowState::NotCheckedOut
+                };
+                Self {
+                    name: name.clone(),
+                    path,
+                    url: entry.url.clone(),
+                    branch: entry.branch.as_ref().map(ToString::to_string),
+                    active: entry.active.unwrap_or(true),
+                    sparse_paths: entry.sparse_paths.clone().unwrap_or_default(),
+                    state,
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| a.name.cmp(&b.name));
+        rows
+    }
+
+    /// Whether the submodule has a clone
+    #[must_use]
+    pub const fn is_checked_out(&self) -> bool {
+        !matches!(self.state, RowState::NotCheckedOut)
+    }
+
+    /// Its working tree's state: clean, dirty, not checked out, or unreadable
+    #[must_use]
+    pub const fn status(&self) -> &'static str {
+        match &self.state {
+            RowState::NotCheckedOut => "not checked out",
+            RowState::CheckedOut(status) if status.is_clean => "clean",
+            RowState::CheckedOut(_) => "dirty",
+            RowState::Unreadable(_) => "unreadable",
+        }
+    }
+
+    /// The first 8 characters of the commit checked out, if there is one
+    #[must_use]
+    pub fn commit(&self) -> Option<&str> {
+        match &self.state {
+            RowState::CheckedOut(status) => status
+                .current_commit
+                .as_deref()
+                .map(|commit| commit.get(..8).unwrap_or(commit)),
+            _ => None,
+        }
+    }
+
+    /// Whether its sparse checkout matches its sparse paths, if it has any
+    #[must_use]
+    pub const fn sparse(&self) -> &'static str {
+        match &self.state {
+            RowState::CheckedOut(status) => match status.sparse_status {
+                SparseStatus::NotEnabled => "-",
+                SparseStatus::NotConfigured => "not applied",
+                SparseStatus::Correct => "ok",
+                SparseStatus::Mismatch { .. } => "mismatch",
+            },
+            _ if self.sparse_paths.is_empty() => "-",
+            _ => "configured",
+        }
+    }
+
+    /// The submodule as a shell opened in it sees it
+    #[must_use]
+    pub fn target(&self) -> ForeachTarget {
+        ForeachTarget {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            url: self.url.clone(),
+        }
+    }
+}
+
+/// Something a key asks for that the app can't do by itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Clone and check out a submodule, like `submod init`
+    Init(String),
+    /// Update a submodule, like `submod update`
+    Update(String),
+    /// Discard a submodule's local changes, like `submod reset`
+    Reset(String),
+    /// Replace a submodule's sparse paths, like `submod change --sparse-paths`, and apply them
+    /// to its checkout if it has one
+    SetSparsePaths {
+        /// The submodule
+        name: String,
+        /// Its new sparse paths
+        paths: Vec<String>,
+        /// Whether it's checked out, so the paths can be applied
+        apply: bool,
+    },
+    /// Make a submodule active or inactive, like `submod change --active`
+    SetActive {
+        /// The submodule
+        name: String,
+        /// Whether it should be active
+        active: bool,
+    },
+    /// Open a shell in a submodule
+    OpenShell(ForeachTarget),
+    /// Stop what's running, as Ctrl-C would
+    Interrupt,
+    /// Leave the app
+    Quit,
+}
+
+/// What the keys do at the moment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Moving around the table and starting actions
+    Browse,
+    /// Asking before resetting a submodule
+    ConfirmReset(String),
+    /// Typing a submodule's sparse paths, separated by commas
+    EditSparse {
+        /// The submodule
+        name: String,
+        /// What's been typed so far
+        input: String,
+    },
+    /// An action is running; it says what it's doing
+    Busy(String),
+}
+
+/// A line in the log pane
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    /// Whether it's a warning or an error
+    pub warning: bool,
+    /// The text
+    pub text: String,
+}
+
+/// Everything `submod tui` shows
+#[derive(Debug)]
+pub struct App {
+    /// The submodules, in the order shown
+    pub rows: Vec<SubmoduleRow>,
+    /// The index of the row the keys act on
+    pub selected: usize,
+    /// What the keys do
+    pub mode: Mode,
+    /// What the actions reported, oldest first
+    pub log: Vec<LogLine>,
+    /// The progress rows of the clones and fetches running now
+    pub progress: Vec<String>,
+}
+
+impl App {
+    /// An app showing `rows`, with the first one selected
+    #[must_use]
+    pub const fn new(rows: Vec<SubmoduleRow>) -> Self {
+        Self {
+            rows,
+            selected: 0,
+            mode: Mode::Browse,
+            log: Vec::new(),
+            progress: Vec::new(),
+        }
+    }
+
+    /// The row the keys act on, if there are any rows
+    #[must_use]
+    pub fn selected_row(&self) -> Option<&SubmoduleRow> {
+        self.rows.get(self.selected)
+    }
+
+    /// Whether an action is running
+    #[must_use]
+    pub const fn is_busy(&self) -> bool {
+        matches!(self.mode, Mode::Busy(_))
+    }
+
+    /// Adds `text` to the log pane, a line at a time
+    pub fn log(&mut self, text: &str, warning: bool) {
+        self.log.extend(text.lines().map(|line| LogLine {
+            warning,
+            text: line.to_string(),
+        }));
+        let excess = self.log.len().saturating_sub(LOG_LIMIT);
+        self.log.drain(..excess);
+    }
+
+    /// Adds what `event` says to the log pane. Events with nothing to say are left out, as
+    /// the CLI leaves them out.
+    pub fn log_event(&mut self, event: &Event) {
+        let text = ConsoleReporter::render(event);
+        if !text.is_empty() {
+            self.log(&text, event.kind == EventKind::Warning);
+        }
+    }
+
+    /// Notes that an action has started, and what it's doing
+    pub fn start(&mut self, doing: String) {
+        self.mode = Mode::Busy(doing);
+    }
+
+    /// Notes that the running action ended with `result`, leaving the submodules as `rows`.
+    /// The same submodule stays selected.
+    pub fn finish(&mut self, rows: Vec<SubmoduleRow>, result: Result<(), String>) {
+        if let Err(error) = result {
+            self.log(&format!("❌ {error}"), true);
+        }
+        self.mode = Mode::Browse;
+        self.progress.clear();
+        self.reload(rows);
+    }
+
+    /// Shows `rows` instead, keeping the same submodule selected if it's still there
+    pub fn reload(&mut self, rows: Vec<SubmoduleRow>) {
+        let name = self.selected_row().map(|row| row.name.clone());
+        self.rows = rows;
+        self.selected = name
+            .and_then(|name| self.rows.iter().position(|row| row.name == name))
+            .unwrap_or(self.selected)
+            .min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Handles a key press, returning what it asks for that the app can't do itself
+    pub fn on_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let ctrl_c =
+            key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+        match self.mode.clone() {
+            Mode::Busy(_) => ctrl_c.then_some(Action::Interrupt),
+            _ if ctrl_c => Some(Action::Quit),
+            Mode::Browse => self.browse(key.code),
+            Mode::ConfirmReset(name) => {
+                self.mode = Mode::Browse;
+                matches!(key.code, KeyCode::Char('y' | 'Y')).then_some(Action::Reset(name))
+            }
+            Mode::EditSparse { name, mut input } => match key.code {
+                KeyCode::Esc => {
+                    self.mode = Mode::Browse;
+                    None
+                }
+                KeyCode::Enter => {
+                    self.mode = Mode::Browse;
+                    let paths: Vec<String> = input
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|path| !path.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                    if paths.is_empty() {
+                        self.log("Sparse paths unchanged: enter at least one path", true);
+                        return None;
+                    }
+                    let apply = self
+                        .rows
+                        .iter()
+                        .any(|row| row.name == name && row.is_checked_out());
+                    Some(Action::SetSparsePaths { name, paths, apply })
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.mode = Mode::EditSparse { name, input };
+                    None
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    self.mode = Mode::EditSparse { name, input };
+                    None
+                }
+                _ => None,
+            },
+        }
+    }
+
+    /// What a key does while moving around the table
+    fn browse(&mut self, code: KeyCode) -> Option<Action> {
+        let last = self.rows.len().saturating_sub(1);
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(last),
+            KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+            KeyCode::End | KeyCode::Char('G') => self.selected = last,
+            _ => {}
+        }
+        let row = self.selected_row()?.clone();
+        match code {
+            KeyCode::Char('i') => Some(Action::Init(row.name)),
+            KeyCode::Char('u') => Some(Action::Update(row.name)),
+            KeyCode::Char('r') => {
+                self.mode = Mode::ConfirmReset(row.name);
+                None
+            }
+            KeyCode::Char('e') => {
+                self.mode = Mode::EditSparse {
+                    input: row.sparse_paths.join(", "),
+                    name: row.name,
+                };
+                None
+            }
+            KeyCode::Char('a') => Some(Action::SetActive {
+                active: !row.active,
+                name: row.name,
+            }),
+            KeyCode::Char('s') if row.is_checked_out() => Some(Action::OpenShell(row.target())),
+            KeyCode::Char('s') => {
+                self.log(
+                    &format!("{} isn't checked out; press i to clone it", row.name),
+                    true,
+                );
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    fn row(name: &str, state: RowState) -> SubmoduleRow {
+        SubmoduleRow {
+            name: name.to_string(),
+            path: format!("lib/{name}"),
+            url: Some(format!("https://example.com/{name}.git")),
+            branch: None,
+            active: true,
+            sparse_paths: vec!["src".to_string()],
+            state,
+        }
+    }
+
+    fn clean() -> RowState {
+        RowState::CheckedOut(SubmoduleStatus {
+            path: String::new(),
+            is_clean: true,
+            current_commit: Some("0123456789abcdef".to_string()),
+            has_remotes: true,
+            is_initialized: true,
+            is_active: true,
+            sparse_status: SparseStatus::Correct,
+            has_submodules: false,
+            shallow_depth: None,
+        })
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> Option<Action> {
+        app.on_key(KeyEvent::from(code))
+    }
+
+    #[test]
+    fn test_keys_move_the_selection_and_start_actions() {
+        let mut app = App::new(vec![
+            row("alpha", clean()),
+            row("beta", RowState::NotCheckedOut),
+        ]);
+
+        assert_eq!(press(&mut app, KeyCode::Down), None);
+        assert_eq!(press(&mut app, KeyCode::Down), None);
+        assert_eq!(app.selected, 1, "the selection stops at the last row");
+        assert_eq!(
+            press(&mut app, KeyCode::Char('u')),
+            Some(Action::Update("beta".to_string()))
+        );
+        assert_eq!(press(&mut app, KeyCode::Char('s')), None);
+        assert!(app.log[0].text.contains("beta isn't checked out"));
+
+        assert_eq!(press(&mut app, KeyCode::Char('k')), None);
+        assert_eq!(
+            press(&mut app, KeyCode::Char('a')),
+            Some(Action::SetActive {
+                name: "alpha".to_string(),
+                active: false
+            })
+        );
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('s')),
+            Some(Action::OpenShell(target)) if target.path == "lib/alpha"
+        ));
+
+        // Reset asks first
+        assert_eq!(press(&mut app, KeyCode::Char('r')), None);
+        assert_eq!(press(&mut app, KeyCode::Char('n')), None);
+        assert_eq!(app.mode, Mode::Browse);
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(
+            press(&mut app, KeyCode::Char('y')),
+            Some(Action::Reset("alpha".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_editing_sparse_paths_starts_from_the_configured_ones() {
+        let mut app = App::new(vec![row("alpha", clean())]);
+
+        press(&mut app, KeyCode::Char('e'));
+        assert_eq!(
+            app.mode,
+            Mode::EditSparse {
+                name: "alpha".to_string(),
+                input: "src".to_string()
+            }
+        );
+        for c in ", docs/ ,".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(
+            press(&mut app, KeyCode::Enter),
+            Some(Action::SetSparsePaths {
+                name: "alpha".to_string(),
+                paths: vec!["src".to_string(), "docs/".to_string()],
+                apply: true
+            })
+        );
+        assert_eq!(app.mode, Mode::Browse);
+    }
+
+    #[test]
+    fn test_busy_app_only_takes_ctrl_c_and_keeps_selection_on_reload() {
+        let mut app = App::new(vec![row("alpha", clean()), row("beta", clean())]);
+        app.selected = 1;
+        app.start("Updating beta".to_string());
+
+        assert_eq!(press(&mut app, KeyCode::Char('q')), None);
+        assert_eq!(
+            app.on_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Action::Interrupt)
+        );
+
+        // A new submodule sorted in ahead of it doesn't move the selection off it
+        app.finish(
+            vec![
+                row("aardvark", clean()),
+                row("alpha", clean()),
+                row("beta", clean()),
+            ],
+            Err("Network error: unreachable".to_string()),
+        );
+        assert_eq!(app.mode, Mode::Browse);
+        assert_eq!(
+            app.selected_row().map(|row| row.name.as_str()),
+            Some("beta")
+        );
+        assert_eq!(
+            app.log.last(),
+            Some(&LogLine {
+                warning: true,
+                text: "❌ Network error: unreachable".to_string()
+            })
+        );
+    }
+}
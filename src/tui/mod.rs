@@ -0,0 +1,266 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+`submod tui`: a terminal dashboard of the configured submodules.
+
+It lists each submodule with its branch, whether its working tree is clean, the commit checked
+out, whether its sparse checkout matches its sparse paths, and whether it's active. Keys update,
+initialize, or reset the selected submodule, edit its sparse paths, toggle it active, or open a
+shell in it.
+
+Everything it does goes through the same [`GitManager`] commands the CLI runs. They run on a
+worker thread while the screen keeps drawing; their [`Event`]s fill the log pane, and their
+clones and fetches report to a [`progress`] tree drawn under it. Ctrl-C stops a running
+command through [`interrupt`], as it would on the command line.
+"]
+
+mod app;
+mod ui;
+
+use std::ffi::OsString;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self as term, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{EnterAlternateScreen, enable_raw_mode};
+
+use crate::foreach::{self, ForeachTarget};
+use crate::git_manager::{GitManager, SubmoduleError};
+use crate::interrupt;
+use crate::progress::{self, Rows};
+use crate::report::Event;
+
+use app::{Action, App, SubmoduleRow};
+
+/// How long to wait for a key before redrawing, so progress keeps moving
+const TICK: Duration = Duration::from_millis(100);
+
+/// A command running on the worker thread. It hands the manager back when it's done.
+type Worker = JoinHandle<(GitManager, Result<(), SubmoduleError>)>;
+
+/// Runs the dashboard until it's quit
+///
+/// # Errors
+///
+/// If stdout isn't a terminal, or the terminal can't be drawn on.
+pub fn run(manager: GitManager) -> Result<()> {
+    if !io::stdout().is_terminal() {
+        bail!("`submod tui` needs a terminal");
+    }
+    let (sender, events) = mpsc::channel();
+    let tree = progress::tree();
+    let manager = manager
+        .with_reporter(move |event: &Event| {
+            // The receiver only goes away once the dashboard has closed
+            let _ = sender.send(event.clone());
+        })
+        .with_progress(Arc::clone(&tree));
+
+    let mut dashboard = Dashboard {
+        app: App::new(SubmoduleRow::load(&manager)),
+        root: manager.root(),
+        manager: Some(manager),
+        worker: None,
+        events,
+        rows: Rows::new(tree),
+    };
+    let mut terminal = ratatui::try_init().context("Failed to set up the terminal")?;
+    let result = dashboard.run(&mut terminal);
+    // A command still running is stopped, rather than left to finish unseen
+    if let Some(worker) = dashboard.worker.take() {
+        interrupt::trigger();
+        let _ = worker.join();
+    }
+    ratatui::restore();
+    result
+}
+
+/// The app, and the manager its actions run on
+struct Dashboard {
+    app: App,
+    root: std::path::PathBuf,
+    /// The manager, unless a command has it on the worker thread
+    manager: Option<GitManager>,
+    worker: Option<Worker>,
+    events: Receiver<Event>,
+    rows: Rows,
+}
+
+impl Dashboard {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            for event in self.events.try_iter() {
+                self.app.log_event(&event);
+            }
+            if self.worker.as_ref().is_some_and(JoinHandle::is_finished) {
+                self.finish(terminal)?;
+            }
+            if self.app.is_busy() {
+                let width = terminal.size()?.width.saturating_sub(2);
+                self.app.progress = self.rows.current(usize::from(width));
+            }
+            terminal.draw(|frame| ui::draw(frame, &self.app))?;
+
+            if !term::poll(TICK)? {
+                continue;
+            }
+            let term::Event::Key(key) = term::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match self.app.on_key(key) {
+                None => {}
+                Some(Action::Quit) => return Ok(()),
+                Some(Action::Interrupt) => interrupt::trigger(),
+                Some(Action::OpenShell(target)) => self.open_shell(terminal, &target)?,
+                Some(action) => self.start(action),
+            }
+        }
+    }
+
+    /// Runs `action` on the worker thread
+    fn start(&mut self, action: Action) {
+        let Some(mut manager) = self.manager.take() else {
+            return;
+        };
+        self.app.start(match &action {
+            Action::Init(name) => format!("Initializing {name}..."),
+            Action::Update(name) => format!("Updating {name}..."),
+            Action::Reset(name) => format!("Resetting {name}..."),
+            Action::SetSparsePaths { name, .. } => format!("Setting sparse paths for {name}..."),
+            Action::SetActive { name, active } => {
+                format!(
+                    "Making {name} {}...",
+                    if *active { "active" } else { "inactive" }
+                )
+            }
+            Action::OpenShell(_) | Action::Interrupt | Action::Quit => String::new(),
+        });
+        self.worker = Some(thread::spawn(move || {
+            let result = perform(&mut manager, action);
+            (manager, result)
+        }));
+    }
+
+    /// Takes the manager back from the finished worker, and shows what it left behind
+    fn finish(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(worker) = self.worker.take() else {
+            return Ok(());
+        };
+        let (manager, result) = worker
+            .join()
+            .map_err(|_| anyhow::anyhow!("The worker thread panicked"))?;
+        for event in self.events.try_iter() {
+            self.app.log_event(&event);
+        }
+        self.app.finish(
+            SubmoduleRow::load(&manager),
+            result.map_err(|e| e.to_string()),
+        );
+        self.manager = Some(manager);
+        interrupt::reset();
+        // Hooks write to the terminal directly; drawing everything again covers what they wrote
+        redraw_all(terminal)?;
+        Ok(())
+    }
+
+    /// Hands the terminal to a shell in `target` until it exits
+    fn open_shell(&mut self, terminal: &mut DefaultTerminal, target: &ForeachTarget) -> Result<()> {
+        ratatui::restore();
+        let status = shell(target, &self.root);
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        redraw_all(terminal)?;
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => self.app.log(
+                &format!("Shell in {} exited with {status}", target.name),
+                true,
+            ),
+            Err(e) => self.app.log(
+                &format!("Failed to open a shell in {}: {e}", target.name),
+                true,
+            ),
+        }
+        if let Some(manager) = &self.manager {
+            self.app.reload(SubmoduleRow::load(manager));
+        }
+        Ok(())
+    }
+}
+
+/// Clears the screen so the next draw covers all of it. Unlike [`Terminal::clear`], it doesn't
+/// ask the terminal where the cursor is, an answer that would race the keys being read.
+///
+/// [`Terminal::clear`]: ratatui::Terminal::clear
+fn redraw_all(terminal: &mut DefaultTerminal) -> io::Result<()> {
+    let area = terminal.size()?.into();
+    terminal.resize(area)
+}
+
+/// Opens a shell in `target`, saying how to get back
+fn shell(target: &ForeachTarget, root: &Path) -> io::Result<std::process::ExitStatus> {
+    println!(
+        "Opening a shell in {}; exit it to return to `submod tui`.",
+        target.path
+    );
+    foreach::open_shell(target, root)
+}
+
+/// Does what `action` asks with `manager`
+fn perform(manager: &mut GitManager, action: Action) -> Result<(), SubmoduleError> {
+    match action {
+        Action::Init(name) => manager.init_submodule(&name),
+        Action::Update(name) => manager.update_submodule(&name),
+        Action::Reset(name) => manager.reset_submodule(&name),
+        Action::SetSparsePaths { name, paths, apply } => {
+            let paths = paths.into_iter().map(OsString::from).collect();
+            set(manager, &name, Some(paths), None)?;
+            // Initializing a checked-out submodule applies its sparse paths to it
+            if apply {
+                manager.init_submodule(&name)?;
+            }
+            Ok(())
+        }
+        Action::SetActive { name, active } => set(manager, &name, None, Some(active)),
+        Action::OpenShell(_) | Action::Interrupt | Action::Quit => Ok(()),
+    }
+}
+
+/// Changes a submodule's sparse paths or whether it's active, as `submod change` would
+fn set(
+    manager: &mut GitManager,
+    name: &str,
+    sparse_paths: Option<Vec<OsString>>,
+    active: Option<bool>,
+) -> Result<(), SubmoduleError> {
+    manager.change_submodule(
+        name,
+        None,
+        None,
+        sparse_paths,
+        false,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        false,
+        active,
+        None,
+        None,
+    )
+}
@@ -0,0 +1,170 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Drawing `submod tui`: the submodule table, the log pane, and a line saying what the keys do
+
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState};
+
+use super::app::{App, Mode, SubmoduleRow};
+
+/// The table's columns, and how wide they are
+const COLUMNS: [(&str, Constraint); 7] = [
+    ("Name", Constraint::Fill(2)),
+    ("Path", Constraint::Fill(3)),
+    ("Branch", Constraint::Fill(1)),
+    ("Status", Constraint::Length(15)),
+    ("Commit", Constraint::Length(8)),
+    ("Sparse", Constraint::Length(11)),
+    ("Active", Constraint::Length(6)),
+];
+
+/// Draws `app` on `frame`
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [table, log, help] = Layout::vertical([
+        Constraint::Min(5),
+        Constraint::Percentage(35),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let header =
+        Row::new(COLUMNS.map(|(title, _)| title)).style(Style::new().add_modifier(Modifier::BOLD));
+    let rows = app.rows.iter().map(row);
+    let mut state = TableState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(
+        Table::new(rows, COLUMNS.map(|(_, width)| width))
+            .header(header)
+            .block(Block::bordered().title(" Submodules "))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> "),
+        table,
+        &mut state,
+    );
+
+    // The progress rows go under the newest lines, which stay in view
+    let inner = log.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = app
+        .log
+        .iter()
+        .map(|line| {
+            let style = if line.warning {
+                Style::new().fg(Color::Yellow)
+            } else {
+                Style::new()
+            };
+            Line::styled(line.text.as_str(), style)
+        })
+        .chain(
+            app.progress
+                .iter()
+                .map(|line| Line::styled(line.as_str(), Style::new().fg(Color::Cyan))),
+        )
+        .collect();
+    let skip = lines.len().saturating_sub(inner);
+    frame.render_widget(
+        Paragraph::new(lines.into_iter().skip(skip).collect::<Vec<_>>())
+            .block(Block::bordered().title(" Log ")),
+        log,
+    );
+
+    frame.render_widget(Paragraph::new(help_line(&app.mode)), help);
+}
+
+/// A submodule's row in the table
+fn row(submodule: &SubmoduleRow) -> Row<'_> {
+    let status_style = match submodule.status() {
+        "clean" => Style::new().fg(Color::Green),
+        "dirty" | "unreadable" => Style::new().fg(Color::Yellow),
+        _ => Style::new().fg(Color::DarkGray),
+    };
+    let sparse_style = match submodule.sparse() {
+        "mismatch" | "not applied" => Style::new().fg(Color::Yellow),
+        _ => Style::new(),
+    };
+    Row::new([
+        Cell::from(submodule.name.as_str()),
+        Cell::from(submodule.path.as_str()),
+        Cell::from(submodule.branch.as_deref().unwrap_or("-")),
+        Cell::from(submodule.status()).style(status_style),
+        Cell::from(submodule.commit().unwrap_or("-")),
+        Cell::from(submodule.sparse()).style(sparse_style),
+        Cell::from(if submodule.active { "yes" } else { "no" }),
+    ])
+}
+
+/// What the keys do in `mode`
+fn help_line(mode: &Mode) -> Line<'_> {
+    match mode {
+        Mode::Browse => Line::from(
+            "↑/↓ select  i init  u update  r reset  s shell  e sparse paths  a toggle active  q quit",
+        ),
+        Mode::ConfirmReset(name) => Line::styled(
+            format!("Reset {name}, discarding its local changes? (y/n)"),
+            Style::new().fg(Color::Yellow),
+        ),
+        Mode::EditSparse { name, input } => Line::from(format!(
+            "Sparse paths for {name}, comma-separated: {input}█  (Enter saves, Esc cancels)"
+        )),
+        Mode::Busy(doing) => Line::styled(
+            format!("{doing}  (Ctrl-C stops it)"),
+            Style::new().fg(Color::Cyan),
+        ),
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::tui::app::RowState;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn test_draw_shows_rows_log_and_keys() {
+        let mut app = App::new(vec![SubmoduleRow {
+            name: "vendor-lib".to_string(),
+            path: "lib/vendor".to_string(),
+            url: None,
+            branch: Some("main".to_string()),
+            active: false,
+            sparse_paths: vec!["src".to_string()],
+            state: RowState::NotCheckedOut,
+        }]);
+        app.log("✅ Updated vendor-lib", false);
+        app.progress
+            .push("vendor-lib  clone  12/40 objects".to_string());
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 16)).expect("terminal");
+        terminal.draw(|frame| draw(frame, &app)).expect("draw");
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect();
+
+        for expected in [
+            "vendor-lib",
+            "lib/vendor",
+            "main",
+            "not checked out",
+            "configured",
+            "no",
+            "Updated vendor-lib",
+            "12/40 objects",
+            "u update",
+        ] {
+            assert!(
+                screen.contains(expected),
+                "{expected:?} not drawn: {screen}"
+            );
+        }
+    }
+}
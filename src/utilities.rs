@@ -7,6 +7,8 @@
 use anyhow::Result;
 use git2::Repository as Git2Repository;
 use gix::open::Options;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
 use std::path::PathBuf;
 
 /// Get the current repository using git2, with an optional provided repository. If no repository is provided, it will attempt to discover one in the current directory.
@@ -68,10 +70,11 @@ pub fn get_main_repo(repo: Option<&gix::Repository>) -> Result<gix::Repository,
     Ok(super_repo)
 }
 
-/// Get the main repository's root directory.
+/// Get the main repository's git directory. From a linked worktree this is still the
+/// main `.git`, which holds the objects, refs, and `.git/modules` all worktrees share.
 pub fn get_main_root(repo: Option<&gix::Repository>) -> Result<PathBuf, anyhow::Error> {
     let repo = get_main_repo(repo)?;
-    let path = repo.path().to_path_buf();
+    let path = repo.common_dir().to_path_buf();
     if path.is_dir() {
         Ok(path)
     } else {
@@ -82,6 +85,18 @@ pub fn get_main_root(repo: Option<&gix::Repository>) -> Result<PathBuf, anyhow::
     }
 }
 
+/// The main worktree's root directory, if `repo` is a linked worktree made by
+/// `git worktree add`. `None` in the main worktree itself.
+pub fn main_worktree_root(repo: &gix::Repository) -> Option<PathBuf> {
+    if !matches!(repo.kind(), gix::repository::Kind::LinkedWorkTree) {
+        return None;
+    }
+    get_main_repo(Some(repo))
+        .ok()?
+        .workdir()
+        .map(std::path::Path::to_path_buf)
+}
+
 /// Get the current branch name from the repository.
 pub fn get_current_branch(repo: Option<&gix::Repository>) -> Result<String, anyhow::Error> {
     fn branch_from_repo(repo: &gix::Repository) -> Result<String, anyhow::Error> {
@@ -103,12 +118,73 @@ pub fn get_current_branch(repo: Option<&gix::Repository>) -> Result<String, anyh
  *                           General Utilities
  *========================================================================*/
 
+/// Get the depth of a shallow repository: the number of generations from HEAD down to
+/// its deepest shallow boundary. Returns `None` if the repository has its full history.
+pub fn shallow_depth(repo: &gix::Repository) -> Option<u32> {
+    let boundary: HashSet<_> = repo.shallow_commits().ok()??.iter().copied().collect();
+    let head = repo.head_id().ok()?.detach();
+    depth_to_shallow_boundary(head, &boundary, |id| {
+        repo.find_commit(id).map_or_else(
+            |_| Vec::new(),
+            |commit| commit.parent_ids().map(gix::Id::detach).collect(),
+        )
+    })
+}
+
+/// The generation, counting `head` as 1, of the deepest `boundary` commit reachable from `head`.
+///
+/// This is the depth a fetch would need to keep all of the history. Commits on the boundary
+/// aren't followed further, since their parents were never fetched.
+pub fn depth_to_shallow_boundary<Id: Copy + Eq + Hash, S: BuildHasher>(
+    head: Id,
+    boundary: &HashSet<Id, S>,
+    parents: impl Fn(Id) -> Vec<Id>,
+) -> Option<u32> {
+    let mut seen = HashSet::from([head]);
+    let mut generation = vec![head];
+    let mut depth = 0;
+    let mut deepest = None;
+    while !generation.is_empty() {
+        depth += 1;
+        let mut next = Vec::new();
+        for id in generation {
+            if boundary.contains(&id) {
+                deepest = Some(depth);
+                continue;
+            }
+            next.extend(
+                parents(id)
+                    .into_iter()
+                    .filter(|parent| seen.insert(*parent)),
+            );
+        }
+        generation = next;
+    }
+    deepest
+}
+
 /// Get the current working directory.
 pub fn get_current_working_directory() -> Result<PathBuf, anyhow::Error> {
     std::env::current_dir()
         .map_err(|e| anyhow::anyhow!("Failed to get current working directory: {e}"))
 }
 
+/// `to` relative to the directory `from`; both must be absolute
+#[must_use]
+pub fn relative_path(from: &std::path::Path, to: &std::path::Path) -> PathBuf {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
 /// Convert a `Path` to a `String`, returning an error if the path is not valid UTF-8
 pub fn path_to_string(path: &std::path::Path) -> Result<String, anyhow::Error> {
     path.to_str()
@@ -0,0 +1,133 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+#![doc = r"
+Turning submodules into plain files in the superproject, for `submod vendor`.
+
+A vendored submodule's checked-out files are added straight to the superproject's index,
+and where they came from is recorded in `VENDORED.toml` at the superproject root: the URL,
+the commit, the branch it tracked, and its sparse paths. That's everything `submod adopt`
+needs to make it a submodule again at the same commit.
+"]
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The provenance file, at the superproject root
+pub const VENDORED_FILE: &str = "VENDORED.toml";
+
+/// Written above the records in `VENDORED.toml`
+const HEADER: &str =
+    "# Submodules vendored by `submod vendor`: where each directory's files came from.\n\n";
+
+/// Where a vendored directory's files came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VendoredRecord {
+    /// The directory, relative to the superproject root
+    pub path: String,
+    /// The repository the files were cloned from
+    pub url: String,
+    /// The commit that was checked out
+    pub commit: String,
+    /// The branch the submodule tracked, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// The sparse paths that limited which files were checked out, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparse_paths: Option<Vec<String>>,
+}
+
+/// The contents of `VENDORED.toml`: one record per vendored submodule, by name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vendored {
+    /// The records, by submodule name
+    #[serde(flatten)]
+    pub submodules: BTreeMap<String, VendoredRecord>,
+}
+
+impl Vendored {
+    /// Read `VENDORED.toml` from the superproject at `root`; a missing file has no records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read or parsed.
+    pub fn read(root: &Path) -> Result<Self> {
+        let path = root.join(VENDORED_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Write `VENDORED.toml` to the superproject at `root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be written.
+    pub fn write(&self, root: &Path) -> Result<()> {
+        let path = root.join(VENDORED_FILE);
+        let text = toml::to_string(self)?;
+        std::fs::write(&path, format!("{HEADER}{text}"))
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// The files checked out in `repo`, relative to its root: every index entry except those
+/// sparse checkout leaves out of the working tree
+///
+/// # Errors
+///
+/// Returns an error if the index can't be read or `repo` has submodules of its own, whose
+/// files can't be vendored with it.
+pub fn checked_out_files(repo: &gix::Repository) -> Result<Vec<String>> {
+    let index = repo.index_or_empty()?;
+    let mut files = Vec::new();
+    for entry in index.entries() {
+        let path = entry.path(&index).to_string();
+        if entry.mode == gix::index::entry::Mode::COMMIT {
+            bail!("it has a submodule of its own at {path}; vendor that first");
+        }
+        if !entry
+            .flags
+            .contains(gix::index::entry::Flags::SKIP_WORKTREE)
+        {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage_nightly, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendored_round_trips_through_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(Vendored::read(dir.path()).unwrap(), Vendored::default());
+
+        let mut vendored = Vendored::default();
+        vendored.submodules.insert(
+            "my-lib".to_string(),
+            VendoredRecord {
+                path: "third_party/my-lib".to_string(),
+                url: "https://example.com/my-lib.git".to_string(),
+                commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+                branch: Some("main".to_string()),
+                sparse_paths: Some(vec!["src/".to_string()]),
+            },
+        );
+        vendored.write(dir.path()).unwrap();
+
+        let text = std::fs::read_to_string(dir.path().join(VENDORED_FILE)).unwrap();
+        assert!(text.starts_with(HEADER), "text: {text}");
+        assert!(text.contains("[my-lib]"), "text: {text}");
+        assert_eq!(Vendored::read(dir.path()).unwrap(), vendored);
+    }
+}
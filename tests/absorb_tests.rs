@@ -0,0 +1,147 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `submod absorb`, which moves embedded `.git` directories into `.git/modules`
+
+mod common;
+use common::TestHarness;
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Clone `remote` to `path` and register it as a submodule of the repository at `dir`
+    /// the way git does for an existing clone, which keeps its `.git` directory inside
+    fn add_embedded(harness: &TestHarness, dir: &str, remote: &str, path: &str) {
+        harness.git_stdout(&["-C", dir, "clone", "--quiet", remote, path]);
+        harness.git_stdout(&["-C", dir, "submodule", "add", "--quiet", remote, path]);
+        harness.git_stdout(&["-C", dir, "commit", "--quiet", "-m", &format!("Add {path}")]);
+    }
+
+    #[test]
+    fn test_absorb_moves_embedded_git_dirs_recursively() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let outer = harness
+            .create_test_remote("absorb_outer")
+            .expect("Failed to create remote");
+        let inner = harness
+            .create_test_remote("absorb_inner")
+            .expect("Failed to create remote");
+        let (outer, inner) = (
+            format!("file://{}", outer.display()),
+            format!("file://{}", inner.display()),
+        );
+        add_embedded(&harness, ".", &outer, "libs/outer");
+        add_embedded(&harness, "libs/outer", &inner, "inner");
+        harness.git_stdout(&["commit", "--quiet", "-am", "Record outer's new commit"]);
+        harness
+            .create_config(&format!(
+                "[outer]\npath = \"libs/outer\"\nurl = \"{outer}\"\nactive = true\n"
+            ))
+            .expect("Failed to write config");
+        assert!(harness.work_dir.join("libs/outer/.git").is_dir());
+        assert!(harness.work_dir.join("libs/outer/inner/.git").is_dir());
+
+        let stdout = harness
+            .run_submod_success(&["check"])
+            .expect("Failed to check");
+        assert!(
+            stdout.contains("libs/outer: Has its own .git directory"),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains("libs/outer/inner: Has its own .git directory"),
+            "stdout: {stdout}"
+        );
+
+        let stdout = harness
+            .run_submod_success(&["absorb"])
+            .expect("Failed to absorb");
+        assert!(
+            stdout
+                .contains("Absorbed libs/outer: its git directory is now .git/modules/libs/outer"),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains(
+                "Absorbed libs/outer/inner: its git directory is now .git/modules/libs/outer/modules/inner"
+            ),
+            "stdout: {stdout}"
+        );
+
+        // Linked both ways, the way git links the submodules it clones
+        let gitfile = std::fs::read_to_string(harness.work_dir.join("libs/outer/inner/.git"))
+            .expect("inner has a .git file");
+        assert_eq!(
+            gitfile,
+            "gitdir: ../../../.git/modules/libs/outer/modules/inner\n"
+        );
+        assert_eq!(
+            harness.git_stdout(&[
+                "config",
+                "-f",
+                ".git/modules/libs/outer/modules/inner/config",
+                "core.worktree"
+            ]),
+            "../../../../../../libs/outer/inner"
+        );
+        let status = harness.git_stdout(&["submodule", "status", "--recursive"]);
+        assert!(
+            !status.contains('-') && !status.contains('+'),
+            "status: {status}"
+        );
+        assert_eq!(
+            harness.git_stdout(&["status", "--porcelain"]),
+            "?? submod.toml"
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "libs/outer/inner", "status", "--porcelain"]),
+            ""
+        );
+
+        let stdout = harness
+            .run_submod_success(&["check"])
+            .expect("Failed to check");
+        assert!(
+            !stdout.contains("Has its own .git directory"),
+            "stdout: {stdout}"
+        );
+        let stdout = harness
+            .run_submod_success(&["absorb", "outer"])
+            .expect("Failed to absorb");
+        assert!(stdout.contains("Nothing to absorb"), "stdout: {stdout}");
+    }
+
+    #[test]
+    fn test_absorb_leaves_everything_in_place_when_a_git_dir_is_taken() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("absorb_taken")
+            .expect("Failed to create remote");
+        let remote = format!("file://{}", remote.display());
+        add_embedded(&harness, ".", &remote, "lib");
+        harness
+            .create_config(&format!(
+                "[lib]\npath = \"lib\"\nurl = \"{remote}\"\nactive = true\n"
+            ))
+            .expect("Failed to write config");
+        std::fs::create_dir_all(harness.work_dir.join(".git/modules/lib/objects"))
+            .expect("Failed to create a stale git dir");
+
+        let output = harness
+            .run_submod(&["absorb", "lib"])
+            .expect("Failed to run submod");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Can't absorb lib"), "stderr: {stderr}");
+        assert!(harness.work_dir.join("lib/.git").is_dir());
+
+        let output = harness
+            .run_submod(&["absorb", "nope"])
+            .expect("Failed to run submod");
+        assert!(!output.status.success());
+    }
+}
@@ -0,0 +1,228 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `submod adopt`, which makes existing directories submodules without recloning
+
+mod common;
+use common::TestHarness;
+
+/// Clones a fresh test remote to `path` in the superproject and returns the remote's URL
+/// and the clone's commit
+fn nested_clone(harness: &TestHarness, path: &str) -> (String, String) {
+    harness.init_git_repo().expect("Failed to init git repo");
+    let remote = harness
+        .create_test_remote("upstream_lib")
+        .expect("Failed to create remote");
+    let url = format!("file://{}", remote.display());
+    harness.git_stdout(&["clone", "-q", &url, path]);
+    let commit = harness.git_stdout(&["-C", path, "rev-parse", "HEAD"]);
+    (url, commit)
+}
+
+/// The mode and object of the superproject's index entry for `path`
+fn staged_entry(harness: &TestHarness, path: &str) -> String {
+    let staged = harness.git_stdout(&["ls-files", "--stage", "--", path]);
+    let mut fields = staged.split_whitespace();
+    format!(
+        "{} {}",
+        fields.next().unwrap_or_default(),
+        fields.next().unwrap_or_default()
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adopt_absorbs_a_nested_clone() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (url, commit) = nested_clone(&harness, "lib/nested");
+        harness.git_stdout(&["-C", "lib/nested", "branch", "local-work"]);
+
+        let stdout = harness
+            .run_submod_success(&["adopt", "lib/nested"])
+            .expect("Failed to adopt clone");
+        assert!(
+            stdout.contains("🏠 Adopted nested at lib/nested (absorbed its clone)"),
+            "stdout: {stdout}"
+        );
+
+        assert_eq!(
+            staged_entry(&harness, "lib/nested"),
+            format!("160000 {commit}")
+        );
+        assert!(harness.file_exists("lib/nested/.git"), ".git is now a file");
+        assert!(harness.dir_exists(".git/modules/nested"));
+        assert_eq!(
+            harness.git_stdout(&["config", "-f", ".gitmodules", "submodule.nested.url"]),
+            url
+        );
+        // Local branches come along
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/nested", "branch", "--list", "local-work"]),
+            "local-work"
+        );
+
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("[nested]"), "config: {config}");
+        assert!(config.contains("branch = \"main\""), "config: {config}");
+    }
+
+    #[test]
+    fn test_adopt_matches_a_plain_copy_to_its_upstream_commit() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (url, commit) = nested_clone(&harness, "lib/copy");
+        std::fs::remove_dir_all(harness.work_dir.join("lib/copy/.git"))
+            .expect("Failed to remove .git");
+        harness.git_stdout(&["add", "lib/copy"]);
+        harness.git_stdout(&["commit", "-q", "-m", "Copy upstream"]);
+
+        harness
+            .run_submod_success(&["adopt", "lib/copy", "--url", &url])
+            .expect("Failed to adopt copy");
+        assert_eq!(
+            staged_entry(&harness, "lib/copy"),
+            format!("160000 {commit}")
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/copy", "status", "--porcelain"]),
+            "",
+            "the copy's files are the commit's files"
+        );
+    }
+
+    #[test]
+    fn test_adopt_refuses_a_copy_no_commit_matches() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (url, _) = nested_clone(&harness, "lib/copy");
+        std::fs::remove_dir_all(harness.work_dir.join("lib/copy/.git"))
+            .expect("Failed to remove .git");
+        std::fs::write(harness.work_dir.join("lib/copy/LICENSE"), "patched\n")
+            .expect("Failed to modify copy");
+
+        let output = harness
+            .run_submod(&["adopt", "lib/copy", "--url", &url])
+            .expect("Failed to run submod");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("No commit of"), "stderr: {stderr}");
+        assert!(
+            !harness.dir_exists("lib/copy/.git"),
+            "the new clone is removed"
+        );
+        assert!(!harness.file_exists(".gitmodules"));
+    }
+
+    #[test]
+    fn test_adopt_puts_the_index_back_when_staging_fails() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (url, _) = nested_clone(&harness, "lib/copy");
+        std::fs::remove_dir_all(harness.work_dir.join("lib/copy/.git"))
+            .expect("Failed to remove .git");
+        harness.git_stdout(&["add", "lib/copy"]);
+        harness.git_stdout(&["commit", "-q", "-m", "Copy upstream"]);
+        let tracked = harness.git_stdout(&["ls-files", "--stage", "--", "lib/copy"]);
+
+        // A file where the clone's git directory should go fails `absorbgitdirs`, after the
+        // copy's files have been dropped from the index
+        std::fs::create_dir_all(harness.work_dir.join(".git/modules"))
+            .expect("Failed to create .git/modules");
+        std::fs::write(harness.work_dir.join(".git/modules/copy"), "in the way\n")
+            .expect("Failed to block .git/modules/copy");
+
+        let output = harness
+            .run_submod(&["adopt", "lib/copy", "--url", &url])
+            .expect("Failed to run submod");
+        assert!(!output.status.success(), "{output:?}");
+        assert_eq!(
+            harness.git_stdout(&["ls-files", "--stage", "--", "lib/copy"]),
+            tracked,
+            "the copy's files are tracked again"
+        );
+        assert_eq!(harness.git_stdout(&["status", "--porcelain"]), "");
+        assert!(
+            !harness.dir_exists("lib/copy/.git"),
+            "the new clone is removed"
+        );
+        assert!(!harness.file_exists(".gitmodules"));
+    }
+
+    #[test]
+    fn test_adopt_reverses_vendor() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("vendored_lib")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        harness
+            .run_submod_success(&["add", &url, "--name", "round-trip", "--path", "lib/rt"])
+            .expect("Failed to add submodule");
+        let commit = harness.git_stdout(&["-C", "lib/rt", "rev-parse", "HEAD"]);
+        harness
+            .run_submod_success(&["vendor", "round-trip"])
+            .expect("Failed to vendor submodule");
+        harness.git_stdout(&["commit", "-q", "-m", "Vendor"]);
+
+        let stdout = harness
+            .run_submod_success(&["adopt", "lib/rt"])
+            .expect("Failed to adopt vendored copy");
+        assert!(
+            stdout.contains("Adopted round-trip at lib/rt"),
+            "stdout: {stdout}"
+        );
+        assert_eq!(staged_entry(&harness, "lib/rt"), format!("160000 {commit}"));
+        assert!(!harness.file_exists("VENDORED.toml"));
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("[round-trip]"), "config: {config}");
+    }
+
+    #[test]
+    fn test_adopt_reverses_sparse_vendor() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_complex_remote("sparse_lib")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        harness
+            .run_submod_success(&[
+                "add",
+                &url,
+                "--name",
+                "sparse",
+                "--path",
+                "lib/sparse",
+                "--sparse-paths",
+                "src",
+            ])
+            .expect("Failed to add submodule");
+        let commit = harness.git_stdout(&["-C", "lib/sparse", "rev-parse", "HEAD"]);
+        harness
+            .run_submod_success(&["vendor", "sparse"])
+            .expect("Failed to vendor submodule");
+        harness.git_stdout(&["commit", "-q", "-m", "Vendor"]);
+
+        harness
+            .run_submod_success(&["adopt", "lib/sparse"])
+            .expect("Failed to adopt vendored copy");
+        assert_eq!(
+            staged_entry(&harness, "lib/sparse"),
+            format!("160000 {commit}")
+        );
+        assert!(harness.file_exists("lib/sparse/src/lib.rs"));
+        assert!(!harness.dir_exists("lib/sparse/tests"));
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/sparse", "status", "--porcelain"]),
+            "",
+            "files outside the sparse paths aren't missing, just not checked out"
+        );
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(
+            config.contains("sparse_paths = [\"src\"]"),
+            "config: {config}"
+        );
+    }
+}
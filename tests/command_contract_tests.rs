@@ -7,12 +7,14 @@
 //!
 //! Covers:
 //! - `completeme` shell completion output
-//! - `add --no-init` (config-only add)
+//! - `add --no-init` (config-only add), and `init` in a fresh clone
 //! - `nuke-it-from-orbit` without `--kill` (reinit) and `--all`
 //! - `generate-config --from-setup` and `--force`
 //! - `change` command: path relocation, URL update, active toggle,
 //!   sparse-path replace and append
 //! - Error contracts for nonexistent targets (delete, disable, reset, change)
+//! - Exit codes and `--format json` error objects
+//! - `tui` refusing to run without a terminal
 //! - Output contracts: exact phrases expected for each command
 
 use std::fs;
@@ -248,6 +250,116 @@ mod tests {
     // nuke-it-from-orbit – all flag
     // =========================================================================
 
+    #[test]
+    fn test_update_keeps_going_past_a_broken_submodule() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        for name in ["good", "broken"] {
+            let remote = harness
+                .create_test_remote(&format!("{name}_lib"))
+                .expect("Failed to create remote");
+            harness
+                .run_submod_success(&[
+                    "add",
+                    &format!("file://{}", remote.display()),
+                    "--name",
+                    name,
+                    "--path",
+                    &format!("lib/{name}"),
+                ])
+                .expect("Failed to add submodule");
+        }
+        let recorded = harness
+            .advance_test_remote("good_lib")
+            .expect("Failed to advance remote");
+        let record = |path: &str, commit: &str| {
+            harness.git_stdout(&[
+                "update-index",
+                "--cacheinfo",
+                &format!("160000,{commit},{path}"),
+            ]);
+        };
+        record("lib/good", &recorded);
+        // A commit its remote doesn't have
+        record("lib/broken", "1234567890123456789012345678901234567890");
+
+        let output = harness
+            .run_submod(&["--format", "json", "update"])
+            .expect("Failed to run update");
+        assert_eq!(output.status.code(), Some(7), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let error = stderr
+            .lines()
+            .find_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .expect("errors must be printed as JSON");
+        assert_eq!(
+            error["error"]["kind"], "partial_success",
+            "stderr: {stderr}"
+        );
+        assert_eq!(error["error"]["operation"], "update", "stderr: {stderr}");
+        assert!(
+            error["error"]["message"]
+                .as_str()
+                .is_some_and(|message| message.contains("failed in 1 of 2 submodule(s): broken")),
+            "stderr: {stderr}"
+        );
+        // The good one was updated all the same
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/good", "rev-parse", "HEAD"]),
+            recorded
+        );
+
+        // When every submodule fails, the exit code is theirs
+        record("lib/good", "1234567890123456789012345678901234567890");
+        let output = harness
+            .run_submod(&["update"])
+            .expect("Failed to run update");
+        assert_eq!(output.status.code(), Some(1), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("failed in 2 of 2 submodule(s)"),
+            "stderr: {stderr}"
+        );
+    }
+
+    #[test]
+    fn test_init_in_a_fresh_clone_checks_out_submodules() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote_repo = harness
+            .create_test_remote("fresh_clone_lib")
+            .expect("Failed to create remote");
+        harness
+            .run_submod_success(&[
+                "add",
+                &format!("file://{}", remote_repo.display()),
+                "--name",
+                "lib",
+                "--path",
+                "lib/fresh",
+            ])
+            .expect("Failed to add submodule");
+        harness.git_stdout(&["add", "submod.toml"]);
+        harness.git_stdout(&["commit", "-m", "Add lib"]);
+        let recorded = harness.git_stdout(&["rev-parse", "HEAD:lib/fresh"]);
+
+        // A clone of the superproject has the gitlink, but no submodule checkout yet
+        let clone = harness.temp_dir.path().join("fresh");
+        let clone_str = clone.to_str().expect("utf-8 path");
+        harness.git_stdout(&["clone", "--quiet", ".", clone_str]);
+        harness
+            .run_submod_in(&clone, &["init"])
+            .expect("Failed to init in the clone");
+
+        assert!(clone.join("lib/fresh/LICENSE").is_file());
+        // Cloned into .git/modules like git would, not with a .git directory of its own
+        assert!(clone.join("lib/fresh/.git").is_file());
+        assert_eq!(
+            harness.git_stdout(&["-C", clone_str, "-C", "lib/fresh", "rev-parse", "HEAD"]),
+            recorded
+        );
+    }
+
     #[test]
     fn test_nuke_all_removes_all_submodules() {
         let harness = TestHarness::new().expect("Failed to create test harness");
@@ -558,7 +670,7 @@ mod tests {
     // =========================================================================
 
     #[test]
-    fn test_change_path_reclones_at_new_location() {
+    fn test_change_path_moves_without_recloning() {
         let harness = TestHarness::new().expect("Failed to create test harness");
         harness.init_git_repo().expect("Failed to init git repo");
 
@@ -579,27 +691,46 @@ mod tests {
             .expect("Failed to add submodule");
 
         assert!(harness.file_exists("lib/original/.git"));
+        let commit = harness.git_stdout(&["-C", "lib/original", "rev-parse", "HEAD"]);
+        // Local state a re-clone would lose
+        harness.git_stdout(&["-C", "lib/original", "branch", "local-work"]);
+        std::fs::write(harness.work_dir.join("lib/original/NOTES.md"), "wip\n")
+            .expect("Failed to write file");
 
         let stdout = harness
-            .run_submod_success(&["change", "movable-lib", "--path", "lib/moved"])
+            .run_submod_success(&["change", "movable-lib", "--path", "vendor/libs/moved"])
             .expect("Failed to change submodule path");
 
-        // Should confirm the update, naming the submodule it re-added at the new path.
         assert!(
-            stdout.contains("Added submodule movable-lib"),
-            "Expected confirmation of path change; got: {stdout}"
+            stdout.contains("Moved movable-lib: lib/original → vendor/libs/moved"),
+            "Expected confirmation of the move; got: {stdout}"
+        );
+        assert!(!harness.dir_exists("lib/original"));
+        assert!(harness.file_exists("vendor/libs/moved/NOTES.md"));
+        assert_eq!(
+            harness.git_stdout(&["-C", "vendor/libs/moved", "branch", "--list", "local-work"]),
+            "local-work"
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "vendor/libs/moved", "rev-parse", "HEAD"]),
+            commit
         );
-
-        // New path should be cloned
         assert!(
-            harness.file_exists("lib/moved/.git"),
-            "Submodule should exist at new path"
+            harness
+                .git_stdout(&["ls-files", "--stage", "vendor/libs/moved"])
+                .starts_with(&format!("160000 {commit}")),
+            "the gitlink moves with it"
+        );
+        // The .gitmodules section keeps the submodule's name; only its path moves
+        assert_eq!(
+            harness.git_stdout(&["config", "-f", ".gitmodules", "submodule.movable-lib.path"]),
+            "vendor/libs/moved"
         );
 
         // Config should reflect the new path
         let config = harness.read_config().expect("Failed to read config");
         assert!(
-            config.contains("path = \"lib/moved\""),
+            config.contains("path = \"vendor/libs/moved\""),
             "Config should have the new path"
         );
         assert!(
@@ -608,32 +739,117 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_change_path_with_trailing_slash_records_one_path() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+
+        let remote = harness
+            .create_test_remote("change_path_slash")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+
+        harness
+            .run_submod_success(&[
+                "add",
+                &url,
+                "--name",
+                "slashed-lib",
+                "--path",
+                "lib/original",
+            ])
+            .expect("Failed to add submodule");
+
+        harness
+            .run_submod_success(&["change", "slashed-lib", "--path", "lib/new/"])
+            .expect("Failed to change submodule path");
+
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("path = \"lib/new\""), "config: {config}");
+        assert_eq!(
+            harness.git_stdout(&["config", "-f", ".gitmodules", "submodule.slashed-lib.path"]),
+            "lib/new"
+        );
+        let output = harness
+            .run_submod(&["doctor"])
+            .expect("Failed to run submod");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            !stdout.contains("path:"),
+            "no path drift expected: {stdout}"
+        );
+
+        // The same path with a slash is no move at all
+        let stdout = harness
+            .run_submod_success(&["change", "slashed-lib", "--path", "lib/new/"])
+            .expect("Failed to change submodule path");
+        assert!(!stdout.contains("Moved"), "stdout: {stdout}");
+    }
+
     // =========================================================================
     // change command – URL update
     // =========================================================================
 
     #[test]
-    fn test_change_url_updates_config() {
+    fn test_change_path_refuses_bad_destinations_and_changes_nothing() {
         let harness = TestHarness::new().expect("Failed to create test harness");
         harness.init_git_repo().expect("Failed to init git repo");
 
-        // Create two remotes
-        let remote1 = harness
+        let remote = harness
+            .create_test_remote("change_path_bad")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        harness
+            .run_submod_success(&["add", &url, "--name", "stay-put", "--path", "lib/stay"])
+            .expect("Failed to add submodule");
+        fs::create_dir_all(harness.work_dir.join("taken")).expect("Failed to create dir");
+
+        for destination in ["taken", "../outside", "/tmp/absolute"] {
+            let output = harness
+                .run_submod(&["change", "stay-put", "--path", destination])
+                .expect("Failed to run submod");
+            assert!(
+                !output.status.success(),
+                "moving to {destination} should fail"
+            );
+        }
+        assert!(harness.file_exists("lib/stay/.git"));
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("path = \"lib/stay\""), "config: {config}");
+    }
+
+    #[test]
+    fn test_change_url_repoints_the_clone_in_place() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+
+        // The same repository, moved to a new host
+        let remote = harness
             .create_test_remote("url_orig")
-            .expect("Failed to create remote 1");
-        let remote2 = harness
-            .create_test_remote("url_new")
-            .expect("Failed to create remote 2");
-        let url1 = format!("file://{}", remote1.display());
-        let url2 = format!("file://{}", remote2.display());
+            .expect("Failed to create remote");
+        let url1 = format!("file://{}", remote.display());
+        let mirror = remote.with_file_name("url_mirror.git");
+        let url2 = format!("file://{}", mirror.to_string_lossy());
 
         harness
             .run_submod_success(&["add", &url1, "--name", "url-lib", "--path", "lib/urltest"])
             .expect("Failed to add submodule");
+        harness.git_stdout(&[
+            "clone",
+            "-q",
+            "--bare",
+            &remote.to_string_lossy(),
+            &mirror.to_string_lossy(),
+        ]);
+        harness.git_stdout(&["-C", "lib/urltest", "branch", "local-work"]);
 
-        harness
+        let stdout = harness
             .run_submod_success(&["change", "url-lib", "--url", &url2])
             .expect("Failed to change URL");
+        assert!(
+            stdout.contains("has the recorded commit"),
+            "stdout: {stdout}"
+        );
 
         let config = harness.read_config().expect("Failed to read config");
         assert!(
@@ -644,6 +860,122 @@ mod tests {
             !config.contains(&format!("url = \"{url1}\"")),
             "Config should not contain the old URL"
         );
+        let gitmodules_urls =
+            harness.git_stdout(&["config", "-f", ".gitmodules", "--get-regexp", "\\.url$"]);
+        assert!(
+            gitmodules_urls.ends_with(&url2),
+            "gitmodules: {gitmodules_urls}"
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/urltest", "remote", "get-url", "origin"]),
+            url2
+        );
+        // The clone is the same one
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/urltest", "branch", "--list", "local-work"]),
+            "local-work"
+        );
+    }
+
+    #[test]
+    fn test_change_url_refuses_a_remote_without_the_recorded_commit() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+
+        let remote = harness
+            .create_test_remote("url_keep")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        harness
+            .run_submod_success(&["add", &url, "--name", "keep-lib", "--path", "lib/keep"])
+            .expect("Failed to add submodule");
+        harness.git_stdout(&["add", "-A"]);
+        harness.git_stdout(&["commit", "-q", "-m", "Add submodule"]);
+
+        // A repository that has moved on past the recorded commit
+        harness
+            .advance_test_remote("url_keep")
+            .expect("Failed to advance remote");
+        let mirror = remote.with_file_name("url_keep_mirror.git");
+        let mirror_url = format!("file://{}", mirror.to_string_lossy());
+        harness.git_stdout(&[
+            "clone",
+            "-q",
+            "--bare",
+            &remote.to_string_lossy(),
+            &mirror.to_string_lossy(),
+        ]);
+
+        // The recorded commit isn't at a branch tip, so only a fetch can find it
+        let output = harness
+            .run_submod(&["change", "keep-lib", "--url", &mirror_url])
+            .expect("Failed to run submod");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--fetch-url"), "stderr: {stderr}");
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/keep", "remote", "get-url", "origin"]),
+            url,
+            "nothing changes"
+        );
+
+        harness
+            .run_submod_success(&["change", "keep-lib", "--url", &mirror_url, "--fetch-url"])
+            .expect("Failed to change URL after fetching");
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/keep", "remote", "get-url", "origin"]),
+            mirror_url
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/keep", "for-each-ref", "refs/submod-verify/"]),
+            "",
+            "the scratch refs are cleaned up"
+        );
+    }
+
+    #[test]
+    fn test_change_url_puts_the_staged_gitmodules_back_when_a_step_fails() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+
+        let remote = harness
+            .create_test_remote("url_roll")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        harness
+            .run_submod_success(&["add", &url, "--name", "roll-lib", "--path", "lib/roll"])
+            .expect("Failed to add submodule");
+        harness.git_stdout(&["add", "-A"]);
+        harness.git_stdout(&["commit", "-q", "-m", "Add submodule"]);
+        let mirror = remote.with_file_name("url_roll_mirror.git");
+        let mirror_url = format!("file://{}", mirror.to_string_lossy());
+        harness.git_stdout(&[
+            "clone",
+            "-q",
+            "--bare",
+            &remote.to_string_lossy(),
+            &mirror.to_string_lossy(),
+        ]);
+
+        // A held lock on the clone's config fails the last git step, after .gitmodules
+        // has been changed and staged
+        let lock = harness.work_dir.join(".git/modules/roll-lib/config.lock");
+        std::fs::write(&lock, "").expect("Failed to take the config lock");
+        let output = harness
+            .run_submod(&["change", "roll-lib", "--url", &mirror_url])
+            .expect("Failed to run submod");
+        assert!(!output.status.success());
+        std::fs::remove_file(&lock).expect("Failed to release the config lock");
+
+        assert_eq!(
+            harness.git_stdout(&["status", "--porcelain", "--", ".gitmodules"]),
+            "",
+            "the index and worktree should be back to the committed .gitmodules"
+        );
+        assert_eq!(
+            harness.git_stdout(&["config", "--get", "submodule.roll-lib.url"]),
+            url
+        );
     }
 
     // =========================================================================
@@ -920,6 +1252,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tui_needs_a_terminal() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        harness
+            .create_config("[defaults]\n")
+            .expect("Failed to create config");
+
+        // The harness captures stdout, so there's no terminal to draw on
+        let output = harness.run_submod(&["tui"]).expect("Failed to run tui");
+
+        assert_eq!(output.status.code(), Some(1), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("`submod tui` needs a terminal"),
+            "stderr: {stderr}"
+        );
+    }
+
+    #[test]
+    fn test_json_format_reports_errors_as_objects() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        harness
+            .create_config("[defaults]\n")
+            .expect("Failed to create config");
+
+        let output = harness
+            .run_submod(&["--format", "json", "reset", "does-not-exist"])
+            .expect("Failed to run reset");
+
+        assert_eq!(output.status.code(), Some(4), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let json: serde_json::Value =
+            serde_json::from_str(&stderr).expect("errors must be printed as JSON");
+        let error = &json["error"];
+        assert_eq!(error["kind"], "submodule_not_found", "stderr: {stderr}");
+        assert_eq!(error["exit_code"], 4);
+        assert_eq!(error["submodule"], "does-not-exist");
+        assert!(
+            error["message"]
+                .as_str()
+                .is_some_and(|message| message.contains("not found")),
+            "stderr: {stderr}"
+        );
+
+        harness
+            .create_config("[[[not toml")
+            .expect("Failed to create config");
+        let output = harness
+            .run_submod(&["--format", "json", "list"])
+            .expect("Failed to run list");
+        assert_eq!(output.status.code(), Some(3), "{output:?}");
+        let json: serde_json::Value =
+            serde_json::from_slice(&output.stderr).expect("errors must be printed as JSON");
+        assert_eq!(json["error"]["kind"], "config_invalid");
+    }
+
     #[test]
     fn test_disable_nonexistent_submodule_fails_with_message() {
         let harness = TestHarness::new().expect("Failed to create test harness");
@@ -1161,6 +1551,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sync_quiet_and_piped_output_contract() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+
+        let remote = harness
+            .create_test_remote("sync_quiet")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        harness
+            .create_config(&format!(
+                "[sync-quiet]\npath = \"lib/sq\"\nurl = \"{url}\"\nactive = true\n"
+            ))
+            .expect("Failed to create config");
+
+        // --quiet does the work and says nothing about it
+        let output = harness
+            .run_submod(&["--quiet", "sync"])
+            .expect("Failed to run submod");
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+        assert!(harness.work_dir.join("lib/sq/.git").exists());
+
+        // Output that isn't a terminal gets plain lines, never redrawn progress rows
+        let output = harness.run_submod(&["sync"]).expect("Failed to run submod");
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stdout.contains("✅ Updated sync-quiet"), "stdout: {stdout}");
+        assert!(
+            !stdout.contains('\x1b') && !stderr.contains('\x1b'),
+            "stdout: {stdout:?}, stderr: {stderr:?}"
+        );
+
+        let output = harness
+            .run_submod(&["update", "-q", "--verbose"])
+            .expect("Failed to run submod");
+        assert!(!output.status.success(), "--quiet and --verbose conflict");
+    }
+
     #[test]
     fn test_update_output_contract_no_submodules() {
         let harness = TestHarness::new().expect("Failed to create test harness");
@@ -255,7 +255,12 @@ impl TestHarness {
     pub fn advance_test_remote(&self, name: &str) -> Result<String, Box<dyn std::error::Error>> {
         let work_copy = self.temp_dir.path().join(format!("{name}_work"));
 
-        fs::write(work_copy.join("ADVANCE.txt"), format!("advanced {name}\n"))?;
+        // Append rather than overwrite so that every call produces a new commit
+        let mut advance_file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(work_copy.join("ADVANCE.txt"))?;
+        std::io::Write::write_all(&mut advance_file, format!("advanced {name}\n").as_bytes())?;
         self.git_cmd()
             .args(["add", "."])
             .current_dir(&work_copy)
@@ -287,6 +292,15 @@ impl TestHarness {
     pub fn run_submod(
         &self,
         args: &[&str],
+    ) -> Result<std::process::Output, Box<dyn std::error::Error>> {
+        self.run_submod_with_env(args, &[])
+    }
+
+    /// Run submod command with extra environment variables set
+    pub fn run_submod_with_env(
+        &self,
+        args: &[&str],
+        envs: &[(&str, &str)],
     ) -> Result<std::process::Output, Box<dyn std::error::Error>> {
         // NOTE: arguments containing an interior NUL byte cannot be passed to a
         // process at all — std's Command rejects them before spawn, so `.output()`
@@ -297,11 +311,73 @@ impl TestHarness {
             .current_dir(&self.work_dir)
             .env("GIT_CONFIG_GLOBAL", &self.git_config_global)
             .env("GIT_CONFIG_SYSTEM", "/dev/null")
+            .envs(envs.iter().copied())
             .output()?;
 
         Ok(output)
     }
 
+    /// Start submod without waiting for it, with extra environment variables set. It runs
+    /// in a process group of its own, so a test can interrupt it and the git processes it
+    /// starts the way a Ctrl-C would, without interrupting the test.
+    #[cfg(unix)]
+    #[allow(dead_code)] // Used by integration tests; required for test harness
+    pub fn spawn_submod_with_env(
+        &self,
+        args: &[&str],
+        envs: &[(&str, &str)],
+    ) -> Result<std::process::Child, Box<dyn std::error::Error>> {
+        use std::os::unix::process::CommandExt;
+        use std::process::Stdio;
+
+        let child = Command::new(&self.submod_bin)
+            .args(args)
+            .current_dir(&self.work_dir)
+            .env("GIT_CONFIG_GLOBAL", &self.git_config_global)
+            .env("GIT_CONFIG_SYSTEM", "/dev/null")
+            .envs(envs.iter().copied())
+            .process_group(0)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        Ok(child)
+    }
+
+    /// Run submod command in another directory, such as a linked worktree, and expect
+    /// success
+    #[allow(dead_code)] // Used by integration tests; required for test harness
+    pub fn run_submod_in(
+        &self,
+        dir: &std::path::Path,
+        args: &[&str],
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        self.run_submod_in_with_env(dir, args, &[])
+    }
+
+    /// Run submod command in another directory with extra environment variables set, and
+    /// expect success
+    #[allow(dead_code)] // Used by integration tests; required for test harness
+    pub fn run_submod_in_with_env(
+        &self,
+        dir: &std::path::Path,
+        args: &[&str],
+        envs: &[(&str, &str)],
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::new(&self.submod_bin)
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_CONFIG_GLOBAL", &self.git_config_global)
+            .env("GIT_CONFIG_SYSTEM", "/dev/null")
+            .envs(envs.iter().copied())
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            return Err(format!("Command failed:\nstdout: {stdout}\nstderr: {stderr}").into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
     /// Run submod command and expect success
     pub fn run_submod_success(&self, args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
         let output = self.run_submod(args)?;
@@ -0,0 +1,139 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for dynamic completion through `COMPLETE=<shell> submod`: the registration scripts,
+//! and completing submodule names, groups, branches, and sparse paths
+
+mod common;
+use common::TestHarness;
+
+/// A repository with one submodule, `lib`, in the groups `core` and `tools`
+fn harness_with_submodule() -> TestHarness {
+    let harness = TestHarness::new().expect("Failed to create test harness");
+    harness.init_git_repo().expect("Failed to init git repo");
+    let remote = harness
+        .create_test_remote("lib")
+        .expect("Failed to create remote");
+    harness
+        .run_submod_success(&[
+            "add",
+            &format!("file://{}", remote.display()),
+            "--name",
+            "lib",
+            "--path",
+            "lib/lib",
+            "--group",
+            "core,tools",
+        ])
+        .expect("Failed to add submodule");
+    harness
+}
+
+/// The candidates for the last word of `line`, as fish lists them: one per line, without
+/// their descriptions
+fn complete(harness: &TestHarness, line: &[&str]) -> Vec<String> {
+    let mut args = vec!["--", "submod"];
+    args.extend_from_slice(line);
+    let output = harness
+        .run_submod_with_env(&args, &[("COMPLETE", "fish")])
+        .expect("Failed to run completion");
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.split('\t').next().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completes_submodule_names_and_groups() {
+        let harness = harness_with_submodule();
+
+        let candidates = complete(&harness, &["reset", ""]);
+        assert!(candidates.contains(&"lib".to_string()), "{candidates:?}");
+        // After a comma, in a comma-separated list
+        let candidates = complete(&harness, &["absorb", "other,l"]);
+        assert_eq!(candidates, ["other,lib"]);
+        // Only configured names
+        assert!(complete(&harness, &["vendor", "x"]).is_empty());
+
+        let candidates = complete(&harness, &["foreach", "--group", ""]);
+        assert_eq!(candidates, ["core", "tools"]);
+    }
+
+    #[test]
+    fn test_completes_branches_and_sparse_paths_from_the_clone() {
+        let harness = harness_with_submodule();
+
+        let candidates = complete(&harness, &["change", "lib", "--branch", ""]);
+        assert!(candidates.contains(&"main".to_string()), "{candidates:?}");
+        assert!(!candidates.contains(&"HEAD".to_string()), "{candidates:?}");
+
+        let candidates = complete(&harness, &["change", "lib", "--sparse-paths", ""]);
+        for dir in ["docs/", "include/", "src/"] {
+            assert!(candidates.contains(&dir.to_string()), "{candidates:?}");
+        }
+        let candidates = complete(&harness, &["change", "lib", "-x", "docs/,src/"]);
+        assert_eq!(candidates, ["docs/,src/main.c"]);
+
+        // A local remote being added
+        let remote = harness.temp_dir.path().join("lib.git");
+        let url = format!("file://{}", remote.display());
+        let candidates = complete(&harness, &["add", &url, "--branch", "m"]);
+        assert_eq!(candidates, ["main"]);
+
+        // Nothing to read for a submodule that isn't named
+        assert!(complete(&harness, &["change", "nope", "--branch", ""]).is_empty());
+    }
+
+    #[test]
+    fn test_nushell_gets_json_candidates() {
+        let harness = harness_with_submodule();
+
+        let output = harness
+            .run_submod_with_env(&["--", "submod", "delete", "l"], &[("COMPLETE", "nushell")])
+            .expect("Failed to run completion");
+        assert!(output.status.success(), "{output:?}");
+        let candidates: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("nushell candidates must be JSON");
+        assert_eq!(
+            candidates,
+            serde_json::json!([{ "value": "lib", "description": "lib/lib" }])
+        );
+    }
+
+    #[test]
+    fn test_every_shell_gets_a_registration_script() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+
+        for shell in [
+            "bash",
+            "elvish",
+            "fish",
+            "powershell",
+            "zsh",
+            "nushell",
+            "nu",
+        ] {
+            let output = harness
+                .run_submod_with_env(&[], &[("COMPLETE", shell)])
+                .expect("Failed to run registration");
+            assert!(output.status.success(), "{shell}: {output:?}");
+            let script = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                script.contains("COMPLETE") && script.contains("submod"),
+                "{shell}: {script}"
+            );
+        }
+
+        let output = harness
+            .run_submod_with_env(&[], &[("COMPLETE", "tcsh")])
+            .expect("Failed to run registration");
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("unknown shell `tcsh`"));
+    }
+}
@@ -479,13 +479,13 @@ active = true
         assert_eq!(config.defaults.ignore, Some(SerializableIgnore::Dirty));
 
         // Test sync_with_git_config and load_with_git_sync
-        let mut git_ops =
+        let mut git2_ops =
             Git2Operations::new(Some(&harness.work_dir)).expect("Failed to open git_ops");
 
         // Initially, config has a submodule but gitmodules has nothing.
         // We sync config with git config, which should write the submodule to .gitmodules
         config
-            .sync_with_git_config(&mut git_ops)
+            .sync_with_git_config(&mut git2_ops)
             .expect("Failed to sync_with_git_config");
 
         // Verify .gitmodules was written
@@ -494,7 +494,7 @@ active = true
 
         // Now test load_with_git_sync
         let loaded_sync_config = submod::Config::default()
-            .load_with_git_sync(&config_path, &mut git_ops, submod::Config::default())
+            .load_with_git_sync(&config_path, &mut git2_ops, submod::Config::default())
             .expect("Failed to load_with_git_sync");
         assert_eq!(
             loaded_sync_config.defaults.ignore,
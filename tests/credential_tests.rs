@@ -0,0 +1,801 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for per-submodule credentials (`ssh_key`, `credential_helper`, `http_extra_header_env`)
+//!
+//! Remotes are served by local stand-ins: an `ssh` wrapper that runs the requested
+//! `git-upload-pack` locally and logs its arguments, and a small HTTP server in front of
+//! `git http-backend` that refuses requests without the expected `Authorization` header.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+mod common;
+use common::TestHarness;
+
+/// Write an executable `ssh` stand-in that logs its arguments to `log` and runs the
+/// remote command (everything after the host) locally. git passes that command as one
+/// argument and gix as several; gix's `-G` probe for the ssh variant just succeeds.
+#[cfg(unix)]
+fn write_fake_ssh(dir: &Path, log: &Path) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let script = dir.join("fake-ssh");
+    fs::write(
+        &script,
+        format!(
+            r#"#!/bin/sh
+echo "$@" >> '{}'
+while [ $# -gt 0 ]; do
+    case "$1" in
+        -G) exit 0 ;;
+        -i|-o|-p|-l) shift 2 ;;
+        -*) shift ;;
+        *) break ;;
+    esac
+done
+shift
+exec sh -c "$*"
+"#,
+            log.display()
+        ),
+    )
+    .expect("Failed to write fake ssh");
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))
+        .expect("Failed to make fake ssh executable");
+    script
+}
+
+/// Write `script` to `path` and make it executable
+#[cfg(unix)]
+fn write_executable(path: &Path, script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    fs::write(path, script).expect("Failed to write script");
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .expect("Failed to make script executable");
+}
+
+/// Serve the bare repository `root` over smart HTTP on a local port, through
+/// `git http-backend`, answering 401 unless a request carries `expected_auth` as its
+/// `Authorization` header. Returns the base URL and the `Authorization` values seen so far.
+fn serve_http(root: &Path, expected_auth: &str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind test server");
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let root = root.to_path_buf();
+    let expected = expected_auth.to_string();
+    let seen_by_server = Arc::clone(&seen);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).is_err() {
+                continue;
+            }
+            let mut headers = HashMap::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+                }
+            }
+            let body = read_body(&mut reader, &headers);
+            let auth = headers.get("authorization");
+            if let Some(auth) = auth {
+                seen_by_server.lock().unwrap().push(auth.clone());
+            }
+            let response = if auth == Some(&expected) {
+                http_backend(&root, &request_line, &headers, &body)
+            } else {
+                b"HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Basic realm=\"submod\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+            };
+            let _ = stream.write_all(&response);
+        }
+    });
+    (base, seen)
+}
+
+/// Read a request's body, sent with a `Content-Length` or in chunks
+fn read_body(reader: &mut impl BufRead, headers: &HashMap<String, String>) -> Vec<u8> {
+    let chunked = headers
+        .get("transfer-encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"));
+    if !chunked {
+        let length = headers
+            .get("content-length")
+            .and_then(|length| length.parse().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        let _ = reader.read_exact(&mut body);
+        return body;
+    }
+    let mut body = Vec::new();
+    loop {
+        let mut size = String::new();
+        if reader.read_line(&mut size).unwrap_or(0) == 0 {
+            break;
+        }
+        let size = usize::from_str_radix(size.trim(), 16).unwrap_or(0);
+        // Each chunk ends in CRLF
+        let mut chunk = vec![0; size + 2];
+        if size == 0 || reader.read_exact(&mut chunk).is_err() {
+            break;
+        }
+        body.extend_from_slice(&chunk[..size]);
+    }
+    body
+}
+
+/// Answer a request for the repository `root` with `git http-backend`'s CGI response
+fn http_backend(
+    root: &Path,
+    request_line: &str,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+) -> Vec<u8> {
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("GET");
+    let target = parts.next().unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let header = |name: &str| headers.get(name).cloned().unwrap_or_default();
+    let mut child = std::process::Command::new("git")
+        .arg("http-backend")
+        .env("GIT_PROJECT_ROOT", root)
+        .env("GIT_HTTP_EXPORT_ALL", "1")
+        .env("REQUEST_METHOD", method)
+        .env("PATH_INFO", path)
+        .env("QUERY_STRING", query)
+        .env("CONTENT_TYPE", header("content-type"))
+        .env("HTTP_CONTENT_ENCODING", header("content-encoding"))
+        .env("GIT_PROTOCOL", header("git-protocol"))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to run git http-backend");
+    child.stdin.take().unwrap().write_all(body).unwrap();
+    let output = child.wait_with_output().expect("git http-backend failed");
+
+    // CGI headers, a blank line, then the body
+    let split = output
+        .stdout
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|at| (at, at + 4))
+        .or_else(|| {
+            output
+                .stdout
+                .windows(2)
+                .position(|window| window == b"\n\n")
+                .map(|at| (at, at + 2))
+        })
+        .unwrap_or((0, 0));
+    let cgi_headers = String::from_utf8_lossy(&output.stdout[..split.0]).into_owned();
+    let content = &output.stdout[split.1..];
+    let mut status = "200 OK".to_string();
+    let mut response_headers = String::new();
+    for line in cgi_headers.lines() {
+        match line.split_once(':') {
+            Some((name, value)) if name.eq_ignore_ascii_case("status") => {
+                status = value.trim().to_string();
+            }
+            Some(_) => {
+                response_headers.push_str(line.trim_end());
+                response_headers.push_str("\r\n");
+            }
+            None => {}
+        }
+    }
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\n{response_headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+        content.len()
+    )
+    .into_bytes();
+    response.extend_from_slice(content);
+    response
+}
+
+/// Deinitialize a submodule and remove its git directory, leaving it registered but not
+/// cloned, as in a fresh clone of the superproject
+fn forget_clone(harness: &TestHarness, name: &str, path: &str) {
+    let output = std::process::Command::new("git")
+        .args(["submodule", "deinit", "--force", "--", path])
+        .current_dir(&harness.work_dir)
+        .output()
+        .expect("Failed to run git submodule deinit");
+    assert!(output.status.success(), "{output:?}");
+    let modules = harness.work_dir.join(".git/modules").join(name);
+    if modules.exists() {
+        fs::remove_dir_all(modules).expect("Failed to remove the git directory");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_add_over_ssh_uses_configured_key() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("ssh_lib")
+            .expect("Failed to create remote");
+
+        let log = harness.temp_dir.path().join("ssh.log");
+        let fake_ssh = write_fake_ssh(harness.temp_dir.path(), &log);
+        let key = harness.temp_dir.path().join("deploy_key");
+        fs::write(&key, "not a real key\n").expect("Failed to write key");
+
+        // `.invalid` never resolves, so only the ssh stand-in can reach the remote
+        let url = format!("ssh://git.invalid{}", remote.display());
+        let output = harness
+            .run_submod_with_env(
+                &[
+                    "add",
+                    &url,
+                    "--name",
+                    "ssh-lib",
+                    "--path",
+                    "lib/ssh",
+                    "--branch",
+                    "main",
+                    "--ssh-key",
+                    key.to_str().unwrap(),
+                ],
+                &[("GIT_SSH_COMMAND", fake_ssh.to_str().unwrap())],
+            )
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "add over ssh failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(harness.file_exists("lib/ssh/LICENSE"));
+
+        let calls = fs::read_to_string(&log).expect("fake ssh was never invoked");
+        assert!(
+            calls.contains(&format!("-i {}", key.display()))
+                && calls.contains("IdentitiesOnly=yes"),
+            "ssh should be offered only the configured key; calls: {calls}"
+        );
+
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(
+            config.contains(&format!("ssh_key = \"{}\"", key.display())),
+            "config: {config}"
+        );
+    }
+
+    #[test]
+    fn test_add_over_http_sends_header_from_env() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("header_lib")
+            .expect("Failed to create remote");
+        let (base, seen) = serve_http(&remote, "Bearer s3cret-token");
+        let url = format!("{base}/");
+
+        let args = [
+            "add",
+            &url,
+            "--name",
+            "header-lib",
+            "--path",
+            "lib/header",
+            "--branch",
+            "main",
+            "--http-extra-header-env",
+            "SUBMOD_TEST_AUTH_HEADER",
+        ];
+
+        // Without the variable there is nothing to send, and that is an error
+        let output = harness.run_submod(&args).expect("Failed to run submod");
+        assert!(!output.status.success());
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("SUBMOD_TEST_AUTH_HEADER"),
+            "error should name the missing variable: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let output = harness
+            .run_submod_with_env(
+                &args,
+                &[(
+                    "SUBMOD_TEST_AUTH_HEADER",
+                    "Authorization: Bearer s3cret-token",
+                )],
+            )
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "add over http failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(harness.file_exists("lib/header/LICENSE"));
+        assert!(
+            seen.lock()
+                .unwrap()
+                .iter()
+                .any(|auth| auth == "Bearer s3cret-token")
+        );
+
+        // Only the variable name is persisted, never the header itself
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(
+            config.contains("http_extra_header_env = \"SUBMOD_TEST_AUTH_HEADER\""),
+            "config: {config}"
+        );
+        assert!(!config.contains("s3cret-token"));
+        let git_config = fs::read_to_string(harness.work_dir.join(".git/config"))
+            .expect("Failed to read .git/config");
+        assert!(!git_config.contains("s3cret-token"));
+    }
+
+    #[test]
+    fn test_change_url_checks_the_new_remote_with_configured_header() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("moving_lib")
+            .expect("Failed to create remote");
+        let (base, _) = serve_http(&remote, "Bearer s3cret-token");
+        let env = [(
+            "SUBMOD_TEST_AUTH_HEADER",
+            "Authorization: Bearer s3cret-token",
+        )];
+        let output = harness
+            .run_submod_with_env(
+                &[
+                    "add",
+                    &format!("{base}/"),
+                    "--name",
+                    "moving-lib",
+                    "--path",
+                    "lib/moving",
+                    "--branch",
+                    "main",
+                    "--http-extra-header-env",
+                    "SUBMOD_TEST_AUTH_HEADER",
+                ],
+                &env,
+            )
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "add over http failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // The same repository at a new address, behind the same header
+        let (new_base, seen) = serve_http(&remote, "Bearer s3cret-token");
+        let new_url = format!("{new_base}/");
+        let output = harness
+            .run_submod_with_env(&["change", "moving-lib", "--url", &new_url], &env)
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "change --url failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(
+            seen.lock()
+                .unwrap()
+                .iter()
+                .any(|auth| auth == "Bearer s3cret-token")
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/moving", "remote", "get-url", "origin"]),
+            new_url
+        );
+    }
+
+    #[test]
+    fn test_adopt_fetches_with_configured_header() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("adopted_lib")
+            .expect("Failed to create remote");
+        // A copy of upstream's files, without its history
+        harness.git_stdout(&["clone", "-q", &remote.to_string_lossy(), "lib/adopted"]);
+        fs::remove_dir_all(harness.work_dir.join("lib/adopted/.git"))
+            .expect("Failed to remove .git");
+        let (base, seen) = serve_http(&remote, "Bearer s3cret-token");
+
+        let output = harness
+            .run_submod_with_env(
+                &[
+                    "adopt",
+                    "lib/adopted",
+                    "--url",
+                    &format!("{base}/"),
+                    "--http-extra-header-env",
+                    "SUBMOD_TEST_AUTH_HEADER",
+                ],
+                &[(
+                    "SUBMOD_TEST_AUTH_HEADER",
+                    "Authorization: Bearer s3cret-token",
+                )],
+            )
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "adopt over http failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(
+            seen.lock()
+                .unwrap()
+                .iter()
+                .any(|auth| auth == "Bearer s3cret-token")
+        );
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(
+            config.contains("http_extra_header_env = \"SUBMOD_TEST_AUTH_HEADER\""),
+            "config: {config}"
+        );
+    }
+
+    #[test]
+    fn test_add_over_http_uses_credential_helper() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("helper_lib")
+            .expect("Failed to create remote");
+        // base64("deploy:hunter2")
+        let (base, seen) = serve_http(&remote, "Basic ZGVwbG95Omh1bnRlcjI=");
+        let url = format!("{base}/");
+        // An installed helper, which git runs as `git credential-deploy`
+        let bin = harness.temp_dir.path().join("bin");
+        fs::create_dir(&bin).expect("Failed to create bin directory");
+        write_executable(
+            &bin.join("git-credential-deploy"),
+            "#!/bin/sh\necho username=deploy\necho password=hunter2\n",
+        );
+        let path = format!(
+            "{}:{}",
+            bin.display(),
+            std::env::var("PATH").unwrap_or_default()
+        );
+        let add = |helper: &str| {
+            harness
+                .run_submod_with_env(
+                    &[
+                        "add",
+                        &url,
+                        "--name",
+                        "helper-lib",
+                        "--path",
+                        "lib/helper",
+                        "--branch",
+                        "main",
+                        "--credential-helper",
+                        helper,
+                    ],
+                    &[("PATH", &path)],
+                )
+                .expect("Failed to run submod")
+        };
+
+        // A helper that runs a command would run it for anyone who clones the superproject
+        let output = add("!f() { echo username=deploy; echo password=hunter2; }; f");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("runs a command"), "stderr: {stderr}");
+        assert!(!harness.file_exists("lib/helper/LICENSE"));
+
+        let output = add("deploy");
+        assert!(
+            output.status.success(),
+            "add over http failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(harness.file_exists("lib/helper/LICENSE"));
+        assert!(
+            seen.lock()
+                .unwrap()
+                .iter()
+                .any(|auth| auth == "Basic ZGVwbG95Omh1bnRlcjI=")
+        );
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("credential_helper = "), "config: {config}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_init_and_unshallow_over_ssh_use_configured_key() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("ssh_init_lib")
+            .expect("Failed to create remote");
+
+        let log = harness.temp_dir.path().join("ssh.log");
+        let fake_ssh = write_fake_ssh(harness.temp_dir.path(), &log);
+        let key = harness.temp_dir.path().join("deploy_key");
+        fs::write(&key, "not a real key\n").expect("Failed to write key");
+        let env = [("GIT_SSH_COMMAND", fake_ssh.to_str().unwrap())];
+        let offered_key = |log: &Path| {
+            let calls = fs::read_to_string(log).expect("fake ssh was never invoked");
+            assert!(
+                calls.contains(&format!("-i {}", key.display()))
+                    && calls.contains("IdentitiesOnly=yes"),
+                "ssh should be offered only the configured key; calls: {calls}"
+            );
+        };
+
+        // gix asks for a user when the URL has none
+        let url = format!("ssh://git@git.invalid{}", remote.display());
+        let output = harness
+            .run_submod_with_env(
+                &[
+                    "add",
+                    &url,
+                    "--name",
+                    "ssh-lib",
+                    "--path",
+                    "lib/ssh",
+                    "--branch",
+                    "main",
+                    "--depth",
+                    "1",
+                    "--ssh-key",
+                    key.to_str().unwrap(),
+                ],
+                &env,
+            )
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "add over ssh failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Initializing a registered submodule clones it again
+        forget_clone(&harness, "ssh-lib", "lib/ssh");
+        fs::remove_file(&log).expect("Failed to clear ssh log");
+        let output = harness
+            .run_submod_with_env(&["init"], &env)
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "init over ssh failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(harness.file_exists("lib/ssh/LICENSE"));
+        offered_key(&log);
+
+        assert!(
+            harness
+                .work_dir
+                .join(".git/modules/ssh-lib/shallow")
+                .exists()
+        );
+
+        fs::remove_file(&log).expect("Failed to clear ssh log");
+        let output = harness
+            .run_submod_with_env(&["unshallow", "ssh-lib"], &env)
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "unshallow over ssh failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(
+            !harness
+                .work_dir
+                .join(".git/modules/ssh-lib/shallow")
+                .exists()
+        );
+        offered_key(&log);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_linked_worktree_fetches_into_main_clone_with_configured_key() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("ssh_worktree_lib")
+            .expect("Failed to create remote");
+
+        let log = harness.temp_dir.path().join("ssh.log");
+        let fake_ssh = write_fake_ssh(harness.temp_dir.path(), &log);
+        let key = harness.temp_dir.path().join("deploy_key");
+        fs::write(&key, "not a real key\n").expect("Failed to write key");
+        let env = [("GIT_SSH_COMMAND", fake_ssh.to_str().unwrap())];
+
+        let url = format!("ssh://git@git.invalid{}", remote.display());
+        let output = harness
+            .run_submod_with_env(
+                &[
+                    "add",
+                    &url,
+                    "--name",
+                    "ssh-lib",
+                    "--path",
+                    "lib/ssh",
+                    "--branch",
+                    "main",
+                    "--ssh-key",
+                    key.to_str().unwrap(),
+                ],
+                &env,
+            )
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "add over ssh failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // The superproject records a commit the main worktree's clone doesn't have yet
+        let recorded = harness
+            .advance_test_remote("ssh_worktree_lib")
+            .expect("Failed to advance remote");
+        harness.git_stdout(&[
+            "update-index",
+            "--cacheinfo",
+            &format!("160000,{recorded},lib/ssh"),
+        ]);
+        harness.git_stdout(&["add", "submod.toml"]);
+        harness.git_stdout(&["commit", "-m", "Add lib"]);
+        let worktree = harness.temp_dir.path().join("feature");
+        let worktree_str = worktree.to_str().expect("utf-8 path");
+        harness.git_stdout(&["worktree", "add", "--quiet", "-b", "feature", worktree_str]);
+
+        fs::remove_file(&log).expect("Failed to clear ssh log");
+        harness
+            .run_submod_in_with_env(&worktree, &["init"], &env)
+            .expect("Failed to init in the worktree");
+        assert_eq!(
+            harness.git_stdout(&["-C", worktree_str, "-C", "lib/ssh", "rev-parse", "HEAD"]),
+            recorded
+        );
+        let calls = fs::read_to_string(&log).expect("fake ssh was never invoked");
+        assert!(
+            calls.contains(&format!("-i {}", key.display()))
+                && calls.contains("IdentitiesOnly=yes"),
+            "ssh should be offered only the configured key; calls: {calls}"
+        );
+    }
+
+    #[test]
+    fn test_init_and_unshallow_over_http_send_header_from_env() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("header_init_lib")
+            .expect("Failed to create remote");
+        let (base, seen) = serve_http(&remote, "Bearer s3cret-token");
+        let url = format!("{base}/");
+        let env = [(
+            "SUBMOD_TEST_AUTH_HEADER",
+            "Authorization: Bearer s3cret-token",
+        )];
+
+        let output = harness
+            .run_submod_with_env(
+                &[
+                    "add",
+                    &url,
+                    "--name",
+                    "header-lib",
+                    "--path",
+                    "lib/header",
+                    "--branch",
+                    "main",
+                    "--depth",
+                    "1",
+                    "--http-extra-header-env",
+                    "SUBMOD_TEST_AUTH_HEADER",
+                ],
+                &env,
+            )
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "add over http failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        forget_clone(&harness, "header-lib", "lib/header");
+        seen.lock().unwrap().clear();
+
+        // Without the header the server refuses the clone
+        let output = harness.run_submod(&["init"]).expect("Failed to run submod");
+        assert!(!output.status.success());
+        assert!(!harness.file_exists("lib/header/LICENSE"));
+
+        let output = harness
+            .run_submod_with_env(&["init"], &env)
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "init over http failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(harness.file_exists("lib/header/LICENSE"));
+        assert!(
+            seen.lock()
+                .unwrap()
+                .iter()
+                .any(|auth| auth == "Bearer s3cret-token")
+        );
+
+        assert!(
+            harness
+                .work_dir
+                .join(".git/modules/header-lib/shallow")
+                .exists()
+        );
+
+        seen.lock().unwrap().clear();
+        let output = harness
+            .run_submod_with_env(&["unshallow", "header-lib"], &env)
+            .expect("Failed to run submod");
+        assert!(
+            output.status.success(),
+            "unshallow over http failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(
+            !harness
+                .work_dir
+                .join(".git/modules/header-lib/shallow")
+                .exists()
+        );
+        assert!(
+            seen.lock()
+                .unwrap()
+                .iter()
+                .any(|auth| auth == "Bearer s3cret-token")
+        );
+    }
+
+    #[test]
+    fn test_credentials_round_trip_through_config() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        harness
+            .create_config(
+                r#"[private-lib]
+path = "lib/private"
+url = "git@example.com:org/private.git"
+ssh_key = "~/.ssh/deploy_private"
+credential_helper = "store"
+http_extra_header_env = "PRIVATE_LIB_HEADER"
+"#,
+            )
+            .expect("Failed to write config");
+
+        // Rewriting the config must keep all three keys
+        harness
+            .run_submod_success(&["disable", "private-lib"])
+            .expect("Failed to disable submodule");
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(
+            config.contains("ssh_key = \"~/.ssh/deploy_private\""),
+            "config: {config}"
+        );
+        assert!(
+            config.contains("credential_helper = \"store\""),
+            "config: {config}"
+        );
+        assert!(
+            config.contains("http_extra_header_env = \"PRIVATE_LIB_HEADER\""),
+            "config: {config}"
+        );
+    }
+}
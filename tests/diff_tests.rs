@@ -0,0 +1,129 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `submod diff`, which summarizes submodule changes between superproject revisions
+
+mod common;
+use common::TestHarness;
+
+/// A superproject with three commits: the initial one, one adding the `tracked` submodule
+/// at `lib/tracked`, and one bumping it to a newer commit. Returns the two recorded commits.
+fn superproject_with_bumped_submodule(harness: &TestHarness) -> (String, String) {
+    harness.init_git_repo().expect("Failed to init git repo");
+    let remote = harness
+        .create_test_remote("tracked_lib")
+        .expect("Failed to create remote");
+    let url = format!("file://{}", remote.display());
+    harness
+        .run_submod_success(&[
+            "add",
+            &url,
+            "--name",
+            "tracked",
+            "--path",
+            "lib/tracked",
+            "--branch",
+            "main",
+        ])
+        .expect("Failed to add submodule");
+    harness.git_stdout(&["add", "-A"]);
+    harness.git_stdout(&["commit", "-m", "Add submodule"]);
+    let added = harness.git_stdout(&["-C", "lib/tracked", "rev-parse", "HEAD"]);
+
+    let bumped = harness
+        .advance_test_remote("tracked_lib")
+        .expect("Failed to advance remote");
+    harness.git_stdout(&["-C", "lib/tracked", "fetch", "origin"]);
+    harness.git_stdout(&["-C", "lib/tracked", "checkout", &bumped]);
+    harness.git_stdout(&["add", "lib/tracked"]);
+    harness.git_stdout(&["commit", "-m", "Bump submodule"]);
+    (added, bumped)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_between_revisions_lists_commits_and_diffstat() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (added, bumped) = superproject_with_bumped_submodule(&harness);
+
+        let stdout = harness
+            .run_submod_success(&["diff", "HEAD~1", "HEAD"])
+            .expect("Failed to run diff");
+        assert!(
+            stdout.contains(&format!(
+                "📦 tracked (updated at lib/tracked) {}..{}",
+                &added[..7],
+                &bumped[..7]
+            )),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains(&format!("{} Advance remote", &bumped[..7])),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains("1 file changed, 1 insertion(+), 0 deletions(-)"),
+            "stdout: {stdout}"
+        );
+
+        // Nothing changed since the last commit
+        let stdout = harness
+            .run_submod_success(&["diff"])
+            .expect("Failed to run diff");
+        assert!(
+            stdout.contains("No submodule changes between HEAD and the working tree"),
+            "stdout: {stdout}"
+        );
+    }
+
+    #[test]
+    fn test_diff_reports_added_and_moved_submodules() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (_, bumped) = superproject_with_bumped_submodule(&harness);
+
+        let stdout = harness
+            .run_submod_success(&["diff", "HEAD~2", "HEAD"])
+            .expect("Failed to run diff");
+        assert!(
+            stdout.contains(&format!(
+                "➕ tracked (added at lib/tracked) {}",
+                &bumped[..7]
+            )),
+            "stdout: {stdout}"
+        );
+
+        harness.git_stdout(&["mv", "lib/tracked", "lib/moved"]);
+        harness.git_stdout(&["commit", "-m", "Move submodule"]);
+        let stdout = harness
+            .run_submod_success(&["diff", "HEAD~1", "HEAD", "--format", "markdown"])
+            .expect("Failed to run diff");
+        assert!(
+            stdout.contains("moved lib/tracked → lib/moved"),
+            "stdout: {stdout}"
+        );
+    }
+
+    #[test]
+    fn test_diff_against_the_working_tree_as_json() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (_, bumped) = superproject_with_bumped_submodule(&harness);
+
+        // Move the checkout back without committing
+        harness.git_stdout(&["-C", "lib/tracked", "checkout", "HEAD~1"]);
+        let stdout = harness
+            .run_submod_success(&["diff", "--format", "json"])
+            .expect("Failed to run diff");
+        let json: serde_json::Value = serde_json::from_str(&stdout).expect("diff must print JSON");
+        assert_eq!(json["from"], "HEAD");
+        let change = &json["submodules"][0];
+        assert_eq!(change["name"], "tracked");
+        assert_eq!(change["kind"], "updated");
+        assert_eq!(change["old_oid"], bumped.as_str());
+        assert_eq!(change["commits"].as_array().unwrap().len(), 0);
+        assert_eq!(change["rewound"][0]["subject"], "Advance remote");
+    }
+}
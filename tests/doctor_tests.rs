@@ -0,0 +1,142 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `submod doctor`, which finds drift between submod.toml, .gitmodules and .git/config
+
+mod common;
+use common::TestHarness;
+
+/// A superproject with the `mylib` submodule at `lib/mylib`, tracking `main`.
+///
+/// `add` records the branch in submod.toml and .git/config but not in .gitmodules, so
+/// the fresh superproject already has one drifting field.
+fn harness_with_submodule() -> TestHarness {
+    let harness = TestHarness::new().expect("Failed to create test harness");
+    harness.init_git_repo().expect("Failed to init git repo");
+    let remote = harness
+        .create_test_remote("doctor_lib")
+        .expect("Failed to create remote");
+    let url = format!("file://{}", remote.display());
+    harness
+        .run_submod_success(&[
+            "add",
+            &url,
+            "--name",
+            "mylib",
+            "--path",
+            "lib/mylib",
+            "--branch",
+            "main",
+        ])
+        .expect("Failed to add submodule");
+    harness
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doctor_reports_drift_and_fixes_it_from_toml() {
+        let harness = harness_with_submodule();
+
+        let output = harness
+            .run_submod(&["doctor"])
+            .expect("Failed to run submod");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!output.status.success(), "drift must fail doctor");
+        assert_eq!(output.status.code(), Some(8));
+        assert!(stdout.contains("❌ mylib (lib/mylib)"), "stdout: {stdout}");
+        assert!(
+            stdout
+                .contains("branch: submod.toml = main, .gitmodules = (unset), .git/config = main"),
+            "stdout: {stdout}"
+        );
+        assert!(stdout.contains("Found 1 problem"), "stdout: {stdout}");
+
+        let stdout = harness
+            .run_submod_success(&["doctor", "--fix"])
+            .expect("Failed to fix drift");
+        assert!(
+            stdout.contains("set branch in .gitmodules to main"),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains("submod.toml, .gitmodules, and .git/config agree"),
+            "stdout: {stdout}"
+        );
+        assert_eq!(
+            harness.git_stdout(&["config", "-f", ".gitmodules", "submodule.mylib.branch"]),
+            "main"
+        );
+        harness
+            .run_submod_success(&["doctor"])
+            .expect("doctor must pass once fixed");
+    }
+
+    #[test]
+    fn test_doctor_fixes_from_gitmodules() {
+        let harness = harness_with_submodule();
+        harness.git_stdout(&[
+            "config",
+            "-f",
+            ".gitmodules",
+            "submodule.mylib.branch",
+            "develop",
+        ]);
+        harness.git_stdout(&[
+            "config",
+            "-f",
+            ".gitmodules",
+            "submodule.mylib.ignore",
+            "dirty",
+        ]);
+
+        let stdout = harness
+            .run_submod_success(&["doctor", "--fix", "--source", "gitmodules"])
+            .expect("Failed to fix drift");
+        assert!(
+            stdout.contains("ignore: submod.toml = none, .gitmodules = dirty"),
+            "stdout: {stdout}"
+        );
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("branch = \"develop\""), "config: {config}");
+        assert!(config.contains("ignore = \"dirty\""), "config: {config}");
+        assert_eq!(
+            harness.git_stdout(&["config", "submodule.mylib.branch"]),
+            "develop"
+        );
+    }
+
+    #[test]
+    fn test_doctor_reports_orphans_and_stale_registrations() {
+        let harness = harness_with_submodule();
+        harness
+            .run_submod_success(&["doctor", "--fix"])
+            .expect("Failed to fix drift");
+
+        let orphan = harness.work_dir.join(".git/modules/lib/old");
+        std::fs::create_dir_all(orphan.join("objects")).expect("Failed to create orphan");
+        std::fs::write(orphan.join("HEAD"), "ref: refs/heads/main\n")
+            .expect("Failed to create orphan");
+        harness.git_stdout(&[
+            "config",
+            "submodule.lib/gone.url",
+            "https://example.com/gone",
+        ]);
+
+        let output = harness
+            .run_submod(&["doctor"])
+            .expect("Failed to run submod");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!output.status.success(), "problems must fail doctor");
+        assert!(
+            stdout.contains(".git/modules/lib/old belongs to no submodule"),
+            "stdout: {stdout}"
+        );
+        assert!(stdout.contains("submodule.lib/gone"), "stdout: {stdout}");
+        assert!(!stdout.contains("mylib"), "stdout: {stdout}");
+        assert!(stdout.contains("Found 2 problems"), "stdout: {stdout}");
+    }
+}
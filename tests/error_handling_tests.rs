@@ -40,15 +40,15 @@ mod tests {
         let harness = TestHarness::new().expect("Failed to create test harness");
         harness.init_git_repo().expect("Failed to init git repo");
 
-        // Try various invalid URLs
+        // Try various invalid URLs; hosts that can't be reached are network failures
         let invalid_urls = vec![
-            "not-a-url",
-            "http://nonexistent.domain.invalid/repo.git",
-            "file:///nonexistent/path.git",
-            "git@invalid-host:user/repo.git",
+            ("not-a-url", 1),
+            ("http://nonexistent.domain.invalid/repo.git", 5),
+            ("file:///nonexistent/path.git", 1),
+            ("git@invalid-host:user/repo.git", 5),
         ];
 
-        for invalid_url in invalid_urls {
+        for (invalid_url, exit_code) in invalid_urls {
             let output = harness
                 .run_submod(&[
                     "add",
@@ -61,7 +61,7 @@ mod tests {
                 .expect("Failed to run submod");
 
             assert!(!output.status.success());
-            assert_eq!(output.status.code(), Some(1));
+            assert_eq!(output.status.code(), Some(exit_code), "{invalid_url}");
             let stderr = String::from_utf8_lossy(&output.stderr);
             assert!(stderr.contains("Failed to add submodule") || stderr.contains("clone failed"));
         }
@@ -202,7 +202,7 @@ mod tests {
                 .run_submod(&["check"])
                 .expect("Failed to run submod");
             assert!(!output.status.success());
-            assert_eq!(output.status.code(), Some(1));
+            assert_eq!(output.status.code(), Some(3));
 
             let stderr = String::from_utf8_lossy(&output.stderr);
             assert!(
@@ -230,7 +230,7 @@ mod tests {
             match operation[0] {
                 "reset" => {
                     assert!(!output.status.success());
-                    assert_eq!(output.status.code(), Some(1));
+                    assert_eq!(output.status.code(), Some(4));
                     let stderr = String::from_utf8_lossy(&output.stderr);
                     assert!(stderr.contains("not found") || stderr.contains("Failed to reset"));
                 }
@@ -451,7 +451,7 @@ active = true
             .expect("Failed to run submod");
 
         assert!(!output.status.success());
-        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(output.status.code(), Some(5));
         let stderr = String::from_utf8_lossy(&output.stderr);
         assert!(
             stderr.contains("Failed to add submodule")
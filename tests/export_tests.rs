@@ -0,0 +1,101 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `submod export`, which writes the submodules as another tool's manifest
+
+mod common;
+use common::TestHarness;
+
+/// A superproject with the `tracked` submodule at `lib/tracked`, added in one commit and
+/// bumped in the next. Returns the remote's URL and the two recorded commits.
+fn superproject_with_bumped_submodule(harness: &TestHarness) -> (String, String, String) {
+    harness.init_git_repo().expect("Failed to init git repo");
+    let remote = harness
+        .create_test_remote("tracked_lib")
+        .expect("Failed to create remote");
+    let url = format!("file://{}", remote.display());
+    harness
+        .run_submod_success(&[
+            "add",
+            &url,
+            "--name",
+            "tracked",
+            "--path",
+            "lib/tracked",
+            "--branch",
+            "main",
+        ])
+        .expect("Failed to add submodule");
+    harness.git_stdout(&["add", "-A"]);
+    harness.git_stdout(&["commit", "-m", "Add submodule"]);
+    let added = harness.git_stdout(&["-C", "lib/tracked", "rev-parse", "HEAD"]);
+
+    let bumped = harness
+        .advance_test_remote("tracked_lib")
+        .expect("Failed to advance remote");
+    harness.git_stdout(&["-C", "lib/tracked", "fetch", "origin"]);
+    harness.git_stdout(&["-C", "lib/tracked", "checkout", &bumped]);
+    harness.git_stdout(&["add", "lib/tracked"]);
+    harness.git_stdout(&["commit", "-m", "Bump submodule"]);
+    (url, added, bumped)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_west_locks_checked_out_commits() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (url, added, bumped) = superproject_with_bumped_submodule(&harness);
+
+        let stdout = harness
+            .run_submod_success(&["export", "west"])
+            .expect("Failed to export");
+        assert!(stdout.contains("- name: tracked"), "stdout: {stdout}");
+        assert!(stdout.contains(&format!("url: {url}")), "stdout: {stdout}");
+        assert!(
+            stdout.contains(&format!("revision: {bumped}")),
+            "stdout: {stdout}"
+        );
+        assert!(stdout.contains("path: lib/tracked"), "stdout: {stdout}");
+
+        // --rev locks to what an older superproject commit recorded
+        let stdout = harness
+            .run_submod_success(&["export", "west", "--rev", "HEAD~1"])
+            .expect("Failed to export");
+        assert!(
+            stdout.contains(&format!("revision: {added}")),
+            "stdout: {stdout}"
+        );
+    }
+
+    #[test]
+    fn test_export_vcstool_writes_a_file_that_imports_back() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (url, _, bumped) = superproject_with_bumped_submodule(&harness);
+
+        let stdout = harness
+            .run_submod_success(&["export", "vcstool", "--output", "deps.repos"])
+            .expect("Failed to export");
+        assert!(stdout.contains("Wrote deps.repos"), "stdout: {stdout}");
+        let repos = std::fs::read_to_string(harness.work_dir.join("deps.repos"))
+            .expect("Failed to read export");
+        assert_eq!(
+            repos,
+            format!(
+                "repositories:\n  lib/tracked:\n    type: git\n    url: {url}\n    version: {bumped}\n"
+            )
+        );
+
+        // Everything in it is already configured
+        let stdout = harness
+            .run_submod_success(&["import", "vcstool", "deps.repos"])
+            .expect("Failed to import");
+        assert!(
+            stdout.contains("already configured as tracked"),
+            "stdout: {stdout}"
+        );
+    }
+}
@@ -15,7 +15,7 @@ mod common;
 use common::TestHarness;
 
 use std::collections::HashMap;
-use submod::config::{SubmoduleAddOptions, SubmoduleEntries, SubmoduleEntry};
+use submod::config::{SubmoduleAddOptions, SubmoduleAuth, SubmoduleEntries, SubmoduleEntry};
 use submod::git_ops::{Git2Operations, GitConfig, GitOperations, GitOpsManager, GixOperations};
 use submod::options::ConfigLevel;
 
@@ -208,6 +208,9 @@ mod fallback_submodule_tests {
             update: None,
             fetch_recurse: None,
             shallow: false,
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth::default(),
             no_init: false,
         };
 
@@ -507,7 +510,7 @@ mod error_propagation_tests {
 
         let mgr = GitOpsManager::new(Some(&harness.work_dir), false).expect("mgr");
 
-        let result = mgr.fetch_submodule("nonexistent/submodule");
+        let result = mgr.fetch_submodule("nonexistent/submodule", &SubmoduleAuth::default());
         assert!(
             result.is_err(),
             "fetch_submodule on nonexistent path should return Err, not silently succeed"
@@ -522,7 +525,7 @@ mod error_propagation_tests {
 
         let gix = GixOperations::new(Some(&harness.work_dir)).expect("gix");
 
-        let result = gix.fetch_submodule("nonexistent/path");
+        let result = gix.fetch_submodule("nonexistent/path", &SubmoduleAuth::default());
         assert!(
             result.is_err(),
             "gix.fetch_submodule should propagate error for invalid path, not swallow it"
@@ -537,7 +540,7 @@ mod error_propagation_tests {
 
         let git2 = Git2Operations::new(Some(&harness.work_dir)).expect("git2");
 
-        let result = git2.fetch_submodule("nonexistent/path");
+        let result = git2.fetch_submodule("nonexistent/path", &SubmoduleAuth::default());
         assert!(
             result.is_err(),
             "git2.fetch_submodule should propagate error for invalid path"
@@ -554,7 +557,8 @@ mod error_propagation_tests {
 
         // All of these should fail, not silently succeed
         assert!(
-            mgr.init_submodule("nonexistent").is_err(),
+            mgr.init_submodule("nonexistent", &SubmoduleAuth::default())
+                .is_err(),
             "init_submodule on nonexistent should error"
         );
         assert!(
@@ -821,6 +825,9 @@ mod git2_fallback_injection_tests {
             update: None,
             fetch_recurse: None,
             shallow: false,
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth::default(),
             no_init: false,
         };
         mgr.add_submodule(&opts)
@@ -835,9 +842,28 @@ mod git2_fallback_injection_tests {
             harness.gitmodules_entries().contains("lib/addinj"),
             "git2 add must write the .gitmodules entry"
         );
+        // git2 names the submodule after its path; add renames it to the given name
+        assert!(
+            harness
+                .submodule_config_entries()
+                .contains("submodule.addinj-sub."),
+            "git2 add must write the submodule.* config section, keyed by name"
+        );
         assert!(
-            harness.submodule_config_entries().contains("lib/addinj"),
-            "git2 add must write the submodule.* config section"
+            harness
+                .gitmodules_entries()
+                .contains("submodule.addinj-sub.path lib/addinj"),
+            "git2 add must key the .gitmodules entry by name"
+        );
+        assert!(
+            harness.work_dir.join(".git/modules/addinj-sub").is_dir()
+                && !harness.work_dir.join(".git/modules/lib").exists(),
+            "git2 add must keep the submodule's git directory under its name"
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/addinj", "rev-parse", "--show-toplevel"]),
+            harness.work_dir.join("lib/addinj").display().to_string(),
+            "the moved git directory must still find its worktree"
         );
         let subs = mgr.list_submodules().expect("git2 list_submodules");
         assert!(
@@ -864,7 +890,7 @@ mod git2_fallback_injection_tests {
             "submodule worktree should exist before delete"
         );
         assert!(
-            harness.submodule_config_entries().contains("lib/inj"),
+            harness.submodule_config_entries().contains("inj-sub"),
             "submodule.* config should exist before delete"
         );
 
@@ -878,7 +904,7 @@ mod git2_fallback_injection_tests {
             "git2 deinit(force) must remove the submodule worktree"
         );
         assert!(
-            !harness.submodule_config_entries().contains("lib/inj"),
+            !harness.submodule_config_entries().contains("inj-sub"),
             "git2 deinit must remove the submodule.* config section"
         );
         // git2 leaves .gitmodules alone — documents why higher-level cleanup exists.
@@ -935,6 +961,9 @@ mod git2_fallback_injection_tests {
             update: None,
             fetch_recurse: None,
             shallow: false,
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth::default(),
             no_init: false,
         };
 
@@ -958,8 +987,8 @@ mod git2_fallback_injection_tests {
             "--",
             "lib/reopen",
         ]);
-        let _ = harness.git_stdout(&["config", "--remove-section", "submodule.lib/reopen"]);
-        let _ = std::fs::remove_dir_all(harness.work_dir.join(".git/modules/lib/reopen"));
+        let _ = harness.git_stdout(&["config", "--remove-section", "submodule.reopen-sub"]);
+        let _ = std::fs::remove_dir_all(harness.work_dir.join(".git/modules/reopen-sub"));
 
         // Refresh cached git2 state after the destructive sequence.
         mgr.reopen().expect("reopen after delete");
@@ -1038,6 +1067,9 @@ mod cli_last_resort_tests {
             update: None,
             fetch_recurse: None,
             shallow: false,
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth::default(),
             no_init: false,
         };
         mgr.add_submodule(&opts)
@@ -1053,7 +1085,7 @@ mod cli_last_resort_tests {
             "CLI add must write the .gitmodules entry"
         );
         // `git submodule add --name cliadd-sub` keys .git/config by the submodule
-        // *name* (submodule.cliadd-sub.url), unlike git2 which keys by path.
+        // *name* (submodule.cliadd-sub.url), as git2's add does.
         assert!(
             harness.submodule_config_entries().contains("cliadd-sub"),
             "CLI add must write the submodule.* config section (keyed by name)"
@@ -1105,6 +1137,9 @@ mod cli_last_resort_tests {
             update: None,
             fetch_recurse: None,
             shallow: false,
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth::default(),
             no_init: false,
         };
         mgr.add_submodule(&opts)
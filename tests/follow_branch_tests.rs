@@ -0,0 +1,187 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for submodules that follow the superproject's branch (`branch = "."`)
+
+mod common;
+use common::TestHarness;
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_follows_superproject_branch_with_fallback() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("follow_lib")
+            .expect("Failed to create remote");
+        // The remote has a `feature` branch, but no `topic`
+        let work_copy = harness.temp_dir.path().join("follow_lib_work");
+        let work_copy = work_copy.to_str().expect("utf-8 path");
+        harness.git_stdout(&["-C", work_copy, "checkout", "-b", "feature"]);
+        harness.git_stdout(&["-C", work_copy, "commit", "--allow-empty", "-m", "Feature"]);
+        harness.git_stdout(&["-C", work_copy, "push", "--quiet", "origin", "feature"]);
+        let feature = harness.git_stdout(&["-C", work_copy, "rev-parse", "HEAD"]);
+
+        harness
+            .run_submod_success(&[
+                "add",
+                &format!("file://{}", remote.display()),
+                "--name",
+                "lib",
+                "--path",
+                "lib",
+                "--branch",
+                ".",
+            ])
+            .expect("Failed to add submodule");
+        harness.git_stdout(&["commit", "-m", "Add lib"]);
+        let current =
+            |harness: &TestHarness| harness.git_stdout(&["-C", "lib", "branch", "--show-current"]);
+
+        let stdout = harness
+            .run_submod_success(&["update"])
+            .expect("Failed to update");
+        assert!(stdout.contains("lib: switched to main"), "stdout: {stdout}");
+        assert_eq!(current(&harness), "main");
+
+        // A new superproject branch the remote has: the submodule switches and tracks it
+        harness.git_stdout(&["checkout", "-b", "feature"]);
+        harness
+            .run_submod_success(&["update"])
+            .expect("Failed to update");
+        assert_eq!(current(&harness), "feature");
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib", "rev-parse", "HEAD"]),
+            feature
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib", "rev-parse", "--abbrev-ref", "@{upstream}"]),
+            "origin/feature"
+        );
+
+        // One it doesn't have, without a fallback: a warning, and the checkout update
+        // leaves it detached at the recorded commit, as git would
+        harness.git_stdout(&["checkout", "-b", "topic"]);
+        let output = harness
+            .run_submod(&["update"])
+            .expect("Failed to run submod");
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("its remote has no 'topic' branch, and no fallback_branch is set"),
+            "stderr: {stderr}"
+        );
+        assert_eq!(current(&harness), "");
+
+        // With a fallback in [defaults], it switches to that, and the setting survives
+        // submod rewriting the config
+        let config = harness.read_config().expect("Failed to read config");
+        harness
+            .create_config(&format!(
+                "[defaults]\nfallback_branch = \"main\"\n\n{config}"
+            ))
+            .expect("Failed to write config");
+        let stdout = harness
+            .run_submod_success(&["update"])
+            .expect("Failed to update");
+        assert!(
+            stdout.contains("lib: switched to main (its remote has no 'topic' branch)"),
+            "stdout: {stdout}"
+        );
+        assert_eq!(current(&harness), "main");
+        harness
+            .run_submod_success(&["change", "lib", "--ignore", "dirty"])
+            .expect("Failed to change submodule");
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(
+            config.contains("fallback_branch = \"main\""),
+            "config: {config}"
+        );
+    }
+
+    #[test]
+    fn test_update_fast_forwards_the_followed_branch() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("ff_lib")
+            .expect("Failed to create remote");
+        harness
+            .run_submod_success(&[
+                "add",
+                &format!("file://{}", remote.display()),
+                "--name",
+                "lib",
+                "--path",
+                "lib",
+                "--branch",
+                ".",
+            ])
+            .expect("Failed to add submodule");
+        harness.git_stdout(&["commit", "-m", "Add lib"]);
+        harness
+            .run_submod_success(&["update"])
+            .expect("Failed to update");
+        let current =
+            |harness: &TestHarness| harness.git_stdout(&["-C", "lib", "branch", "--show-current"]);
+        assert_eq!(current(&harness), "main");
+
+        // The superproject records a newer commit: the local branch moves up to it rather
+        // than taking the submodule back to its old tip
+        let record = |harness: &TestHarness, commit: &str| {
+            harness.git_stdout(&[
+                "update-index",
+                "--cacheinfo",
+                &format!("160000,{commit},lib"),
+            ]);
+            harness.git_stdout(&["commit", "-m", "Record lib"]);
+        };
+        let recorded = harness
+            .advance_test_remote("ff_lib")
+            .expect("Failed to advance remote");
+        record(&harness, &recorded);
+        harness
+            .run_submod_success(&["update"])
+            .expect("Failed to update");
+        assert_eq!(current(&harness), "main");
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib", "rev-parse", "HEAD"]),
+            recorded
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib", "rev-parse", "--abbrev-ref", "@{upstream}"]),
+            "origin/main"
+        );
+
+        // A local commit the recorded one doesn't have: the branch can't be fast-forwarded,
+        // so the submodule stays detached at the recorded commit
+        harness.git_stdout(&["-C", "lib", "commit", "--allow-empty", "-m", "Local"]);
+        let local = harness.git_stdout(&["-C", "lib", "rev-parse", "HEAD"]);
+        let recorded = harness
+            .advance_test_remote("ff_lib")
+            .expect("Failed to advance remote");
+        record(&harness, &recorded);
+        let output = harness
+            .run_submod(&["update"])
+            .expect("Failed to run submod");
+        assert!(output.status.success(), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("its 'main' branch can't be fast-forwarded"),
+            "stderr: {stderr}"
+        );
+        assert_eq!(current(&harness), "");
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib", "rev-parse", "HEAD"]),
+            recorded
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib", "rev-parse", "main"]),
+            local
+        );
+    }
+}
@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `submod foreach`
+
+mod common;
+use common::TestHarness;
+
+/// A superproject with two submodules: `alpha` in the `core` group and `beta` in `tools`
+fn harness_with_grouped_submodules() -> TestHarness {
+    let harness = TestHarness::new().expect("Failed to create test harness");
+    harness.init_git_repo().expect("Failed to init git repo");
+    for (name, group) in [("alpha", "core"), ("beta", "tools")] {
+        let remote = harness
+            .create_test_remote(&format!("{name}_lib"))
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        harness
+            .run_submod_success(&[
+                "add",
+                &url,
+                "--name",
+                name,
+                "--path",
+                &format!("lib/{name}"),
+                "--branch",
+                "main",
+                "--group",
+                group,
+            ])
+            .expect("Failed to add submodule");
+    }
+    harness
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_foreach_runs_in_each_submodule_with_env() {
+        let harness = harness_with_grouped_submodules();
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("groups = [\"core\"]"), "config: {config}");
+
+        let stdout = harness
+            .run_submod_success(&[
+                "foreach",
+                "--jobs",
+                "2",
+                "--",
+                "echo \"$SUBMOD_NAME at $SUBMOD_PATH\"; test \"$PWD\" = \"$SUBMOD_ROOT/$SUBMOD_PATH\"",
+            ])
+            .expect("Failed to run foreach");
+        let alpha = stdout.find("alpha at lib/alpha").expect(&stdout);
+        let beta = stdout.find("beta at lib/beta").expect(&stdout);
+        assert!(alpha < beta, "output must be sorted by name: {stdout}");
+        assert!(
+            stdout.contains("Command succeeded in 2 submodules"),
+            "stdout: {stdout}"
+        );
+    }
+
+    #[test]
+    fn test_foreach_filters_by_group_and_dirtiness() {
+        let harness = harness_with_grouped_submodules();
+
+        let stdout = harness
+            .run_submod_success(&["foreach", "--group", "tools", "--", "echo", "in", "tools"])
+            .expect("Failed to run foreach");
+        assert!(stdout.contains("beta (lib/beta)"), "stdout: {stdout}");
+        assert!(!stdout.contains("alpha"), "stdout: {stdout}");
+
+        let stdout = harness
+            .run_submod_success(&["foreach", "--dirty-only", "--", "pwd"])
+            .expect("Failed to run foreach");
+        assert!(
+            stdout.contains("No submodules to run in"),
+            "stdout: {stdout}"
+        );
+
+        std::fs::write(harness.work_dir.join("lib/alpha/LICENSE"), "changed\n")
+            .expect("Failed to dirty submodule");
+        let stdout = harness
+            .run_submod_success(&["foreach", "--dirty-only", "--", "git", "diff", "--stat"])
+            .expect("Failed to run foreach");
+        assert!(stdout.contains("LICENSE"), "stdout: {stdout}");
+        assert!(!stdout.contains("beta"), "stdout: {stdout}");
+    }
+
+    #[test]
+    fn test_foreach_summarizes_failures_and_exits_non_zero() {
+        let harness = harness_with_grouped_submodules();
+
+        let output = harness
+            .run_submod(&[
+                "foreach",
+                "--",
+                "echo checked $SUBMOD_NAME; [ \"$SUBMOD_NAME\" != beta ] || { echo broken >&2; exit 4; }",
+            ])
+            .expect("Failed to run submod");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success(), "a failure must fail foreach");
+        assert_eq!(
+            output.status.code(),
+            Some(7),
+            "one of two failing is a partial success"
+        );
+        // Every submodule still ran
+        assert!(stdout.contains("checked alpha"), "stdout: {stdout}");
+        assert!(stdout.contains("checked beta"), "stdout: {stdout}");
+        assert!(stderr.contains("broken"), "stderr: {stderr}");
+        assert!(
+            stdout.contains("Command failed in 1 of 2 submodules"),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains("beta: exited with exit status: 4"),
+            "stdout: {stdout}"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `submod hooks install/uninstall` and `submod sync --only-changed`
+//!
+//! The managed hooks fall back to the binary that installed them when `submod` isn't on
+//! `PATH`, so these tests drive real `git checkout`s with the test build's binary.
+
+use std::fs;
+
+mod common;
+use common::TestHarness;
+
+/// A superproject with one submodule, committed on `main`, plus a `feature` branch that
+/// records a newer commit of the submodule. Returns the two recorded commits.
+fn superproject_with_two_branches(harness: &TestHarness) -> (String, String) {
+    harness.init_git_repo().expect("Failed to init git repo");
+    let remote = harness
+        .create_test_remote("synced_lib")
+        .expect("Failed to create remote");
+    let url = format!("file://{}", remote.display());
+    harness
+        .run_submod_success(&[
+            "add",
+            &url,
+            "--name",
+            "synced",
+            "--path",
+            "lib/synced",
+            "--branch",
+            "main",
+        ])
+        .expect("Failed to add submodule");
+    harness.git_stdout(&["add", "-A"]);
+    harness.git_stdout(&["commit", "-m", "Add submodule"]);
+    let on_main = harness.git_stdout(&["-C", "lib/synced", "rev-parse", "HEAD"]);
+
+    let on_feature = harness
+        .advance_test_remote("synced_lib")
+        .expect("Failed to advance remote");
+    harness.git_stdout(&["checkout", "-b", "feature"]);
+    harness.git_stdout(&["-C", "lib/synced", "fetch", "origin"]);
+    harness.git_stdout(&["-C", "lib/synced", "checkout", &on_feature]);
+    harness.git_stdout(&["add", "lib/synced"]);
+    harness.git_stdout(&["commit", "-m", "Bump submodule"]);
+    (on_main, on_feature)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_installed_hooks_sync_submodules_on_checkout() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (on_main, on_feature) = superproject_with_two_branches(&harness);
+
+        let stdout = harness
+            .run_submod_success(&["hooks", "install"])
+            .expect("Failed to install hooks");
+        assert!(stdout.contains("Installed post-checkout hook"), "{stdout}");
+        for hook in ["post-checkout", "post-merge", "post-rewrite"] {
+            assert!(
+                harness.file_exists(&format!(".git/hooks/{hook}")),
+                "{hook} not installed"
+            );
+        }
+
+        // A plain checkout leaves submodules alone; the hook brings them along
+        harness.git_stdout(&["checkout", "main"]);
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/synced", "rev-parse", "HEAD"]),
+            on_main
+        );
+        harness.git_stdout(&["checkout", "feature"]);
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/synced", "rev-parse", "HEAD"]),
+            on_feature
+        );
+    }
+
+    #[test]
+    fn test_installed_hooks_pass_the_config_relative_to_the_worktree() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (on_main, _) = superproject_with_two_branches(&harness);
+        let hook_path = harness.work_dir.join(".git/hooks/post-checkout");
+
+        // The default config, however it is spelled, needs no flag
+        harness
+            .run_submod_success(&["--config", "./submod.toml", "hooks", "install"])
+            .expect("Failed to install hooks");
+        let hook = fs::read_to_string(&hook_path).expect("Failed to read hook");
+        assert!(!hook.contains("--config"), "hook: {hook}");
+
+        // Any other config inside the worktree is given from its top, where git runs hooks
+        fs::create_dir_all(harness.work_dir.join("conf")).expect("Failed to create conf");
+        let config = harness.work_dir.join("conf/alt.toml");
+        fs::rename(harness.work_dir.join("submod.toml"), &config).expect("Failed to move config");
+        harness
+            .run_submod_success(&[
+                "--config",
+                config.to_str().expect("utf-8 path"),
+                "hooks",
+                "install",
+            ])
+            .expect("Failed to install hooks");
+        let hook = fs::read_to_string(&hook_path).expect("Failed to read hook");
+        assert!(hook.contains("--config 'conf/alt.toml'"), "hook: {hook}");
+
+        harness.git_stdout(&["checkout", "main"]);
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/synced", "rev-parse", "HEAD"]),
+            on_main
+        );
+    }
+
+    #[test]
+    fn test_install_chains_to_existing_hook_and_uninstall_restores_it() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (on_main, _) = superproject_with_two_branches(&harness);
+
+        let user_hook = "#!/bin/sh\necho \"$@\" >> user-hook.log\n";
+        let hook_path = harness.work_dir.join(".git/hooks/post-checkout");
+        fs::write(&hook_path, user_hook).expect("Failed to write user hook");
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))
+                .expect("Failed to make user hook executable");
+        }
+
+        harness
+            .run_submod_success(&["hooks", "install"])
+            .expect("Failed to install hooks");
+        harness.git_stdout(&["checkout", "main"]);
+
+        // Both the user's hook and the sync ran
+        let log = fs::read_to_string(harness.work_dir.join("user-hook.log"))
+            .expect("the user's hook must still run");
+        assert!(log.trim_end().ends_with(" 1"), "log: {log}");
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/synced", "rev-parse", "HEAD"]),
+            on_main
+        );
+
+        harness
+            .run_submod_success(&["hooks", "uninstall"])
+            .expect("Failed to uninstall hooks");
+        assert_eq!(fs::read_to_string(&hook_path).unwrap(), user_hook);
+        assert!(!harness.file_exists(".git/hooks/post-checkout.pre-submod"));
+        assert!(!harness.file_exists(".git/hooks/post-merge"));
+        assert!(!harness.file_exists(".git/hooks/post-rewrite"));
+    }
+
+    #[test]
+    fn test_sync_only_changed_skips_submodules_in_sync() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        let (on_main, _) = superproject_with_two_branches(&harness);
+
+        // On `feature`, the checkout matches the recorded commit: nothing to do
+        let stdout = harness
+            .run_submod_success(&["sync", "--only-changed"])
+            .expect("Failed to sync");
+        assert!(!stdout.contains("Updated synced"), "stdout: {stdout}");
+
+        // Without hooks, switching branches leaves the submodule behind
+        harness.git_stdout(&["checkout", "main"]);
+        assert_ne!(
+            harness.git_stdout(&["-C", "lib/synced", "rev-parse", "HEAD"]),
+            on_main
+        );
+        let stdout = harness
+            .run_submod_success(&["sync", "--only-changed"])
+            .expect("Failed to sync");
+        assert!(stdout.contains("Updated synced"), "stdout: {stdout}");
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/synced", "rev-parse", "HEAD"]),
+            on_main
+        );
+    }
+}
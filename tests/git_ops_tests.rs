@@ -11,7 +11,7 @@ mod common;
 use common::TestHarness;
 
 use std::collections::HashMap;
-use submod::config::{SubmoduleEntries, SubmoduleEntry, SubmoduleUpdateOptions};
+use submod::config::{SubmoduleAuth, SubmoduleEntries, SubmoduleEntry, SubmoduleUpdateOptions};
 use submod::git_ops::{
     Git2Operations, GitConfig, GitOperations, GitOpsManager, GixOperations, SubmoduleStatusFlags,
 };
@@ -164,7 +164,10 @@ mod git2_ops_tests {
         let harness = TestHarness::new().expect("harness");
         harness.init_git_repo().expect("init repo");
         let mut ops = Git2Operations::new(Some(&harness.work_dir)).expect("ops");
-        assert!(ops.init_submodule("nonexistent").is_err());
+        assert!(
+            ops.init_submodule("nonexistent", &SubmoduleAuth::default())
+                .is_err()
+        );
     }
 
     #[test]
@@ -207,7 +210,10 @@ mod git2_ops_tests {
         let harness = TestHarness::new().expect("harness");
         harness.init_git_repo().expect("init repo");
         let ops = Git2Operations::new(Some(&harness.work_dir)).expect("ops");
-        assert!(ops.fetch_submodule("nonexistent").is_err());
+        assert!(
+            ops.fetch_submodule("nonexistent", &SubmoduleAuth::default())
+                .is_err()
+        );
     }
 
     #[test]
@@ -520,7 +526,7 @@ mod git2_ops_tests {
 #[cfg(test)]
 mod gix_ops_tests {
     use super::*;
-    use submod::config::SubmoduleAddOptions;
+    use submod::config::{SubmoduleAddOptions, SubmoduleAuth};
 
     #[test]
     fn test_new_from_valid_path() {
@@ -737,6 +743,9 @@ mod gix_ops_tests {
             update: None,
             fetch_recurse: None,
             shallow: false,
+            depth: None,
+            shallow_since: None,
+            auth: SubmoduleAuth::default(),
             no_init: false,
         };
         let result = ops.add_submodule(&add_opts);
@@ -0,0 +1,233 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for lifecycle hooks declared in `[defaults.hooks]` and `[<name>.hooks]`
+//!
+//! Hooks append a line describing what they saw to `hooks.log` in the superproject,
+//! which the tests then read back.
+
+mod common;
+use common::TestHarness;
+
+/// A hook command that logs its event and `SUBMOD_*` variables to `hooks.log`
+const LOG_HOOK: &str = r#""echo \"$SUBMOD_HOOK $SUBMOD_NAME $SUBMOD_PATH ${SUBMOD_OLD_OID:--} ${SUBMOD_NEW_OID:--}\" >> hooks.log""#;
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Set up a superproject whose config logs every event, with one submodule added
+    fn harness_with_logged_submodule() -> TestHarness {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("hooked_lib")
+            .expect("Failed to create remote");
+        harness
+            .create_config(&format!(
+                "[defaults]\n\n[defaults.hooks]\npre_add = [{LOG_HOOK}]\npost_add = [{LOG_HOOK}]\npre_update = [{LOG_HOOK}]\npost_update = [{LOG_HOOK}]\npre_sync = [{LOG_HOOK}]\npost_sync = [{LOG_HOOK}]\n"
+            ))
+            .expect("Failed to write config");
+
+        let url = format!("file://{}", remote.display());
+        harness
+            .run_submod_success(&[
+                "add",
+                &url,
+                "--name",
+                "hooked",
+                "--path",
+                "lib/hooked",
+                "--branch",
+                "main",
+            ])
+            .expect("Failed to add submodule");
+        harness
+    }
+
+    fn read_log(harness: &TestHarness) -> Vec<String> {
+        std::fs::read_to_string(harness.work_dir.join("hooks.log"))
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_add_and_update_run_hooks_with_submodule_env() {
+        let harness = harness_with_logged_submodule();
+        let head = harness.git_stdout(&["-C", "lib/hooked", "rev-parse", "HEAD"]);
+
+        assert_eq!(
+            read_log(&harness),
+            [
+                "pre_add hooked lib/hooked - -".to_string(),
+                format!("post_add hooked lib/hooked - {head}"),
+            ]
+        );
+        // Adding a submodule must not lose the hooks table
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("[defaults.hooks]"), "config: {config}");
+
+        harness
+            .run_submod_success(&["update"])
+            .expect("Failed to run update");
+        assert_eq!(
+            read_log(&harness)[2..],
+            [
+                format!("pre_update hooked lib/hooked {head} -"),
+                format!("post_update hooked lib/hooked {head} {head}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sync_runs_sync_hooks_around_update() {
+        let harness = harness_with_logged_submodule();
+        let head = harness.git_stdout(&["-C", "lib/hooked", "rev-parse", "HEAD"]);
+
+        harness
+            .run_submod_success(&["sync"])
+            .expect("Failed to run sync");
+        let log = read_log(&harness);
+        let events: Vec<&str> = log[2..]
+            .iter()
+            .map(|line| line.split(' ').next().unwrap())
+            .collect();
+        assert_eq!(
+            events,
+            ["pre_sync", "pre_update", "post_update", "post_sync"]
+        );
+        assert_eq!(
+            log.last().unwrap(),
+            &format!("post_sync hooked lib/hooked {head} {head}")
+        );
+    }
+
+    #[test]
+    fn test_no_init_add_skips_add_hooks_until_init() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("later_lib")
+            .expect("Failed to create remote");
+        harness
+            .create_config(&format!(
+                "[defaults]\n\n[defaults.hooks]\npre_add = [{LOG_HOOK}]\npost_add = [{LOG_HOOK}]\npre_init = [{LOG_HOOK}]\npost_init = [{LOG_HOOK}]\n"
+            ))
+            .expect("Failed to write config");
+
+        let url = format!("file://{}", remote.display());
+        harness
+            .run_submod_success(&[
+                "add",
+                &url,
+                "--name",
+                "later",
+                "--path",
+                "lib/later",
+                "--branch",
+                "main",
+                "--no-init",
+            ])
+            .expect("Failed to add submodule");
+        // Only the config was written; there was nothing to add hooks around
+        assert!(read_log(&harness).is_empty());
+
+        harness
+            .run_submod_success(&["init"])
+            .expect("Failed to run init");
+        let head = harness.git_stdout(&["-C", "lib/later", "rev-parse", "HEAD"]);
+        assert_eq!(
+            read_log(&harness),
+            [
+                "pre_init later lib/later - -".to_string(),
+                format!("post_init later lib/later - {head}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_hooks_skips_hooks() {
+        let harness = harness_with_logged_submodule();
+        let before = read_log(&harness);
+
+        harness
+            .run_submod_success(&["--no-hooks", "sync"])
+            .expect("Failed to run sync");
+        assert_eq!(read_log(&harness), before);
+    }
+
+    #[test]
+    fn test_failing_hook_warns_by_default_and_aborts_when_asked() {
+        let harness = harness_with_logged_submodule();
+        let config = harness.read_config().expect("Failed to read config");
+
+        // The submodule's own list replaces the defaults' list for the event
+        harness
+            .create_config(&format!(
+                "{config}\n[hooked.hooks]\npre_update = [\"exit 7\"]\n"
+            ))
+            .expect("Failed to write config");
+        let output = harness
+            .run_submod(&["update"])
+            .expect("Failed to run submod");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            output.status.success(),
+            "warn policy must not fail: {stderr}"
+        );
+        assert!(
+            stderr.contains("pre_update hook for hooked failed"),
+            "stderr: {stderr}"
+        );
+        assert!(
+            read_log(&harness)
+                .last()
+                .unwrap()
+                .starts_with("post_update hooked"),
+            "the update and its post hook still run"
+        );
+
+        harness
+            .create_config(&format!(
+                "{config}\n[hooked.hooks]\npre_update = [\"exit 7\"]\non_failure = \"abort\"\n"
+            ))
+            .expect("Failed to write config");
+        let before = read_log(&harness);
+        let output = harness
+            .run_submod(&["update"])
+            .expect("Failed to run submod");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            !output.status.success(),
+            "abort policy must fail the update"
+        );
+        assert!(
+            stderr.contains("pre_update hook for hooked failed"),
+            "stderr: {stderr}"
+        );
+        assert_eq!(read_log(&harness), before, "nothing runs after the abort");
+    }
+
+    #[test]
+    fn test_hook_timeout_stops_the_hook() {
+        let harness = harness_with_logged_submodule();
+        let config = harness.read_config().expect("Failed to read config");
+        harness
+            .create_config(&format!(
+                "{config}\n[hooked.hooks]\npre_update = [\"sleep 30\"]\ntimeout = 1\non_failure = \"abort\"\n"
+            ))
+            .expect("Failed to write config");
+
+        let start = std::time::Instant::now();
+        let output = harness
+            .run_submod(&["update"])
+            .expect("Failed to run submod");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success());
+        assert!(stderr.contains("timed out after 1s"), "stderr: {stderr}");
+        assert!(start.elapsed() < std::time::Duration::from_secs(20));
+    }
+}
@@ -0,0 +1,212 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `submod import`, which imports submodules from other tools' manifests
+
+mod common;
+use common::TestHarness;
+
+/// Creates test remotes `alpha_lib` and `beta_lib`, and returns the `file://` URL of the
+/// directory holding them
+fn create_remotes(harness: &TestHarness) -> String {
+    let remote = harness
+        .create_test_remote("alpha_lib")
+        .expect("Failed to create remote");
+    harness
+        .create_test_remote("beta_lib")
+        .expect("Failed to create remote");
+    let url = format!("file://{}", remote.display());
+    url.rsplit_once('/')
+        .map(|(dir, _)| dir.to_string())
+        .expect("remotes have a parent directory")
+}
+
+/// Writes a `repo` manifest with two projects on a `file://` remote holding test remotes
+/// `alpha_lib` and `beta_lib`, and returns its path
+fn write_repo_manifest(harness: &TestHarness) -> std::path::PathBuf {
+    let fetch = create_remotes(harness);
+    let manifest = harness.work_dir.join("default.xml");
+    std::fs::write(
+        &manifest,
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest>
+  <remote name="local" fetch="{fetch}" review="https://review.example.com" />
+  <default remote="local" revision="main" />
+  <project name="alpha_lib.git" path="lib/alpha" groups="core" clone-depth="1">
+    <linkfile src="README.md" dest="ALPHA.md" />
+  </project>
+  <project name="beta_lib.git" path="lib/beta" groups="notdefault,tools" />
+</manifest>
+"#
+        ),
+    )
+    .expect("Failed to write manifest");
+    manifest
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_repo_manifest_writes_config_and_reports_unsupported() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let manifest = write_repo_manifest(&harness);
+
+        let stdout = harness
+            .run_submod_success(&["import", "repo-manifest", manifest.to_str().unwrap()])
+            .expect("Failed to import manifest");
+        assert!(
+            stdout.contains("➕ alpha_lib.git (lib/alpha)"),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains("not checked out by default in the manifest"),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains("`<linkfile>` (alpha_lib.git)"),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains("`review` on `<remote>` (local)"),
+            "stdout: {stdout}"
+        );
+        assert!(stdout.contains("Imported 2 submodules"), "stdout: {stdout}");
+
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("[\"alpha_lib.git\"]"), "config: {config}");
+        assert!(config.contains("path = \"lib/alpha\""), "config: {config}");
+        assert!(config.contains("branch = \"main\""), "config: {config}");
+        assert!(config.contains("depth = 1"), "config: {config}");
+        assert!(config.contains("groups = [\"core\"]"), "config: {config}");
+        assert!(config.contains("active = false"), "config: {config}");
+        // Nothing is cloned without --init
+        assert!(!harness.dir_exists("lib/alpha"));
+
+        // Importing again skips what's already configured
+        let stdout = harness
+            .run_submod_success(&["import", "repo-manifest", manifest.to_str().unwrap()])
+            .expect("Failed to import manifest");
+        assert!(stdout.contains("already configured"), "stdout: {stdout}");
+        assert!(
+            stdout.contains("No submodules to import"),
+            "stdout: {stdout}"
+        );
+    }
+
+    #[test]
+    fn test_import_repo_manifest_with_init_clones_active_projects() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let manifest = write_repo_manifest(&harness);
+
+        harness
+            .run_submod_success(&[
+                "import",
+                "repo-manifest",
+                manifest.to_str().unwrap(),
+                "--init",
+            ])
+            .expect("Failed to import manifest");
+        assert!(harness.file_exists("lib/alpha/src/main.c"));
+        assert!(
+            !harness.dir_exists("lib/beta"),
+            "inactive projects stay out"
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/alpha", "rev-list", "--count", "HEAD"]),
+            "1"
+        );
+    }
+
+    #[test]
+    fn test_import_vcstool_with_init_clones_git_repositories() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let base = create_remotes(&harness);
+        std::fs::write(
+            harness.work_dir.join("deps.repos"),
+            format!(
+                "repositories:
+  lib/alpha:
+    type: git
+    url: {base}/alpha_lib.git
+    version: main
+  lib/legacy:
+    type: svn
+    url: https://svn.example.com/legacy
+"
+            ),
+        )
+        .expect("Failed to write .repos file");
+
+        let stdout = harness
+            .run_submod_success(&["import", "vcstool", "deps.repos", "--init"])
+            .expect("Failed to import .repos file");
+        assert!(stdout.contains("➕ alpha (lib/alpha)"), "stdout: {stdout}");
+        assert!(
+            stdout.contains("`svn` repository (not imported) (lib/legacy)"),
+            "stdout: {stdout}"
+        );
+        assert!(stdout.contains("Imported 1 submodule"), "stdout: {stdout}");
+
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("[alpha]"), "config: {config}");
+        assert!(config.contains("branch = \"main\""), "config: {config}");
+        assert!(harness.file_exists("lib/alpha/src/main.c"));
+    }
+
+    #[test]
+    fn test_import_west_resolves_remotes_and_group_filter() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let base = create_remotes(&harness);
+        std::fs::write(
+            harness.work_dir.join("west.yml"),
+            format!(
+                "manifest:
+  defaults:
+    remote: local
+    revision: main
+  remotes:
+    - name: local
+      url-base: {base}
+  group-filter: [-optional]
+  projects:
+    - name: alpha
+      repo-path: alpha_lib.git
+      path: modules/alpha
+      clone-depth: 1
+    - name: beta
+      repo-path: beta_lib.git
+      groups: [optional]
+      west-commands: scripts/west-commands.yml
+"
+            ),
+        )
+        .expect("Failed to write west manifest");
+
+        let stdout = harness
+            .run_submod_success(&["import", "west", "west.yml", "--init"])
+            .expect("Failed to import west manifest");
+        assert!(
+            stdout.contains("`west-commands` (beta)"),
+            "stdout: {stdout}"
+        );
+        assert!(stdout.contains("Imported 2 submodules"), "stdout: {stdout}");
+
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(
+            config.contains("path = \"modules/alpha\""),
+            "config: {config}"
+        );
+        assert!(config.contains("depth = 1"), "config: {config}");
+        assert!(config.contains("active = false"), "config: {config}");
+        assert!(harness.file_exists("modules/alpha/src/main.c"));
+        assert!(!harness.dir_exists("beta"), "inactive projects stay out");
+    }
+}
@@ -1181,6 +1181,172 @@ active = true
         );
     }
 
+    #[test]
+    fn test_add_with_depth_then_deepen_and_unshallow() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+
+        let remote_repo = harness
+            .create_test_remote("depth_lib")
+            .expect("Failed to create remote");
+        // Give the remote three commits of history
+        harness
+            .advance_test_remote("depth_lib")
+            .expect("Failed to advance remote");
+        harness
+            .advance_test_remote("depth_lib")
+            .expect("Failed to advance remote");
+
+        std::process::Command::new("git")
+            .args(["config", "protocol.file.allow", "always"])
+            .current_dir(&harness.work_dir)
+            .output()
+            .expect("Failed to configure git protocol");
+
+        let remote_url = format!("file://{}", remote_repo.display());
+        let commit_count = || {
+            let output = std::process::Command::new("git")
+                .args(["rev-list", "--count", "HEAD"])
+                .current_dir(harness.work_dir.join("lib/depth"))
+                .output()
+                .expect("Failed to run git");
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+
+        harness
+            .run_submod_success(&[
+                "add",
+                &remote_url,
+                "--name",
+                "depth-lib",
+                "--path",
+                "lib/depth",
+                "--depth",
+                "1",
+                "--branch",
+                "main",
+            ])
+            .expect("Failed to add submodule");
+
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("depth = 1"), "config: {config}");
+        assert_eq!(commit_count(), "1");
+
+        // `check` reports the current shallow depth
+        let stdout = harness
+            .run_submod_success(&["check"])
+            .expect("Failed to check");
+        assert!(
+            stdout.contains("depth-lib: Shallow clone (depth 1)"),
+            "check should report the shallow depth; got: {stdout}"
+        );
+
+        let stdout = harness
+            .run_submod_success(&["deepen", "depth-lib", "--by", "1"])
+            .expect("Failed to deepen submodule");
+        assert!(stdout.contains("Deepened depth-lib"), "got: {stdout}");
+        assert_eq!(commit_count(), "2");
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("depth = 2"), "config: {config}");
+
+        harness
+            .run_submod_success(&["unshallow", "depth-lib"])
+            .expect("Failed to unshallow submodule");
+        assert_eq!(commit_count(), "3");
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/depth", "rev-parse", "--is-shallow-repository"]),
+            "false"
+        );
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(!config.contains("depth ="), "config: {config}");
+    }
+
+    #[test]
+    fn test_depth_counts_generations_across_a_merge() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+
+        let remote_repo = harness
+            .create_test_remote("merged_lib")
+            .expect("Failed to create remote");
+        // End the remote's history in a merge, so two commits sit one generation down
+        let work_copy = harness.temp_dir.path().join("merged_lib_work");
+        let work_copy = work_copy.to_str().expect("utf-8 path");
+        for args in [
+            &["checkout", "--quiet", "-b", "side"][..],
+            &["commit", "--quiet", "--allow-empty", "-m", "Side"],
+            &["checkout", "--quiet", "main"],
+            &["commit", "--quiet", "--allow-empty", "-m", "Main"],
+            &["merge", "--quiet", "--no-ff", "side", "-m", "Merge side"],
+            &["push", "--quiet", "origin", "main"],
+        ] {
+            harness.git_stdout(&[&["-C", work_copy][..], args].concat());
+        }
+
+        harness
+            .run_submod_success(&[
+                "add",
+                &format!("file://{}", remote_repo.display()),
+                "--name",
+                "merged-lib",
+                "--path",
+                "lib/merged",
+                "--depth",
+                "2",
+                "--branch",
+                "main",
+            ])
+            .expect("Failed to add submodule");
+        // The merge and both of its parents: three commits, but only two generations
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/merged", "rev-list", "--count", "HEAD"]),
+            "3"
+        );
+
+        let stdout = harness
+            .run_submod_success(&["check"])
+            .expect("Failed to check");
+        assert!(
+            stdout.contains("merged-lib: Shallow clone (depth 2)"),
+            "check should count generations, not commits; got: {stdout}"
+        );
+
+        harness
+            .run_submod_success(&["deepen", "merged-lib", "--by", "1"])
+            .expect("Failed to deepen submodule");
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("depth = 3"), "config: {config}");
+    }
+
+    #[test]
+    fn test_shallow_alias_reads_as_depth_one() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+
+        let remote_repo = harness
+            .create_test_remote("alias_lib")
+            .expect("Failed to create remote");
+        harness
+            .advance_test_remote("alias_lib")
+            .expect("Failed to advance remote");
+        let remote_url = format!("file://{}", remote_repo.display());
+
+        let config_content =
+            format!("[alias-lib]\npath = \"lib/alias\"\nurl = \"{remote_url}\"\nshallow = true\n");
+        harness
+            .create_config(&config_content)
+            .expect("Failed to create config");
+
+        harness
+            .run_submod_success(&["init"])
+            .expect("Failed to init submodules");
+
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/alias", "rev-list", "--count", "HEAD"]),
+            "1"
+        );
+    }
+
     // ---------------------------------------------------------------------
     // Git-state assertions for add / delete / nuke (issue #62, P0-2).
     //
@@ -1236,14 +1402,18 @@ active = true
         // `.git/config` must carry a `submodule.*` section for the submodule.
         let config_entries = harness.submodule_config_entries();
         assert!(
-            config_entries.contains("lib/state"),
-            "expected a submodule.* config section for lib/state, got:\n{config_entries}"
+            config_entries.contains("submodule.state-lib."),
+            "expected a submodule.* config section for state-lib, got:\n{config_entries}"
         );
 
-        // The per-submodule git directory must exist.
+        // The per-submodule git directory must exist, under the submodule's name.
+        assert!(
+            harness.git_modules_dir_exists("state-lib"),
+            ".git/modules/state-lib should exist after add"
+        );
         assert!(
-            harness.git_modules_dir_exists("lib/state"),
-            ".git/modules/lib/state should exist after add"
+            !harness.git_modules_dir_exists("lib"),
+            "nothing should be left under the submodule's path in .git/modules"
         );
 
         // The worktree must be checked out at exactly the gitlinked commit.
@@ -1294,8 +1464,8 @@ active = true
             "precondition: submodule.* config should exist before delete"
         );
         assert!(
-            harness.git_modules_dir_exists("lib/delstate"),
-            "precondition: .git/modules/lib/delstate should exist before delete"
+            harness.git_modules_dir_exists("del-state"),
+            "precondition: .git/modules/del-state should exist before delete"
         );
         assert!(harness.dir_exists("lib/delstate"));
 
@@ -1323,8 +1493,8 @@ active = true
             harness.submodule_config_entries()
         );
         assert!(
-            !harness.git_modules_dir_exists("lib/delstate"),
-            ".git/modules/lib/delstate should be removed"
+            !harness.git_modules_dir_exists("del-state"),
+            ".git/modules/del-state should be removed"
         );
     }
 
@@ -1356,7 +1526,7 @@ active = true
             Some("160000"),
             "precondition: gitlink should exist before nuke"
         );
-        assert!(harness.git_modules_dir_exists("lib/nukestate"));
+        assert!(harness.git_modules_dir_exists("nuke-state"));
 
         harness
             .run_submod_success(&["nuke-it-from-orbit", "nuke-state", "--kill"])
@@ -1382,8 +1552,8 @@ active = true
             harness.submodule_config_entries()
         );
         assert!(
-            !harness.git_modules_dir_exists("lib/nukestate"),
-            ".git/modules/lib/nukestate should be removed after nuke --kill"
+            !harness.git_modules_dir_exists("nuke-state"),
+            ".git/modules/nuke-state should be removed after nuke --kill"
         );
     }
 
@@ -1450,13 +1620,13 @@ active = true
         let gm_raw = std::fs::read_to_string(harness.work_dir.join(".gitmodules"))
             .expect("read .gitmodules");
         assert_eq!(
-            gm_raw.matches("[submodule \"lib/idem\"]").count(),
+            gm_raw.matches("[submodule \"idem\"]").count(),
             1,
             ".gitmodules must hold exactly one section for the re-added submodule, got:\n{gm_raw}"
         );
         let cfg = harness.submodule_config_entries();
         assert_eq!(
-            cfg.matches("submodule.lib/idem.url").count(),
+            cfg.matches("submodule.idem.url").count(),
             1,
             "git config must hold exactly one entry for the submodule, got:\n{cfg}"
         );
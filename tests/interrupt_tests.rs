@@ -0,0 +1,187 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for stopping cleanly on Ctrl-C
+//!
+//! A `pre_init` hook sends `submod` the `SIGINT` a Ctrl-C would, at a known point in the run,
+//! or the test sends it while a clone's transfer is stalled part way through.
+
+mod common;
+use common::TestHarness;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// A hook that interrupts `submod` (the shell's parent) the second time it runs
+const INTERRUPT_SECOND: &str = "'n=$(cat runs 2>/dev/null || echo 0); n=$((n + 1)); echo $n > runs; if [ $n = 2 ]; then kill -INT $PPID; sleep 1; fi'";
+
+/// Serve HTTP on a local port that stalls libgit2's first request, touching `started` when
+/// it arrives, and holds the connection open until `release` is set. gix is turned away,
+/// so the clone falls back to git2, which leaves its partial clone for `submod` to remove.
+fn serve_stalling_http(started: &Path, release: Arc<AtomicBool>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind test server");
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let started = started.to_path_buf();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut headers = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                    break;
+                }
+                headers.push_str(&line.to_ascii_lowercase());
+            }
+            if !headers.contains("libgit2") {
+                let _ = stream.write_all(
+                    b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+                continue;
+            }
+            std::fs::write(&started, "").expect("Failed to mark the transfer started");
+            while !release.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        }
+    });
+    base
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fmt::Write as _;
+    use std::time::Instant;
+
+    #[test]
+    fn test_interrupted_clone_is_removed() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let started = harness.temp_dir.path().join("transfer-started");
+        let release = Arc::new(AtomicBool::new(false));
+        let base = serve_stalling_http(&started, Arc::clone(&release));
+        harness
+            .create_config(&format!(
+                "[slow]\npath = \"lib/slow\"\nurl = \"{base}/slow.git\"\n"
+            ))
+            .expect("Failed to write config");
+
+        let child = harness
+            .spawn_submod_with_env(&["init"], &[])
+            .expect("Failed to start init");
+        let deadline = Instant::now() + Duration::from_secs(60);
+        while !started.exists() {
+            assert!(
+                Instant::now() < deadline,
+                "the clone never reached the remote"
+            );
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        // git2 keys the git directory by path until the add is done
+        let module_dir = harness.work_dir.join(".git/modules/lib/slow");
+        assert!(
+            module_dir.exists(),
+            "the clone should have started writing before the transfer"
+        );
+        // What a Ctrl-C sends
+        let status = std::process::Command::new("kill")
+            .args(["-s", "INT", "--", &format!("-{}", child.id())])
+            .status()
+            .expect("Failed to run kill");
+        assert!(status.success());
+        // Once the flag is surely set, let the transfer fail, as it would when the remote
+        // saw the connection drop
+        std::thread::sleep(Duration::from_millis(200));
+        release.store(true, Ordering::SeqCst);
+        let output = child.wait_with_output().expect("Failed to wait for init");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(130), "stderr: {stderr}");
+        assert!(
+            stderr.contains("Interrupted during init. Finished: none. Not finished: slow."),
+            "stderr: {stderr}"
+        );
+        assert!(
+            !harness.work_dir.join(".git/modules/lib").exists(),
+            "the interrupted clone left its git directory behind"
+        );
+        assert!(
+            !harness.work_dir.join("lib/slow/.git").exists(),
+            "the interrupted clone left its checkout behind"
+        );
+    }
+
+    #[test]
+    fn test_interrupted_init_reports_progress_and_leaves_no_partial_clone() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let mut config =
+            format!("[defaults]\n\n[defaults.hooks]\npre_init = [{INTERRUPT_SECOND}]\n");
+        for name in ["alpha", "beta"] {
+            let remote = harness
+                .create_test_remote(name)
+                .expect("Failed to create remote");
+            let _ = write!(
+                config,
+                "\n[{name}]\npath = \"lib/{name}\"\nurl = \"file://{}\"\n",
+                remote.display()
+            );
+        }
+        harness
+            .create_config(&config)
+            .expect("Failed to write config");
+
+        let output = harness.run_submod(&["init"]).expect("Failed to run init");
+        assert_eq!(output.status.code(), Some(130), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        // Submodules are visited in no set order; whichever came first finished
+        let cloned: Vec<&str> = ["alpha", "beta"]
+            .into_iter()
+            .filter(|name| {
+                harness
+                    .work_dir
+                    .join("lib")
+                    .join(name)
+                    .join(".git")
+                    .exists()
+            })
+            .collect();
+        let [finished] = cloned[..] else {
+            panic!("expected one clone, found {cloned:?}; stderr: {stderr}");
+        };
+        let unfinished = if finished == "alpha" { "beta" } else { "alpha" };
+        assert!(
+            stderr.contains(&format!(
+                "Interrupted during init. Finished: {finished}. Not finished: {unfinished}."
+            )),
+            "stderr: {stderr}"
+        );
+        assert!(
+            !harness
+                .work_dir
+                .join(".git/modules")
+                .join(unfinished)
+                .exists(),
+            "the interrupted submodule left a git directory behind"
+        );
+
+        // The next run picks up where this one stopped
+        harness
+            .run_submod_success(&["init"])
+            .expect("Failed to rerun init");
+        assert!(
+            harness
+                .work_dir
+                .join("lib")
+                .join(unfinished)
+                .join(".git")
+                .exists()
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `-v`/`-vv`/`-vvv` logging, `SUBMOD_LOG`, and `--log-file`
+
+mod common;
+use common::TestHarness;
+
+/// A repository with one submodule, `lib`, added from a local remote
+fn harness_with_submodule() -> TestHarness {
+    let harness = TestHarness::new().expect("Failed to create test harness");
+    harness.init_git_repo().expect("Failed to init git repo");
+    let remote = harness
+        .create_test_remote("lib")
+        .expect("Failed to create remote");
+    harness
+        .run_submod_success(&[
+            "add",
+            &format!("file://{}", remote.display()),
+            "--name",
+            "lib",
+            "--path",
+            "lib/lib",
+        ])
+        .expect("Failed to add submodule");
+    harness
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_file_has_spans_with_backend_and_timings() {
+        let harness = harness_with_submodule();
+
+        let stdout = harness
+            .run_submod_success(&["-vv", "--log-file", "submod.log", "update"])
+            .expect("Failed to update");
+        assert!(stdout.contains("Updated lib"), "stdout: {stdout}");
+
+        let log = std::fs::read_to_string(harness.work_dir.join("submod.log"))
+            .expect("Failed to read log file");
+        // The git operation, inside the command, with the backend that served it
+        assert!(
+            log.lines().any(|line| line.contains(
+                "update_submodule{submodule=\"lib\"}:update_submodule{path=\"lib/lib\" backend="
+            ) && line.contains("close time.busy=")),
+            "no timed operation with a backend in log: {log}"
+        );
+        assert!(
+            log.lines().any(
+                |line| line.contains("INFO update_submodule{submodule=\"lib\"}: ")
+                    && line.contains("close time.busy=")
+            ),
+            "no timing for the command in log: {log}"
+        );
+    }
+
+    #[test]
+    fn test_rename_goes_straight_to_git2() {
+        let harness = harness_with_submodule();
+
+        harness
+            .run_submod_success(&[
+                "-vv",
+                "--log-file",
+                "submod.log",
+                "change",
+                "lib",
+                "--rename",
+                "core",
+            ])
+            .expect("Failed to rename");
+
+        let log = std::fs::read_to_string(harness.work_dir.join("submod.log"))
+            .expect("Failed to read log file");
+        assert!(
+            log.lines()
+                .any(|line| line.contains("rename_submodule{path=\"lib/lib\" backend=git2}")),
+            "no rename served by git2 in log: {log}"
+        );
+        // gix was never asked, so it can't have failed
+        assert!(!log.contains("gix failed"), "log: {log}");
+    }
+
+    #[test]
+    fn test_submod_log_filters_and_overrides_verbosity() {
+        let harness = harness_with_submodule();
+
+        // Only the git operations, though no `-v` was given
+        let output = harness
+            .run_submod_with_env(&["update"], &[("SUBMOD_LOG", "submod::git_ops=debug")])
+            .expect("Failed to run update");
+        assert!(output.status.success(), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("update_submodule{path=\"lib/lib\""),
+            "stderr: {stderr}"
+        );
+        assert!(!stderr.contains("submod::git_manager"), "stderr: {stderr}");
+
+        // Nothing but warnings by default
+        let output = harness
+            .run_submod(&["update"])
+            .expect("Failed to run update");
+        assert!(output.status.success(), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!stderr.contains("time.busy"), "stderr: {stderr}");
+
+        let output = harness
+            .run_submod_with_env(&["list"], &[("SUBMOD_LOG", "submod=[")])
+            .expect("Failed to run list");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("Invalid SUBMOD_LOG filter"),
+            "stderr: {stderr}"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for submodules whose git name differs from their path, as `git submodule add --name`
+//! makes them
+
+mod common;
+use common::TestHarness;
+
+/// Adds and commits the `compression` submodule at `path` with plain git, then writes a
+/// submod.toml that knows it by the same name
+fn add_with_plain_git(harness: &TestHarness, url: &str, path: &str) {
+    harness.git_stdout(&[
+        "-c",
+        "protocol.file.allow=always",
+        "submodule",
+        "add",
+        "--name",
+        "compression",
+        url,
+        path,
+    ]);
+    harness.git_stdout(&["commit", "-m", "Add compression"]);
+    harness
+        .create_config(&format!(
+            "[compression]\npath = \"{path}\"\nurl = \"{url}\"\n"
+        ))
+        .expect("Failed to write config");
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_registers_by_path_not_by_matching_text() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("zlib")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        add_with_plain_git(&harness, &url, "third_party/zlib-ng");
+        // `.gitmodules` has `path = third_party/zlib-ng`, but nothing at `third_party/zlib`
+        let config = harness.read_config().expect("Failed to read config");
+        harness
+            .create_config(&format!(
+                "{config}\n[zlib]\npath = \"third_party/zlib\"\nurl = \"{url}\"\n"
+            ))
+            .expect("Failed to write config");
+
+        harness
+            .run_submod_success(&["init"])
+            .expect("Failed to init submodules");
+
+        assert!(harness.file_exists("third_party/zlib/src/main.c"));
+        let paths =
+            harness.git_stdout(&["config", "-f", ".gitmodules", "--get-regexp", "\\.path$"]);
+        assert!(
+            paths.contains("submodule.compression.path third_party/zlib-ng")
+                && paths.contains("submodule.zlib.path third_party/zlib"),
+            "paths: {paths}"
+        );
+        assert!(harness.git_modules_dir_exists("zlib"));
+    }
+
+    #[test]
+    fn test_move_rename_and_delete_keep_name_and_path_apart() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("zlib")
+            .expect("Failed to create remote");
+        add_with_plain_git(
+            &harness,
+            &format!("file://{}", remote.display()),
+            "third_party/zlib",
+        );
+        let commit = harness.git_stdout(&["-C", "third_party/zlib", "rev-parse", "HEAD"]);
+
+        harness
+            .run_submod_success(&["change", "compression", "--path", "vendor/zlib"])
+            .expect("Failed to move submodule");
+        assert_eq!(
+            harness.git_stdout(&["config", "-f", ".gitmodules", "submodule.compression.path"]),
+            "vendor/zlib"
+        );
+        assert!(harness.git_modules_dir_exists("compression"));
+
+        // A name with a slash nests its git directory, like git's own
+        let stdout = harness
+            .run_submod_success(&["change", "compression", "--rename", "libs/zlib"])
+            .expect("Failed to rename submodule");
+        assert!(
+            stdout.contains("Renamed compression to libs/zlib"),
+            "stdout: {stdout}"
+        );
+        assert_eq!(
+            harness.git_stdout(&["config", "-f", ".gitmodules", "--get-regexp", "\\.path$"]),
+            "submodule.libs/zlib.path vendor/zlib"
+        );
+        assert!(
+            harness
+                .git_stdout(&["diff", "--cached", "--name-only"])
+                .contains(".gitmodules")
+        );
+        assert!(
+            harness
+                .submodule_config_entries()
+                .contains("submodule.libs/zlib.url")
+        );
+        assert!(harness.git_modules_dir_exists("libs/zlib"));
+        assert!(!harness.git_modules_dir_exists("compression"));
+        // The clone still works from its worktree, and git finds it by its new name
+        assert_eq!(
+            harness.git_stdout(&["-C", "vendor/zlib", "rev-parse", "HEAD"]),
+            commit
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "vendor/zlib", "rev-parse", "--show-toplevel"]),
+            harness.work_dir.join("vendor/zlib").display().to_string()
+        );
+        assert!(
+            harness
+                .git_stdout(&["submodule", "status"])
+                .starts_with(&format!("{commit} vendor/zlib"))
+        );
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("[\"libs/zlib\"]"), "config: {config}");
+        assert!(!config.contains("[compression]"), "config: {config}");
+
+        // Renaming back out of the nested name leaves no empty directories behind
+        harness
+            .run_submod_success(&["change", "libs/zlib", "--rename", "zlib"])
+            .expect("Failed to rename submodule");
+        assert!(harness.git_modules_dir_exists("zlib"));
+        assert!(!harness.git_modules_dir_exists("libs"));
+
+        harness
+            .run_submod_success(&["delete", "zlib"])
+            .expect("Failed to delete submodule");
+        assert!(!harness.git_modules_dir_exists("zlib"));
+        assert_eq!(harness.submodule_config_entries(), "");
+        assert!(!harness.dir_exists("vendor/zlib"));
+    }
+
+    #[test]
+    fn test_rename_nests_inside_its_old_name_and_back() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("zlib")
+            .expect("Failed to create remote");
+        add_with_plain_git(
+            &harness,
+            &format!("file://{}", remote.display()),
+            "third_party/zlib",
+        );
+        let commit = harness.git_stdout(&["-C", "third_party/zlib", "rev-parse", "HEAD"]);
+
+        // The new git directory goes inside the old one
+        harness
+            .run_submod_success(&["change", "compression", "--rename", "compression/core"])
+            .expect("Failed to rename submodule");
+        assert!(harness.git_modules_dir_exists("compression/core"));
+        assert_eq!(
+            harness.git_stdout(&["-C", "third_party/zlib", "rev-parse", "HEAD"]),
+            commit
+        );
+
+        // And back out: `compression` only holds the directory being renamed
+        harness
+            .run_submod_success(&["change", "compression/core", "--rename", "compression"])
+            .expect("Failed to rename submodule");
+        assert!(harness.git_modules_dir_exists("compression"));
+        assert!(!harness.git_modules_dir_exists("compression/core"));
+        assert_eq!(
+            harness.git_stdout(&["-C", "third_party/zlib", "rev-parse", "HEAD"]),
+            commit
+        );
+        assert!(
+            harness
+                .git_stdout(&["submodule", "status"])
+                .starts_with(&format!("{commit} third_party/zlib"))
+        );
+    }
+
+    #[test]
+    fn test_rename_refuses_taken_and_invalid_names() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("zlib")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        add_with_plain_git(&harness, &url, "third_party/zlib");
+        harness
+            .run_submod_success(&["add", &url, "--name", "other", "--path", "lib/other"])
+            .expect("Failed to add submodule");
+
+        for (new_name, error) in [
+            ("other", "already a submodule named 'other'"),
+            ("../escape", "isn't a valid submodule name"),
+        ] {
+            let output = harness
+                .run_submod(&["change", "compression", "--rename", new_name])
+                .expect("Failed to run submod");
+            assert!(!output.status.success());
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(stderr.contains(error), "stderr: {stderr}");
+        }
+        // Nothing was touched
+        assert!(harness.git_modules_dir_exists("compression"));
+        assert_eq!(
+            harness.git_stdout(&["config", "-f", ".gitmodules", "submodule.compression.path"]),
+            "third_party/zlib"
+        );
+        assert!(
+            std::fs::read_to_string(harness.config_path())
+                .expect("Failed to read config")
+                .contains("[compression]")
+        );
+    }
+}
@@ -0,0 +1,208 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for the `merge`, `rebase`, and `!command` update strategies
+
+mod common;
+use common::TestHarness;
+
+/// A superproject with the `lib` submodule at `lib/lib`, where the submodule has a local
+/// commit on `main` and the superproject records a newer upstream commit than the
+/// submodule has checked out. Returns the harness, the local commit, and the recorded one.
+fn diverged_submodule(local_file: &str, strategy: &str) -> (TestHarness, String, String) {
+    let harness = TestHarness::new().expect("Failed to create test harness");
+    harness.init_git_repo().expect("Failed to init git repo");
+    let remote = harness
+        .create_test_remote("strategy_lib")
+        .expect("Failed to create remote");
+    harness
+        .run_submod_success(&[
+            "add",
+            &format!("file://{}", remote.display()),
+            "--name",
+            "lib",
+            "--path",
+            "lib/lib",
+            "--update",
+            strategy,
+        ])
+        .expect("Failed to add submodule");
+    harness.git_stdout(&["commit", "-m", "Add lib"]);
+
+    harness.git_stdout(&["-C", "lib/lib", "checkout", "-B", "main"]);
+    std::fs::write(harness.work_dir.join("lib/lib").join(local_file), "local\n")
+        .expect("Failed to write local change");
+    harness.git_stdout(&["-C", "lib/lib", "add", "."]);
+    harness.git_stdout(&["-C", "lib/lib", "commit", "-m", "Local change"]);
+    let local = harness.git_stdout(&["-C", "lib/lib", "rev-parse", "HEAD"]);
+
+    let recorded = harness
+        .advance_test_remote("strategy_lib")
+        .expect("Failed to advance remote");
+    harness.git_stdout(&[
+        "update-index",
+        "--cacheinfo",
+        &format!("160000,{recorded},lib/lib"),
+    ]);
+    (harness, local, recorded)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_combines_recorded_commit_with_local_work() {
+        let (harness, local, recorded) = diverged_submodule("LOCAL.txt", "merge");
+
+        harness
+            .run_submod_success(&["update"])
+            .expect("Failed to update submodules");
+
+        let parents = harness.git_stdout(&["-C", "lib/lib", "rev-list", "--parents", "-1", "HEAD"]);
+        assert!(
+            parents.ends_with(&format!("{local} {recorded}")),
+            "parents: {parents}"
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/lib", "branch", "--show-current"]),
+            "main"
+        );
+        assert!(harness.file_exists("lib/lib/LOCAL.txt"));
+        assert!(harness.file_exists("lib/lib/ADVANCE.txt"));
+    }
+
+    #[test]
+    fn test_rebase_replays_local_work_onto_recorded_commit() {
+        let (harness, local, recorded) = diverged_submodule("LOCAL.txt", "rebase");
+
+        harness
+            .run_submod_success(&["update"])
+            .expect("Failed to update submodules");
+
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/lib", "rev-parse", "HEAD^"]),
+            recorded
+        );
+        assert_ne!(
+            harness.git_stdout(&["-C", "lib/lib", "rev-parse", "HEAD"]),
+            local
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/lib", "log", "-1", "--format=%s"]),
+            "Local change"
+        );
+        assert!(harness.file_exists("lib/lib/LOCAL.txt"));
+    }
+
+    #[test]
+    fn test_conflicts_stop_the_update_and_name_the_files() {
+        for (strategy, abort) in [
+            ("merge", "git merge --abort"),
+            ("rebase", "git rebase --abort"),
+        ] {
+            // The local commit adds the same file the upstream commit does
+            let (harness, _, _) = diverged_submodule("ADVANCE.txt", strategy);
+
+            let output = harness
+                .run_submod(&["update"])
+                .expect("Failed to run submod");
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(!output.status.success(), "{strategy} must fail");
+            assert!(
+                stderr.contains("stopped on conflicts in: ADVANCE.txt"),
+                "stderr: {stderr}"
+            );
+            assert!(stderr.contains(abort), "stderr: {stderr}");
+            // The conflict is left for the user to resolve
+            assert_eq!(
+                harness.git_stdout(&["-C", "lib/lib", "diff", "--name-only", "--diff-filter=U"]),
+                "ADVANCE.txt"
+            );
+        }
+    }
+
+    #[test]
+    fn test_update_command_runs_with_recorded_commit() {
+        let (harness, _, recorded) = diverged_submodule("LOCAL.txt", "!git checkout -q");
+
+        // git only honors commands from .git/config
+        assert_eq!(
+            harness.git_stdout(&["config", "submodule.lib.update"]),
+            "!git checkout -q"
+        );
+        assert_eq!(
+            harness.git_stdout(&["config", "-f", ".gitmodules", "submodule.lib.update"]),
+            ""
+        );
+        // nor from submod.toml, which is committed with the superproject
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(!config.contains("!git checkout"), "config: {config}");
+
+        harness
+            .run_submod_success(&["update"])
+            .expect("Failed to update submodules");
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/lib", "rev-parse", "HEAD"]),
+            recorded
+        );
+    }
+
+    #[test]
+    fn test_committed_update_command_is_not_run() {
+        let (harness, local, _) = diverged_submodule("LOCAL.txt", "checkout");
+        // A cloned superproject whose submod.toml asks to run a command
+        let config = harness.read_config().expect("Failed to read config");
+        let config = config.replace(
+            "update = \"checkout\"",
+            "update = \"!touch ../../PWNED; true\"",
+        );
+        assert!(config.contains("PWNED"), "config: {config}");
+        harness
+            .create_config(&config)
+            .expect("Failed to write config");
+
+        for command in ["update", "sync"] {
+            let output = harness
+                .run_submod(&[command])
+                .expect("Failed to run submod");
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert!(!output.status.success(), "{command} must fail");
+            assert!(
+                stderr.contains("update commands only run from .git/config"),
+                "stderr: {stderr}"
+            );
+            assert!(!harness.file_exists("PWNED"), "{command} ran the command");
+        }
+        assert_eq!(
+            harness.git_stdout(&["config", "submodule.lib.update"]),
+            "checkout"
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/lib", "rev-parse", "HEAD"]),
+            local
+        );
+    }
+
+    #[test]
+    fn test_named_strategy_replaces_local_command() {
+        let (harness, _, recorded) = diverged_submodule("LOCAL.txt", "!touch ../../RAN; true");
+
+        harness
+            .run_submod_success(&["change", "lib", "--update", "checkout"])
+            .expect("Failed to change strategy");
+        assert_eq!(
+            harness.git_stdout(&["config", "submodule.lib.update"]),
+            "checkout"
+        );
+        harness
+            .run_submod_success(&["update"])
+            .expect("Failed to update submodules");
+        assert!(!harness.file_exists("RAN"));
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/lib", "rev-parse", "HEAD"]),
+            recorded
+        );
+    }
+}
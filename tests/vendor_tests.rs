@@ -0,0 +1,211 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for `submod vendor`, which replaces a submodule with its checked-out files
+
+mod common;
+use common::TestHarness;
+
+/// Adds and commits the `vendored` submodule at `lib/vendored`, cloned from `url` with
+/// `extra_args`
+fn add_committed_submodule(harness: &TestHarness, url: &str, extra_args: &[&str]) {
+    let mut args = vec!["add", url, "--name", "vendored", "--path", "lib/vendored"];
+    args.extend_from_slice(extra_args);
+    harness
+        .run_submod_success(&args)
+        .expect("Failed to add submodule");
+    harness.git_stdout(&["add", "-A"]);
+    harness.git_stdout(&["commit", "-m", "Add submodule"]);
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor_stages_files_and_records_provenance() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("vendored_lib")
+            .expect("Failed to create remote");
+        let url = format!("file://{}", remote.display());
+        add_committed_submodule(&harness, &url, &[]);
+        let commit = harness.git_stdout(&["-C", "lib/vendored", "rev-parse", "HEAD"]);
+
+        let stdout = harness
+            .run_submod_success(&["vendor", "vendored"])
+            .expect("Failed to vendor submodule");
+        assert!(stdout.contains("📦 Vendored vendored"), "stdout: {stdout}");
+
+        // The gitlink is now a tree of plain files
+        let staged = harness.git_stdout(&["ls-files", "--stage", "lib/vendored"]);
+        assert!(!staged.contains("160000"), "staged: {staged}");
+        assert!(
+            staged.contains("lib/vendored/src/main.c"),
+            "staged: {staged}"
+        );
+        assert!(!harness.file_exists("lib/vendored/.git"));
+        assert!(!harness.dir_exists(".git/modules/vendored"));
+        assert!(!harness.file_exists(".gitmodules"));
+        assert_eq!(
+            harness.git_stdout(&["config", "--get-regexp", "^submodule\\."]),
+            ""
+        );
+
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(!config.contains("[vendored]"), "config: {config}");
+
+        let vendored = std::fs::read_to_string(harness.work_dir.join("VENDORED.toml"))
+            .expect("Failed to read VENDORED.toml");
+        assert!(vendored.contains("[vendored]"), "vendored: {vendored}");
+        assert!(
+            vendored.contains("path = \"lib/vendored\""),
+            "vendored: {vendored}"
+        );
+        assert!(
+            vendored.contains(&format!("url = \"{url}\"")),
+            "vendored: {vendored}"
+        );
+        assert!(
+            vendored.contains(&format!("commit = \"{commit}\"")),
+            "vendored: {vendored}"
+        );
+        assert!(
+            harness
+                .git_stdout(&["diff", "--cached", "--name-status"])
+                .contains("A\tVENDORED.toml")
+        );
+    }
+
+    #[test]
+    fn test_vendor_keeps_only_sparse_checked_out_files() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_complex_remote("vendored_lib")
+            .expect("Failed to create remote");
+        add_committed_submodule(
+            &harness,
+            &format!("file://{}", remote.display()),
+            &["--sparse-paths", "src"],
+        );
+
+        harness
+            .run_submod_success(&["vendor", "vendored"])
+            .expect("Failed to vendor submodule");
+
+        let staged = harness.git_stdout(&["ls-files", "lib/vendored"]);
+        assert!(staged.contains("lib/vendored/src/"), "staged: {staged}");
+        assert!(!staged.contains("lib/vendored/tests/"), "staged: {staged}");
+        let vendored = std::fs::read_to_string(harness.work_dir.join("VENDORED.toml"))
+            .expect("Failed to read VENDORED.toml");
+        assert!(
+            vendored.contains("sparse_paths = [\"src\"]"),
+            "vendored: {vendored}"
+        );
+    }
+
+    #[test]
+    fn test_vendor_refuses_uncommitted_changes() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("vendored_lib")
+            .expect("Failed to create remote");
+        add_committed_submodule(&harness, &format!("file://{}", remote.display()), &[]);
+        std::fs::write(
+            harness.work_dir.join("lib/vendored/src/main.c"),
+            "changed\n",
+        )
+        .expect("Failed to modify file");
+
+        let output = harness
+            .run_submod(&["vendor", "vendored"])
+            .expect("Failed to run submod");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("uncommitted changes"), "stderr: {stderr}");
+        // Nothing was touched
+        assert!(harness.file_exists("lib/vendored/.git"));
+        assert!(!harness.file_exists("VENDORED.toml"));
+    }
+
+    #[test]
+    fn test_vendor_refuses_an_unpushed_commit_without_force() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("vendored_lib")
+            .expect("Failed to create remote");
+        add_committed_submodule(&harness, &format!("file://{}", remote.display()), &[]);
+        harness.git_stdout(&[
+            "-C",
+            "lib/vendored",
+            "commit",
+            "--allow-empty",
+            "-m",
+            "Local",
+        ]);
+        let commit = harness.git_stdout(&["-C", "lib/vendored", "rev-parse", "HEAD"]);
+
+        let output = harness
+            .run_submod(&["vendor", "vendored"])
+            .expect("Failed to run submod");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("which no remote-tracking branch contains"),
+            "stderr: {stderr}"
+        );
+        assert!(harness.file_exists("lib/vendored/.git"));
+        assert!(!harness.file_exists("VENDORED.toml"));
+
+        harness
+            .run_submod_success(&["vendor", "vendored", "--force"])
+            .expect("Failed to vendor submodule");
+        let vendored = std::fs::read_to_string(harness.work_dir.join("VENDORED.toml"))
+            .expect("Failed to read VENDORED.toml");
+        assert!(
+            vendored.contains(&format!("commit = \"{commit}\"")),
+            "vendored: {vendored}"
+        );
+    }
+
+    #[test]
+    fn test_vendor_puts_everything_back_when_a_step_fails() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("vendored_lib")
+            .expect("Failed to create remote");
+        add_committed_submodule(&harness, &format!("file://{}", remote.display()), &[]);
+        let staged = harness.git_stdout(&["ls-files", "--stage"]);
+        let gitmodules = std::fs::read_to_string(harness.work_dir.join(".gitmodules"))
+            .expect("Failed to read .gitmodules");
+        // VENDORED.toml can't be read, which fails the last step
+        std::fs::create_dir(harness.work_dir.join("VENDORED.toml"))
+            .expect("Failed to create directory");
+
+        let output = harness
+            .run_submod(&["vendor", "vendored"])
+            .expect("Failed to run submod");
+        assert!(!output.status.success(), "{output:?}");
+
+        assert_eq!(harness.git_stdout(&["ls-files", "--stage"]), staged);
+        assert_eq!(
+            std::fs::read_to_string(harness.work_dir.join(".gitmodules"))
+                .expect("Failed to read .gitmodules"),
+            gitmodules
+        );
+        assert!(harness.file_exists("lib/vendored/.git"));
+        assert!(harness.dir_exists(".git/modules/vendored"));
+        assert_eq!(
+            harness.git_stdout(&["-C", "lib/vendored", "status", "--porcelain"]),
+            ""
+        );
+        let config = harness.read_config().expect("Failed to read config");
+        assert!(config.contains("[vendored]"), "config: {config}");
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-FileCopyrightText: 2025 Adam Poulemanos <89049923+bashandbone@users.noreply.github.com>
+//
+// SPDX-License-Identifier: LicenseRef-PlainMIT OR MIT
+
+//! Tests for submodules in linked worktrees of the superproject (`git worktree add`)
+
+mod common;
+use common::TestHarness;
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linked_worktrees_share_the_main_clone_and_prune_cleans_up() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("worktree_lib")
+            .expect("Failed to create remote");
+        harness
+            .run_submod_success(&[
+                "add",
+                &format!("file://{}", remote.display()),
+                "--name",
+                "lib",
+                "--path",
+                "libs/lib",
+            ])
+            .expect("Failed to add submodule");
+        harness.git_stdout(&["add", "submod.toml"]);
+        harness.git_stdout(&["commit", "-m", "Add lib"]);
+        let main_head = harness.git_stdout(&["-C", "libs/lib", "rev-parse", "HEAD"]);
+
+        let worktree = harness.temp_dir.path().join("feature");
+        let worktree_str = worktree.to_str().expect("utf-8 path");
+        harness.git_stdout(&["worktree", "add", "--quiet", "-b", "feature", worktree_str]);
+        let in_worktree = |args: &[&str]| {
+            let mut full = vec!["-C", worktree_str];
+            full.extend_from_slice(args);
+            harness.git_stdout(&full)
+        };
+
+        // The checkout is a worktree of the main clone, not a clone of its own
+        harness
+            .run_submod_in(&worktree, &["init"])
+            .expect("Failed to init in the worktree");
+        let shared = harness
+            .work_dir
+            .join(".git/modules/lib")
+            .canonicalize()
+            .expect("main clone exists");
+        assert_eq!(
+            in_worktree(&[
+                "-C",
+                "libs/lib",
+                "rev-parse",
+                "--path-format=absolute",
+                "--git-common-dir"
+            ]),
+            shared.to_str().expect("utf-8 path")
+        );
+        assert_eq!(
+            in_worktree(&["-C", "libs/lib", "rev-parse", "HEAD"]),
+            main_head
+        );
+        assert!(worktree.join("libs/lib/LICENSE").is_file());
+        // git alone would have cloned it again, into the worktree's own git directory
+        assert!(
+            !harness
+                .work_dir
+                .join(".git/worktrees/feature/modules")
+                .exists()
+        );
+        assert_eq!(in_worktree(&["status", "--porcelain"]), "");
+
+        // Updating in the worktree fetches into the shared clone and leaves the main
+        // worktree's checkout where it was
+        let recorded = harness
+            .advance_test_remote("worktree_lib")
+            .expect("Failed to advance remote");
+        in_worktree(&[
+            "update-index",
+            "--cacheinfo",
+            &format!("160000,{recorded},libs/lib"),
+        ]);
+        harness
+            .run_submod_in(&worktree, &["update"])
+            .expect("Failed to update in the worktree");
+        assert_eq!(
+            in_worktree(&["-C", "libs/lib", "rev-parse", "HEAD"]),
+            recorded
+        );
+        assert_eq!(
+            harness.git_stdout(&["-C", "libs/lib", "rev-parse", "HEAD"]),
+            main_head
+        );
+
+        // git won't `worktree remove` a worktree with submodules, so it gets deleted;
+        // prune forgets it in the superproject and in the submodule's clone
+        std::fs::remove_dir_all(&worktree).expect("Failed to delete worktree");
+        let dry_run = harness
+            .run_submod_success(&["worktree", "prune", "--dry-run"])
+            .expect("Failed to dry-run prune");
+        assert!(
+            dry_run.contains("Would prune superproject worktree 'feature'"),
+            "stdout: {dry_run}"
+        );
+        assert!(
+            dry_run.contains("Would prune lib worktree 'lib'"),
+            "stdout: {dry_run}"
+        );
+        assert_eq!(
+            harness
+                .git_stdout(&["-C", ".git/modules/lib", "worktree", "list"])
+                .lines()
+                .count(),
+            2
+        );
+
+        let stdout = harness
+            .run_submod_success(&["worktree", "prune"])
+            .expect("Failed to prune");
+        assert!(
+            stdout.contains("Pruned superproject worktree 'feature'"),
+            "stdout: {stdout}"
+        );
+        assert!(
+            stdout.contains("Pruned lib worktree 'lib'"),
+            "stdout: {stdout}"
+        );
+        assert_eq!(harness.git_stdout(&["worktree", "list"]).lines().count(), 1);
+        assert_eq!(
+            harness
+                .git_stdout(&["-C", ".git/modules/lib", "worktree", "list"])
+                .lines()
+                .count(),
+            1
+        );
+        let stdout = harness
+            .run_submod_success(&["worktree", "prune"])
+            .expect("Failed to prune");
+        assert!(stdout.contains("No stale worktrees"), "stdout: {stdout}");
+    }
+
+    #[test]
+    fn test_linked_worktree_finds_the_main_clone_by_its_gitmodules_name() {
+        let harness = TestHarness::new().expect("Failed to create test harness");
+        harness.init_git_repo().expect("Failed to init git repo");
+        let remote = harness
+            .create_test_remote("renamed_lib")
+            .expect("Failed to create remote");
+        harness
+            .run_submod_success(&[
+                "add",
+                &format!("file://{}", remote.display()),
+                "--name",
+                "upstream-name",
+                "--path",
+                "libs/lib",
+            ])
+            .expect("Failed to add submodule");
+        // submod.toml knows it by another name than .gitmodules and .git/modules do
+        let config = harness.read_config().expect("Failed to read config");
+        harness
+            .create_config(&config.replace("[upstream-name]", "[local-name]"))
+            .expect("Failed to write config");
+        harness.git_stdout(&["add", "submod.toml"]);
+        harness.git_stdout(&["commit", "-m", "Add lib"]);
+        // The main worktree keeps the clone but not the checkout
+        harness.git_stdout(&["submodule", "deinit", "--quiet", "-f", "libs/lib"]);
+
+        let worktree = harness.temp_dir.path().join("feature");
+        let worktree_str = worktree.to_str().expect("utf-8 path");
+        harness.git_stdout(&["worktree", "add", "--quiet", "-b", "feature", worktree_str]);
+        harness
+            .run_submod_in(&worktree, &["init"])
+            .expect("Failed to init in the worktree");
+
+        let shared = harness
+            .work_dir
+            .join(".git/modules/upstream-name")
+            .canonicalize()
+            .expect("main clone exists");
+        assert_eq!(
+            harness.git_stdout(&[
+                "-C",
+                worktree.join("libs/lib").to_str().expect("utf-8 path"),
+                "rev-parse",
+                "--path-format=absolute",
+                "--git-common-dir"
+            ]),
+            shared.to_str().expect("utf-8 path")
+        );
+        assert!(
+            !harness
+                .work_dir
+                .join(".git/worktrees/feature/modules")
+                .exists()
+        );
+    }
+}